@@ -6,6 +6,7 @@ use tauri::{AppHandle, Wry};
 
 use crate::core::Feature;
 use crate::features::todo::sync::CalDavSyncManager;
+use crate::infrastructure::database::DatabaseRegistry;
 use crate::infrastructure::notification::NotificationManager;
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 use crate::infrastructure::tray::TrayManager;
@@ -20,6 +21,9 @@ pub struct AppState {
     db: DatabaseConnection,
     features: HashMap<&'static str, Arc<dyn Feature>>,
 
+    // 数据库注册表（恢复备份时用于升级表结构）
+    database_registry: Arc<DatabaseRegistry>,
+
     // 通知管理器
     notification_manager: NotificationManager,
 
@@ -56,6 +60,7 @@ impl AppState {
             app_handle,
             db,
             features: feature_map,
+            database_registry: Arc::new(DatabaseRegistry::new()),
             notification_manager,
             caldav_sync_manager,
             #[cfg(not(any(target_os = "android", target_os = "ios")))]
@@ -85,6 +90,11 @@ impl AppState {
         &self.features
     }
 
+    /// 获取数据库注册表
+    pub fn database_registry(&self) -> Arc<DatabaseRegistry> {
+        self.database_registry.clone()
+    }
+
     /// 获取通知管理器
     pub fn notification(&self) -> &NotificationManager {
         &self.notification_manager
@@ -116,6 +126,20 @@ impl AppState {
             .and_then(|todo_feature| todo_feature.scheduler())
     }
 
+    /// 获取备份 Feature 的自动备份调度器
+    pub fn backup_scheduler(&self) -> Option<&Arc<crate::features::backup::BackupScheduler>> {
+        use crate::features::backup::BackupFeature;
+
+        self.get_feature("backup")
+            .and_then(|feature| feature.as_any().downcast_ref::<BackupFeature>())
+            .and_then(|backup_feature| backup_feature.scheduler())
+    }
+
+    /// 设置数据库注册表（Migrations 执行完成后调用）
+    pub fn set_database_registry(&mut self, registry: DatabaseRegistry) {
+        self.database_registry = Arc::new(registry);
+    }
+
     /// 设置托盘注册表（仅桌面平台）
     #[cfg(not(any(target_os = "android", target_os = "ios")))]
     pub fn set_tray_registry(&mut self, registry: crate::infrastructure::tray::TrayRegistry) {
@@ -14,13 +14,51 @@ pub fn get_handler() -> impl Fn(Invoke<tauri::Wry>) -> bool + Send + Sync + 'sta
         crate::features::todo::api::commands::get_subtasks,
         crate::features::todo::api::commands::update_todo_parent,
         crate::features::todo::api::commands::reorder_todo,
+        crate::features::todo::api::commands::get_todo_dependencies,
+        crate::features::todo::api::commands::add_todo_dependency,
+        crate::features::todo::api::commands::remove_todo_dependency,
+        crate::features::todo::api::commands::list_next_actionable_todos,
+        crate::features::todo::api::commands::get_todo_reminders,
+        crate::features::todo::api::commands::set_todo_reminders,
+        crate::features::todo::api::commands::snooze_todo,
+        crate::features::todo::api::commands::get_reminder_catch_up_policy,
+        crate::features::todo::api::commands::set_reminder_catch_up_policy,
+        crate::features::todo::api::commands::query_todos,
+        crate::features::todo::api::commands::list_todo_filters,
+        crate::features::todo::api::commands::save_todo_filter,
+        crate::features::todo::api::commands::delete_todo_filter,
+        crate::features::todo::api::commands::run_todo_filter,
+        crate::features::todo::api::commands::export_todos_ics,
+        crate::features::todo::api::commands::import_todos_ics,
+        crate::features::todo::api::commands::undo_todo_change,
+        crate::features::todo::api::commands::redo_todo_change,
+        crate::features::todo::api::commands::get_todo_history,
+        crate::features::todo::api::commands::get_todo_trash_retention,
+        crate::features::todo::api::commands::set_todo_trash_retention,
+        crate::features::todo::api::commands::list_todo_trash,
+        crate::features::todo::api::commands::restore_todo,
+        crate::features::todo::api::commands::purge_todo,
+        crate::features::todo::api::commands::empty_todo_trash,
         // CalDAV Commands
         crate::features::todo::sync::caldav_commands::get_caldav_status,
         crate::features::todo::sync::caldav_commands::save_caldav_config,
         crate::features::todo::sync::caldav_commands::clear_caldav_config,
         crate::features::todo::sync::caldav_commands::sync_caldav_now,
+        crate::features::todo::sync::caldav_commands::dry_run_caldav_sync,
         crate::features::todo::sync::caldav_commands::get_caldav_sync_interval,
         crate::features::todo::sync::caldav_commands::set_caldav_sync_interval,
+        crate::features::todo::sync::caldav_commands::list_caldav_calendars,
+        crate::features::todo::sync::caldav_commands::add_caldav_calendar,
+        crate::features::todo::sync::caldav_commands::update_caldav_calendar,
+        crate::features::todo::sync::caldav_commands::remove_caldav_calendar,
+        crate::features::todo::sync::caldav_commands::move_todo_to_calendar,
+        crate::features::todo::sync::caldav_commands::discover_caldav_calendars,
+        crate::features::todo::sync::caldav_commands::list_todo_sync_conflicts,
+        crate::features::todo::sync::caldav_commands::resolve_todo_sync_conflict,
+        crate::features::todo::sync::caldav_commands::list_caldav_outbox,
+        crate::features::todo::sync::caldav_commands::retry_caldav_outbox_item,
+        crate::features::todo::sync::caldav_commands::discard_caldav_outbox_item,
+        crate::features::todo::sync::caldav_commands::list_caldav_sync_history,
         // Settings Feature Commands
         crate::features::settings::api::commands::get_theme_preference,
         crate::features::settings::api::commands::set_theme_preference,
@@ -51,6 +89,9 @@ pub fn get_handler() -> impl Fn(Invoke<tauri::Wry>) -> bool + Send + Sync + 'sta
         crate::features::pomodoro::api::commands::pomodoro_status,
         crate::features::pomodoro::api::commands::pomodoro_get_config,
         crate::features::pomodoro::api::commands::pomodoro_set_config,
+        crate::features::pomodoro::api::commands::pomodoro_get_calendar_export,
+        crate::features::pomodoro::api::commands::pomodoro_set_calendar_export,
+        crate::features::pomodoro::api::commands::pomodoro_export_calendar_now,
         crate::features::pomodoro::api::commands::pomodoro_list_sessions,
         crate::features::pomodoro::api::commands::pomodoro_delete_session,
         crate::features::pomodoro::api::commands::pomodoro_stats,
@@ -77,6 +118,16 @@ pub fn get_handler() -> impl Fn(Invoke<tauri::Wry>) -> bool + Send + Sync + 'sta
         crate::features::achievement::api::commands::achievement_get_coins,
         crate::features::achievement::api::commands::achievement_list,
         crate::features::achievement::api::commands::achievement_list_transactions,
+        // Backup Feature Commands
+        crate::features::backup::api::commands::backup_export,
+        crate::features::backup::api::commands::backup_create,
+        crate::features::backup::api::commands::backup_list,
+        crate::features::backup::api::commands::backup_restore,
+        crate::features::backup::api::commands::backup_restore_file,
+        crate::features::backup::api::commands::backup_get_config,
+        crate::features::backup::api::commands::backup_set_config,
+        // Search Feature Commands
+        crate::features::search::api::commands::search,
         // WebServer Commands (Desktop only)
         #[cfg(not(any(target_os = "android", target_os = "ios")))]
         crate::infrastructure::webserver::api::commands::start_web_server,
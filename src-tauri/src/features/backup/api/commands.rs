@@ -0,0 +1,97 @@
+use tauri::{Emitter, State};
+
+use crate::core::AppState;
+use crate::features::backup::core::{
+    models::{BackupConfig, BackupInfo, BackupKind, RestoreSummary},
+    service, storage,
+};
+
+/// 恢复完成后通知前端重新加载数据
+const BACKUP_RESTORED_EVENT: &str = "backup-restored";
+
+/// 导出整个数据库为 JSON 备份内容
+#[tauri::command]
+pub async fn backup_export(state: State<'_, AppState>) -> Result<String, String> {
+    let backup = service::create_backup(state.db(), &state.database_registry())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())
+}
+
+/// 立即在备份目录中创建一个备份
+#[tauri::command]
+pub async fn backup_create(state: State<'_, AppState>) -> Result<BackupInfo, String> {
+    storage::write_backup(state.db(), &state.database_registry(), BackupKind::Manual)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 列出备份目录中的备份
+#[tauri::command]
+pub async fn backup_list() -> Result<Vec<BackupInfo>, String> {
+    storage::list_backups().map_err(|e| e.to_string())
+}
+
+/// 从 JSON 备份内容恢复
+#[tauri::command]
+pub async fn backup_restore(
+    state: State<'_, AppState>,
+    content: String,
+) -> Result<RestoreSummary, String> {
+    restore_and_reload(&state, &content).await
+}
+
+/// 从备份目录中的备份恢复
+#[tauri::command]
+pub async fn backup_restore_file(
+    state: State<'_, AppState>,
+    file_name: String,
+) -> Result<RestoreSummary, String> {
+    let content = storage::read_backup(&file_name).map_err(|e| e.to_string())?;
+    restore_and_reload(&state, &content).await
+}
+
+/// 获取自动备份配置
+#[tauri::command]
+pub async fn backup_get_config(state: State<'_, AppState>) -> Result<BackupConfig, String> {
+    storage::get_config(state.db())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 保存自动备份配置
+#[tauri::command]
+pub async fn backup_set_config(
+    state: State<'_, AppState>,
+    config: BackupConfig,
+) -> Result<BackupConfig, String> {
+    storage::set_config(state.db(), &config)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(scheduler) = state.backup_scheduler() {
+        scheduler.restart();
+    }
+
+    Ok(config)
+}
+
+/// 恢复后重新加载依赖数据库内容的后台任务
+async fn restore_and_reload(state: &AppState, content: &str) -> Result<RestoreSummary, String> {
+    let summary = storage::restore(state.db(), &state.database_registry(), content)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+    if let Some(scheduler) = state.backup_scheduler() {
+        scheduler.restart();
+    }
+    state.caldav_sync_manager().restart_scheduler();
+
+    let _ = state.app_handle().emit(BACKUP_RESTORED_EVENT, &summary);
+
+    Ok(summary)
+}
@@ -0,0 +1,65 @@
+use anyhow::Context;
+use serde_json::json;
+use tauri::Manager;
+
+use crate::core::AppState;
+use crate::features::backup::core::{models::BackupKind, service, storage};
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::infrastructure::webserver::HandlerRegistry;
+
+/// 注册 Backup Feature 的 WebSocket handlers
+///
+/// 恢复会替换全部数据，只通过应用内的 Tauri Command 提供
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub fn register_handlers(registry: &mut HandlerRegistry) {
+    // 导出整个数据库
+    registry.register_call("backup.export", |_method, _params, ctx| {
+        Box::pin(async move {
+            let state = ctx
+                .app_handle()
+                .try_state::<AppState>()
+                .context("App state not available")?;
+
+            let backup = service::create_backup(ctx.db(), &state.database_registry())
+                .await
+                .context("Failed to export backup")?;
+
+            Ok(serde_json::to_value(backup)?)
+        })
+    });
+
+    // 立即创建备份
+    registry.register_call("backup.create", |_method, _params, ctx| {
+        Box::pin(async move {
+            let state = ctx
+                .app_handle()
+                .try_state::<AppState>()
+                .context("App state not available")?;
+
+            let info =
+                storage::write_backup(ctx.db(), &state.database_registry(), BackupKind::Manual)
+                    .await
+                    .context("Failed to create backup")?;
+
+            Ok(json!(info))
+        })
+    });
+
+    // 列出备份目录中的备份
+    registry.register_call("backup.list", |_method, _params, _ctx| {
+        Box::pin(async move {
+            let backups = storage::list_backups().context("Failed to list backups")?;
+            Ok(json!(backups))
+        })
+    });
+
+    // 获取自动备份配置
+    registry.register_call("backup.get_config", |_method, _params, ctx| {
+        Box::pin(async move {
+            let config = storage::get_config(ctx.db())
+                .await
+                .context("Failed to get backup config")?;
+            Ok(json!(config))
+        })
+    });
+}
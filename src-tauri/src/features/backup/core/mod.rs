@@ -0,0 +1,4 @@
+pub mod models;
+pub mod scheduler;
+pub mod service;
+pub mod storage;
@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 备份文件格式标识
+pub const BACKUP_FORMAT: &str = "pet-focus-backup";
+/// 当前备份格式版本（备份文件结构变化时递增）
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// 完整的数据库备份
+///
+/// 表结构的版本由 `migrations` 记录：即创建备份时已执行的 DatabaseRegistry Migration 名称
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub migrations: Vec<String>,
+    pub tables: Vec<BackupTable>,
+    /// AUTOINCREMENT 计数器（sqlite_sequence），恢复后新记录不会复用已删除记录的 id
+    #[serde(default)]
+    pub sequences: BTreeMap<String, i64>,
+}
+
+/// 一张表的结构和全部数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupTable {
+    pub name: String,
+    /// 建表语句（sqlite_master.sql），仅供查看：恢复时按 `migrations` 重建表结构，不执行该语句
+    pub schema: String,
+    pub columns: Vec<String>,
+    /// 每行的值与 columns 一一对应；BLOB 以 `{"base64": "..."}` 表示
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// 备份来源
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupKind {
+    /// 手动创建
+    Manual,
+    /// 定时自动备份（参与轮换）
+    Auto,
+    /// 恢复前自动保存的当前数据
+    PreRestore,
+}
+
+impl BackupKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupKind::Manual => "manual",
+            BackupKind::Auto => "auto",
+            BackupKind::PreRestore => "pre-restore",
+        }
+    }
+}
+
+/// 备份目录中的一个备份文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub kind: BackupKind,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+/// 自动备份配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupConfig {
+    pub enabled: bool,
+    /// 自动备份间隔（小时）
+    pub interval_hours: u32,
+    /// 保留的自动备份数量
+    pub keep: u32,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSummary {
+    pub tables: usize,
+    pub rows: usize,
+    /// 恢复前保存的当前数据备份
+    pub safety_backup: Option<String>,
+    /// 恢复后需要用户处理的问题（如无法解密的凭据、被跳过的表）
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+use tokio::sync::Notify;
+
+use super::models::BackupKind;
+use super::storage;
+use crate::infrastructure::database::DatabaseRegistry;
+
+/// 未启用自动备份时重新检查配置的间隔
+const DISABLED_POLL: Duration = Duration::from_secs(60 * 60);
+/// 自动备份失败后的重试间隔
+const RETRY_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// 自动备份调度器
+///
+/// 距离最近一次自动备份超过配置的间隔时备份一次，并轮换旧的自动备份
+pub struct BackupScheduler {
+    db: DatabaseConnection,
+    registry: Arc<DatabaseRegistry>,
+    restart: Notify,
+}
+
+impl BackupScheduler {
+    pub fn new(db: DatabaseConnection, registry: Arc<DatabaseRegistry>) -> Arc<Self> {
+        Arc::new(Self {
+            db,
+            registry,
+            restart: Notify::new(),
+        })
+    }
+
+    pub fn registry(&self) -> &Arc<DatabaseRegistry> {
+        &self.registry
+    }
+
+    pub fn start(self: &Arc<Self>) {
+        let scheduler = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let delay = scheduler.next_backup_delay().await;
+                println!(
+                    "[BackupScheduler] next automatic backup in {} seconds",
+                    delay.as_secs()
+                );
+
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {
+                        if !scheduler.run_if_due().await {
+                            tokio::time::sleep(RETRY_DELAY).await;
+                        }
+                    }
+                    _ = scheduler.restart.notified() => continue,
+                }
+            }
+        });
+    }
+
+    /// 重启调度器（配置修改或恢复备份后立即生效）
+    pub fn restart(&self) {
+        self.restart.notify_one();
+    }
+
+    async fn next_backup_delay(&self) -> Duration {
+        let config = storage::get_config(&self.db).await.unwrap_or_default();
+        if !config.enabled {
+            return DISABLED_POLL;
+        }
+
+        let Some(last) = last_auto_backup() else {
+            return Duration::ZERO;
+        };
+        let due_at = last + chrono::Duration::hours(config.interval_hours as i64);
+        (due_at - Utc::now()).to_std().unwrap_or(Duration::ZERO)
+    }
+
+    /// 到期时执行一次自动备份，失败时返回 false
+    async fn run_if_due(&self) -> bool {
+        let config = match storage::get_config(&self.db).await {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("[BackupScheduler] 读取备份配置失败: {err:#}");
+                return false;
+            }
+        };
+        if !config.enabled {
+            return true;
+        }
+        if let Some(last) = last_auto_backup() {
+            if Utc::now() < last + chrono::Duration::hours(config.interval_hours as i64) {
+                return true;
+            }
+        }
+
+        match storage::write_backup(&self.db, &self.registry, BackupKind::Auto).await {
+            Ok(info) => println!("[BackupScheduler] created {}", info.file_name),
+            Err(err) => {
+                eprintln!("[BackupScheduler] 自动备份失败: {err:#}");
+                return false;
+            }
+        }
+        if let Err(err) = storage::rotate(config.keep as usize) {
+            eprintln!("[BackupScheduler] 清理旧备份失败: {err:#}");
+        }
+        true
+    }
+}
+
+fn last_auto_backup() -> Option<DateTime<Utc>> {
+    storage::list_backups()
+        .ok()?
+        .into_iter()
+        .find(|info| info.kind == BackupKind::Auto)
+        .and_then(|info| DateTime::parse_from_rfc3339(&info.created_at).ok())
+        .map(|created_at| created_at.with_timezone(&Utc))
+}
@@ -0,0 +1,514 @@
+// 数据库备份与恢复
+//
+// 备份按表导出 sqlite_master 中的所有数据表（不依赖各 Feature 的 Entity），新 Feature 的表自动包含在内。
+// 恢复时先在内存中的临时数据库执行备份记录的 Migration 重建当时的表结构并写入数据（不执行备份文件中的 SQL），
+// 再执行 DatabaseRegistry 的全部 Migration 升级到当前表结构，最后在一个事务中替换当前数据库的内容：
+// 任何一步失败都不会改动现有数据
+
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Value};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use sqlx::{Row, SqliteConnection, SqlitePool, TypeInfo, ValueRef};
+
+use super::models::{
+    BackupFile, BackupTable, RestoreSummary, BACKUP_FORMAT, BACKUP_FORMAT_VERSION,
+};
+use crate::infrastructure::database::DatabaseRegistry;
+
+/// 导出整个数据库
+pub async fn create_backup(
+    db: &DatabaseConnection,
+    registry: &DatabaseRegistry,
+) -> Result<BackupFile> {
+    let pool = db.get_sqlite_connection_pool();
+    let tables = dump_tables(pool).await?;
+    let sequences = read_sequences(&mut *pool.acquire().await?).await?;
+
+    Ok(BackupFile {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        migrations: registry
+            .migration_names()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        tables,
+        sequences,
+    })
+}
+
+/// 解析并校验备份文件
+pub fn parse_backup(content: &str, registry: &DatabaseRegistry) -> Result<BackupFile> {
+    let backup: BackupFile =
+        serde_json::from_str(content).context("failed to parse backup file")?;
+    validate_backup(&backup, registry)?;
+    Ok(backup)
+}
+
+/// 校验备份格式和表结构版本
+///
+/// 包含当前版本未知的 Migration 说明备份来自更新的应用版本，无法安全恢复
+pub fn validate_backup(backup: &BackupFile, registry: &DatabaseRegistry) -> Result<()> {
+    if backup.format != BACKUP_FORMAT {
+        bail!("not a pet-focus backup file");
+    }
+    if backup.version > BACKUP_FORMAT_VERSION {
+        bail!(
+            "backup format version {} is newer than supported version {}",
+            backup.version,
+            BACKUP_FORMAT_VERSION
+        );
+    }
+
+    let known: HashSet<&str> = registry.migration_names().into_iter().collect();
+    let unknown: Vec<&str> = backup
+        .migrations
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !known.contains(name))
+        .collect();
+    if !unknown.is_empty() {
+        bail!(
+            "backup was created by a newer version of the app (unknown migrations: {})",
+            unknown.join(", ")
+        );
+    }
+
+    for table in &backup.tables {
+        if table.name.starts_with("sqlite_") {
+            bail!("backup contains reserved table {}", table.name);
+        }
+        if let Some(row) = table
+            .rows
+            .iter()
+            .find(|row| row.len() != table.columns.len())
+        {
+            bail!(
+                "table {} has a row with {} values but {} columns",
+                table.name,
+                row.len(),
+                table.columns.len()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// 用备份替换当前数据库的全部数据
+///
+/// 备份中没有的表（来自更新的 Migration）保持为 Migration 创建的初始内容
+pub async fn restore_backup(
+    db: &DatabaseConnection,
+    registry: &DatabaseRegistry,
+    backup: &BackupFile,
+) -> Result<RestoreSummary> {
+    validate_backup(backup, registry)?;
+    let mut warnings = Vec::new();
+
+    // 1. 在临时数据库中执行备份时已执行的 Migration 还原当时的表结构，写入数据后升级到当前表结构
+    let staging = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(SqliteConnectOptions::from_str("sqlite::memory:")?.foreign_keys(false))
+        .await
+        .context("failed to create staging database")?;
+    let staging_db: DatabaseConnection = staging.clone().into();
+    registry
+        .run_named_migrations(&staging_db, &backup.migrations)
+        .await
+        .context("failed to recreate the schema of the backup")?;
+
+    let staging_tables = table_names(&staging).await?;
+    let mut conn = staging.acquire().await?;
+    for table in &backup.tables {
+        if !staging_tables.contains(&table.name) {
+            warnings.push(format!(
+                "table {} is not part of the backup's schema and was skipped",
+                table.name
+            ));
+            continue;
+        }
+        let columns = column_names(&mut conn, &table.name).await?;
+        insert_rows(&mut conn, table, &columns).await?;
+    }
+    write_sequences(&mut conn, &backup.sequences, &staging_tables).await?;
+    drop(conn);
+
+    registry
+        .run_migrations(&staging_db)
+        .await
+        .context("failed to upgrade backup to the current schema")?;
+    let upgraded = dump_tables(&staging).await?;
+    let sequences = read_sequences(&mut *staging.acquire().await?).await?;
+    staging.close().await;
+
+    // 2. 在一个事务中清空当前数据并写入升级后的数据
+    let pool = db.get_sqlite_connection_pool();
+    let live_tables = table_names(pool).await?;
+    let mut tx = pool.begin().await?;
+    // 外键在提交时统一检查，写入顺序不受表之间引用关系的影响
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+
+    for name in &live_tables {
+        sqlx::query(&format!("DELETE FROM {}", quote_ident(name)))
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to clear table {name}"))?;
+    }
+    if has_sequence_table(&mut tx).await? {
+        sqlx::query("DELETE FROM sqlite_sequence")
+            .execute(&mut *tx)
+            .await
+            .context("failed to clear sqlite_sequence")?;
+    }
+
+    let mut tables = 0;
+    let mut rows = 0;
+    for table in upgraded
+        .iter()
+        .filter(|table| live_tables.contains(&table.name))
+    {
+        let live_columns = column_names(&mut tx, &table.name).await?;
+        insert_rows(&mut tx, table, &live_columns).await?;
+        tables += 1;
+        rows += table.rows.len();
+    }
+    write_sequences(&mut tx, &sequences, &live_tables).await?;
+
+    tx.commit().await.context("failed to commit restore")?;
+    Ok(RestoreSummary {
+        tables,
+        rows,
+        safety_backup: None,
+        warnings,
+    })
+}
+
+/// 普通数据表。全文索引等虚拟表及其影子表由 Migration 和触发器维护，不备份
+const DATA_TABLES: &str = "SELECT name FROM pragma_table_list \
+     WHERE schema = 'main' AND type = 'table' AND name NOT LIKE 'sqlite_%'";
+
+/// 导出所有数据表
+async fn dump_tables(pool: &SqlitePool) -> Result<Vec<BackupTable>> {
+    let schemas: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT name, sql FROM sqlite_master WHERE name IN ({DATA_TABLES}) ORDER BY name"
+    ))
+    .fetch_all(pool)
+    .await
+    .context("failed to list tables")?;
+
+    let mut conn = pool.acquire().await?;
+    let mut tables = Vec::with_capacity(schemas.len());
+    for (name, schema) in schemas {
+        let columns = column_names(&mut conn, &name).await?;
+        let select = format!(
+            "SELECT {} FROM {}",
+            columns
+                .iter()
+                .map(|column| quote_ident(column))
+                .collect::<Vec<_>>()
+                .join(", "),
+            quote_ident(&name)
+        );
+        let rows = sqlx::query(&select)
+            .fetch_all(&mut *conn)
+            .await
+            .with_context(|| format!("failed to read table {name}"))?
+            .iter()
+            .map(|row| (0..columns.len()).map(|i| decode_value(row, i)).collect())
+            .collect::<Result<Vec<Vec<Value>>>>()?;
+
+        tables.push(BackupTable {
+            name,
+            schema,
+            columns,
+            rows,
+        });
+    }
+
+    Ok(tables)
+}
+
+async fn table_names(pool: &SqlitePool) -> Result<HashSet<String>> {
+    let names: Vec<(String,)> = sqlx::query_as(DATA_TABLES)
+        .fetch_all(pool)
+        .await
+        .context("failed to list tables")?;
+    Ok(names.into_iter().map(|(name,)| name).collect())
+}
+
+async fn column_names(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    let columns: Vec<(String,)> =
+        sqlx::query_as("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+            .bind(table)
+            .fetch_all(conn)
+            .await
+            .with_context(|| format!("failed to read columns of {table}"))?;
+    Ok(columns.into_iter().map(|(name,)| name).collect())
+}
+
+/// sqlite_sequence 只在存在 AUTOINCREMENT 表时由 SQLite 创建
+async fn has_sequence_table(conn: &mut SqliteConnection) -> Result<bool> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'sqlite_sequence'",
+    )
+    .fetch_one(conn)
+    .await
+    .context("failed to check sqlite_sequence")?;
+    Ok(count > 0)
+}
+
+/// 读取 AUTOINCREMENT 计数器
+async fn read_sequences(conn: &mut SqliteConnection) -> Result<BTreeMap<String, i64>> {
+    if !has_sequence_table(conn).await? {
+        return Ok(BTreeMap::new());
+    }
+    let sequences: Vec<(String, i64)> = sqlx::query_as("SELECT name, seq FROM sqlite_sequence")
+        .fetch_all(conn)
+        .await
+        .context("failed to read sqlite_sequence")?;
+    Ok(sequences.into_iter().collect())
+}
+
+/// 写入 tables 中各表的 AUTOINCREMENT 计数器（不小于表中已使用的最大 id）
+async fn write_sequences(
+    conn: &mut SqliteConnection,
+    sequences: &BTreeMap<String, i64>,
+    tables: &HashSet<String>,
+) -> Result<()> {
+    if sequences.is_empty() || !has_sequence_table(conn).await? {
+        return Ok(());
+    }
+
+    for (name, seq) in sequences.iter().filter(|(name, _)| tables.contains(*name)) {
+        let updated = sqlx::query("UPDATE sqlite_sequence SET seq = MAX(seq, ?) WHERE name = ?")
+            .bind(seq)
+            .bind(name)
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to restore sequence of {name}"))?;
+        if updated.rows_affected() == 0 {
+            sqlx::query("INSERT INTO sqlite_sequence (name, seq) VALUES (?, ?)")
+                .bind(name)
+                .bind(seq)
+                .execute(&mut *conn)
+                .await
+                .with_context(|| format!("failed to restore sequence of {name}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// 写入表数据，只写入 target_columns 中存在的列（其余列使用默认值）
+async fn insert_rows(
+    conn: &mut SqliteConnection,
+    table: &BackupTable,
+    target_columns: &[String],
+) -> Result<()> {
+    let indexes: Vec<usize> = table
+        .columns
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| target_columns.contains(column))
+        .map(|(index, _)| index)
+        .collect();
+    if indexes.is_empty() || table.rows.is_empty() {
+        return Ok(());
+    }
+
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote_ident(&table.name),
+        indexes
+            .iter()
+            .map(|&i| quote_ident(&table.columns[i]))
+            .collect::<Vec<_>>()
+            .join(", "),
+        vec!["?"; indexes.len()].join(", ")
+    );
+
+    for row in &table.rows {
+        let mut query = sqlx::query(&insert);
+        for &i in &indexes {
+            query = match &row[i] {
+                Value::Null => query.bind(None::<i64>),
+                Value::Bool(value) => query.bind(*value),
+                Value::Number(number) => match number.as_i64() {
+                    Some(value) => query.bind(value),
+                    None => query.bind(number.as_f64()),
+                },
+                Value::String(value) => query.bind(value.as_str()),
+                Value::Object(object) => {
+                    let encoded = object
+                        .get("base64")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| anyhow!("invalid value in table {}", table.name))?;
+                    query.bind(
+                        STANDARD
+                            .decode(encoded)
+                            .with_context(|| format!("invalid BLOB in table {}", table.name))?,
+                    )
+                }
+                Value::Array(_) => bail!("invalid value in table {}", table.name),
+            };
+        }
+        query
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to restore a row of {}", table.name))?;
+    }
+
+    Ok(())
+}
+
+/// 按值的实际存储类型读取（SQLite 列的声明类型不限制存储的值）
+fn decode_value(row: &SqliteRow, index: usize) -> Result<Value> {
+    let raw = row.try_get_raw(index)?;
+    if raw.is_null() {
+        return Ok(Value::Null);
+    }
+    let type_name = raw.type_info().name().to_string();
+
+    Ok(match type_name.as_str() {
+        "INTEGER" => json!(row.try_get::<i64, _>(index)?),
+        "REAL" => json!(row.try_get::<f64, _>(index)?),
+        "BLOB" => json!({ "base64": STANDARD.encode(row.try_get::<Vec<u8>, _>(index)?) }),
+        _ => json!(row.try_get::<String, _>(index)?),
+    })
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::settings::core::service::SettingService;
+    use crate::features::settings::data::migration::SettingsMigration;
+    use sea_orm::{ConnectionTrait, Database};
+    use sea_orm_migration::MigrationTrait;
+
+    fn registry() -> DatabaseRegistry {
+        let mut registry = DatabaseRegistry::new();
+        registry.register_migration("settings_migration", |manager| {
+            Box::pin(async move { SettingsMigration.up(manager).await })
+        });
+        registry
+    }
+
+    #[tokio::test]
+    async fn test_backup_round_trip() {
+        let registry = registry();
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        registry.run_migrations(&db).await.unwrap();
+        SettingService::set(&db, "ui.theme", "dark").await.unwrap();
+
+        let backup = create_backup(&db, &registry).await.unwrap();
+        let content = serde_json::to_string(&backup).unwrap();
+
+        SettingService::set(&db, "ui.theme", "light").await.unwrap();
+        SettingService::set(&db, "other", "value").await.unwrap();
+
+        let parsed = parse_backup(&content, &registry).unwrap();
+        let summary = restore_backup(&db, &registry, &parsed).await.unwrap();
+        assert_eq!((summary.tables, summary.rows), (1, 1));
+        assert!(summary.warnings.is_empty());
+        assert_eq!(
+            SettingService::get(&db, "ui.theme")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("dark")
+        );
+        assert_eq!(SettingService::get(&db, "other").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_restores_sequences_without_running_backup_sql() {
+        let mut registry = registry();
+        registry.register_migration("notes_migration", |manager| {
+            Box::pin(async move {
+                manager
+                    .get_connection()
+                    .execute_unprepared(
+                        "CREATE TABLE IF NOT EXISTS notes \
+                         (id INTEGER PRIMARY KEY AUTOINCREMENT, body TEXT)",
+                    )
+                    .await
+                    .map(|_| ())
+            })
+        });
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        registry.run_migrations(&db).await.unwrap();
+        for body in ["a", "b", "c"] {
+            db.execute_unprepared(&format!("INSERT INTO notes (body) VALUES ('{body}')"))
+                .await
+                .unwrap();
+        }
+        db.execute_unprepared("DELETE FROM notes WHERE id = 3")
+            .await
+            .unwrap();
+
+        let mut backup = create_backup(&db, &registry).await.unwrap();
+        assert_eq!(backup.sequences.get("notes"), Some(&3));
+
+        // 备份文件中的建表语句不会被执行，未知的表被跳过
+        let notes = backup
+            .tables
+            .iter_mut()
+            .find(|t| t.name == "notes")
+            .unwrap();
+        notes.schema = "CREATE TABLE notes (id); CREATE TABLE injected (x)".to_string();
+        backup.tables.push(BackupTable {
+            name: "injected".to_string(),
+            schema: "CREATE TABLE injected (x)".to_string(),
+            columns: vec!["x".to_string()],
+            rows: vec![vec![json!(1)]],
+        });
+
+        let summary = restore_backup(&db, &registry, &backup).await.unwrap();
+        assert_eq!(summary.rows, 2);
+        assert_eq!(summary.warnings.len(), 1);
+        assert!(summary.warnings[0].contains("injected"));
+        let pool = db.get_sqlite_connection_pool();
+        assert!(!table_names(pool).await.unwrap().contains("injected"));
+
+        db.execute_unprepared("INSERT INTO notes (body) VALUES ('d')")
+            .await
+            .unwrap();
+        let (max_id,): (i64,) = sqlx::query_as("SELECT MAX(id) FROM notes")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(max_id, 4);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_backup_from_newer_schema() {
+        let registry = registry();
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        registry.run_migrations(&db).await.unwrap();
+
+        let mut backup = create_backup(&db, &registry).await.unwrap();
+        backup.migrations.push("future_migration".to_string());
+        let err = validate_backup(&backup, &registry).unwrap_err();
+        assert!(err.to_string().contains("future_migration"));
+
+        backup.migrations.pop();
+        backup.version = BACKUP_FORMAT_VERSION + 1;
+        assert!(validate_backup(&backup, &registry).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+// 备份文件管理
+//
+// 备份保存在应用数据目录的 backups/ 下，文件名为 `<类型>-<UTC 时间>.json`，
+// 自动备份按配置的数量轮换，手动备份和恢复前备份不会被自动删除
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::DatabaseConnection;
+
+use super::models::{BackupConfig, BackupInfo, BackupKind, RestoreSummary};
+use super::service;
+use crate::features::settings::core::service::SettingService;
+use crate::features::todo::sync::CalDavConfigService;
+use crate::infrastructure::database::{app_data_dir, DatabaseRegistry};
+
+const CONFIG_KEY: &str = "backup.config";
+const BACKUP_DIR: &str = "backups";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+pub async fn get_config(db: &DatabaseConnection) -> Result<BackupConfig> {
+    match SettingService::get(db, CONFIG_KEY).await? {
+        Some(raw) => {
+            serde_json::from_str(&raw).context("failed to deserialize backup configuration")
+        }
+        None => Ok(BackupConfig::default()),
+    }
+}
+
+pub async fn set_config(db: &DatabaseConnection, config: &BackupConfig) -> Result<()> {
+    if config.interval_hours == 0 {
+        bail!("backup interval must be at least 1 hour");
+    }
+    if config.keep == 0 {
+        bail!("at least one automatic backup must be kept");
+    }
+
+    let payload =
+        serde_json::to_string(config).context("failed to serialize backup configuration")?;
+    SettingService::set(db, CONFIG_KEY, &payload).await?;
+    Ok(())
+}
+
+fn backup_dir() -> Result<PathBuf> {
+    let dir = app_data_dir()?.join(BACKUP_DIR);
+    fs::create_dir_all(&dir).context("failed to create backup directory")?;
+    Ok(dir)
+}
+
+/// 导出数据库并写入备份目录
+pub async fn write_backup(
+    db: &DatabaseConnection,
+    registry: &DatabaseRegistry,
+    kind: BackupKind,
+) -> Result<BackupInfo> {
+    let backup = service::create_backup(db, registry).await?;
+    let content = serde_json::to_vec_pretty(&backup).context("failed to serialize backup")?;
+
+    let mut file_name = format!(
+        "{}-{}.json",
+        kind.as_str(),
+        Utc::now().format(TIMESTAMP_FORMAT)
+    );
+    let dir = backup_dir()?;
+    // 同一秒内的多次备份追加序号
+    let mut suffix = 1;
+    while dir.join(&file_name).exists() {
+        suffix += 1;
+        file_name = format!(
+            "{}-{}-{suffix}.json",
+            kind.as_str(),
+            Utc::now().format(TIMESTAMP_FORMAT)
+        );
+    }
+
+    let path = dir.join(&file_name);
+    fs::write(&path, &content)
+        .with_context(|| format!("failed to write backup {}", path.display()))?;
+
+    parse_file_name(&file_name, content.len() as u64)
+        .ok_or_else(|| anyhow!("unexpected backup file name {file_name}"))
+}
+
+/// 列出备份目录中的备份，最新的在前
+pub fn list_backups() -> Result<Vec<BackupInfo>> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(backup_dir()?).context("failed to read backup directory")? {
+        let entry = entry?;
+        let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+        if let Some(info) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| parse_file_name(name, size))
+        {
+            backups.push(info);
+        }
+    }
+
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(backups)
+}
+
+/// 读取备份目录中的备份文件内容
+pub fn read_backup(file_name: &str) -> Result<String> {
+    if parse_file_name(file_name, 0).is_none() {
+        bail!("invalid backup file name {file_name}");
+    }
+    let path = backup_dir()?.join(file_name);
+    fs::read_to_string(&path).with_context(|| format!("failed to read backup {file_name}"))
+}
+
+/// 只保留最新的 keep 个自动备份
+pub fn rotate(keep: usize) -> Result<usize> {
+    let dir = backup_dir()?;
+    let mut removed = 0;
+    for info in list_backups()?
+        .into_iter()
+        .filter(|info| info.kind == BackupKind::Auto)
+        .skip(keep)
+    {
+        fs::remove_file(dir.join(&info.file_name))
+            .with_context(|| format!("failed to remove backup {}", info.file_name))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// 从备份内容恢复，恢复前把当前数据保存为 pre-restore 备份
+pub async fn restore(
+    db: &DatabaseConnection,
+    registry: &DatabaseRegistry,
+    content: &str,
+) -> Result<RestoreSummary> {
+    let backup = service::parse_backup(content, registry)?;
+    let safety_backup = write_backup(db, registry, BackupKind::PreRestore)
+        .await
+        .context("failed to back up current data before restoring")?;
+
+    let mut summary = service::restore_backup(db, registry, &backup).await?;
+    summary.safety_backup = Some(safety_backup.file_name);
+
+    // 凭据用本机的 secrets.key 加密，来自其他设备的备份中的凭据无法解密
+    let calendars = CalDavConfigService::calendars_with_unreadable_credentials(db).await?;
+    if !calendars.is_empty() {
+        summary.warnings.push(format!(
+            "CalDAV credentials of {} were encrypted on another device and must be entered again",
+            calendars.join(", ")
+        ));
+    }
+
+    Ok(summary)
+}
+
+/// 解析 `<类型>-<时间>[-序号].json`
+fn parse_file_name(file_name: &str, size_bytes: u64) -> Option<BackupInfo> {
+    let stem = file_name.strip_suffix(".json")?;
+    let (kind, rest) = [BackupKind::Manual, BackupKind::Auto, BackupKind::PreRestore]
+        .into_iter()
+        .find_map(|kind| {
+            stem.strip_prefix(kind.as_str())
+                .and_then(|rest| rest.strip_prefix('-'))
+                .map(|rest| (kind, rest))
+        })?;
+    let timestamp = rest.get(..15)?;
+    if !(rest.len() == 15 || rest[15..].strip_prefix('-')?.parse::<u32>().is_ok()) {
+        return None;
+    }
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .ok()?
+        .and_utc();
+
+    Some(BackupInfo {
+        file_name: file_name.to_string(),
+        kind,
+        created_at: created_at.to_rfc3339(),
+        size_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_name() {
+        let info = parse_file_name("auto-20260102-030405.json", 10).unwrap();
+        assert_eq!(info.kind, BackupKind::Auto);
+        assert_eq!(info.created_at, "2026-01-02T03:04:05+00:00");
+
+        let info = parse_file_name("pre-restore-20260102-030405-2.json", 10).unwrap();
+        assert_eq!(info.kind, BackupKind::PreRestore);
+
+        assert!(parse_file_name("auto-20260102-030405.txt", 0).is_none());
+        assert!(parse_file_name("auto-2026.json", 0).is_none());
+        assert!(parse_file_name("../auto-20260102-030405.json", 0).is_none());
+        assert!(parse_file_name("auto-20260102-030405-x.json", 0).is_none());
+    }
+}
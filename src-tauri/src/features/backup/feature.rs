@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::core::{AppState, Feature};
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::infrastructure::webserver::HandlerRegistry;
+
+use super::core::scheduler::BackupScheduler;
+
+/// 备份 Feature
+///
+/// 负责整个数据库的 JSON 备份与恢复，以及定时自动备份
+pub struct BackupFeature {
+    scheduler: OnceCell<Arc<BackupScheduler>>,
+}
+
+impl BackupFeature {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            scheduler: OnceCell::new(),
+        })
+    }
+
+    /// 获取自动备份调度器（仅在初始化后可用）
+    pub fn scheduler(&self) -> Option<&Arc<BackupScheduler>> {
+        self.scheduler.get()
+    }
+}
+
+#[async_trait]
+impl Feature for BackupFeature {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &'static str {
+        "backup"
+    }
+
+    fn command_names(&self) -> Vec<&'static str> {
+        vec![
+            "backup_export",
+            "backup_create",
+            "backup_list",
+            "backup_restore",
+            "backup_restore_file",
+            "backup_get_config",
+            "backup_set_config",
+        ]
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    fn register_ws_handlers(&self, registry: &mut HandlerRegistry) {
+        super::api::handlers::register_handlers(registry);
+    }
+
+    async fn initialize(&self, app_state: &AppState) -> Result<()> {
+        let scheduler = BackupScheduler::new(app_state.db().clone(), app_state.database_registry());
+
+        self.scheduler
+            .set(scheduler.clone())
+            .map_err(|_| anyhow::anyhow!("Backup scheduler already initialized"))?;
+
+        scheduler.start();
+
+        println!("[BackupFeature] Initialized with automatic backup scheduler");
+        Ok(())
+    }
+}
+
+impl Default for BackupFeature {
+    fn default() -> Self {
+        Self {
+            scheduler: OnceCell::new(),
+        }
+    }
+}
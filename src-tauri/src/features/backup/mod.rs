@@ -0,0 +1,6 @@
+pub mod api;
+pub mod core;
+mod feature;
+
+pub use core::scheduler::BackupScheduler;
+pub use feature::BackupFeature;
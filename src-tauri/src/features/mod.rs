@@ -1,7 +1,9 @@
 pub mod achievement;
+pub mod backup;
 #[cfg(target_os = "windows")]
 pub mod pet;
 pub mod pomodoro;
+pub mod search;
 pub mod settings;
 pub mod tag;
 pub mod todo;
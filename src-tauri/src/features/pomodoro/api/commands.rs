@@ -1,7 +1,11 @@
 use tauri::State;
 
 use crate::core::AppState;
-use crate::features::pomodoro::core::{models::PomodoroStatus, service, PomodoroConfig};
+use crate::features::pomodoro::core::{
+    calendar_export::{self, CalendarExportConfig, CalendarExportSummary},
+    models::PomodoroStatus,
+    service, PomodoroConfig,
+};
 use crate::features::pomodoro::data::entities::{
     pomodoro_records as record_entity, pomodoro_sessions as session_entity,
 };
@@ -119,6 +123,41 @@ pub async fn pomodoro_set_config(
         .map_err(|e| e.to_string())
 }
 
+// ==================== Calendar Export Commands ====================
+
+/// 获取专注记录导出到 CalDAV 日历的配置
+#[tauri::command]
+pub async fn pomodoro_get_calendar_export(
+    state: State<'_, AppState>,
+) -> Result<CalendarExportConfig, String> {
+    calendar_export::get_config(state.db())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 保存导出配置，启用后立即在后台导出
+#[tauri::command]
+pub async fn pomodoro_set_calendar_export(
+    state: State<'_, AppState>,
+    config: CalendarExportConfig,
+) -> Result<(), String> {
+    calendar_export::set_config(state.db(), &config)
+        .await
+        .map_err(|e| e.to_string())?;
+    calendar_export::schedule(state.db().clone());
+    Ok(())
+}
+
+/// 立即导出专注记录
+#[tauri::command]
+pub async fn pomodoro_export_calendar_now(
+    state: State<'_, AppState>,
+) -> Result<CalendarExportSummary, String> {
+    calendar_export::export(state.db())
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ==================== Record Commands (保留兼容性) ====================
 
 #[tauri::command]
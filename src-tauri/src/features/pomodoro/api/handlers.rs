@@ -4,6 +4,7 @@ use serde_json::json;
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 use crate::infrastructure::webserver;
 
+use crate::features::pomodoro::core::calendar_export::{self, CalendarExportConfig};
 use crate::features::pomodoro::core::service;
 
 use crate::features::pomodoro::PomodoroFeature;
@@ -79,6 +80,57 @@ pub fn register_handlers(feature: &PomodoroFeature, registry: &mut webserver::Ha
         })
     });
 
+    // ==================== Calendar Export ====================
+
+    // Get calendar export config
+    registry.register_call("pomodoro.calendar_export.get", |_method, _params, ctx| {
+        Box::pin(async move {
+            let config = calendar_export::get_config(ctx.db())
+                .await
+                .context("Failed to read calendar export config")?;
+            Ok(serde_json::to_value(config).unwrap_or(json!({})))
+        })
+    });
+
+    // Set calendar export config
+    registry.register_call("pomodoro.calendar_export.set", |_method, params, ctx| {
+        Box::pin(async move {
+            let config = CalendarExportConfig {
+                enabled: params
+                    .get("enabled")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                calendar_id: params
+                    .get("calendarId")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                url: params
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            };
+
+            calendar_export::set_config(ctx.db(), &config)
+                .await
+                .context("Failed to save calendar export config")?;
+            calendar_export::schedule(ctx.db().clone());
+
+            Ok(json!({ "success": true }))
+        })
+    });
+
+    // Export focus records now
+    registry.register_call("pomodoro.calendar_export.run", |_method, _params, ctx| {
+        Box::pin(async move {
+            let summary = calendar_export::export(ctx.db())
+                .await
+                .context("Failed to export focus records")?;
+            Ok(serde_json::to_value(summary).unwrap_or(json!({})))
+        })
+    });
+
     // ==================== Session-Todo Links ====================
 
     // List session todo links
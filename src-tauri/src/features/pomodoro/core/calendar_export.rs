@@ -0,0 +1,487 @@
+// 专注记录导出到 CalDAV 日历
+//
+// 已完成的专注记录以 VEVENT 形式发布到配置的日历集合，和会议一起显示为时间块。
+// 每次导出对比专注记录与导出表：新记录创建事件；内容（Session 备注、关联任务标题）变化时更新；
+// 记录被删除后删除远端事件。凭据沿用已配置的 CalDAV 日历，按日历 id 引用
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::features::pomodoro::data::entities::{
+    pomodoro_records as record_entity, pomodoro_sessions as session_entity,
+    record_exports as export_entity, session_todo_links as link_entity,
+};
+use crate::features::settings::core::service::SettingService;
+use crate::features::todo::data::entity as todo_entity;
+use crate::features::todo::sync::{
+    client::{CalDavClient, CalDavHttpError},
+    CalDavConfig, CalDavConfigService,
+};
+
+const CONFIG_KEY: &str = "pomodoro.calendar_export";
+const UID_PREFIX: &str = "pet-focus-focus-";
+
+/// 同一时间只运行一次导出，避免同一记录被重复创建
+static EXPORT_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// 专注记录导出配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarExportConfig {
+    pub enabled: bool,
+    /// 提供凭据的 CalDAV 日历 id
+    pub calendar_id: String,
+    /// 接收 VEVENT 的日历集合 URL，为空时使用该日历自身的 URL
+    #[serde(default)]
+    pub url: String,
+}
+
+/// 一次导出的结果
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarExportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub failed: usize,
+}
+
+/// 待导出的专注记录及其展示内容
+struct FocusEvent {
+    record: record_entity::Model,
+    todo_titles: Vec<String>,
+    note: Option<String>,
+}
+
+pub async fn get_config(db: &DatabaseConnection) -> Result<CalendarExportConfig> {
+    match SettingService::get(db, CONFIG_KEY).await? {
+        Some(raw) => serde_json::from_str(&raw)
+            .context("failed to deserialize pomodoro calendar export configuration"),
+        None => Ok(CalendarExportConfig::default()),
+    }
+}
+
+/// 保存导出配置，启用时要求引用的 CalDAV 日历存在
+pub async fn set_config(db: &DatabaseConnection, config: &CalendarExportConfig) -> Result<()> {
+    if config.enabled
+        && CalDavConfigService::get_calendar(db, &config.calendar_id)
+            .await?
+            .is_none()
+    {
+        return Err(anyhow!("CalDAV calendar {} not found", config.calendar_id));
+    }
+
+    let payload = serde_json::to_string(config)
+        .context("failed to serialize pomodoro calendar export configuration")?;
+    SettingService::set(db, CONFIG_KEY, &payload).await?;
+    Ok(())
+}
+
+/// 在后台导出（专注记录或其关联内容变化后调用），未启用时不做任何事
+pub fn schedule(db: DatabaseConnection) {
+    tauri::async_runtime::spawn(async move {
+        match get_config(&db).await {
+            Ok(config) if config.enabled => {}
+            Ok(_) => return,
+            Err(err) => {
+                eprintln!("[PomodoroExport] 读取导出配置失败: {err:#}");
+                return;
+            }
+        }
+
+        match export(&db).await {
+            Ok(summary) if summary.failed > 0 => {
+                eprintln!("[PomodoroExport] {} 条专注记录导出失败", summary.failed);
+            }
+            Ok(_) => {}
+            Err(err) => eprintln!("[PomodoroExport] 导出专注记录失败: {err:#}"),
+        }
+    });
+}
+
+/// 将专注记录导出到配置的日历
+pub async fn export(db: &DatabaseConnection) -> Result<CalendarExportSummary> {
+    let _guard = EXPORT_LOCK.lock().await;
+
+    let config = get_config(db).await?;
+    if !config.enabled {
+        return Err(anyhow!("pomodoro calendar export is not enabled"));
+    }
+    let calendar = CalDavConfigService::get_calendar(db, &config.calendar_id)
+        .await?
+        .ok_or_else(|| anyhow!("CalDAV calendar {} not found", config.calendar_id))?;
+
+    let url = match config.url.trim() {
+        "" => calendar.url.clone(),
+        url => url.to_string(),
+    };
+    let client = CalDavClient::new(&CalDavConfig {
+        url,
+        ..calendar.connection()
+    })?;
+
+    let result = export_with_client(db, &client).await;
+
+    // 保存请求过程中刷新的 OAuth2 token
+    let auth = client.auth();
+    if auth != calendar.auth {
+        if let Err(err) = CalDavConfigService::set_calendar_auth(db, &calendar.id, &auth).await {
+            eprintln!("[PomodoroExport] 保存刷新后的凭据失败: {err:#}");
+        }
+    }
+
+    result
+}
+
+async fn export_with_client(
+    db: &DatabaseConnection,
+    client: &CalDavClient,
+) -> Result<CalendarExportSummary> {
+    let calendar_url = client.calendar_url().as_str();
+    let now = Utc::now();
+    let events = load_focus_events(db).await?;
+
+    let mut exports: HashMap<i32, export_entity::Model> = export_entity::Entity::find()
+        .all(db)
+        .await
+        .context("failed to load pomodoro record exports")?
+        .into_iter()
+        .map(|export| (export.record_id, export))
+        .collect();
+
+    // 目标日历变更后不再跟踪旧日历中的事件（旧事件保留在原日历）
+    let stale: Vec<i32> = exports
+        .values()
+        .filter(|export| export.calendar_url != calendar_url)
+        .map(|export| export.id)
+        .collect();
+    if !stale.is_empty() {
+        export_entity::Entity::delete_many()
+            .filter(export_entity::Column::Id.is_in(stale))
+            .exec(db)
+            .await
+            .context("failed to forget exports of previous calendar")?;
+        exports.retain(|_, export| export.calendar_url == calendar_url);
+    }
+
+    let mut summary = CalendarExportSummary::default();
+
+    for event in events {
+        let record_id = event.record.id;
+        let existing = exports.remove(&record_id);
+        let uid = existing
+            .as_ref()
+            .map(|export| export.uid.clone())
+            .unwrap_or_else(|| format!("{UID_PREFIX}{}", Uuid::new_v4()));
+        let body = build_vevent(&uid, &event);
+        let content_hash = format!("{:x}", md5::compute(body.as_bytes()));
+
+        let result = match existing {
+            Some(export) if export.content_hash == content_hash => continue,
+            Some(export) => update_event(db, client, export, &body, content_hash, now)
+                .await
+                .map(|_| summary.updated += 1),
+            None => create_event(db, client, record_id, uid, &body, content_hash, now)
+                .await
+                .map(|_| summary.created += 1),
+        };
+
+        if let Err(err) = result {
+            eprintln!("[PomodoroExport] 导出专注记录 {record_id} 失败: {err:#}");
+            summary.failed += 1;
+        }
+    }
+
+    // 剩余的导出对应的专注记录已被删除
+    for export in exports.into_values() {
+        match delete_event(db, client, export).await {
+            Ok(()) => summary.deleted += 1,
+            Err(err) => {
+                eprintln!("[PomodoroExport] 删除远端事件失败: {err:#}");
+                summary.failed += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// 加载已完成的专注记录，以及 Session 备注和关联任务标题
+async fn load_focus_events(db: &DatabaseConnection) -> Result<Vec<FocusEvent>> {
+    let records = record_entity::Entity::find()
+        .filter(record_entity::Column::Kind.eq("focus"))
+        .filter(record_entity::Column::Status.eq("completed"))
+        .order_by_asc(record_entity::Column::StartAt)
+        .all(db)
+        .await
+        .context("failed to load focus records")?;
+
+    let notes: HashMap<i32, Option<String>> = session_entity::Entity::find()
+        .all(db)
+        .await
+        .context("failed to load pomodoro sessions")?
+        .into_iter()
+        .map(|session| (session.id, session.note))
+        .collect();
+
+    let mut session_todos: HashMap<i32, Vec<i32>> = HashMap::new();
+    for link in link_entity::Entity::find()
+        .order_by_asc(link_entity::Column::SortOrder)
+        .all(db)
+        .await
+        .context("failed to load session todo links")?
+    {
+        session_todos
+            .entry(link.session_id)
+            .or_default()
+            .push(link.todo_id);
+    }
+
+    let titles: HashMap<i32, String> = todo_entity::Entity::find()
+        .filter(todo_entity::Column::DeletedAt.is_null())
+        .all(db)
+        .await
+        .context("failed to load todos")?
+        .into_iter()
+        .map(|todo| (todo.id, todo.title))
+        .collect();
+
+    Ok(records
+        .into_iter()
+        .map(|record| {
+            // 记录直接关联的任务优先，否则使用 Session 关联的任务
+            let todo_ids = match record.related_todo_id {
+                Some(todo_id) => vec![todo_id],
+                None => session_todos
+                    .get(&record.session_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            FocusEvent {
+                todo_titles: todo_ids
+                    .iter()
+                    .filter_map(|id| titles.get(id).cloned())
+                    .collect(),
+                note: notes.get(&record.session_id).cloned().flatten(),
+                record,
+            }
+        })
+        .collect())
+}
+
+async fn create_event(
+    db: &DatabaseConnection,
+    client: &CalDavClient,
+    record_id: i32,
+    uid: String,
+    body: &str,
+    content_hash: String,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let upload = client
+        .create_todo(&uid, body)
+        .await
+        .with_context(|| format!("failed to create event for focus record {record_id}"))?;
+
+    export_entity::ActiveModel {
+        id: NotSet,
+        record_id: Set(record_id),
+        uid: Set(uid),
+        calendar_url: Set(client.calendar_url().to_string()),
+        href: Set(upload.href),
+        etag: Set(upload.etag),
+        content_hash: Set(content_hash),
+        exported_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .with_context(|| format!("failed to record export of focus record {record_id}"))?;
+
+    Ok(())
+}
+
+/// 更新远端事件；远端被其他客户端修改过（412）时以本地记录为准覆盖
+async fn update_event(
+    db: &DatabaseConnection,
+    client: &CalDavClient,
+    export: export_entity::Model,
+    body: &str,
+    content_hash: String,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let upload = match client
+        .update_todo(&export.href, body, export.etag.as_deref())
+        .await
+    {
+        Err(err) if is_precondition_failed(&err) => client
+            .update_todo(&export.href, body, None)
+            .await
+            .context("failed to overwrite modified event")?,
+        result => result.with_context(|| {
+            format!(
+                "failed to update event for focus record {}",
+                export.record_id
+            )
+        })?,
+    };
+
+    let mut active: export_entity::ActiveModel = export.into();
+    active.href = Set(upload.href);
+    active.etag = Set(upload.etag);
+    active.content_hash = Set(content_hash);
+    active.exported_at = Set(now);
+    active
+        .update(db)
+        .await
+        .context("failed to record updated event")?;
+
+    Ok(())
+}
+
+async fn delete_event(
+    db: &DatabaseConnection,
+    client: &CalDavClient,
+    export: export_entity::Model,
+) -> Result<()> {
+    match client
+        .delete_todo(&export.href, export.etag.as_deref())
+        .await
+    {
+        Err(err) if is_precondition_failed(&err) => client
+            .delete_todo(&export.href, None)
+            .await
+            .context("failed to delete modified event")?,
+        result => result.with_context(|| {
+            format!(
+                "failed to delete event of focus record {}",
+                export.record_id
+            )
+        })?,
+    }
+
+    export_entity::Entity::delete_by_id(export.id)
+        .exec(db)
+        .await
+        .context("failed to forget deleted event")?;
+
+    Ok(())
+}
+
+fn is_precondition_failed(err: &anyhow::Error) -> bool {
+    CalDavHttpError::status_of(err) == Some(StatusCode::PRECONDITION_FAILED)
+}
+
+/// 生成专注记录对应的 VEVENT
+///
+/// 内容只取决于记录本身（DTSTAMP 使用记录创建时间），用于比较是否需要重新上传
+fn build_vevent(uid: &str, event: &FocusEvent) -> String {
+    let record = &event.record;
+    let summary = if event.todo_titles.is_empty() {
+        "专注".to_string()
+    } else {
+        format!("专注：{}", event.todo_titles.join("、"))
+    };
+
+    let mut description = Vec::new();
+    if let Some(note) = event.note.as_deref().filter(|note| !note.trim().is_empty()) {
+        description.push(note.to_string());
+    }
+    description.push(format!(
+        "专注时长：{} 分钟",
+        (record.elapsed_seconds + 30) / 60
+    ));
+
+    let lines = [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//pet-focus//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", escape_text(uid)),
+        format!("DTSTAMP:{}", format_datetime(&record.created_at)),
+        format!("DTSTART:{}", format_datetime(&record.start_at)),
+        format!("DTEND:{}", format_datetime(&record.end_at)),
+        format!("SUMMARY:{}", escape_text(&summary)),
+        format!("DESCRIPTION:{}", escape_text(&description.join("\n\n"))),
+        "TRANSP:OPAQUE".to_string(),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ];
+    lines.join("\r\n")
+}
+
+fn format_datetime(value: &DateTime<Utc>) -> String {
+    value.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn focus_event(todo_titles: &[&str], note: Option<&str>) -> FocusEvent {
+        let start_at = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+        let end_at = Utc.with_ymd_and_hms(2025, 3, 1, 9, 25, 0).unwrap();
+        FocusEvent {
+            record: record_entity::Model {
+                id: 1,
+                session_id: 1,
+                kind: "focus".to_string(),
+                status: "completed".to_string(),
+                round: 1,
+                start_at,
+                end_at,
+                elapsed_seconds: 1500,
+                related_todo_id: None,
+                created_at: end_at,
+                updated_at: end_at,
+            },
+            todo_titles: todo_titles.iter().map(|title| title.to_string()).collect(),
+            note: note.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_vevent_contains_time_block_and_content() {
+        let body = build_vevent(
+            "pet-focus-focus-1",
+            &focus_event(&["写周报", "Review, merge"], Some("上午; 状态不错")),
+        );
+
+        assert!(body.contains("BEGIN:VEVENT\r\n"));
+        assert!(body.contains("UID:pet-focus-focus-1\r\n"));
+        assert!(body.contains("DTSTART:20250301T090000Z\r\n"));
+        assert!(body.contains("DTEND:20250301T092500Z\r\n"));
+        assert!(body.contains("SUMMARY:专注：写周报、Review\\, merge\r\n"));
+        assert!(body.contains("DESCRIPTION:上午\\; 状态不错\\n\\n专注时长：25 分钟\r\n"));
+    }
+
+    #[test]
+    fn test_vevent_is_stable_and_reflects_changes() {
+        let event = focus_event(&[], None);
+        let body = build_vevent("uid", &event);
+        assert_eq!(body, build_vevent("uid", &event));
+        assert!(body.contains("SUMMARY:专注\r\n"));
+
+        let with_note = focus_event(&[], Some("new note"));
+        assert_ne!(body, build_vevent("uid", &with_note));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod calendar_export;
 pub mod models;
 pub mod scheduler;
 pub mod service;
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::features::settings::core::service::SettingService;
 
+use super::calendar_export;
 use super::models::{PomodoroConfig, PomodoroSessionKind, PomodoroSessionStatus};
 use crate::features::pomodoro::data::entities::{
     pomodoro_records as record_entity, pomodoro_sessions as session_entity,
@@ -114,6 +115,7 @@ pub async fn delete_record(db: &DatabaseConnection, record_id: i32) -> Result<()
     record_entity::Entity::delete_by_id(record_id)
         .exec(db)
         .await?;
+    calendar_export::schedule(db.clone());
     Ok(())
 }
 
@@ -175,7 +177,9 @@ pub async fn update_session_note(
     active.note = Set(note);
     active.updated_at = Set(Utc::now());
 
-    Ok(active.update(db).await?)
+    let session = active.update(db).await?;
+    calendar_export::schedule(db.clone());
+    Ok(session)
 }
 
 /// 归档 Session
@@ -202,6 +206,7 @@ pub async fn delete_session_cascade(db: &DatabaseConnection, session_id: i32) ->
     session_entity::Entity::delete_by_id(session_id)
         .exec(db)
         .await?;
+    calendar_export::schedule(db.clone());
     Ok(())
 }
 
@@ -282,7 +287,11 @@ pub async fn create_record_with_session(
         updated_at: Set(now),
     };
 
-    Ok(active.insert(db).await?)
+    let record = active.insert(db).await?;
+    if record.kind == "focus" && record.status == "completed" {
+        calendar_export::schedule(db.clone());
+    }
+    Ok(record)
 }
 
 /// 生成 Session 动态标题
@@ -417,6 +426,7 @@ pub async fn add_session_todo_link(
     };
 
     let model = active.insert(db).await?;
+    calendar_export::schedule(db.clone());
     Ok(SessionTodoLink::from(model))
 }
 
@@ -431,6 +441,7 @@ pub async fn remove_session_todo_link(
         .filter(link_entity::Column::TodoId.eq(todo_id))
         .exec(db)
         .await?;
+    calendar_export::schedule(db.clone());
     Ok(())
 }
 
@@ -448,5 +459,6 @@ pub async fn reorder_session_todo_links(
             .exec(db)
             .await?;
     }
+    calendar_export::schedule(db.clone());
     Ok(())
 }
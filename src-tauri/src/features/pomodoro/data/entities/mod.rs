@@ -1,3 +1,4 @@
 pub mod pomodoro_records;
 pub mod pomodoro_sessions;
+pub mod record_exports;
 pub mod session_todo_links;
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 专注记录导出到 CalDAV 日历的 VEVENT
+///
+/// 不设外键：记录被删除（包括随 Session 级联删除）后仍需要这里的 href 删除远端事件
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "pomodoro_record_exports")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub record_id: i32,
+    /// VEVENT 的 UID
+    pub uid: String,
+    /// 导出到的日历集合 URL
+    pub calendar_url: String,
+    pub href: String,
+    pub etag: Option<String>,
+    /// 上次上传内容的 MD5，内容变化时重新上传
+    pub content_hash: String,
+    pub exported_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,6 @@
 pub mod entities; // 新的实体结构
 pub mod entity; // 保留旧的 entity.rs 用于兼容
 pub mod migration;
+pub mod record_exports_migration;
 pub mod restructure_migration;
 pub mod session_todo_links_migration;
@@ -0,0 +1,39 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema};
+use sea_orm_migration::prelude::*;
+
+use super::entities::record_exports;
+
+/// 创建 pomodoro_record_exports 表（专注记录导出为 VEVENT 后的远端地址）
+#[derive(Debug, Clone, Copy)]
+pub struct RecordExportsMigration;
+
+impl MigrationName for RecordExportsMigration {
+    fn name(&self) -> &str {
+        "m20251225_000001_create_pomodoro_record_exports"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for RecordExportsMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        let mut create_table = schema.create_table_from_entity(record_exports::Entity);
+        create_table.if_not_exists();
+        db.execute(backend.build(&create_table))
+            .await
+            .context("Failed to create pomodoro_record_exports table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(record_exports::Entity).to_owned())
+            .await
+    }
+}
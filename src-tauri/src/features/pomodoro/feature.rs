@@ -49,6 +49,9 @@ impl Feature for PomodoroFeature {
             "pomodoro_status",
             "pomodoro_get_config",
             "pomodoro_set_config",
+            "pomodoro_get_calendar_export",
+            "pomodoro_set_calendar_export",
+            "pomodoro_export_calendar_now",
         ]
     }
 
@@ -89,6 +92,12 @@ impl Feature for PomodoroFeature {
             let migration = super::data::session_todo_links_migration::SessionTodoLinksMigration;
             Box::pin(async move { migration.up(manager).await })
         });
+
+        // 专注记录导出（CalDAV VEVENT）跟踪表迁移
+        registry.register_migration("pomodoro_record_exports_migration", |manager| {
+            let migration = super::data::record_exports_migration::RecordExportsMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
     }
 }
 
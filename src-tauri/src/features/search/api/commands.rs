@@ -0,0 +1,15 @@
+use tauri::State;
+
+use crate::core::AppState;
+use crate::features::search::core::service::{self, SearchQuery, SearchResult};
+
+/// 搜索 todo（含子任务）和专注会话备注
+#[tauri::command]
+pub async fn search(
+    state: State<'_, AppState>,
+    query: SearchQuery,
+) -> Result<Vec<SearchResult>, String> {
+    service::search(state.db(), &query)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,23 @@
+use anyhow::Context;
+use serde_json::json;
+
+use crate::features::search::core::service::{self, SearchQuery};
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::infrastructure::webserver::HandlerRegistry;
+
+/// 注册 Search Feature 的 WebSocket handlers
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub fn register_handlers(registry: &mut HandlerRegistry) {
+    // 全文搜索，参数与 search 命令的 query 相同
+    registry.register_call("search.query", |_method, params, ctx| {
+        Box::pin(async move {
+            let query: SearchQuery =
+                serde_json::from_value(params).context("Invalid search query format")?;
+            let results = service::search(ctx.db(), &query)
+                .await
+                .context("Failed to search")?;
+
+            Ok(json!(results))
+        })
+    });
+}
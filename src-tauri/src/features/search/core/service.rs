@@ -0,0 +1,475 @@
+// 全文搜索
+//
+// 搜索词按空白拆分，所有词都要匹配（不区分先后）。trigram 索引只能匹配至少 3 个字符的词，
+// 较短的词（例如两个字的中文词）对源表做子串匹配；有长词时按 bm25 排序，否则按最近修改排序。
+// 结果以 HTML 返回：原文已转义，匹配部分用 <mark> 包裹
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, QueryResult, Statement, Value};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+/// trigram 分词能匹配的最短词长（字符数）
+const MIN_INDEXED_TERM_CHARS: usize = 3;
+const MAX_TERMS: usize = 10;
+/// 摘要中匹配位置前后保留的字符数
+const SNIPPET_BEFORE: usize = 20;
+const SNIPPET_AFTER: usize = 60;
+
+const MARK_START: &str = "<mark>";
+const MARK_END: &str = "</mark>";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    pub query: String,
+    /// 搜索范围，为空时搜索全部
+    #[serde(default)]
+    pub kinds: Vec<SearchKind>,
+    #[serde(default = "default_include_completed")]
+    pub include_completed: bool,
+    pub limit: Option<u32>,
+}
+
+fn default_include_completed() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchKind {
+    /// Todo 和子任务（标题、描述、地点）
+    Todo,
+    /// 专注会话备注
+    Session,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    pub id: i32,
+    /// 子任务的父任务
+    pub parent_id: Option<i32>,
+    /// Todo 标题或会话备注（高亮）
+    pub title: String,
+    /// Todo 描述或地点中匹配位置附近的摘要（高亮）
+    pub snippet: Option<String>,
+    pub completed: Option<bool>,
+    /// bm25 得分，越小越相关（只有短词时为 0）
+    pub rank: f64,
+    pub updated_at: String,
+}
+
+pub async fn search(db: &DatabaseConnection, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+    let terms = parse_terms(&query.query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let wants = |kind| query.kinds.is_empty() || query.kinds.contains(&kind);
+
+    let mut results = Vec::new();
+    if wants(SearchKind::Todo) {
+        results.extend(search_todos(db, &terms, query.include_completed, limit).await?);
+    }
+    if wants(SearchKind::Session) {
+        results.extend(search_sessions(db, &terms, limit).await?);
+    }
+
+    results.sort_by(|a, b| {
+        a.rank
+            .total_cmp(&b.rank)
+            .then_with(|| b.updated_at.cmp(&a.updated_at))
+    });
+    results.truncate(limit as usize);
+    Ok(results)
+}
+
+/// 拆分搜索词（去掉 FTS5 语法中的双引号，忽略重复的词）
+fn parse_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace() {
+        let term = term.replace('"', "");
+        if !term.is_empty()
+            && !terms
+                .iter()
+                .any(|t| t.to_lowercase() == term.to_lowercase())
+        {
+            terms.push(term);
+        }
+    }
+    terms.truncate(MAX_TERMS);
+    terms
+}
+
+/// 按词长分组生成匹配条件：长词组成 FTS5 MATCH 表达式，短词对源表列做 LIKE 匹配
+struct MatchClause {
+    fts_match: Option<String>,
+    like_terms: Vec<String>,
+}
+
+impl MatchClause {
+    fn new(terms: &[String]) -> Self {
+        let (indexed, short): (Vec<&String>, Vec<&String>) = terms
+            .iter()
+            .partition(|term| term.chars().count() >= MIN_INDEXED_TERM_CHARS);
+
+        Self {
+            fts_match: (!indexed.is_empty()).then(|| {
+                indexed
+                    .iter()
+                    .map(|term| format!("\"{term}\""))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }),
+            like_terms: short
+                .into_iter()
+                .map(|term| {
+                    let escaped = term
+                        .replace('\\', "\\\\")
+                        .replace('%', "\\%")
+                        .replace('_', "\\_");
+                    format!("%{escaped}%")
+                })
+                .collect(),
+        }
+    }
+
+    /// 生成 FROM 之后的 JOIN 和 WHERE 条件，返回 (join, conditions, rank 表达式, 参数)
+    fn to_sql(
+        &self,
+        fts_table: &str,
+        source_alias: &str,
+        columns: &[&str],
+        weights: &str,
+    ) -> (String, Vec<String>, String, Vec<Value>) {
+        let mut values = Vec::new();
+        let mut conditions = Vec::new();
+        let (join, rank) = match &self.fts_match {
+            Some(expression) => {
+                conditions.push(format!("{fts_table} MATCH ?"));
+                values.push(expression.clone().into());
+                (
+                    format!("JOIN {fts_table} ON {fts_table}.rowid = {source_alias}.id"),
+                    format!("bm25({fts_table}, {weights})"),
+                )
+            }
+            None => (String::new(), "0.0".to_string()),
+        };
+
+        for pattern in &self.like_terms {
+            let any_column = columns
+                .iter()
+                .map(|column| format!("{source_alias}.{column} LIKE ? ESCAPE '\\'"))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            conditions.push(format!("({any_column})"));
+            values.extend(columns.iter().map(|_| Value::from(pattern.clone())));
+        }
+
+        (join, conditions, rank, values)
+    }
+}
+
+async fn search_todos(
+    db: &DatabaseConnection,
+    terms: &[String],
+    include_completed: bool,
+    limit: u32,
+) -> Result<Vec<SearchResult>> {
+    let (join, mut conditions, rank, mut values) = MatchClause::new(terms).to_sql(
+        "todos_fts",
+        "t",
+        &["title", "description", "location"],
+        "10.0, 2.0, 1.0",
+    );
+    conditions.push("t.deleted_at IS NULL".to_string());
+    if !include_completed {
+        conditions.push("t.completed = 0".to_string());
+    }
+    values.push(limit.into());
+
+    let sql = format!(
+        "SELECT t.id, t.parent_id, t.title, t.description, t.location, t.completed, \
+         t.updated_at, {rank} AS rank \
+         FROM todos t {join} WHERE {} ORDER BY rank, t.updated_at DESC LIMIT ?",
+        conditions.join(" AND ")
+    );
+    let rows = query_rows(db, sql, values)
+        .await
+        .context("failed to search todos")?;
+
+    rows.iter()
+        .map(|row| {
+            let title: String = row.try_get("", "title")?;
+            let description: Option<String> = row.try_get("", "description")?;
+            let location: Option<String> = row.try_get("", "location")?;
+            let snippet = [description, location]
+                .into_iter()
+                .flatten()
+                .find_map(|text| snippet(&text, terms));
+
+            Ok(SearchResult {
+                kind: SearchKind::Todo,
+                id: row.try_get("", "id")?,
+                parent_id: row.try_get("", "parent_id")?,
+                title: highlight(&title, terms),
+                snippet,
+                completed: Some(row.try_get("", "completed")?),
+                rank: row.try_get("", "rank")?,
+                updated_at: row.try_get::<DateTime<Utc>>("", "updated_at")?.to_rfc3339(),
+            })
+        })
+        .collect()
+}
+
+async fn search_sessions(
+    db: &DatabaseConnection,
+    terms: &[String],
+    limit: u32,
+) -> Result<Vec<SearchResult>> {
+    let (join, mut conditions, rank, mut values) =
+        MatchClause::new(terms).to_sql("pomodoro_sessions_fts", "s", &["note"], "1.0");
+    conditions.push("s.note IS NOT NULL".to_string());
+    values.push(limit.into());
+
+    let sql = format!(
+        "SELECT s.id, s.note, s.updated_at, {rank} AS rank \
+         FROM pomodoro_sessions s {join} WHERE {} ORDER BY rank, s.updated_at DESC LIMIT ?",
+        conditions.join(" AND ")
+    );
+    let rows = query_rows(db, sql, values)
+        .await
+        .context("failed to search session notes")?;
+
+    rows.iter()
+        .map(|row| {
+            let note: String = row.try_get("", "note")?;
+            Ok(SearchResult {
+                kind: SearchKind::Session,
+                id: row.try_get("", "id")?,
+                parent_id: None,
+                title: snippet(&note, terms).unwrap_or_else(|| highlight(&note, terms)),
+                snippet: None,
+                completed: None,
+                rank: row.try_get("", "rank")?,
+                updated_at: row.try_get::<DateTime<Utc>>("", "updated_at")?.to_rfc3339(),
+            })
+        })
+        .collect()
+}
+
+async fn query_rows(
+    db: &DatabaseConnection,
+    sql: String,
+    values: Vec<Value>,
+) -> Result<Vec<QueryResult>> {
+    let backend = db.get_database_backend();
+    match db
+        .query_all(Statement::from_sql_and_values(backend, sql, values))
+        .await
+    {
+        Ok(rows) => Ok(rows),
+        Err(err) if err.to_string().contains("fts5") => {
+            bail!("invalid search query: {err}")
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// 每个字符是否属于某个搜索词的匹配（不区分大小写）
+fn match_mask(chars: &[char], terms: &[String]) -> Vec<bool> {
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let lowered: Vec<char> = chars.iter().map(|&c| fold(c)).collect();
+    let mut mask = vec![false; chars.len()];
+
+    for term in terms {
+        let term: Vec<char> = term.chars().map(fold).collect();
+        if term.is_empty() || term.len() > lowered.len() {
+            continue;
+        }
+        for start in 0..=lowered.len() - term.len() {
+            if lowered[start..start + term.len()] == term[..] {
+                mask[start..start + term.len()].fill(true);
+            }
+        }
+    }
+    mask
+}
+
+/// 转义 HTML 并用 <mark> 包裹匹配部分
+fn highlight(text: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mask = match_mask(&chars, terms);
+    render(&chars, &mask)
+}
+
+/// 第一个匹配位置附近的高亮摘要，没有匹配时返回 None
+fn snippet(text: &str, terms: &[String]) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mask = match_mask(&chars, terms);
+    let first = mask.iter().position(|&matched| matched)?;
+
+    let start = first.saturating_sub(SNIPPET_BEFORE);
+    let end = (first + SNIPPET_AFTER).min(chars.len());
+    let mut result = String::new();
+    if start > 0 {
+        result.push('…');
+    }
+    result.push_str(&render(&chars[start..end], &mask[start..end]));
+    if end < chars.len() {
+        result.push('…');
+    }
+    Some(result)
+}
+
+fn render(chars: &[char], mask: &[bool]) -> String {
+    let mut result = String::with_capacity(chars.len() + 16);
+    let mut marked = false;
+    for (&c, &matched) in chars.iter().zip(mask) {
+        if matched != marked {
+            result.push_str(if matched { MARK_START } else { MARK_END });
+            marked = matched;
+        }
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            _ => result.push(c),
+        }
+    }
+    if marked {
+        result.push_str(MARK_END);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::pomodoro::data::{
+        migration::PomodoroMigration, restructure_migration::PomodoroRestructureMigration,
+    };
+    use crate::features::search::data::migration::SearchIndexMigration;
+    use crate::features::todo::core::service;
+    use crate::features::todo::data::{
+        add_order_index_migration::AddOrderIndexMigration,
+        add_subtask_migration::AddSubtaskMigration, dependency_migration::DependencyMigration,
+        entity, journal_migration::JournalMigration, migration::TodoMigration,
+        reminder_migration::TodoReminderMigration,
+    };
+    use sea_orm::{ActiveModelTrait, ActiveValue::Set, Database, EntityTrait};
+    use sea_orm_migration::{MigrationTrait, SchemaManager};
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery {
+            query: text.to_string(),
+            kinds: Vec::new(),
+            include_completed: true,
+            limit: None,
+        }
+    }
+
+    async fn create(db: &DatabaseConnection, title: &str, description: &str) -> i32 {
+        let todo = service::create_todo(db, Some(title.to_string()))
+            .await
+            .unwrap();
+        let mut active: entity::ActiveModel = entity::Entity::find_by_id(todo.id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        active.description = Set(Some(description.to_string()));
+        active.update(db).await.unwrap().id
+    }
+
+    #[tokio::test]
+    async fn search_follows_source_tables_and_highlights_matches() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let manager = SchemaManager::new(&db);
+        TodoMigration.up(&manager).await.unwrap();
+        AddSubtaskMigration.up(&manager).await.unwrap();
+        AddOrderIndexMigration.up(&manager).await.unwrap();
+        TodoReminderMigration.up(&manager).await.unwrap();
+        JournalMigration.up(&manager).await.unwrap();
+        DependencyMigration.up(&manager).await.unwrap();
+        PomodoroMigration.up(&manager).await.unwrap();
+        PomodoroRestructureMigration.up(&manager).await.unwrap();
+
+        // 建索引之前已有的数据由迁移重建
+        let report = create(&db, "写周报", "整理本周的 Release notes").await;
+        SearchIndexMigration.up(&manager).await.unwrap();
+        let milk = create(&db, "Buy milk", "skim <2%>").await;
+        db.execute(Statement::from_string(
+            db.get_database_backend(),
+            "INSERT INTO pomodoro_sessions (note, archived, created_at, updated_at) \
+             VALUES ('review release plan', 0, '2026-01-01T00:00:00+00:00', \
+             '2026-01-01T00:00:00+00:00')"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+
+        // 长词走 FTS 索引，短词（中文两个字）按子串匹配
+        let results = search(&db, &query("周报 release")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, report);
+        assert_eq!(results[0].title, "写<mark>周报</mark>");
+        assert_eq!(
+            results[0].snippet.as_deref(),
+            Some("整理本周的 <mark>Release</mark> notes")
+        );
+
+        let results = search(&db, &query("RELEASE")).await.unwrap();
+        assert_eq!(results.len(), 2);
+        let session = results
+            .iter()
+            .find(|result| result.kind == SearchKind::Session)
+            .unwrap();
+        assert_eq!(session.title, "review <mark>release</mark> plan");
+
+        let only_todos = SearchQuery {
+            kinds: vec![SearchKind::Todo],
+            ..query("release")
+        };
+        assert_eq!(search(&db, &only_todos).await.unwrap().len(), 1);
+
+        // 原文中的 HTML 被转义
+        let results = search(&db, &query("skim")).await.unwrap();
+        assert_eq!(results[0].id, milk);
+        assert_eq!(
+            results[0].snippet.as_deref(),
+            Some("<mark>skim</mark> &lt;2%&gt;")
+        );
+
+        // 修改后旧内容不再命中；软删除的 todo 不出现在结果中
+        let mut active: entity::ActiveModel = entity::Entity::find_by_id(milk)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        active.title = Set("Buy bread".to_string());
+        active.update(&db).await.unwrap();
+        assert!(search(&db, &query("milk")).await.unwrap().is_empty());
+        assert_eq!(search(&db, &query("bread")).await.unwrap().len(), 1);
+
+        let mut active: entity::ActiveModel = entity::Entity::find_by_id(report)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        active.deleted_at = Set(Some(Utc::now()));
+        active.update(&db).await.unwrap();
+        assert!(search(&db, &query("周报")).await.unwrap().is_empty());
+
+        assert!(search(&db, &query("  \"\" ")).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use sea_orm::{ConnectionTrait, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+/// 全文索引迁移
+///
+/// 为 todos（标题、描述、地点）和 pomodoro_sessions（备注）创建 FTS5 外部内容索引，
+/// 由触发器在源表增删改时同步。使用 trigram 分词，中文等没有空格分词的文本也能按子串匹配。
+/// 索引表首次创建时从源表重建索引
+#[derive(Debug, Clone, Copy)]
+pub struct SearchIndexMigration;
+
+impl MigrationName for SearchIndexMigration {
+    fn name(&self) -> &str {
+        "m20251230_000001_search_index"
+    }
+}
+
+const TODOS_FTS: &str = "todos_fts";
+const SESSIONS_FTS: &str = "pomodoro_sessions_fts";
+
+const CREATE_STATEMENTS: &[&str] = &[
+    "CREATE VIRTUAL TABLE IF NOT EXISTS todos_fts USING fts5(
+        title, description, location,
+        content='todos', content_rowid='id', tokenize='trigram'
+    )",
+    "CREATE TRIGGER IF NOT EXISTS todos_fts_insert AFTER INSERT ON todos BEGIN
+        INSERT INTO todos_fts(rowid, title, description, location)
+        VALUES (new.id, new.title, new.description, new.location);
+    END",
+    "CREATE TRIGGER IF NOT EXISTS todos_fts_delete AFTER DELETE ON todos BEGIN
+        INSERT INTO todos_fts(todos_fts, rowid, title, description, location)
+        VALUES ('delete', old.id, old.title, old.description, old.location);
+    END",
+    "CREATE TRIGGER IF NOT EXISTS todos_fts_update
+    AFTER UPDATE OF title, description, location ON todos BEGIN
+        INSERT INTO todos_fts(todos_fts, rowid, title, description, location)
+        VALUES ('delete', old.id, old.title, old.description, old.location);
+        INSERT INTO todos_fts(rowid, title, description, location)
+        VALUES (new.id, new.title, new.description, new.location);
+    END",
+    "CREATE VIRTUAL TABLE IF NOT EXISTS pomodoro_sessions_fts USING fts5(
+        note,
+        content='pomodoro_sessions', content_rowid='id', tokenize='trigram'
+    )",
+    "CREATE TRIGGER IF NOT EXISTS pomodoro_sessions_fts_insert
+    AFTER INSERT ON pomodoro_sessions BEGIN
+        INSERT INTO pomodoro_sessions_fts(rowid, note) VALUES (new.id, new.note);
+    END",
+    "CREATE TRIGGER IF NOT EXISTS pomodoro_sessions_fts_delete
+    AFTER DELETE ON pomodoro_sessions BEGIN
+        INSERT INTO pomodoro_sessions_fts(pomodoro_sessions_fts, rowid, note)
+        VALUES ('delete', old.id, old.note);
+    END",
+    "CREATE TRIGGER IF NOT EXISTS pomodoro_sessions_fts_update
+    AFTER UPDATE OF note ON pomodoro_sessions BEGIN
+        INSERT INTO pomodoro_sessions_fts(pomodoro_sessions_fts, rowid, note)
+        VALUES ('delete', old.id, old.note);
+        INSERT INTO pomodoro_sessions_fts(rowid, note) VALUES (new.id, new.note);
+    END",
+];
+
+async fn table_exists<C: ConnectionTrait>(db: &C, table: &str) -> Result<bool, DbErr> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [table.into()],
+        ))
+        .await?;
+    Ok(row.is_some())
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for SearchIndexMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let todos_indexed = table_exists(db, TODOS_FTS).await?;
+        let sessions_indexed = table_exists(db, SESSIONS_FTS).await?;
+
+        for stmt in CREATE_STATEMENTS {
+            db.execute(Statement::from_string(backend, *stmt)).await?;
+        }
+
+        // 索引建立之前已有的数据
+        for (table, existed) in [(TODOS_FTS, todos_indexed), (SESSIONS_FTS, sessions_indexed)] {
+            if !existed {
+                db.execute(Statement::from_string(
+                    backend,
+                    format!("INSERT INTO {table}({table}) VALUES ('rebuild')"),
+                ))
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        for table in [TODOS_FTS, SESSIONS_FTS] {
+            // 触发器建在源表上，删除索引表时不会一起删除
+            for suffix in ["insert", "delete", "update"] {
+                db.execute(Statement::from_string(
+                    backend,
+                    format!("DROP TRIGGER IF EXISTS {table}_{suffix}"),
+                ))
+                .await?;
+            }
+            db.execute(Statement::from_string(
+                backend,
+                format!("DROP TABLE IF EXISTS {table}"),
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+}
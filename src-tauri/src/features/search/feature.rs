@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sea_orm_migration::MigrationTrait;
+
+use crate::core::{AppState, Feature};
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::infrastructure::webserver::HandlerRegistry;
+
+use super::data::migration::SearchIndexMigration;
+
+/// 全文搜索 Feature
+///
+/// 维护 todo 和专注会话备注的 FTS5 索引，提供排序、高亮的搜索结果
+pub struct SearchFeature;
+
+impl SearchFeature {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+}
+
+#[async_trait]
+impl Feature for SearchFeature {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &'static str {
+        "search"
+    }
+
+    fn command_names(&self) -> Vec<&'static str> {
+        vec!["search"]
+    }
+
+    fn register_database(&self, registry: &mut crate::infrastructure::database::DatabaseRegistry) {
+        // 索引建在 todos 和 pomodoro_sessions 上，须在 Todo / Pomodoro 的迁移之后注册
+        registry.register_migration("search_index_migration", |manager| {
+            let migration = SearchIndexMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    fn register_ws_handlers(&self, registry: &mut HandlerRegistry) {
+        super::api::handlers::register_handlers(registry);
+    }
+
+    async fn initialize(&self, _app_state: &AppState) -> Result<()> {
+        println!("[SearchFeature] Initialized");
+        Ok(())
+    }
+}
+
+impl Default for SearchFeature {
+    fn default() -> Self {
+        Self
+    }
+}
@@ -0,0 +1,6 @@
+pub mod api;
+pub mod core;
+pub mod data;
+mod feature;
+
+pub use feature::SearchFeature;
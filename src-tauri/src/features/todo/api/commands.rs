@@ -2,7 +2,20 @@ use serde::Deserialize;
 use tauri::State;
 
 use crate::core::AppState;
-use crate::features::todo::core::{models::Todo, service};
+use crate::features::todo::core::{
+    catch_up::{CatchUpPolicy, CatchUpPolicyService},
+    dependencies::{self, TodoDependencies},
+    journal::{self, JournalEntry, JournalStatus},
+    models::{ReminderInput, Todo, TodoReminder},
+    query::{self, TodoQuery, TodoQueryPage},
+    reminders,
+    saved_filters::{self, SavedFilter},
+    service,
+    trash::{self, TrashedTodo},
+};
+use crate::features::todo::sync::ical_file::{
+    self, IcsExportFilter, IcsImportMode, IcsImportSummary,
+};
 
 #[derive(Debug, Default, Deserialize)]
 pub struct CreateTodoPayload {
@@ -32,6 +45,46 @@ pub struct UpdateTodoDetailsPayload {
     pub timezone: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SnoozeTodoPayload {
+    pub id: i32,
+    /// 推迟的分钟数
+    pub minutes: Option<i32>,
+    /// 推迟到的时间（RFC3339），优先于 minutes
+    pub until: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTodoRemindersPayload {
+    pub todo_id: i32,
+    #[serde(default)]
+    pub reminders: Vec<ReminderInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveTodoFilterPayload {
+    /// 为空时新建
+    pub id: Option<i32>,
+    pub name: String,
+    #[serde(default)]
+    pub query: TodoQuery,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunTodoFilterPayload {
+    pub id: i32,
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportTodosIcsPayload {
+    /// .ics 文件内容
+    pub content: String,
+    #[serde(default)]
+    pub mode: IcsImportMode,
+}
+
 /// 列出所有 todo
 #[tauri::command]
 pub async fn list_todos(state: State<'_, AppState>) -> Result<Vec<Todo>, String> {
@@ -60,6 +113,9 @@ pub async fn create_todo(
         scheduler.reschedule().await;
     }
 
+    // 安排推送到 CalDAV（短时间内的多次修改合并为一次同步）
+    state.caldav_sync_manager().schedule_push();
+
     Ok(result)
 }
 
@@ -81,6 +137,9 @@ pub async fn update_todo(
         scheduler.reschedule().await;
     }
 
+    // 安排推送到 CalDAV（短时间内的多次修改合并为一次同步）
+    state.caldav_sync_manager().schedule_push();
+
     Ok(result)
 }
 
@@ -99,6 +158,9 @@ pub async fn delete_todo(state: State<'_, AppState>, id: i32) -> Result<(), Stri
         scheduler.reschedule().await;
     }
 
+    // 安排推送到 CalDAV（短时间内的多次修改合并为一次同步）
+    state.caldav_sync_manager().schedule_push();
+
     Ok(())
 }
 
@@ -133,6 +195,9 @@ pub async fn update_todo_details(
         scheduler.reschedule().await;
     }
 
+    // 安排推送到 CalDAV（短时间内的多次修改合并为一次同步）
+    state.caldav_sync_manager().schedule_push();
+
     Ok(result)
 }
 
@@ -151,9 +216,13 @@ pub async fn update_todo_parent(
     id: i32,
     parent_id: Option<i32>,
 ) -> Result<Todo, String> {
-    service::update_parent(state.db(), id, parent_id)
+    let result = service::update_parent(state.db(), id, parent_id)
         .await
-        .map_err(|err| err.to_string())
+        .map_err(|err| err.to_string())?;
+
+    state.caldav_sync_manager().schedule_push();
+
+    Ok(result)
 }
 
 /// 重新排序任务
@@ -165,7 +234,308 @@ pub async fn reorder_todo(
     after_id: Option<i32>,
     new_parent_id: Option<i32>,
 ) -> Result<Todo, String> {
-    service::reorder_todo(state.db(), id, before_id, after_id, new_parent_id)
+    let result = service::reorder_todo(state.db(), id, before_id, after_id, new_parent_id)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    state.caldav_sync_manager().schedule_push();
+
+    Ok(result)
+}
+
+/// 获取 todo 的依赖和依赖它的 todo
+#[tauri::command]
+pub async fn get_todo_dependencies(
+    state: State<'_, AppState>,
+    todo_id: i32,
+) -> Result<TodoDependencies, String> {
+    dependencies::get_dependencies(state.db(), todo_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 添加依赖：todo_id 需要等 depends_on_id 完成
+#[tauri::command]
+pub async fn add_todo_dependency(
+    state: State<'_, AppState>,
+    todo_id: i32,
+    depends_on_id: i32,
+) -> Result<Todo, String> {
+    let result = dependencies::add_dependency(state.db(), todo_id, depends_on_id)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    state.caldav_sync_manager().schedule_push();
+
+    Ok(result)
+}
+
+/// 移除依赖
+#[tauri::command]
+pub async fn remove_todo_dependency(
+    state: State<'_, AppState>,
+    todo_id: i32,
+    depends_on_id: i32,
+) -> Result<Todo, String> {
+    let result = dependencies::remove_dependency(state.db(), todo_id, depends_on_id)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    state.caldav_sync_manager().schedule_push();
+
+    Ok(result)
+}
+
+/// 下一步可执行的 todo
+#[tauri::command]
+pub async fn list_next_actionable_todos(
+    state: State<'_, AppState>,
+    limit: Option<u64>,
+) -> Result<Vec<Todo>, String> {
+    dependencies::next_actionable(state.db(), limit)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 获取 todo 的全部提醒
+#[tauri::command]
+pub async fn get_todo_reminders(
+    state: State<'_, AppState>,
+    todo_id: i32,
+) -> Result<Vec<TodoReminder>, String> {
+    reminders::list_reminders(state.db(), todo_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 设置 todo 的全部提醒（整体替换）
+#[tauri::command]
+pub async fn set_todo_reminders(
+    state: State<'_, AppState>,
+    payload: SetTodoRemindersPayload,
+) -> Result<Vec<TodoReminder>, String> {
+    let result = reminders::set_reminders(state.db(), payload.todo_id, payload.reminders)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    // 触发调度器重新规划提醒
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+
+    // 安排推送到 CalDAV（短时间内的多次修改合并为一次同步）
+    state.caldav_sync_manager().schedule_push();
+
+    Ok(result)
+}
+
+/// 稍后提醒 todo
+#[tauri::command]
+pub async fn snooze_todo(
+    state: State<'_, AppState>,
+    payload: SnoozeTodoPayload,
+) -> Result<Todo, String> {
+    let result = service::snooze_todo(state.db(), payload.id, payload.minutes, payload.until)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    // 触发调度器重新规划提醒
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+
+    Ok(result)
+}
+
+/// 获取错过提醒的补发策略
+#[tauri::command]
+pub async fn get_reminder_catch_up_policy(
+    state: State<'_, AppState>,
+) -> Result<CatchUpPolicy, String> {
+    CatchUpPolicyService::get_policy(state.db())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 设置错过提醒的补发策略（下次启动时生效）
+#[tauri::command]
+pub async fn set_reminder_catch_up_policy(
+    state: State<'_, AppState>,
+    policy: CatchUpPolicy,
+) -> Result<CatchUpPolicy, String> {
+    CatchUpPolicyService::set_policy(state.db(), &policy)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(policy)
+}
+
+/// 按条件查询 todo（筛选、排序、分页）
+#[tauri::command]
+pub async fn query_todos(
+    state: State<'_, AppState>,
+    query: Option<TodoQuery>,
+) -> Result<TodoQueryPage, String> {
+    query::query_todos(state.db(), &query.unwrap_or_default())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 列出保存的筛选条件
+#[tauri::command]
+pub async fn list_todo_filters(state: State<'_, AppState>) -> Result<Vec<SavedFilter>, String> {
+    saved_filters::list_filters(state.db())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 新建或更新保存的筛选条件
+#[tauri::command]
+pub async fn save_todo_filter(
+    state: State<'_, AppState>,
+    payload: SaveTodoFilterPayload,
+) -> Result<SavedFilter, String> {
+    saved_filters::save_filter(state.db(), payload.id, &payload.name, &payload.query)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 删除保存的筛选条件
+#[tauri::command]
+pub async fn delete_todo_filter(state: State<'_, AppState>, id: i32) -> Result<(), String> {
+    saved_filters::delete_filter(state.db(), id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 执行保存的筛选条件
+#[tauri::command]
+pub async fn run_todo_filter(
+    state: State<'_, AppState>,
+    payload: RunTodoFilterPayload,
+) -> Result<TodoQueryPage, String> {
+    saved_filters::run_filter(state.db(), payload.id, payload.page, payload.page_size)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 导出 todo 为 .ics 文件内容
+#[tauri::command]
+pub async fn export_todos_ics(
+    state: State<'_, AppState>,
+    payload: Option<IcsExportFilter>,
+) -> Result<String, String> {
+    ical_file::export_ics(state.db(), &payload.unwrap_or_default())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 从 .ics 文件内容导入 todo
+#[tauri::command]
+pub async fn import_todos_ics(
+    state: State<'_, AppState>,
+    payload: ImportTodosIcsPayload,
+) -> Result<IcsImportSummary, String> {
+    let summary = ical_file::import_ics(state.db(), &payload.content, payload.mode)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+    state.caldav_sync_manager().schedule_push();
+
+    Ok(summary)
+}
+
+/// 撤销最近一次 todo 操作，没有可撤销的操作时返回 None
+#[tauri::command]
+pub async fn undo_todo_change(state: State<'_, AppState>) -> Result<Option<JournalEntry>, String> {
+    let entry = journal::undo(state.db())
+        .await
+        .map_err(|err| err.to_string())?;
+    if entry.is_some() {
+        reload_after_journal(&state).await;
+    }
+    Ok(entry)
+}
+
+/// 重做最近一次撤销的 todo 操作，没有可重做的操作时返回 None
+#[tauri::command]
+pub async fn redo_todo_change(state: State<'_, AppState>) -> Result<Option<JournalEntry>, String> {
+    let entry = journal::redo(state.db())
+        .await
+        .map_err(|err| err.to_string())?;
+    if entry.is_some() {
+        reload_after_journal(&state).await;
+    }
+    Ok(entry)
+}
+
+async fn reload_after_journal(state: &AppState) {
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+    state.caldav_sync_manager().schedule_push();
+}
+
+/// 获取当前可撤销和可重做的操作
+#[tauri::command]
+pub async fn get_todo_history(state: State<'_, AppState>) -> Result<JournalStatus, String> {
+    journal::status(state.db())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 获取回收站保留天数
+#[tauri::command]
+pub async fn get_todo_trash_retention(state: State<'_, AppState>) -> Result<u32, String> {
+    trash::get_retention_days(state.db())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 设置回收站保留天数
+#[tauri::command]
+pub async fn set_todo_trash_retention(state: State<'_, AppState>, days: u32) -> Result<(), String> {
+    trash::set_retention_days(state.db(), days)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 列出回收站中的 todo（含子任务）
+#[tauri::command]
+pub async fn list_todo_trash(state: State<'_, AppState>) -> Result<Vec<TrashedTodo>, String> {
+    trash::list(state.db()).await.map_err(|err| err.to_string())
+}
+
+/// 从回收站恢复 todo 及其子任务
+#[tauri::command]
+pub async fn restore_todo(state: State<'_, AppState>, id: i32) -> Result<Todo, String> {
+    let todo = service::restore_todo(state.db(), id)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+    state.caldav_sync_manager().schedule_push();
+
+    Ok(todo)
+}
+
+/// 彻底删除回收站中的 todo 及其子任务，返回删除的数量
+#[tauri::command]
+pub async fn purge_todo(state: State<'_, AppState>, id: i32) -> Result<usize, String> {
+    trash::purge(state.db(), id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 清空回收站，返回删除的数量
+#[tauri::command]
+pub async fn empty_todo_trash(state: State<'_, AppState>) -> Result<usize, String> {
+    trash::empty(state.db())
         .await
         .map_err(|err| err.to_string())
 }
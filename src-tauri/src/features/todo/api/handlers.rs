@@ -6,7 +6,18 @@ use tauri::Manager;
 use crate::infrastructure::webserver;
 
 use super::notifications;
-use crate::features::todo::core::service;
+use crate::features::todo::core::{
+    dependencies, journal,
+    models::ReminderInput,
+    query::{self, TodoQuery},
+    reminders, saved_filters, service, trash,
+};
+use crate::features::todo::sync::{
+    conflicts::{self, ConflictResolution},
+    history,
+    ical_file::{self, IcsExportFilter, IcsImportMode},
+    sync::SyncReason,
+};
 
 /// 注册 Todo Feature 的所有 WebSocket handlers
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
@@ -16,6 +27,14 @@ pub fn register_handlers(registry: &mut webserver::HandlerRegistry) {
         notifications::TODO_DUE_EVENT,
         "Todo 到期提醒事件 - 当待办事项到期时广播",
     );
+    registry.register_event(
+        notifications::TODO_MISSED_EVENT,
+        "Todo 错过提醒汇总事件 - 启动时合并应用关闭期间错过的提醒后广播",
+    );
+    registry.register_event(
+        notifications::TODO_SYNC_CONFLICT_EVENT,
+        "Todo 同步冲突事件 - CalDAV 同步时本地和远端修改了同一字段后广播",
+    );
     registry.register_event(
         notifications::TODO_CHANGES_EVENT,
         "Todo 数据变更事件 - 创建/更新/删除时广播",
@@ -78,6 +97,9 @@ pub fn register_handlers(registry: &mut webserver::HandlerRegistry) {
                 if let Some(scheduler) = state.todo_scheduler() {
                     scheduler.reschedule().await;
                 }
+
+                // 安排推送到 CalDAV
+                state.caldav_sync_manager().schedule_push();
             }
 
             Ok(json!(todo))
@@ -121,6 +143,9 @@ pub fn register_handlers(registry: &mut webserver::HandlerRegistry) {
                 if let Some(scheduler) = state.todo_scheduler() {
                     scheduler.reschedule().await;
                 }
+
+                // 安排推送到 CalDAV
+                state.caldav_sync_manager().schedule_push();
             }
 
             Ok(json!(todo))
@@ -158,6 +183,9 @@ pub fn register_handlers(registry: &mut webserver::HandlerRegistry) {
                 if let Some(scheduler) = state.todo_scheduler() {
                     scheduler.reschedule().await;
                 }
+
+                // 安排推送到 CalDAV
+                state.caldav_sync_manager().schedule_push();
             }
 
             Ok(json!({"success": true}))
@@ -269,9 +297,522 @@ pub fn register_handlers(registry: &mut webserver::HandlerRegistry) {
                 if let Some(scheduler) = state.todo_scheduler() {
                     scheduler.reschedule().await;
                 }
+
+                // 安排推送到 CalDAV
+                state.caldav_sync_manager().schedule_push();
+            }
+
+            Ok(json!(todo))
+        })
+    });
+
+    // 获取待办的全部提醒
+    registry.register_call("todo.get_reminders", |_method, params, ctx| {
+        Box::pin(async move {
+            let todo_id = params
+                .get("todo_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid todo_id")? as i32;
+
+            let reminders = reminders::list_reminders(ctx.db(), todo_id)
+                .await
+                .context("Failed to get todo reminders")?;
+
+            Ok(json!(reminders))
+        })
+    });
+
+    // 设置待办的全部提醒（整体替换）
+    registry.register_call("todo.set_reminders", |_method, params, ctx| {
+        Box::pin(async move {
+            let todo_id = params
+                .get("todo_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid todo_id")? as i32;
+
+            let inputs: Vec<ReminderInput> = match params.get("reminders") {
+                Some(value) => {
+                    serde_json::from_value(value.clone()).context("Invalid reminders format")?
+                }
+                None => Vec::new(),
+            };
+
+            let reminders = reminders::set_reminders(ctx.db(), todo_id, inputs)
+                .await
+                .context("Failed to set todo reminders")?;
+
+            // 同时通过 Tauri Event 通知前端（给内置前端）
+            use tauri::Emitter;
+            let _ = ctx.app_handle().emit(
+                "todo-data-updated",
+                json!({
+                    "action": "updated",
+                    "todoId": todo_id,
+                    "source": "webserver"
+                }),
+            );
+
+            // 触发调度器重新规划提醒
+            if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+                if let Some(scheduler) = state.todo_scheduler() {
+                    scheduler.reschedule().await;
+                }
+
+                // 安排推送到 CalDAV
+                state.caldav_sync_manager().schedule_push();
+            }
+
+            Ok(json!(reminders))
+        })
+    });
+
+    // 稍后提醒待办
+    registry.register_call("todo.snooze", |_method, params, ctx| {
+        Box::pin(async move {
+            let id = params
+                .get("id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid id")? as i32;
+
+            let minutes = params
+                .get("minutes")
+                .and_then(|v| v.as_i64())
+                .map(|v| v as i32);
+
+            let until = params
+                .get("until")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+
+            let todo = service::snooze_todo(ctx.db(), id, minutes, until)
+                .await
+                .context("Failed to snooze todo")?;
+
+            // 同时通过 Tauri Event 通知前端（给内置前端）
+            use tauri::Emitter;
+            let _ = ctx.app_handle().emit(
+                "todo-data-updated",
+                json!({
+                    "action": "updated",
+                    "todoId": id,
+                    "source": "webserver"
+                }),
+            );
+
+            // 触发调度器重新规划提醒
+            if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+                if let Some(scheduler) = state.todo_scheduler() {
+                    scheduler.reschedule().await;
+                }
+            }
+
+            Ok(json!(todo))
+        })
+    });
+
+    // 按条件查询待办；传入 saved_filter_id 时执行保存的筛选条件
+    registry.register_call("todo.query", |_method, params, ctx| {
+        Box::pin(async move {
+            let page = params.get("page").and_then(|v| v.as_u64());
+            let page_size = params.get("page_size").and_then(|v| v.as_u64());
+
+            let result = match params.get("saved_filter_id").and_then(|v| v.as_i64()) {
+                Some(filter_id) => {
+                    saved_filters::run_filter(ctx.db(), filter_id as i32, page, page_size).await
+                }
+                None => {
+                    let query: TodoQuery =
+                        serde_json::from_value(params).context("Invalid query format")?;
+                    query::query_todos(ctx.db(), &query).await
+                }
+            }
+            .context("Failed to query todos")?;
+
+            Ok(json!(result))
+        })
+    });
+
+    // 列出保存的筛选条件
+    registry.register_call("todo.saved_filters", |_method, _params, ctx| {
+        Box::pin(async move {
+            let filters = saved_filters::list_filters(ctx.db())
+                .await
+                .context("Failed to load saved filters")?;
+            Ok(json!(filters))
+        })
+    });
+
+    // 导出待办为 .ics 文件内容
+    registry.register_call("todo.export_ics", |_method, params, ctx| {
+        Box::pin(async move {
+            let filter = IcsExportFilter {
+                ids: params.get("ids").and_then(|v| v.as_array()).map(|ids| {
+                    ids.iter()
+                        .filter_map(|id| id.as_i64())
+                        .map(|id| id as i32)
+                        .collect()
+                }),
+                include_completed: params
+                    .get("include_completed")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true),
+            };
+
+            let content = ical_file::export_ics(ctx.db(), &filter)
+                .await
+                .context("Failed to export todos")?;
+
+            Ok(json!({ "content": content }))
+        })
+    });
+
+    // 从 .ics 文件内容导入待办
+    registry.register_call("todo.import_ics", |_method, params, ctx| {
+        Box::pin(async move {
+            let content = params
+                .get("content")
+                .and_then(|v| v.as_str())
+                .context("Missing content")?;
+
+            let mode: IcsImportMode = match params.get("mode") {
+                Some(value) => serde_json::from_value(value.clone()).context("Invalid mode")?,
+                None => IcsImportMode::default(),
+            };
+
+            let summary = ical_file::import_ics(ctx.db(), content, mode)
+                .await
+                .context("Failed to import todos")?;
+
+            // 同时通过 Tauri Event 通知前端（给内置前端）
+            use tauri::Emitter;
+            let _ = ctx.app_handle().emit(
+                "todo-data-updated",
+                json!({
+                    "action": "imported",
+                    "source": "webserver"
+                }),
+            );
+
+            if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+                if let Some(scheduler) = state.todo_scheduler() {
+                    scheduler.reschedule().await;
+                }
+                state.caldav_sync_manager().schedule_push();
+            }
+
+            Ok(json!(summary))
+        })
+    });
+
+    // 获取依赖关系
+    registry.register_call("todo.dependencies", |_method, params, ctx| {
+        Box::pin(async move {
+            let todo_id = params
+                .get("todo_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid todo_id")? as i32;
+
+            let result = dependencies::get_dependencies(ctx.db(), todo_id)
+                .await
+                .context("Failed to load todo dependencies")?;
+            Ok(json!(result))
+        })
+    });
+
+    // 添加依赖
+    registry.register_call("todo.add_dependency", |_method, params, ctx| {
+        Box::pin(async move {
+            let todo_id = params
+                .get("todo_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid todo_id")? as i32;
+            let depends_on_id = params
+                .get("depends_on_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid depends_on_id")?
+                as i32;
+
+            let todo = dependencies::add_dependency(ctx.db(), todo_id, depends_on_id)
+                .await
+                .context("Failed to add todo dependency")?;
+
+            use tauri::Emitter;
+            let _ = ctx.app_handle().emit(
+                "todo-data-updated",
+                json!({
+                    "action": "updated",
+                    "todoId": todo_id,
+                    "source": "webserver"
+                }),
+            );
+
+            if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+                state.caldav_sync_manager().schedule_push();
+            }
+
+            Ok(json!(todo))
+        })
+    });
+
+    // 移除依赖
+    registry.register_call("todo.remove_dependency", |_method, params, ctx| {
+        Box::pin(async move {
+            let todo_id = params
+                .get("todo_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid todo_id")? as i32;
+            let depends_on_id = params
+                .get("depends_on_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid depends_on_id")?
+                as i32;
+
+            let todo = dependencies::remove_dependency(ctx.db(), todo_id, depends_on_id)
+                .await
+                .context("Failed to remove todo dependency")?;
+
+            use tauri::Emitter;
+            let _ = ctx.app_handle().emit(
+                "todo-data-updated",
+                json!({
+                    "action": "updated",
+                    "todoId": todo_id,
+                    "source": "webserver"
+                }),
+            );
+
+            if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+                state.caldav_sync_manager().schedule_push();
+            }
+
+            Ok(json!(todo))
+        })
+    });
+
+    // 下一步可执行的 todo
+    registry.register_call("todo.next_actionable", |_method, params, ctx| {
+        Box::pin(async move {
+            let limit = params.get("limit").and_then(|v| v.as_u64());
+            let todos = dependencies::next_actionable(ctx.db(), limit)
+                .await
+                .context("Failed to load next actionable todos")?;
+            Ok(json!(todos))
+        })
+    });
+
+    // 撤销最近一次操作
+    registry.register_call("todo.undo", |_method, _params, ctx| {
+        Box::pin(async move {
+            let entry = journal::undo(ctx.db())
+                .await
+                .context("Failed to undo todo change")?;
+            if entry.is_some() {
+                notify_journal_applied(&ctx, "undone").await;
+            }
+            Ok(json!(entry))
+        })
+    });
+
+    // 重做最近一次撤销的操作
+    registry.register_call("todo.redo", |_method, _params, ctx| {
+        Box::pin(async move {
+            let entry = journal::redo(ctx.db())
+                .await
+                .context("Failed to redo todo change")?;
+            if entry.is_some() {
+                notify_journal_applied(&ctx, "redone").await;
+            }
+            Ok(json!(entry))
+        })
+    });
+
+    // 获取当前可撤销和可重做的操作
+    registry.register_call("todo.history", |_method, _params, ctx| {
+        Box::pin(async move {
+            let status = journal::status(ctx.db())
+                .await
+                .context("Failed to load todo history")?;
+            Ok(json!(status))
+        })
+    });
+
+    // 列出回收站
+    registry.register_call("todo.trash", |_method, _params, ctx| {
+        Box::pin(async move {
+            let trashed = trash::list(ctx.db())
+                .await
+                .context("Failed to list trashed todos")?;
+            Ok(json!(trashed))
+        })
+    });
+
+    // 从回收站恢复
+    registry.register_call("todo.restore", |_method, params, ctx| {
+        Box::pin(async move {
+            let id = params
+                .get("id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid id")? as i32;
+
+            let todo = service::restore_todo(ctx.db(), id)
+                .await
+                .context("Failed to restore todo")?;
+
+            use tauri::Emitter;
+            let _ = ctx.app_handle().emit(
+                "todo-data-updated",
+                json!({
+                    "action": "restored",
+                    "todoId": id,
+                    "source": "webserver"
+                }),
+            );
+
+            if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+                if let Some(scheduler) = state.todo_scheduler() {
+                    scheduler.reschedule().await;
+                }
+                state.caldav_sync_manager().schedule_push();
             }
 
             Ok(json!(todo))
         })
     });
+
+    // 彻底删除回收站中的 todo
+    registry.register_call("todo.purge", |_method, params, ctx| {
+        Box::pin(async move {
+            let id = params
+                .get("id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid id")? as i32;
+
+            let purged = trash::purge(ctx.db(), id)
+                .await
+                .context("Failed to purge todo")?;
+            Ok(json!({ "purged": purged }))
+        })
+    });
+
+    // 清空回收站
+    registry.register_call("todo.empty_trash", |_method, _params, ctx| {
+        Box::pin(async move {
+            let purged = trash::empty(ctx.db())
+                .await
+                .context("Failed to empty trash")?;
+            Ok(json!({ "purged": purged }))
+        })
+    });
+
+    // 获取 CalDAV 同步冲突
+    registry.register_call("todo.sync_conflicts", |_method, params, ctx| {
+        Box::pin(async move {
+            let todo_id = params
+                .get("todo_id")
+                .and_then(|v| v.as_i64())
+                .map(|v| v as i32);
+
+            let conflicts = conflicts::list_conflicts(ctx.db(), todo_id)
+                .await
+                .context("Failed to list sync conflicts")?;
+            Ok(json!(conflicts))
+        })
+    });
+
+    // 解决 CalDAV 同步冲突
+    registry.register_call("todo.resolve_sync_conflict", |_method, params, ctx| {
+        Box::pin(async move {
+            let conflict_id = params
+                .get("conflict_id")
+                .and_then(|v| v.as_i64())
+                .context("Missing or invalid conflict_id")? as i32;
+
+            let resolution: ConflictResolution = serde_json::from_value(
+                params
+                    .get("resolution")
+                    .cloned()
+                    .context("Missing resolution")?,
+            )
+            .context("Invalid resolution")?;
+
+            let has_remaining = conflicts::resolve_conflict(ctx.db(), conflict_id, resolution)
+                .await
+                .context("Failed to resolve sync conflict")?;
+
+            use tauri::Emitter;
+            let _ = ctx.app_handle().emit(
+                "todo-data-updated",
+                json!({
+                    "action": "updated",
+                    "todoId": Value::Null,
+                    "source": "webserver"
+                }),
+            );
+
+            if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+                // 冲突全部解决后推送本地版本
+                if !has_remaining {
+                    state.caldav_sync_manager().trigger(SyncReason::DataChanged);
+                }
+                if let Some(scheduler) = state.todo_scheduler() {
+                    scheduler.reschedule().await;
+                }
+            }
+
+            Ok(json!({ "has_remaining": has_remaining }))
+        })
+    });
+
+    // CalDAV 同步试运行（不写入本地或远端）
+    registry.register_call("caldav.dry_run", |_method, _params, ctx| {
+        Box::pin(async move {
+            let state = ctx
+                .app_handle()
+                .try_state::<crate::core::AppState>()
+                .context("App state not available")?;
+
+            let plan = state
+                .caldav_sync_manager()
+                .sync_now_dry_run()
+                .await
+                .context("Failed to plan CalDAV sync")?;
+            Ok(json!(plan))
+        })
+    });
+
+    // CalDAV 同步历史（分页，最新的在前）
+    registry.register_call("caldav.history", |_method, params, ctx| {
+        Box::pin(async move {
+            let page = params.get("page").and_then(|v| v.as_u64()).unwrap_or(0);
+            let page_size = params.get("page_size").and_then(|v| v.as_u64());
+
+            let history = history::list_runs(ctx.db(), page, page_size)
+                .await
+                .context("Failed to load CalDAV sync history")?;
+            Ok(json!(history))
+        })
+    });
+}
+
+/// 撤销 / 重做后通知前端并重新规划提醒、推送到 CalDAV
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+async fn notify_journal_applied(
+    ctx: &crate::infrastructure::webserver::core::ws::ApiContext,
+    action: &str,
+) {
+    use tauri::Emitter;
+    let _ = ctx.app_handle().emit(
+        "todo-data-updated",
+        json!({
+            "action": action,
+            "source": "webserver"
+        }),
+    );
+
+    if let Some(state) = ctx.app_handle().try_state::<crate::core::AppState>() {
+        if let Some(scheduler) = state.todo_scheduler() {
+            scheduler.reschedule().await;
+        }
+        state.caldav_sync_manager().schedule_push();
+    }
 }
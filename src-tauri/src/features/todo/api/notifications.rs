@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde_json::json;
 
 use crate::infrastructure::notification::{NotificationManager, ToastLevel};
@@ -13,6 +14,8 @@ use crate::infrastructure::notification::{NotificationManager, ToastLevel};
 /// WebSocket Event 名称
 pub const TODO_CHANGES_EVENT: &str = "todo.changes";
 pub const TODO_DUE_EVENT: &str = "todo.due";
+pub const TODO_MISSED_EVENT: &str = "todo.missed";
+pub const TODO_SYNC_CONFLICT_EVENT: &str = "todo.sync_conflict";
 
 /// 创建 Todo 成功通知（Toast + WebSocket）
 pub fn notify_todo_created(notification_manager: &NotificationManager, todo_id: i32, title: &str) {
@@ -54,7 +57,14 @@ pub fn notify_todo_deleted(notification_manager: &NotificationManager, todo_id:
 }
 
 /// Todo 到期提醒通知（Toast + WebSocket）
-pub fn notify_todo_due(notification_manager: &NotificationManager, todo_id: i32, title: &str) {
+///
+/// `due_at` 为本次提醒对应的实例到期时间（重复任务每个实例各提醒一次）
+pub fn notify_todo_due(
+    notification_manager: &NotificationManager,
+    todo_id: i32,
+    title: &str,
+    due_at: DateTime<Utc>,
+) {
     let _ = notification_manager.notify(
         format!("⏰ 待办 \"{}\" 已到期", title),
         ToastLevel::Warning,
@@ -62,6 +72,7 @@ pub fn notify_todo_due(notification_manager: &NotificationManager, todo_id: i32,
         json!({
             "todo_id": todo_id,
             "title": title,
+            "due_at": due_at.to_rfc3339(),
         }),
     );
 
@@ -93,6 +104,20 @@ pub fn notify_sync_error(notification_manager: &NotificationManager, error: &str
     let _ = notification_manager.send_toast(format!("同步失败：{}", error), ToastLevel::Error);
 }
 
+/// CalDAV 同步冲突通知（Toast + WebSocket）
+///
+/// 本地和远端修改了同一字段，需要用户选择保留哪一方
+pub fn notify_sync_conflicts(notification_manager: &NotificationManager, count: usize) {
+    let _ = notification_manager.notify(
+        format!("同步发现 {} 处冲突，请手动处理", count),
+        ToastLevel::Warning,
+        TODO_SYNC_CONFLICT_EVENT.to_string(),
+        json!({
+            "count": count,
+        }),
+    );
+}
+
 /// CalDAV 配置保存成功通知
 pub fn notify_caldav_config_saved(notification_manager: &NotificationManager) {
     let _ = notification_manager.send_toast("CalDAV 配置已保存".to_string(), ToastLevel::Success);
@@ -102,3 +127,34 @@ pub fn notify_caldav_config_saved(notification_manager: &NotificationManager) {
 pub fn notify_caldav_config_cleared(notification_manager: &NotificationManager) {
     let _ = notification_manager.send_toast("已清除 CalDAV 配置".to_string(), ToastLevel::Success);
 }
+
+/// 错过提醒汇总通知（Toast + WebSocket）
+///
+/// 应用关闭期间错过的提醒在启动时合并为一条通知
+pub fn notify_todos_missed(
+    notification_manager: &NotificationManager,
+    count: usize,
+    titles: &[String],
+) {
+    let summary = match titles {
+        [title] => format!("\"{}\"", title),
+        [first, rest @ ..] => format!("\"{}\" 等 {} 个待办", first, rest.len() + 1),
+        [] => String::new(),
+    };
+
+    let _ = notification_manager.notify(
+        format!("⏰ 错过了 {} 个提醒：{}", count, summary),
+        ToastLevel::Warning,
+        TODO_MISSED_EVENT.to_string(),
+        json!({
+            "count": count,
+            "titles": titles,
+        }),
+    );
+
+    // 系统通知（原生通知中心）
+    let _ = notification_manager.send_native(
+        "错过的待办提醒".to_string(),
+        format!("应用关闭期间错过了 {} 个提醒：{}", count, summary),
+    );
+}
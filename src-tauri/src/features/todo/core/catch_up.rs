@@ -0,0 +1,361 @@
+// 错过提醒的补发策略
+//
+// 应用关闭、系统休眠期间或修改 RRULE 后错过的提醒，在启动时和调度器每次重新调度前按策略统一处理
+// （调度器只调度提醒时间未过的提醒）：
+// - coalesce：合并为一条汇总通知
+// - drop：丢弃超过 N 小时的提醒，其余逐个补发
+// - individual：逐个补发
+// 逐个补发时每个 Todo 只补发最晚的一个提醒，更早的实例合并为汇总通知
+// 每个 Todo 的处理结果记录在 missed_reminder_action / missed_reminder_handled_at
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+
+use super::service::{self, PendingReminder};
+use crate::features::settings::core::service::SettingService;
+use crate::features::todo::api::notifications;
+use crate::infrastructure::notification::NotificationManager;
+
+const POLICY_KEY: &str = "todo.reminder_catch_up_policy";
+
+pub const ACTION_COALESCED: &str = "coalesced";
+pub const ACTION_DROPPED: &str = "dropped";
+pub const ACTION_FIRED: &str = "fired";
+
+/// 错过提醒的处理策略
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum CatchUpPolicy {
+    /// 合并为一条汇总通知
+    #[default]
+    Coalesce,
+    /// 丢弃超过 max_age_hours 小时的提醒，其余逐个补发
+    Drop { max_age_hours: u32 },
+    /// 逐个补发
+    Individual,
+}
+
+/// 补发的处理结果（按提醒实例计数）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CatchUpReport {
+    pub coalesced: usize,
+    pub dropped: usize,
+    pub fired: usize,
+}
+
+pub struct CatchUpPolicyService;
+
+impl CatchUpPolicyService {
+    pub async fn get_policy(db: &DatabaseConnection) -> Result<CatchUpPolicy> {
+        match SettingService::get(db, POLICY_KEY).await? {
+            Some(raw) => serde_json::from_str(&raw)
+                .with_context(|| "failed to deserialize reminder catch-up policy"),
+            None => Ok(CatchUpPolicy::default()),
+        }
+    }
+
+    pub async fn set_policy(db: &DatabaseConnection, policy: &CatchUpPolicy) -> Result<()> {
+        let payload = serde_json::to_string(policy)
+            .with_context(|| "failed to serialize reminder catch-up policy")?;
+        SettingService::set(db, POLICY_KEY, &payload).await?;
+        Ok(())
+    }
+}
+
+/// 处理提醒时间早于 `now` 的错过提醒（启动时及调度器每次重新调度前调用）
+///
+/// 所有错过的提醒都会标记为已触发：被合并的汇总为一条通知，逐个补发的立即发送
+pub async fn run_catch_up(
+    db: &DatabaseConnection,
+    notification_manager: &NotificationManager,
+    now: DateTime<Utc>,
+) -> Result<CatchUpReport> {
+    let policy = CatchUpPolicyService::get_policy(db).await?;
+    let overdue = service::get_overdue_reminders(db, now).await?;
+
+    let mut report = CatchUpReport::default();
+    if overdue.is_empty() {
+        return Ok(report);
+    }
+
+    println!(
+        "[CatchUp] 发现 {} 个错过的提醒，处理策略: {:?}",
+        overdue.len(),
+        policy
+    );
+
+    let plan = plan_catch_up(policy, overdue, now);
+    apply_plan(db, &plan, now).await?;
+
+    if !plan.coalesced.is_empty() {
+        let titles = distinct_titles(&plan.coalesced);
+        notifications::notify_todos_missed(notification_manager, plan.coalesced.len(), &titles);
+    }
+    for reminder in &plan.fired {
+        notifications::notify_todo_due(
+            notification_manager,
+            reminder.todo.id,
+            &reminder.todo.title,
+            reminder.occurrence_at,
+        );
+    }
+
+    report.coalesced = plan.coalesced.len();
+    report.dropped = plan.dropped.len();
+    report.fired = plan.fired.len();
+
+    println!(
+        "[CatchUp] 处理完成: 合并 {}，丢弃 {}，逐个补发 {}",
+        report.coalesced, report.dropped, report.fired
+    );
+
+    Ok(report)
+}
+
+/// 错过提醒的处理计划
+#[derive(Debug, Default)]
+struct CatchUpPlan {
+    /// 合并到汇总通知中的提醒
+    coalesced: Vec<PendingReminder>,
+    /// 直接丢弃的提醒
+    dropped: Vec<PendingReminder>,
+    /// 逐个补发的提醒（每个 Todo 最多一个）
+    fired: Vec<PendingReminder>,
+}
+
+/// 按策略划分错过的提醒（overdue 按提醒时间升序）
+///
+/// 逐个补发时每个 Todo 只补发最晚的一个提醒，更早的实例计入汇总通知，
+/// 避免长时间未启动后重复任务的每个实例都单独通知一次
+fn plan_catch_up(
+    policy: CatchUpPolicy,
+    overdue: Vec<PendingReminder>,
+    now: DateTime<Utc>,
+) -> CatchUpPlan {
+    let mut plan = CatchUpPlan::default();
+    let candidates = match policy {
+        CatchUpPolicy::Coalesce => {
+            plan.coalesced = overdue;
+            return plan;
+        }
+        CatchUpPolicy::Drop { max_age_hours } => {
+            let cutoff = now - chrono::Duration::hours(max_age_hours as i64);
+            let (dropped, kept): (Vec<_>, Vec<_>) = overdue
+                .into_iter()
+                .partition(|reminder| reminder.remind_at < cutoff);
+            plan.dropped = dropped;
+            kept
+        }
+        CatchUpPolicy::Individual => overdue,
+    };
+
+    let mut latest: HashMap<i32, usize> = HashMap::new();
+    for (index, reminder) in candidates.iter().enumerate() {
+        latest.insert(reminder.todo.id, index);
+    }
+    for (index, reminder) in candidates.into_iter().enumerate() {
+        if latest.get(&reminder.todo.id) == Some(&index) {
+            plan.fired.push(reminder);
+        } else {
+            plan.coalesced.push(reminder);
+        }
+    }
+
+    plan
+}
+
+/// 将错过的提醒全部标记为已触发并记录每个 Todo 的处理结果
+async fn apply_plan(db: &DatabaseConnection, plan: &CatchUpPlan, now: DateTime<Utc>) -> Result<()> {
+    mark_handled(
+        db,
+        plan.coalesced
+            .iter()
+            .chain(&plan.dropped)
+            .chain(&plan.fired),
+    )
+    .await?;
+
+    // 按 丢弃 → 合并 → 补发 的顺序记录：同一 Todo 有多种结果时以后者为准
+    record_action(db, &plan.dropped, ACTION_DROPPED, now).await?;
+    record_action(db, &plan.coalesced, ACTION_COALESCED, now).await?;
+    record_action(db, &plan.fired, ACTION_FIRED, now).await?;
+
+    Ok(())
+}
+
+/// 将提醒标记为已触发：每个提醒只需推进到最晚错过的实例
+async fn mark_handled(
+    db: &DatabaseConnection,
+    handled: impl IntoIterator<Item = &PendingReminder>,
+) -> Result<()> {
+    let mut latest: HashMap<(i32, Option<i32>), DateTime<Utc>> = HashMap::new();
+    for reminder in handled {
+        let entry = latest
+            .entry((reminder.todo.id, reminder.reminder_id))
+            .or_insert(reminder.occurrence_at);
+        *entry = (*entry).max(reminder.occurrence_at);
+    }
+
+    for ((todo_id, reminder_id), occurrence_at) in latest {
+        match reminder_id {
+            Some(reminder_id) => {
+                service::mark_todo_reminded(db, todo_id, reminder_id, occurrence_at).await?
+            }
+            None => service::clear_todo_snooze(db, todo_id).await?,
+        }
+    }
+
+    Ok(())
+}
+
+async fn record_action(
+    db: &DatabaseConnection,
+    reminders: &[PendingReminder],
+    action: &str,
+    handled_at: DateTime<Utc>,
+) -> Result<()> {
+    let mut todo_ids: Vec<i32> = reminders.iter().map(|reminder| reminder.todo.id).collect();
+    todo_ids.sort_unstable();
+    todo_ids.dedup();
+
+    for todo_id in todo_ids {
+        service::record_missed_reminder_action(db, todo_id, action, handled_at).await?;
+    }
+
+    Ok(())
+}
+
+fn distinct_titles(reminders: &[PendingReminder]) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut titles = Vec::new();
+    for reminder in reminders {
+        if !seen.contains(&reminder.todo.id) {
+            seen.push(reminder.todo.id);
+            titles.push(reminder.todo.title.clone());
+        }
+    }
+    titles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::todo::data::entity;
+    use crate::infrastructure::database::test_db::memory_db;
+    use chrono::Duration;
+    use sea_orm::{ActiveModelTrait, ActiveValue::Set};
+
+    /// 每日重复任务错过 5 个实例（提醒在 1~5 天前），单次任务错过 1 个（2 小时前）
+    async fn setup(now: DateTime<Utc>) -> (DatabaseConnection, i32, i32) {
+        let db = memory_db().await;
+        let daily = create(
+            &db,
+            "Daily standup",
+            now - Duration::days(5) + Duration::hours(1),
+            Some("FREQ=DAILY"),
+        )
+        .await;
+        let once = create(&db, "Pay rent", now - Duration::hours(2), None).await;
+        (db, daily, once)
+    }
+
+    async fn create(
+        db: &DatabaseConnection,
+        title: &str,
+        due_date: DateTime<Utc>,
+        rrule: Option<&str>,
+    ) -> i32 {
+        let todo = service::create_todo(db, Some(title.to_string()))
+            .await
+            .unwrap();
+        let mut active: entity::ActiveModel =
+            service::get_todo_by_id(db, todo.id).await.unwrap().into();
+        active.due_date = Set(Some(due_date));
+        active.recurrence_rule = Set(rrule.map(str::to_string));
+        active.update(db).await.unwrap();
+        todo.id
+    }
+
+    async fn catch_up(
+        db: &DatabaseConnection,
+        policy: CatchUpPolicy,
+        now: DateTime<Utc>,
+    ) -> CatchUpPlan {
+        let overdue = service::get_overdue_reminders(db, now).await.unwrap();
+        assert_eq!(overdue.len(), 6);
+        let plan = plan_catch_up(policy, overdue, now);
+        apply_plan(db, &plan, now).await.unwrap();
+        plan
+    }
+
+    async fn action(db: &DatabaseConnection, id: i32) -> Option<String> {
+        service::get_todo_by_id(db, id)
+            .await
+            .unwrap()
+            .missed_reminder_action
+    }
+
+    fn todo_ids(reminders: &[PendingReminder]) -> Vec<i32> {
+        reminders.iter().map(|reminder| reminder.todo.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_marks_everything_handled() {
+        let now = Utc::now();
+        let (db, daily, once) = setup(now).await;
+
+        let plan = catch_up(&db, CatchUpPolicy::Coalesce, now).await;
+        assert_eq!(plan.coalesced.len(), 6);
+        assert!(plan.dropped.is_empty());
+        assert!(plan.fired.is_empty());
+
+        // 全部标记为已触发，调度器不会再补发
+        assert!(service::get_overdue_reminders(&db, now)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(action(&db, daily).await.as_deref(), Some(ACTION_COALESCED));
+        assert_eq!(action(&db, once).await.as_deref(), Some(ACTION_COALESCED));
+    }
+
+    #[tokio::test]
+    async fn test_drop_discards_old_reminders_and_fires_latest_per_todo() {
+        let now = Utc::now();
+        let (db, daily, once) = setup(now).await;
+
+        let plan = catch_up(&db, CatchUpPolicy::Drop { max_age_hours: 48 }, now).await;
+        assert_eq!(todo_ids(&plan.dropped), vec![daily; 3]);
+        assert_eq!(todo_ids(&plan.coalesced), vec![daily]);
+        assert_eq!(todo_ids(&plan.fired), vec![daily, once]);
+
+        // 补发的提醒同样标记为已触发
+        assert!(service::get_overdue_reminders(&db, now)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(action(&db, daily).await.as_deref(), Some(ACTION_FIRED));
+        assert_eq!(action(&db, once).await.as_deref(), Some(ACTION_FIRED));
+    }
+
+    #[tokio::test]
+    async fn test_individual_fires_only_latest_occurrence_per_todo() {
+        let now = Utc::now();
+        let (db, daily, once) = setup(now).await;
+
+        let plan = catch_up(&db, CatchUpPolicy::Individual, now).await;
+        assert!(plan.dropped.is_empty());
+        assert_eq!(todo_ids(&plan.coalesced), vec![daily; 4]);
+        assert_eq!(todo_ids(&plan.fired), vec![daily, once]);
+        assert!(plan.fired[0].remind_at > now - Duration::days(1));
+
+        assert!(service::get_overdue_reminders(&db, now)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(action(&db, daily).await.as_deref(), Some(ACTION_FIRED));
+    }
+}
@@ -0,0 +1,430 @@
+// 任务依赖（blocked-by）
+//
+// todo_dependencies 表记录 todo 依赖的其他 todo，按 UID 关联，与 iCalendar 的
+// RELATED-TO;RELTYPE=DEPENDS-ON 一一对应：远端引用了本地不存在的 todo 时关系原样保留并推送回去。
+// - 依赖中存在未完成、且不在回收站中的 todo 时，todo 处于阻塞（blocked）状态
+// - 本地添加依赖时检查循环依赖；同步和导入的依赖直接写入，不做检查
+// - 添加或移除依赖会标记 todo 为 dirty，随下次同步推送
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{Expr, NullOrdering, Query, SelectStatement},
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde::Serialize;
+
+use super::models::Todo;
+use crate::features::todo::data::{dependency_entity, entity};
+
+/// 下一步可执行任务的默认条数和上限
+const DEFAULT_NEXT_LIMIT: u64 = 20;
+const MAX_NEXT_LIMIT: u64 = 500;
+
+/// todo 的依赖关系（只包含本地存在且不在回收站中的 todo）
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoDependencies {
+    /// 该 todo 依赖的 todo
+    pub depends_on: Vec<Todo>,
+    /// 依赖该 todo 的 todo
+    pub dependents: Vec<Todo>,
+}
+
+/// 被阻塞的 todo id（用于查询条件的子查询）
+pub(super) fn blocked_todo_ids() -> SelectStatement {
+    Query::select()
+        .column((dependency_entity::Entity, dependency_entity::Column::TodoId))
+        .from(dependency_entity::Entity)
+        .inner_join(
+            entity::Entity,
+            Expr::col((entity::Entity, entity::Column::Uid)).equals((
+                dependency_entity::Entity,
+                dependency_entity::Column::DependsOnUid,
+            )),
+        )
+        .and_where(Expr::col((entity::Entity, entity::Column::Completed)).eq(false))
+        .and_where(Expr::col((entity::Entity, entity::Column::DeletedAt)).is_null())
+        .to_owned()
+}
+
+/// 转换为 Todo 并填充依赖和阻塞状态
+pub async fn with_dependencies(
+    db: &DatabaseConnection,
+    models: Vec<entity::Model>,
+) -> Result<Vec<Todo>> {
+    let mut todos: Vec<Todo> = models.into_iter().map(Into::into).collect();
+    annotate(db, &mut todos).await?;
+    Ok(todos)
+}
+
+pub async fn to_todo(db: &DatabaseConnection, model: entity::Model) -> Result<Todo> {
+    let mut todos = with_dependencies(db, vec![model]).await?;
+    Ok(todos.remove(0))
+}
+
+/// 填充 todo 的 depends_on 和 blocked
+pub async fn annotate(db: &DatabaseConnection, todos: &mut [Todo]) -> Result<()> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+
+    let rows = dependency_entity::Entity::find()
+        .filter(dependency_entity::Column::TodoId.is_in(todos.iter().map(|todo| todo.id)))
+        .order_by_asc(dependency_entity::Column::Id)
+        .all(db)
+        .await
+        .context("failed to load todo dependencies")?;
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let targets = live_todos_by_uid(db, rows.iter().map(|row| row.depends_on_uid.clone())).await?;
+    let mut by_todo: HashMap<i32, Vec<&entity::Model>> = HashMap::new();
+    for row in &rows {
+        if let Some(target) = targets.get(&row.depends_on_uid) {
+            by_todo.entry(row.todo_id).or_default().push(target);
+        }
+    }
+
+    for todo in todos.iter_mut() {
+        if let Some(dependencies) = by_todo.get(&todo.id) {
+            todo.depends_on = dependencies.iter().map(|target| target.id).collect();
+            todo.blocked = dependencies.iter().any(|target| !target.completed);
+        }
+    }
+    Ok(())
+}
+
+async fn live_todos_by_uid(
+    db: &DatabaseConnection,
+    uids: impl IntoIterator<Item = String>,
+) -> Result<HashMap<String, entity::Model>> {
+    let uids: HashSet<String> = uids.into_iter().collect();
+    let models = entity::Entity::find()
+        .filter(entity::Column::Uid.is_in(uids))
+        .filter(entity::Column::DeletedAt.is_null())
+        .all(db)
+        .await
+        .context("failed to load dependency todos")?;
+    Ok(models
+        .into_iter()
+        .map(|model| (model.uid.clone(), model))
+        .collect())
+}
+
+pub async fn get_dependencies(db: &DatabaseConnection, todo_id: i32) -> Result<TodoDependencies> {
+    let todo = find_live(db, todo_id).await?;
+
+    let uids = dependency_uids(db, todo_id).await?;
+    let mut targets = live_todos_by_uid(db, uids.iter().cloned()).await?;
+    let depends_on = uids.iter().filter_map(|uid| targets.remove(uid)).collect();
+
+    let dependents = entity::Entity::find()
+        .filter(
+            entity::Column::Id.in_subquery(
+                Query::select()
+                    .column(dependency_entity::Column::TodoId)
+                    .from(dependency_entity::Entity)
+                    .and_where(dependency_entity::Column::DependsOnUid.eq(todo.uid))
+                    .to_owned(),
+            ),
+        )
+        .filter(entity::Column::DeletedAt.is_null())
+        .order_by_asc(entity::Column::Id)
+        .all(db)
+        .await
+        .with_context(|| format!("failed to load dependents of todo {todo_id}"))?;
+
+    Ok(TodoDependencies {
+        depends_on: with_dependencies(db, depends_on).await?,
+        dependents: with_dependencies(db, dependents).await?,
+    })
+}
+
+/// 添加依赖：todo_id 需要等 depends_on_id 完成，返回更新后的 todo
+pub async fn add_dependency(
+    db: &DatabaseConnection,
+    todo_id: i32,
+    depends_on_id: i32,
+) -> Result<Todo> {
+    if todo_id == depends_on_id {
+        bail!("A task cannot depend on itself");
+    }
+    let todo = find_live(db, todo_id).await?;
+    let depends_on = find_live(db, depends_on_id).await?;
+
+    if dependency_uids(db, todo_id)
+        .await?
+        .contains(&depends_on.uid)
+    {
+        return to_todo(db, todo).await;
+    }
+    if creates_cycle(db, &todo.uid, &depends_on.uid).await? {
+        bail!("Cannot create circular dependency between tasks");
+    }
+
+    dependency_entity::ActiveModel {
+        id: NotSet,
+        todo_id: Set(todo_id),
+        depends_on_uid: Set(depends_on.uid),
+        created_at: Set(Utc::now()),
+    }
+    .insert(db)
+    .await
+    .with_context(|| format!("failed to add dependency of todo {todo_id}"))?;
+
+    let updated = mark_dirty(db, todo).await?;
+    to_todo(db, updated).await
+}
+
+/// 移除依赖，返回更新后的 todo
+pub async fn remove_dependency(
+    db: &DatabaseConnection,
+    todo_id: i32,
+    depends_on_id: i32,
+) -> Result<Todo> {
+    let todo = find_live(db, todo_id).await?;
+    let depends_on = entity::Entity::find_by_id(depends_on_id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to find todo {depends_on_id}"))?
+        .ok_or_else(|| anyhow!("todo {depends_on_id} not found"))?;
+
+    let removed = dependency_entity::Entity::delete_many()
+        .filter(dependency_entity::Column::TodoId.eq(todo_id))
+        .filter(dependency_entity::Column::DependsOnUid.eq(depends_on.uid))
+        .exec(db)
+        .await
+        .with_context(|| format!("failed to remove dependency of todo {todo_id}"))?;
+
+    let todo = if removed.rows_affected > 0 {
+        mark_dirty(db, todo).await?
+    } else {
+        todo
+    };
+    to_todo(db, todo).await
+}
+
+/// 下一步可执行的 todo
+///
+/// 未完成、未被阻塞、已到开始时间，且没有未完成的子任务；按到期时间、优先级和手动顺序排列
+pub async fn next_actionable(db: &DatabaseConnection, limit: Option<u64>) -> Result<Vec<Todo>> {
+    let open_parents = Query::select()
+        .column(entity::Column::ParentId)
+        .from(entity::Entity)
+        .and_where(entity::Column::ParentId.is_not_null())
+        .and_where(entity::Column::Completed.eq(false))
+        .and_where(entity::Column::DeletedAt.is_null())
+        .to_owned();
+
+    let models = entity::Entity::find()
+        .filter(entity::Column::DeletedAt.is_null())
+        .filter(entity::Column::Completed.eq(false))
+        .filter(entity::Column::StartAt.lte(Utc::now()))
+        .filter(entity::Column::Id.not_in_subquery(blocked_todo_ids()))
+        .filter(entity::Column::Id.not_in_subquery(open_parents))
+        .order_by_with_nulls(entity::Column::DueDate, Order::Asc, NullOrdering::Last)
+        .order_by_with_nulls(entity::Column::Priority, Order::Asc, NullOrdering::Last)
+        .order_by_with_nulls(entity::Column::OrderIndex, Order::Asc, NullOrdering::Last)
+        .order_by_asc(entity::Column::Id)
+        .limit(limit.unwrap_or(DEFAULT_NEXT_LIMIT).clamp(1, MAX_NEXT_LIMIT))
+        .all(db)
+        .await
+        .context("failed to query next actionable todos")?;
+
+    with_dependencies(db, models).await
+}
+
+/// todo 依赖的 UID（按添加顺序，用于生成 iCalendar）
+pub async fn dependency_uids(db: &DatabaseConnection, todo_id: i32) -> Result<Vec<String>> {
+    let rows = dependency_entity::Entity::find()
+        .filter(dependency_entity::Column::TodoId.eq(todo_id))
+        .order_by_asc(dependency_entity::Column::Id)
+        .all(db)
+        .await
+        .with_context(|| format!("failed to load dependencies of todo {todo_id}"))?;
+    Ok(rows.into_iter().map(|row| row.depends_on_uid).collect())
+}
+
+/// 用 iCalendar 中的 DEPENDS-ON 替换 todo 的依赖（同步和导入时调用，不标记 dirty）
+pub async fn replace_uids(db: &DatabaseConnection, todo_id: i32, uids: &[String]) -> Result<()> {
+    let existing = dependency_uids(db, todo_id).await?;
+
+    let stale: Vec<&String> = existing.iter().filter(|uid| !uids.contains(uid)).collect();
+    if !stale.is_empty() {
+        dependency_entity::Entity::delete_many()
+            .filter(dependency_entity::Column::TodoId.eq(todo_id))
+            .filter(dependency_entity::Column::DependsOnUid.is_in(stale))
+            .exec(db)
+            .await
+            .with_context(|| format!("failed to remove dependencies of todo {todo_id}"))?;
+    }
+
+    let mut known: HashSet<&String> = existing.iter().collect();
+    let now = Utc::now();
+    for uid in uids {
+        if !known.insert(uid) {
+            continue;
+        }
+        dependency_entity::ActiveModel {
+            id: NotSet,
+            todo_id: Set(todo_id),
+            depends_on_uid: Set(uid.clone()),
+            created_at: Set(now),
+        }
+        .insert(db)
+        .await
+        .with_context(|| format!("failed to add dependency of todo {todo_id}"))?;
+    }
+    Ok(())
+}
+
+/// 删除指向这些 UID 的依赖（todo 被彻底删除时调用）
+pub(super) async fn forget_uids(db: &DatabaseConnection, uids: Vec<String>) -> Result<()> {
+    dependency_entity::Entity::delete_many()
+        .filter(dependency_entity::Column::DependsOnUid.is_in(uids))
+        .exec(db)
+        .await
+        .context("failed to remove dependencies on purged todos")?;
+    Ok(())
+}
+
+/// 添加 todo_uid → depends_on_uid 后是否会成环：从 depends_on_uid 出发沿依赖关系能否回到 todo_uid
+async fn creates_cycle(
+    db: &DatabaseConnection,
+    todo_uid: &str,
+    depends_on_uid: &str,
+) -> Result<bool> {
+    let rows = dependency_entity::Entity::find()
+        .all(db)
+        .await
+        .context("failed to load todo dependencies")?;
+    let todo_ids: HashSet<i32> = rows.iter().map(|row| row.todo_id).collect();
+    let uid_by_id: HashMap<i32, String> = entity::Entity::find()
+        .filter(entity::Column::Id.is_in(todo_ids))
+        .all(db)
+        .await
+        .context("failed to load dependent todos")?
+        .into_iter()
+        .map(|model| (model.id, model.uid))
+        .collect();
+
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    for row in &rows {
+        if let Some(uid) = uid_by_id.get(&row.todo_id) {
+            graph
+                .entry(uid.as_str())
+                .or_default()
+                .push(row.depends_on_uid.as_str());
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut stack = vec![depends_on_uid];
+    while let Some(uid) = stack.pop() {
+        if uid == todo_uid {
+            return Ok(true);
+        }
+        if visited.insert(uid) {
+            stack.extend(graph.get(uid).into_iter().flatten());
+        }
+    }
+    Ok(false)
+}
+
+async fn find_live(db: &DatabaseConnection, id: i32) -> Result<entity::Model> {
+    entity::Entity::find_by_id(id)
+        .filter(entity::Column::DeletedAt.is_null())
+        .one(db)
+        .await
+        .with_context(|| format!("failed to find todo {id}"))?
+        .ok_or_else(|| anyhow!("todo {id} not found"))
+}
+
+async fn mark_dirty(db: &DatabaseConnection, model: entity::Model) -> Result<entity::Model> {
+    let id = model.id;
+    let now = Utc::now();
+    let mut active: entity::ActiveModel = model.into();
+    active.dirty = Set(true);
+    active.last_modified_at = Set(now);
+    active.updated_at = Set(now);
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to update todo {id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::todo::core::{query, service};
+    use crate::infrastructure::database::test_db::memory_db;
+
+    async fn create(db: &DatabaseConnection, title: &str) -> i32 {
+        service::create_todo(db, Some(title.to_string()))
+            .await
+            .unwrap()
+            .id
+    }
+
+    fn ids(todos: &[Todo]) -> Vec<i32> {
+        todos.iter().map(|todo| todo.id).collect()
+    }
+
+    #[tokio::test]
+    async fn dependencies_block_until_completed() {
+        let db = memory_db().await;
+        let design = create(&db, "design").await;
+        let build = create(&db, "build").await;
+        let release = create(&db, "release").await;
+        let notes = create(&db, "notes").await;
+        service::update_parent(&db, notes, Some(release))
+            .await
+            .unwrap();
+
+        let todo = add_dependency(&db, build, design).await.unwrap();
+        assert_eq!(todo.depends_on, vec![design]);
+        assert!(todo.blocked);
+        assert!(todo.dirty);
+        add_dependency(&db, release, build).await.unwrap();
+
+        assert!(add_dependency(&db, design, design).await.is_err());
+        let err = add_dependency(&db, design, release).await.unwrap_err();
+        assert!(err.to_string().contains("circular"));
+
+        let deps = get_dependencies(&db, build).await.unwrap();
+        assert_eq!(ids(&deps.depends_on), vec![design]);
+        assert_eq!(ids(&deps.dependents), vec![release]);
+
+        // release 被阻塞，且还有未完成的子任务
+        let next = next_actionable(&db, None).await.unwrap();
+        assert_eq!(ids(&next), vec![design, notes]);
+
+        let blocked = query::query_todos(
+            &db,
+            &query::TodoQuery {
+                blocked: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(&blocked.items), vec![build, release]);
+
+        service::update_todo(&db, design, None, Some(true))
+            .await
+            .unwrap();
+        let todo = service::get_todo(&db, build).await.unwrap();
+        assert!(!todo.blocked);
+        let next = next_actionable(&db, None).await.unwrap();
+        assert_eq!(ids(&next), vec![build, notes]);
+
+        let todo = remove_dependency(&db, release, build).await.unwrap();
+        assert!(todo.depends_on.is_empty());
+        assert!(!todo.blocked);
+    }
+}
@@ -0,0 +1,433 @@
+// 撤销 / 重做日志
+//
+// 每次用户操作（新建、修改、删除、恢复、排序、调整父任务）记录受影响 todo 在操作前后的状态，
+// 撤销时写回操作前的状态，重做时写回操作后的状态；状态为空表示 todo 在回收站中。
+// 一次撤销 / 重做的所有写回和条目状态在同一事务中提交，中途失败时不会只恢复一部分 todo。
+// 日志只保留最近 MAX_ENTRIES 条，记录新操作时丢弃已撤销（可重做）的条目
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{reminders, trash};
+use crate::features::todo::data::{entity, journal_entity};
+
+/// 保留的日志条数
+pub const MAX_ENTRIES: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalOperation {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Reorder,
+    Reparent,
+}
+
+impl JournalOperation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+            Self::Reorder => "reorder",
+            Self::Reparent => "reparent",
+        }
+    }
+}
+
+/// 可撤销的 todo 字段（同步、提醒触发记录等内部状态不在其中）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TodoState {
+    pub parent_id: Option<i32>,
+    pub order_index: Option<f64>,
+    pub title: String,
+    pub description: Option<String>,
+    pub completed: bool,
+    pub status: String,
+    pub percent_complete: Option<i32>,
+    pub priority: Option<i32>,
+    pub location: Option<String>,
+    pub tags: Option<String>,
+    pub start_at: DateTime<Utc>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub recurrence_rule: Option<String>,
+    pub reminder_offset_minutes: i32,
+    pub timezone: Option<String>,
+    pub reminder_method: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl TodoState {
+    /// 回收站中的 todo 没有状态
+    pub fn capture(model: &entity::Model) -> Option<Self> {
+        if model.deleted_at.is_some() {
+            return None;
+        }
+        Some(Self {
+            parent_id: model.parent_id,
+            order_index: model.order_index,
+            title: model.title.clone(),
+            description: model.description.clone(),
+            completed: model.completed,
+            status: model.status.clone(),
+            percent_complete: model.percent_complete,
+            priority: model.priority,
+            location: model.location.clone(),
+            tags: model.tags.clone(),
+            start_at: model.start_at,
+            due_date: model.due_date,
+            recurrence_rule: model.recurrence_rule.clone(),
+            reminder_offset_minutes: model.reminder_offset_minutes,
+            timezone: model.timezone.clone(),
+            reminder_method: model.reminder_method.clone(),
+            completed_at: model.completed_at,
+        })
+    }
+}
+
+/// 一个 todo 在操作前后的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoChange {
+    pub todo_id: i32,
+    pub before: Option<TodoState>,
+    pub after: Option<TodoState>,
+}
+
+impl TodoChange {
+    pub fn new(before: &entity::Model, after: &entity::Model) -> Self {
+        Self {
+            todo_id: before.id,
+            before: TodoState::capture(before),
+            after: TodoState::capture(after),
+        }
+    }
+
+    pub fn created(model: &entity::Model) -> Self {
+        Self {
+            todo_id: model.id,
+            before: None,
+            after: TodoState::capture(model),
+        }
+    }
+
+    pub fn trashed(model: &entity::Model) -> Self {
+        Self {
+            todo_id: model.id,
+            before: TodoState::capture(model),
+            after: None,
+        }
+    }
+}
+
+/// 撤销 / 重做的操作（供界面显示）
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub id: i32,
+    pub operation: String,
+    pub label: String,
+    pub todo_ids: Vec<i32>,
+    pub created_at: String,
+}
+
+impl JournalEntry {
+    fn from_model(model: &journal_entity::Model, changes: &[TodoChange]) -> Self {
+        Self {
+            id: model.id,
+            operation: model.operation.clone(),
+            label: model.label.clone(),
+            todo_ids: changes.iter().map(|change| change.todo_id).collect(),
+            created_at: model.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// 当前可撤销和可重做的操作
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JournalStatus {
+    pub undo: Option<JournalEntry>,
+    pub redo: Option<JournalEntry>,
+}
+
+/// 记录一次操作，没有实际变化时不记录
+pub async fn record(
+    db: &DatabaseConnection,
+    operation: JournalOperation,
+    label: &str,
+    changes: Vec<TodoChange>,
+) -> Result<()> {
+    let changes: Vec<TodoChange> = changes
+        .into_iter()
+        .filter(|change| change.before != change.after)
+        .collect();
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    // 新操作之后无法再重做之前撤销的操作
+    journal_entity::Entity::delete_many()
+        .filter(journal_entity::Column::Undone.eq(true))
+        .exec(db)
+        .await
+        .context("failed to clear redo history")?;
+
+    journal_entity::ActiveModel {
+        id: NotSet,
+        operation: Set(operation.as_str().to_string()),
+        label: Set(label.to_string()),
+        changes: Set(serde_json::to_string(&changes).context("failed to serialize changes")?),
+        undone: Set(false),
+        created_at: Set(Utc::now()),
+    }
+    .insert(db)
+    .await
+    .context("failed to record todo change")?;
+
+    trim(db).await
+}
+
+/// 只保留最近 MAX_ENTRIES 条
+async fn trim(db: &DatabaseConnection) -> Result<()> {
+    let oldest_kept = journal_entity::Entity::find()
+        .order_by_desc(journal_entity::Column::Id)
+        .offset(MAX_ENTRIES - 1)
+        .one(db)
+        .await?;
+    if let Some(oldest_kept) = oldest_kept {
+        journal_entity::Entity::delete_many()
+            .filter(journal_entity::Column::Id.lt(oldest_kept.id))
+            .exec(db)
+            .await
+            .context("failed to trim todo journal")?;
+    }
+    Ok(())
+}
+
+pub async fn status(db: &DatabaseConnection) -> Result<JournalStatus> {
+    let undo = next_undo(db).await?;
+    let redo = next_redo(db).await?;
+    Ok(JournalStatus {
+        undo: undo.map(|(model, changes)| JournalEntry::from_model(&model, &changes)),
+        redo: redo.map(|(model, changes)| JournalEntry::from_model(&model, &changes)),
+    })
+}
+
+/// 撤销最近一次操作，没有可撤销的操作时返回 None
+pub async fn undo(db: &DatabaseConnection) -> Result<Option<JournalEntry>> {
+    let txn = db
+        .begin()
+        .await
+        .context("failed to start undo transaction")?;
+    let Some((model, changes)) = next_undo(&txn).await? else {
+        return Ok(None);
+    };
+    // 按记录的相反顺序写回
+    for change in changes.iter().rev() {
+        apply_state(&txn, change.todo_id, change.before.as_ref()).await?;
+    }
+    set_undone(&txn, &model, true).await?;
+    txn.commit().await.context("failed to commit undo")?;
+    Ok(Some(JournalEntry::from_model(&model, &changes)))
+}
+
+/// 重做最近一次撤销的操作，没有可重做的操作时返回 None
+pub async fn redo(db: &DatabaseConnection) -> Result<Option<JournalEntry>> {
+    let txn = db
+        .begin()
+        .await
+        .context("failed to start redo transaction")?;
+    let Some((model, changes)) = next_redo(&txn).await? else {
+        return Ok(None);
+    };
+    for change in &changes {
+        apply_state(&txn, change.todo_id, change.after.as_ref()).await?;
+    }
+    set_undone(&txn, &model, false).await?;
+    txn.commit().await.context("failed to commit redo")?;
+    Ok(Some(JournalEntry::from_model(&model, &changes)))
+}
+
+async fn next_undo<C: ConnectionTrait>(
+    db: &C,
+) -> Result<Option<(journal_entity::Model, Vec<TodoChange>)>> {
+    let model = journal_entity::Entity::find()
+        .filter(journal_entity::Column::Undone.eq(false))
+        .order_by_desc(journal_entity::Column::Id)
+        .one(db)
+        .await
+        .context("failed to load todo journal")?;
+    model.map(with_changes).transpose()
+}
+
+/// 已撤销的条目中最早撤销的是 id 最小的一条
+async fn next_redo<C: ConnectionTrait>(
+    db: &C,
+) -> Result<Option<(journal_entity::Model, Vec<TodoChange>)>> {
+    let model = journal_entity::Entity::find()
+        .filter(journal_entity::Column::Undone.eq(true))
+        .order_by_asc(journal_entity::Column::Id)
+        .one(db)
+        .await
+        .context("failed to load todo journal")?;
+    model.map(with_changes).transpose()
+}
+
+fn with_changes(model: journal_entity::Model) -> Result<(journal_entity::Model, Vec<TodoChange>)> {
+    let changes = serde_json::from_str(&model.changes)
+        .with_context(|| format!("invalid changes in journal entry {}", model.id))?;
+    Ok((model, changes))
+}
+
+async fn set_undone<C: ConnectionTrait>(
+    db: &C,
+    model: &journal_entity::Model,
+    undone: bool,
+) -> Result<()> {
+    journal_entity::ActiveModel {
+        id: Set(model.id),
+        undone: Set(undone),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+    .with_context(|| format!("failed to update journal entry {}", model.id))?;
+    Ok(())
+}
+
+/// 将 todo 写回指定状态：为空时移入回收站，否则（必要时从回收站恢复）覆盖可撤销字段
+///
+/// 已从回收站彻底删除的 todo 跳过
+async fn apply_state<C: ConnectionTrait>(db: &C, id: i32, state: Option<&TodoState>) -> Result<()> {
+    let Some(model) = entity::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load todo {id}"))?
+    else {
+        println!("[Journal] todo {id} no longer exists, skipping");
+        return Ok(());
+    };
+    let now = Utc::now();
+
+    let Some(state) = state else {
+        if model.deleted_at.is_none() {
+            trash::trash_todo(db, model, now).await?;
+        }
+        return Ok(());
+    };
+
+    let trigger_changed = model.completed != state.completed || model.due_date != state.due_date;
+    let offset_changed = model.reminder_offset_minutes != state.reminder_offset_minutes;
+
+    let mut active: entity::ActiveModel = model.into();
+    active.parent_id = Set(state.parent_id);
+    active.order_index = Set(state.order_index);
+    active.title = Set(state.title.clone());
+    active.description = Set(state.description.clone());
+    active.completed = Set(state.completed);
+    active.status = Set(state.status.clone());
+    active.percent_complete = Set(state.percent_complete);
+    active.priority = Set(state.priority);
+    active.location = Set(state.location.clone());
+    active.tags = Set(state.tags.clone());
+    active.start_at = Set(state.start_at);
+    active.due_date = Set(state.due_date);
+    active.recurrence_rule = Set(state.recurrence_rule.clone());
+    active.reminder_offset_minutes = Set(state.reminder_offset_minutes);
+    active.timezone = Set(state.timezone.clone());
+    active.reminder_method = Set(state.reminder_method.clone());
+    active.completed_at = Set(state.completed_at);
+    if trigger_changed {
+        active.notified = Set(false);
+        active.reminder_last_triggered_at = Set(None);
+        active.last_reminded_occurrence_at = Set(None);
+        active.snoozed_until = Set(None);
+        active.snoozed_occurrence_at = Set(None);
+    }
+    active.deleted_at = Set(None);
+    active.dirty = Set(true);
+    active.last_modified_at = Set(now);
+    active.updated_at = Set(now);
+
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to restore todo {id}"))?;
+
+    if offset_changed {
+        reminders::set_primary_offset(db, id, state.reminder_offset_minutes).await?;
+    }
+    if trigger_changed {
+        reminders::reset_trigger_state(db, id).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::todo::core::service;
+    use crate::infrastructure::database::test_db::memory_db;
+
+    async fn load(db: &DatabaseConnection, id: i32) -> entity::Model {
+        entity::Entity::find_by_id(id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_restore_previous_states() {
+        let db = memory_db().await;
+        let parent = service::create_todo(&db, Some("Parent".to_string()))
+            .await
+            .unwrap();
+        let child = service::create_todo(&db, Some("Child".to_string()))
+            .await
+            .unwrap();
+        service::update_parent(&db, child.id, Some(parent.id))
+            .await
+            .unwrap();
+        service::update_todo(&db, parent.id, Some("Renamed".to_string()), None)
+            .await
+            .unwrap();
+
+        // 删除父任务时子任务一起进入回收站，撤销后一起恢复
+        service::delete_todo(&db, parent.id).await.unwrap();
+        assert!(load(&db, child.id).await.deleted_at.is_some());
+        let status = status(&db).await.unwrap();
+        assert_eq!(status.undo.unwrap().operation, "delete");
+
+        let undone = undo(&db).await.unwrap().unwrap();
+        assert_eq!(undone.todo_ids, vec![parent.id, child.id]);
+        let restored = load(&db, child.id).await;
+        assert!(restored.deleted_at.is_none());
+        assert_eq!(restored.parent_id, Some(parent.id));
+
+        undo(&db).await.unwrap();
+        assert_eq!(load(&db, parent.id).await.title, "Parent");
+        redo(&db).await.unwrap();
+        assert_eq!(load(&db, parent.id).await.title, "Renamed");
+
+        // 新操作之后不能再重做
+        undo(&db).await.unwrap();
+        service::update_todo(&db, parent.id, None, Some(true))
+            .await
+            .unwrap();
+        assert!(redo(&db).await.unwrap().is_none());
+
+        // 撤销新建：移入回收站
+        undo(&db).await.unwrap();
+        undo(&db).await.unwrap();
+        undo(&db).await.unwrap();
+        assert!(load(&db, child.id).await.deleted_at.is_some());
+    }
+}
@@ -2,7 +2,15 @@
 //
 // 负责业务逻辑实现：CRUD 操作、业务规则、调度任务
 
+pub mod catch_up;
+pub mod dependencies;
 pub mod fractional_index;
+pub mod journal;
 pub mod models;
+pub mod query;
+pub mod recurrence;
+pub mod reminders;
+pub mod saved_filters;
 pub mod scheduler;
 pub mod service;
+pub mod trash;
@@ -1,4 +1,4 @@
-use crate::features::todo::data::entity;
+use crate::features::todo::data::{entity, outbox_entity, reminder_entity, sync_conflict_entity};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +23,11 @@ pub struct Todo {
     pub timezone: Option<String>,
     pub reminder_method: Option<String>,
     pub reminder_last_triggered_at: Option<String>,
+    pub last_reminded_occurrence_at: Option<String>,
+    pub snoozed_until: Option<String>,
+    pub snoozed_occurrence_at: Option<String>,
+    pub missed_reminder_action: Option<String>,
+    pub missed_reminder_handled_at: Option<String>,
     pub completed_at: Option<String>,
     pub notified: bool,
     pub dirty: bool,
@@ -33,6 +38,12 @@ pub struct Todo {
     pub last_synced_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// 依赖的 todo id（见 `dependencies` 模块，From 转换时为空）
+    #[serde(default)]
+    pub depends_on: Vec<i32>,
+    /// 存在未完成的依赖
+    #[serde(default)]
+    pub blocked: bool,
 }
 
 impl From<entity::Model> for Todo {
@@ -64,6 +75,11 @@ impl From<entity::Model> for Todo {
             timezone: model.timezone,
             reminder_method: model.reminder_method,
             reminder_last_triggered_at: model.reminder_last_triggered_at.map(|d| d.to_rfc3339()),
+            last_reminded_occurrence_at: model.last_reminded_occurrence_at.map(|d| d.to_rfc3339()),
+            snoozed_until: model.snoozed_until.map(|d| d.to_rfc3339()),
+            snoozed_occurrence_at: model.snoozed_occurrence_at.map(|d| d.to_rfc3339()),
+            missed_reminder_action: model.missed_reminder_action,
+            missed_reminder_handled_at: model.missed_reminder_handled_at.map(|d| d.to_rfc3339()),
             completed_at: model.completed_at.map(|d| d.to_rfc3339()),
             notified: model.notified,
             dirty: model.dirty,
@@ -74,6 +90,120 @@ impl From<entity::Model> for Todo {
             last_synced_at: model.last_synced_at.map(|d| d.to_rfc3339()),
             created_at: model.created_at.to_rfc3339(),
             updated_at: model.updated_at.to_rfc3339(),
+            depends_on: Vec::new(),
+            blocked: false,
+        }
+    }
+}
+
+/// Todo 提醒（对应 iCalendar 的一个 VALARM）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoReminder {
+    pub id: i32,
+    pub todo_id: i32,
+    /// 相对提醒：提前的分钟数（负数表示之后）
+    pub offset_minutes: Option<i32>,
+    /// 相对提醒的基准："END"（到期时间）或 "START"（开始时间）
+    pub related: String,
+    /// 绝对提醒：固定的提醒时间
+    pub trigger_at: Option<String>,
+    pub action: String,
+    pub description: Option<String>,
+    pub last_triggered_occurrence_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<reminder_entity::Model> for TodoReminder {
+    fn from(model: reminder_entity::Model) -> Self {
+        Self {
+            id: model.id,
+            todo_id: model.todo_id,
+            offset_minutes: model.offset_minutes,
+            related: model.related,
+            trigger_at: model.trigger_at.map(|d| d.to_rfc3339()),
+            action: model.action,
+            description: model.description,
+            last_triggered_occurrence_at: model
+                .last_triggered_occurrence_at
+                .map(|d| d.to_rfc3339()),
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// 设置提醒时的输入（offset_minutes 与 trigger_at 二选一）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReminderInput {
+    pub offset_minutes: Option<i32>,
+    pub related: Option<String>,
+    pub trigger_at: Option<String>,
+    pub action: Option<String>,
+    pub description: Option<String>,
+}
+
+/// CalDAV 同步冲突（本地和远端修改了同一字段）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoSyncConflict {
+    pub id: i32,
+    pub todo_id: i32,
+    pub field: String,
+    pub base_value: Option<String>,
+    pub local_value: Option<String>,
+    pub remote_value: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<sync_conflict_entity::Model> for TodoSyncConflict {
+    fn from(model: sync_conflict_entity::Model) -> Self {
+        Self {
+            id: model.id,
+            todo_id: model.todo_id,
+            field: model.field,
+            base_value: model.base_value,
+            local_value: model.local_value,
+            remote_value: model.remote_value,
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// CalDAV 待推送操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalDavOutboxItem {
+    pub id: i32,
+    pub todo_id: i32,
+    pub todo_title: Option<String>,
+    /// create / update / delete
+    pub operation: String,
+    /// pending（等待推送或重试）/ failed（永久失败，需要用户处理）
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: String,
+    pub last_error: Option<String>,
+    /// 移动到其他日历后，等待删除的原日历资源
+    pub source_href: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl CalDavOutboxItem {
+    pub fn from_model(model: outbox_entity::Model, todo_title: Option<String>) -> Self {
+        Self {
+            id: model.id,
+            todo_id: model.todo_id,
+            todo_title,
+            operation: model.operation,
+            status: model.status,
+            attempts: model.attempts,
+            next_attempt_at: model.next_attempt_at.to_rfc3339(),
+            last_error: model.last_error,
+            source_href: model.source_href,
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
         }
     }
 }
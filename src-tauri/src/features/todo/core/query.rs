@@ -0,0 +1,417 @@
+// Todo 查询
+//
+// 在数据库中完成筛选、排序和分页，条件之间为“且”的关系，未设置的条件不参与筛选。
+// 保存的筛选条件（智能列表）也以 TodoQuery 的形式存储
+
+use anyhow::{bail, Context, Result};
+use sea_orm::{
+    sea_query::{Expr, NullOrdering, Query},
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter,
+    QueryOrder, Select,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{dependencies, models::Todo, service::parse_datetime};
+use crate::features::tag::data::task_tag_entity;
+use crate::features::todo::data::{entity, reminder_entity};
+
+/// 每页条数上限
+const MAX_PAGE_SIZE: u64 = 500;
+
+/// Todo 筛选条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TodoQuery {
+    /// 状态（NEEDS-ACTION / IN-PROCESS / COMPLETED / CANCELLED），满足其一即可
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<bool>,
+    /// 优先级范围（iCalendar：1 最高、9 最低），设置后不含未设置优先级的 todo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_min: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_max: Option<i32>,
+    /// 到期时间范围（RFC3339），设置后不含没有到期时间的 todo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_due_date: Option<bool>,
+    /// 标签（task_tags）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_ids: Vec<i32>,
+    #[serde(default)]
+    pub tag_match: TagMatch,
+    /// 只查询该 todo 的直接子任务
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<i32>,
+    /// 只查询顶层 todo（与 parent_id 同时设置时以 parent_id 为准）
+    #[serde(default)]
+    pub top_level_only: bool,
+    /// 在标题、描述和地点中匹配的文本（不区分大小写）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// 是否设置了提醒（todo_reminders）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_reminder: Option<bool>,
+    /// 是否存在未完成的依赖（todo_dependencies）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<bool>,
+    #[serde(default)]
+    pub sort_by: TodoSortField,
+    #[serde(default)]
+    pub sort_desc: bool,
+    /// 页码（从 0 开始）
+    #[serde(default)]
+    pub page: u64,
+    /// 每页条数，为空时返回全部结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u64>,
+}
+
+/// 多个标签的匹配方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    /// 包含任一标签
+    #[default]
+    Any,
+    /// 包含全部标签
+    All,
+}
+
+/// 排序字段（没有值的 todo 总是排在最后）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoSortField {
+    /// 手动排序（order_index）
+    #[default]
+    Manual,
+    DueDate,
+    Priority,
+    Title,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoQueryPage {
+    pub items: Vec<Todo>,
+    pub page: u64,
+    pub page_size: Option<u64>,
+    pub total: u64,
+}
+
+pub async fn query_todos(db: &DatabaseConnection, query: &TodoQuery) -> Result<TodoQueryPage> {
+    let select = sort(
+        entity::Entity::find().filter(build_condition(query)?),
+        query,
+    );
+
+    let Some(page_size) = query.page_size else {
+        let models = select.all(db).await.context("failed to query todos")?;
+        let items = dependencies::with_dependencies(db, models).await?;
+        return Ok(TodoQueryPage {
+            total: items.len() as u64,
+            items,
+            page: 0,
+            page_size: None,
+        });
+    };
+
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    let paginator = select.paginate(db, page_size);
+    let total = paginator
+        .num_items()
+        .await
+        .context("failed to count todos")?;
+    let models = paginator
+        .fetch_page(query.page)
+        .await
+        .context("failed to query todos")?;
+    let items = dependencies::with_dependencies(db, models).await?;
+
+    Ok(TodoQueryPage {
+        items,
+        page: query.page,
+        page_size: Some(page_size),
+        total,
+    })
+}
+
+/// 校验筛选条件（保存智能列表时调用，避免保存无法执行的条件）
+pub fn validate(query: &TodoQuery) -> Result<()> {
+    build_condition(query).map(|_| ())
+}
+
+fn build_condition(query: &TodoQuery) -> Result<Condition> {
+    let mut condition = Condition::all().add(entity::Column::DeletedAt.is_null());
+
+    if !query.statuses.is_empty() {
+        let statuses: Vec<String> = query
+            .statuses
+            .iter()
+            .map(|status| status.trim().to_ascii_uppercase())
+            .collect();
+        condition = condition.add(entity::Column::Status.is_in(statuses));
+    }
+    if let Some(completed) = query.completed {
+        condition = condition.add(entity::Column::Completed.eq(completed));
+    }
+
+    if let (Some(min), Some(max)) = (query.priority_min, query.priority_max) {
+        if min > max {
+            bail!("priority_min must not be greater than priority_max");
+        }
+    }
+    if let Some(min) = query.priority_min {
+        condition = condition.add(entity::Column::Priority.gte(min));
+    }
+    if let Some(max) = query.priority_max {
+        condition = condition.add(entity::Column::Priority.lte(max));
+    }
+
+    if let Some(before) = non_empty(&query.due_before) {
+        condition = condition.add(entity::Column::DueDate.lt(parse_datetime(before)?));
+    }
+    if let Some(after) = non_empty(&query.due_after) {
+        condition = condition.add(entity::Column::DueDate.gte(parse_datetime(after)?));
+    }
+    match query.has_due_date {
+        Some(true) => condition = condition.add(entity::Column::DueDate.is_not_null()),
+        Some(false) => condition = condition.add(entity::Column::DueDate.is_null()),
+        None => {}
+    }
+
+    if !query.tag_ids.is_empty() {
+        let mut tagged = Query::select();
+        tagged
+            .column(task_tag_entity::Column::TaskId)
+            .from(task_tag_entity::Entity)
+            .and_where(task_tag_entity::Column::TagId.is_in(query.tag_ids.clone()));
+        if query.tag_match == TagMatch::All {
+            let mut tag_ids = query.tag_ids.clone();
+            tag_ids.sort_unstable();
+            tag_ids.dedup();
+            tagged
+                .group_by_col(task_tag_entity::Column::TaskId)
+                .and_having(
+                    Expr::col(task_tag_entity::Column::TagId)
+                        .count_distinct()
+                        .eq(tag_ids.len() as i32),
+                );
+        }
+        condition = condition.add(entity::Column::Id.in_subquery(tagged.to_owned()));
+    }
+
+    if let Some(parent_id) = query.parent_id {
+        condition = condition.add(entity::Column::ParentId.eq(parent_id));
+    } else if query.top_level_only {
+        condition = condition.add(entity::Column::ParentId.is_null());
+    }
+
+    if let Some(text) = non_empty(&query.text) {
+        condition = condition.add(
+            Condition::any()
+                .add(entity::Column::Title.contains(text))
+                .add(entity::Column::Description.contains(text))
+                .add(entity::Column::Location.contains(text)),
+        );
+    }
+
+    if let Some(has_reminder) = query.has_reminder {
+        let with_reminders = Query::select()
+            .column(reminder_entity::Column::TodoId)
+            .from(reminder_entity::Entity)
+            .to_owned();
+        condition = condition.add(if has_reminder {
+            entity::Column::Id.in_subquery(with_reminders)
+        } else {
+            entity::Column::Id.not_in_subquery(with_reminders)
+        });
+    }
+
+    if let Some(blocked) = query.blocked {
+        condition = condition.add(if blocked {
+            entity::Column::Id.in_subquery(dependencies::blocked_todo_ids())
+        } else {
+            entity::Column::Id.not_in_subquery(dependencies::blocked_todo_ids())
+        });
+    }
+
+    Ok(condition)
+}
+
+fn sort(select: Select<entity::Entity>, query: &TodoQuery) -> Select<entity::Entity> {
+    let order = if query.sort_desc {
+        Order::Desc
+    } else {
+        Order::Asc
+    };
+    let column = match query.sort_by {
+        TodoSortField::Manual => entity::Column::OrderIndex,
+        TodoSortField::DueDate => entity::Column::DueDate,
+        TodoSortField::Priority => entity::Column::Priority,
+        TodoSortField::Title => entity::Column::Title,
+        TodoSortField::CreatedAt => entity::Column::CreatedAt,
+        TodoSortField::UpdatedAt => entity::Column::UpdatedAt,
+    };
+
+    // id 作为最后的排序键，保证分页结果稳定
+    select
+        .order_by_with_nulls(column, order.clone(), NullOrdering::Last)
+        .order_by(entity::Column::Id, order)
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::tag::data::entity as tag_entity;
+    use crate::features::todo::core::{reminders, service};
+    use crate::infrastructure::database::test_db::memory_db;
+    use chrono::{Duration, Utc};
+    use sea_orm::{ActiveModelTrait, ActiveValue::Set};
+
+    async fn create(db: &DatabaseConnection, title: &str, priority: Option<i32>) -> entity::Model {
+        let todo = service::create_todo(db, Some(title.to_string()))
+            .await
+            .unwrap();
+        let mut active: entity::ActiveModel =
+            service::get_todo_by_id(db, todo.id).await.unwrap().into();
+        active.priority = Set(priority);
+        active.update(db).await.unwrap()
+    }
+
+    async fn tag(db: &DatabaseConnection, todo_id: i32, name: &str) -> i32 {
+        let tag = tag_entity::Entity::find()
+            .filter(tag_entity::Column::Name.eq(name))
+            .one(db)
+            .await
+            .unwrap();
+        let tag_id = match tag {
+            Some(tag) => tag.id,
+            None => {
+                tag_entity::ActiveModel {
+                    name: Set(name.to_string()),
+                    created_at: Set(Utc::now()),
+                    updated_at: Set(Utc::now()),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await
+                .unwrap()
+                .id
+            }
+        };
+        task_tag_entity::ActiveModel {
+            task_id: Set(todo_id),
+            tag_id: Set(tag_id),
+            created_at: Set(Utc::now()),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        tag_id
+    }
+
+    async fn titles(db: &DatabaseConnection, query: TodoQuery) -> Vec<String> {
+        query_todos(db, &query)
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|todo| todo.title)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_filters_sort_and_pagination() {
+        let db = memory_db().await;
+        let report = create(&db, "Write report", Some(1)).await;
+        let groceries = create(&db, "Buy groceries", Some(5)).await;
+        let call = create(&db, "Call plumber", None).await;
+
+        let mut active: entity::ActiveModel = report.clone().into();
+        active.due_date = Set(Some(Utc::now() + Duration::days(1)));
+        active.update(&db).await.unwrap();
+        reminders::set_reminders(&db, call.id, Vec::new())
+            .await
+            .unwrap();
+        let work = tag(&db, report.id, "work").await;
+        let urgent = tag(&db, report.id, "urgent").await;
+        tag(&db, groceries.id, "work").await;
+
+        let by_priority = TodoQuery {
+            priority_max: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(titles(&db, by_priority).await, ["Write report"]);
+
+        let due_soon = TodoQuery {
+            due_before: Some((Utc::now() + Duration::days(2)).to_rfc3339()),
+            ..Default::default()
+        };
+        assert_eq!(titles(&db, due_soon).await, ["Write report"]);
+
+        let any_tag = TodoQuery {
+            tag_ids: vec![work, urgent],
+            ..Default::default()
+        };
+        assert_eq!(titles(&db, any_tag).await.len(), 2);
+        let all_tags = TodoQuery {
+            tag_ids: vec![work, urgent],
+            tag_match: TagMatch::All,
+            ..Default::default()
+        };
+        assert_eq!(titles(&db, all_tags).await, ["Write report"]);
+
+        let text = TodoQuery {
+            text: Some("PLUMB".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(titles(&db, text).await, ["Call plumber"]);
+        let without_reminder = TodoQuery {
+            has_reminder: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(titles(&db, without_reminder).await, ["Call plumber"]);
+
+        // 没有优先级的排在最后
+        let sorted = TodoQuery {
+            sort_by: TodoSortField::Priority,
+            ..Default::default()
+        };
+        assert_eq!(
+            titles(&db, sorted).await,
+            ["Write report", "Buy groceries", "Call plumber"]
+        );
+
+        let page = query_todos(
+            &db,
+            &TodoQuery {
+                page: 1,
+                page_size: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 1);
+
+        let invalid = TodoQuery {
+            priority_min: Some(5),
+            priority_max: Some(1),
+            ..Default::default()
+        };
+        assert!(validate(&invalid).is_err());
+    }
+}
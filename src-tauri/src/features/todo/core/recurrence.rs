@@ -0,0 +1,599 @@
+// RRULE 重复规则解析与实例计算
+//
+// 支持 RFC 5545 中常用的子集：
+// - FREQ: DAILY / WEEKLY / MONTHLY / YEARLY
+// - INTERVAL / COUNT / UNTIL / WKST
+// - BYDAY（MONTHLY 支持序号，如 1MO、-1FR；YEARLY 不支持 BYDAY）
+// - BYMONTHDAY（支持负数，如 -1 表示月末；MONTHLY 同时指定时 BYDAY 不能带序号）
+// 其他部分（BYSETPOS、BYMONTH 等）和不支持的组合在解析时报错，而不是忽略其中一部分条件后按错误的日期展开。
+//
+// 实例在 Todo 的本地时区中展开，保证"每周一 9 点"在夏令时切换后仍是本地 9 点。
+// COUNT 表示"包括当前实例在内剩余的实例数"，每次向后滚动时由调用方递减。
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+/// 向后查找的最大周期数（防止规则永远无法匹配时死循环）
+const MAX_PERIODS: u32 = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// BYDAY 中的一项，例如 `MO`、`2TU`、`-1FR`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// UNTIL 的取值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    /// DATE-TIME，按 UTC 处理
+    At(DateTime<Utc>),
+    /// 纯日期，包含当天（按 Todo 的本地时区判断）
+    Date(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+/// 下一次实例
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NextOccurrence {
+    pub at: DateTime<Utc>,
+    /// 从当前实例到下一次实例跨过的实例数（至少为 1，跳过已过期的实例时更大）
+    pub skipped: u32,
+}
+
+impl RecurrenceRule {
+    /// 解析 RRULE 字符串（允许带或不带 `RRULE:` 前缀）
+    pub fn parse(raw: &str) -> Result<Self> {
+        let body = strip_rrule_prefix(raw.trim());
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = Vec::new();
+        let mut by_month_day = Vec::new();
+        let mut week_start = Weekday::Mon;
+
+        for part in body.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("invalid RRULE part: {part}"))?;
+            let value = value.trim();
+
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => bail!("unsupported RRULE frequency: {other}"),
+                    });
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .with_context(|| format!("invalid RRULE INTERVAL: {value}"))?
+                        .max(1);
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse::<u32>()
+                            .with_context(|| format!("invalid RRULE COUNT: {value}"))?,
+                    );
+                }
+                "UNTIL" => until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<Vec<_>>>()?;
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = value
+                        .split(',')
+                        .map(|item| {
+                            let day = item
+                                .trim()
+                                .parse::<i32>()
+                                .with_context(|| format!("invalid RRULE BYMONTHDAY: {item}"))?;
+                            if day == 0 || !(-31..=31).contains(&day) {
+                                bail!("invalid RRULE BYMONTHDAY: {item}");
+                            }
+                            Ok(day)
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                }
+                "WKST" => week_start = parse_weekday(value)?,
+                // 其他部分（BYSETPOS、BYMONTH 等）不支持，忽略后会按错误的日期展开
+                other => bail!("unsupported RRULE part: {other}"),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| anyhow!("RRULE missing FREQ"))?;
+
+        if frequency == Frequency::Yearly && !by_day.is_empty() {
+            bail!("unsupported RRULE: BYDAY with FREQ=YEARLY");
+        }
+        if frequency == Frequency::Weekly && !by_month_day.is_empty() {
+            bail!("unsupported RRULE: BYMONTHDAY with FREQ=WEEKLY");
+        }
+        if by_day.iter().any(|d| d.ordinal.is_some())
+            && (frequency != Frequency::Monthly || !by_month_day.is_empty())
+        {
+            bail!("unsupported RRULE: BYDAY ordinals are only supported with FREQ=MONTHLY without BYMONTHDAY");
+        }
+
+        Ok(Self {
+            frequency,
+            interval,
+            count,
+            until,
+            by_day,
+            by_month_day,
+            week_start,
+        })
+    }
+
+    /// 计算 `dtstart` 之后、晚于 `not_before` 的第一个实例
+    ///
+    /// `dtstart` 视为当前实例（第 0 个）。返回 None 表示规则已经结束
+    /// （COUNT 用尽或超过 UNTIL）。
+    pub fn next_occurrence(
+        &self,
+        dtstart: DateTime<Utc>,
+        tz: Tz,
+        not_before: DateTime<Utc>,
+    ) -> Option<NextOccurrence> {
+        if matches!(self.count, Some(count) if count <= 1) {
+            return None;
+        }
+
+        let local_start = dtstart.with_timezone(&tz).naive_local();
+        let start_date = local_start.date();
+        let time = local_start.time();
+
+        let mut index = 0u32;
+        for period in 0..MAX_PERIODS {
+            let mut dates = self.period_dates(start_date, period.saturating_mul(self.interval));
+            dates.sort();
+            dates.dedup();
+
+            for date in dates {
+                // 当前实例本身以及之前的日期不参与计数
+                if date <= start_date {
+                    continue;
+                }
+                let Some(at) = resolve_local(&tz, date.and_time(time)) else {
+                    continue;
+                };
+
+                index += 1;
+                let past_until = match self.until {
+                    Some(Until::At(until)) => at > until,
+                    Some(Until::Date(until)) => date > until,
+                    None => false,
+                };
+                if past_until {
+                    return None;
+                }
+                if matches!(self.count, Some(count) if index >= count) {
+                    return None;
+                }
+                if at > not_before {
+                    return Some(NextOccurrence { at, skipped: index });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// 第 `offset` 个周期（已乘以 INTERVAL）内的候选日期
+    fn period_dates(&self, start: NaiveDate, offset: u32) -> Vec<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start + Duration::days(offset as i64);
+                if self.matches_filters(date) {
+                    vec![date]
+                } else {
+                    vec![]
+                }
+            }
+            Frequency::Weekly => {
+                let week_begin = start
+                    - Duration::days(days_between(self.week_start, start.weekday()) as i64)
+                    + Duration::weeks(offset as i64);
+                if self.by_day.is_empty() {
+                    return vec![start + Duration::weeks(offset as i64)];
+                }
+                self.by_day
+                    .iter()
+                    .map(|by_day| {
+                        week_begin
+                            + Duration::days(days_between(self.week_start, by_day.weekday) as i64)
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let Some((year, month)) = add_months(start.year(), start.month(), offset) else {
+                    return vec![];
+                };
+
+                if !self.by_month_day.is_empty() {
+                    return self
+                        .by_month_day
+                        .iter()
+                        .filter_map(|day| month_day(year, month, *day))
+                        .filter(|date| {
+                            self.by_day.is_empty()
+                                || self.by_day.iter().any(|d| d.weekday == date.weekday())
+                        })
+                        .collect();
+                }
+
+                if !self.by_day.is_empty() {
+                    return self
+                        .by_day
+                        .iter()
+                        .flat_map(|by_day| match by_day.ordinal {
+                            Some(n) => nth_weekday(year, month, by_day.weekday, n)
+                                .into_iter()
+                                .collect::<Vec<_>>(),
+                            None => weekdays_in_month(year, month, by_day.weekday),
+                        })
+                        .collect();
+                }
+
+                // 不存在的日期（如 2 月 30 日）按 RFC 5545 跳过
+                NaiveDate::from_ymd_opt(year, month, start.day())
+                    .into_iter()
+                    .collect()
+            }
+            Frequency::Yearly => {
+                let year = start.year() + offset as i32;
+                if !self.by_month_day.is_empty() {
+                    return self
+                        .by_month_day
+                        .iter()
+                        .filter_map(|day| month_day(year, start.month(), *day))
+                        .collect();
+                }
+                NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+
+    /// DAILY 下 BYDAY / BYMONTHDAY 作为过滤条件
+    fn matches_filters(&self, date: NaiveDate) -> bool {
+        let day_ok =
+            self.by_day.is_empty() || self.by_day.iter().any(|d| d.weekday == date.weekday());
+        let month_day_ok = self.by_month_day.is_empty()
+            || self
+                .by_month_day
+                .iter()
+                .any(|day| month_day(date.year(), date.month(), *day) == Some(date));
+        day_ok && month_day_ok
+    }
+}
+
+/// 将 RRULE 中的 COUNT 改写为新值，其余部分保持原样
+pub fn with_count(raw: &str, count: u32) -> String {
+    let trimmed = raw.trim();
+    let body = strip_rrule_prefix(trimmed);
+    let prefix = &trimmed[..trimmed.len() - body.len()];
+
+    let parts = body
+        .split(';')
+        .filter(|p| !p.trim().is_empty())
+        .map(|part| match part.split_once('=') {
+            Some((key, _)) if key.trim().eq_ignore_ascii_case("COUNT") => {
+                format!("COUNT={count}")
+            }
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    format!("{}{}", prefix, parts.join(";"))
+}
+
+fn strip_rrule_prefix(value: &str) -> &str {
+    if value.len() >= 6 && value[..6].eq_ignore_ascii_case("RRULE:") {
+        &value[6..]
+    } else {
+        value
+    }
+}
+
+fn parse_until(value: &str) -> Result<Until> {
+    // 纯日期：包含当天，在展开时与本地日期比较
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d")
+            .with_context(|| format!("invalid RRULE UNTIL: {value}"))?;
+        return Ok(Until::Date(date));
+    }
+
+    let trimmed = value.trim_end_matches('Z');
+    let naive = NaiveDateTime::parse_from_str(trimmed, "%Y%m%dT%H%M%S")
+        .with_context(|| format!("invalid RRULE UNTIL: {value}"))?;
+    Ok(Until::At(Utc.from_utc_datetime(&naive)))
+}
+
+fn parse_by_day(value: &str) -> Result<ByDay> {
+    let value = value.trim();
+    if value.len() < 2 {
+        bail!("invalid RRULE BYDAY: {value}");
+    }
+    let (ordinal, day) = value.split_at(value.len() - 2);
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        let n = ordinal
+            .trim_start_matches('+')
+            .parse::<i32>()
+            .with_context(|| format!("invalid RRULE BYDAY: {value}"))?;
+        if n == 0 {
+            bail!("invalid RRULE BYDAY: {value}");
+        }
+        Some(n)
+    };
+
+    Ok(ByDay {
+        ordinal,
+        weekday: parse_weekday(day)?,
+    })
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday> {
+    match value.trim().to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        other => bail!("invalid weekday: {other}"),
+    }
+}
+
+/// 从 `from` 到 `to` 需要向后数的天数（0..=6）
+fn days_between(from: Weekday, to: Weekday) -> u32 {
+    (to.num_days_from_monday() + 7 - from.num_days_from_monday()) % 7
+}
+
+fn add_months(year: i32, month: u32, offset: u32) -> Option<(i32, u32)> {
+    let total = year as i64 * 12 + (month as i64 - 1) + offset as i64;
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    Some((year, (total.rem_euclid(12) + 1) as u32))
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+/// BYMONTHDAY 解析为具体日期（负数从月末倒数）
+fn month_day(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    if day > 0 {
+        NaiveDate::from_ymd_opt(year, month, day as u32)
+    } else {
+        let resolved = days_in_month(year, month) as i32 + day + 1;
+        if resolved < 1 {
+            return None;
+        }
+        NaiveDate::from_ymd_opt(year, month, resolved as u32)
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: i32) -> Option<NaiveDate> {
+    if n > 0 {
+        return NaiveDate::from_weekday_of_month_opt(year, month, weekday, u8::try_from(n).ok()?);
+    }
+
+    let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month))?;
+    let last_match = last - Duration::days(days_between(weekday, last.weekday()) as i64);
+    let date = last_match - Duration::weeks((-n - 1) as i64);
+    (date.month() == month).then_some(date)
+}
+
+fn weekdays_in_month(year: i32, month: u32, weekday: Weekday) -> Vec<NaiveDate> {
+    (1..=5)
+        .filter_map(|n| NaiveDate::from_weekday_of_month_opt(year, month, weekday, n))
+        .collect()
+}
+
+/// 本地时间转 UTC；落在夏令时空隙中的时间顺延一小时
+fn resolve_local(tz: &Tz, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_with_prefix() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE").unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_day.len(), 2);
+        assert_eq!(rule.by_day[1].weekday, Weekday::Wed);
+    }
+
+    #[test]
+    fn test_parse_rejects_unsupported() {
+        assert!(RecurrenceRule::parse("FREQ=HOURLY").is_err());
+        assert!(RecurrenceRule::parse("INTERVAL=2").is_err());
+        assert!(RecurrenceRule::parse("FREQ=YEARLY;BYDAY=MO").is_err());
+        assert!(
+            RecurrenceRule::parse("FREQ=MONTHLY;BYMONTHDAY=8,9,10,11,12,13,14;BYDAY=2MO").is_err()
+        );
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=1MO").is_err());
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;BYMONTHDAY=1").is_err());
+        // 未支持的部分不能被忽略：否则会展开成每个周一 / 每个月
+        assert!(RecurrenceRule::parse("FREQ=MONTHLY;BYDAY=MO;BYSETPOS=1").is_err());
+        assert!(RecurrenceRule::parse("FREQ=YEARLY;BYMONTH=3").is_err());
+        assert!(RecurrenceRule::parse("FREQ=MONTHLY;BYMONTHDAY=1;BYMONTH=1,7").is_err());
+        assert!(RecurrenceRule::parse("FREQ=YEARLY;BYYEARDAY=100").is_err());
+
+        // 不带序号的 BYDAY 作为 BYMONTHDAY 的过滤条件
+        assert!(RecurrenceRule::parse("FREQ=MONTHLY;BYMONTHDAY=13;BYDAY=FR").is_ok());
+    }
+
+    #[test]
+    fn test_daily_skips_past_occurrences() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY").unwrap();
+        let start = utc("2024-03-01T09:00:00Z");
+        let now = utc("2024-03-04T12:00:00Z");
+
+        let next = rule.next_occurrence(start, Tz::UTC, now).unwrap();
+        assert_eq!(next.at, utc("2024-03-05T09:00:00Z"));
+        assert_eq!(next.skipped, 4);
+    }
+
+    #[test]
+    fn test_weekly_by_day() {
+        // 2024-03-04 是周一
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=MO,FR").unwrap();
+        let start = utc("2024-03-04T09:00:00Z");
+
+        let next = rule.next_occurrence(start, Tz::UTC, start).unwrap();
+        assert_eq!(next.at, utc("2024-03-08T09:00:00Z"));
+
+        let next = rule.next_occurrence(next.at, Tz::UTC, next.at).unwrap();
+        assert_eq!(next.at, utc("2024-03-11T09:00:00Z"));
+    }
+
+    #[test]
+    fn test_weekly_keeps_local_time_across_dst() {
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY").unwrap();
+        // 本地 09:00（UTC+1），2024-03-31 起柏林切换为夏令时（UTC+2）
+        let start = utc("2024-03-25T08:00:00Z");
+
+        let next = rule.next_occurrence(start, tz, start).unwrap();
+        assert_eq!(
+            next.at.with_timezone(&tz).time(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+        );
+        assert_eq!(next.at, utc("2024-04-01T07:00:00Z"));
+    }
+
+    #[test]
+    fn test_monthly_by_month_day_and_last_weekday() {
+        let start = utc("2024-01-31T10:00:00Z");
+
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;BYMONTHDAY=-1").unwrap();
+        let next = rule.next_occurrence(start, Tz::UTC, start).unwrap();
+        assert_eq!(next.at, utc("2024-02-29T10:00:00Z"));
+
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;BYDAY=-1FR").unwrap();
+        let next = rule.next_occurrence(start, Tz::UTC, start).unwrap();
+        assert_eq!(next.at, utc("2024-02-23T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_monthly_skips_invalid_dates() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY").unwrap();
+        let start = utc("2024-01-31T10:00:00Z");
+        let next = rule.next_occurrence(start, Tz::UTC, start).unwrap();
+        assert_eq!(next.at, utc("2024-03-31T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_yearly_leap_day() {
+        let rule = RecurrenceRule::parse("FREQ=YEARLY").unwrap();
+        let start = utc("2024-02-29T10:00:00Z");
+        let next = rule.next_occurrence(start, Tz::UTC, start).unwrap();
+        assert_eq!(next.at, utc("2028-02-29T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_count_and_until_end_series() {
+        let start = utc("2024-03-01T09:00:00Z");
+
+        let rule = RecurrenceRule::parse("FREQ=DAILY;COUNT=1").unwrap();
+        assert!(rule.next_occurrence(start, Tz::UTC, start).is_none());
+
+        let rule = RecurrenceRule::parse("FREQ=DAILY;COUNT=3").unwrap();
+        let now = utc("2024-03-05T00:00:00Z");
+        assert!(rule.next_occurrence(start, Tz::UTC, now).is_none());
+
+        let rule = RecurrenceRule::parse("FREQ=DAILY;UNTIL=20240302").unwrap();
+        let next = rule.next_occurrence(start, Tz::UTC, start).unwrap();
+        assert_eq!(next.at, utc("2024-03-02T09:00:00Z"));
+        assert!(rule.next_occurrence(next.at, Tz::UTC, next.at).is_none());
+    }
+
+    #[test]
+    fn test_date_until_uses_local_day() {
+        // 上海 08:00 是 UTC 00:00：UNTIL 当天的实例按本地日期包含在内
+        let tz: Tz = "Asia/Shanghai".parse().unwrap();
+        let rule = RecurrenceRule::parse("FREQ=DAILY;UNTIL=20240302").unwrap();
+        let start = utc("2024-03-01T00:00:00Z");
+
+        let next = rule.next_occurrence(start, tz, start).unwrap();
+        assert_eq!(next.at, utc("2024-03-02T00:00:00Z"));
+        assert!(rule.next_occurrence(next.at, tz, next.at).is_none());
+
+        // 纽约 20:00 是次日 UTC 01:00：本地 3 月 2 日的实例仍在 UNTIL 之内
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let start = utc("2024-03-01T01:00:00Z");
+        let next = rule.next_occurrence(start, tz, start).unwrap();
+        assert_eq!(next.at, utc("2024-03-02T01:00:00Z"));
+        let next = rule.next_occurrence(next.at, tz, next.at).unwrap();
+        assert_eq!(next.at, utc("2024-03-03T01:00:00Z"));
+        assert!(rule.next_occurrence(next.at, tz, next.at).is_none());
+    }
+
+    #[test]
+    fn test_with_count_preserves_other_parts() {
+        assert_eq!(
+            with_count("RRULE:FREQ=DAILY;COUNT=5;BYDAY=MO", 3),
+            "RRULE:FREQ=DAILY;COUNT=3;BYDAY=MO"
+        );
+        assert_eq!(with_count("FREQ=WEEKLY;COUNT=2", 1), "FREQ=WEEKLY;COUNT=1");
+    }
+}
@@ -0,0 +1,333 @@
+// Todo 多提醒（VALARM）
+//
+// 每个 Todo 可以有多个提醒，对应 iCalendar 的多个 VALARM：
+// - 相对提醒：相对实例到期时间（END）或开始时间（START）提前 N 分钟
+// - 绝对提醒：在固定时间触发一次
+// 触发状态按提醒单独记录，重复任务的每个实例会逐个触发每个提醒
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+
+use super::models::{ReminderInput, TodoReminder};
+use crate::features::todo::data::{entity, reminder_entity};
+
+pub const RELATED_END: &str = "END";
+pub const RELATED_START: &str = "START";
+pub const DEFAULT_ACTION: &str = "DISPLAY";
+
+const SUPPORTED_ACTIONS: [&str; 3] = ["DISPLAY", "AUDIO", "EMAIL"];
+
+/// 提醒定义（不含触发状态），用于整体替换某个 Todo 的提醒
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReminderSpec {
+    pub offset_minutes: Option<i32>,
+    pub related: String,
+    pub trigger_at: Option<DateTime<Utc>>,
+    pub action: String,
+    pub description: Option<String>,
+}
+
+impl ReminderSpec {
+    /// 相对到期时间提前 `offset_minutes` 分钟的显示提醒
+    pub fn relative(offset_minutes: i32) -> Self {
+        Self {
+            offset_minutes: Some(offset_minutes),
+            related: RELATED_END.to_string(),
+            trigger_at: None,
+            action: DEFAULT_ACTION.to_string(),
+            description: None,
+        }
+    }
+
+    /// 校验并规范化前端传入的提醒
+    pub fn from_input(input: ReminderInput) -> Result<Self> {
+        let trigger_at = match input.trigger_at.as_deref().map(str::trim) {
+            Some(value) if !value.is_empty() => Some(
+                DateTime::parse_from_rfc3339(value)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .with_context(|| format!("failed to parse reminder trigger: {value}"))?,
+            ),
+            _ => None,
+        };
+
+        match (input.offset_minutes, trigger_at) {
+            (Some(_), Some(_)) => bail!("reminder cannot have both offset_minutes and trigger_at"),
+            (None, None) => bail!("reminder requires offset_minutes or trigger_at"),
+            _ => {}
+        }
+
+        let related = match input.related.as_deref().map(str::to_ascii_uppercase) {
+            None => RELATED_END.to_string(),
+            Some(value) if value == RELATED_END || value == RELATED_START => value,
+            Some(value) => bail!("unsupported reminder related value: {value}"),
+        };
+
+        let action = match input.action.as_deref().map(str::to_ascii_uppercase) {
+            None => DEFAULT_ACTION.to_string(),
+            Some(value) if SUPPORTED_ACTIONS.contains(&value.as_str()) => value,
+            Some(value) => bail!("unsupported reminder action: {value}"),
+        };
+
+        Ok(Self {
+            offset_minutes: input.offset_minutes,
+            related,
+            trigger_at,
+            action,
+            description: input.description.filter(|d| !d.trim().is_empty()),
+        })
+    }
+
+    /// 是否与已有提醒的触发条件相同（相同则可沿用其触发状态）
+    fn same_trigger(&self, model: &reminder_entity::Model) -> bool {
+        self.offset_minutes == model.offset_minutes
+            && self.related == model.related
+            && self.trigger_at == model.trigger_at
+    }
+}
+
+/// 主提醒的偏移：第一个相对到期时间的提醒，没有时为 0
+///
+/// 用于同步 todos.remind_before_minutes 字段，保持旧字段与提醒表一致
+pub fn primary_offset(specs: &[ReminderSpec]) -> i32 {
+    specs
+        .iter()
+        .find(|spec| spec.trigger_at.is_none() && spec.related == RELATED_END)
+        .and_then(|spec| spec.offset_minutes)
+        .unwrap_or(0)
+}
+
+/// 获取 Todo 的全部提醒
+pub async fn list_reminders(db: &DatabaseConnection, todo_id: i32) -> Result<Vec<TodoReminder>> {
+    Ok(load_reminders(db, todo_id)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// 获取 Todo 的全部提醒（实体）
+pub async fn load_reminders<C: ConnectionTrait>(
+    db: &C,
+    todo_id: i32,
+) -> Result<Vec<reminder_entity::Model>> {
+    reminder_entity::Entity::find()
+        .filter(reminder_entity::Column::TodoId.eq(todo_id))
+        .order_by_asc(reminder_entity::Column::Id)
+        .all(db)
+        .await
+        .with_context(|| format!("failed to load reminders for todo {todo_id}"))
+}
+
+/// 批量获取多个 Todo 的提醒，按 todo_id 分组（用于调度器）
+pub async fn load_reminders_for_todos(
+    db: &DatabaseConnection,
+    todo_ids: &[i32],
+) -> Result<HashMap<i32, Vec<reminder_entity::Model>>> {
+    if todo_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let reminders = reminder_entity::Entity::find()
+        .filter(reminder_entity::Column::TodoId.is_in(todo_ids.iter().copied()))
+        .order_by_asc(reminder_entity::Column::Id)
+        .all(db)
+        .await
+        .context("failed to load todo reminders")?;
+
+    let mut grouped: HashMap<i32, Vec<reminder_entity::Model>> = HashMap::new();
+    for reminder in reminders {
+        grouped.entry(reminder.todo_id).or_default().push(reminder);
+    }
+    Ok(grouped)
+}
+
+/// 设置 Todo 的全部提醒（整体替换）
+///
+/// 触发条件未变化的提醒保留触发状态，Todo 标记为 dirty 以便同步到 CalDAV
+pub async fn set_reminders(
+    db: &DatabaseConnection,
+    todo_id: i32,
+    inputs: Vec<ReminderInput>,
+) -> Result<Vec<TodoReminder>> {
+    let todo = entity::Entity::find_by_id(todo_id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load todo {todo_id}"))?
+        .ok_or_else(|| anyhow!("todo {todo_id} not found"))?;
+
+    let specs = inputs
+        .into_iter()
+        .map(ReminderSpec::from_input)
+        .collect::<Result<Vec<_>>>()?;
+
+    replace_reminders(db, todo_id, &specs, true).await?;
+
+    let now = Utc::now();
+    let mut active: entity::ActiveModel = todo.into();
+    active.reminder_offset_minutes = Set(primary_offset(&specs));
+    active.notified = Set(false);
+    active.last_modified_at = Set(now);
+    active.updated_at = Set(now);
+    active.dirty = Set(true);
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to update todo {todo_id} after setting reminders"))?;
+
+    list_reminders(db, todo_id).await
+}
+
+/// 用给定定义整体替换 Todo 的提醒
+///
+/// `keep_state` 为 true 时，触发条件相同的提醒沿用原有触发状态，避免重复提醒
+pub async fn replace_reminders(
+    db: &DatabaseConnection,
+    todo_id: i32,
+    specs: &[ReminderSpec],
+    keep_state: bool,
+) -> Result<()> {
+    let mut existing = load_reminders(db, todo_id).await?;
+
+    reminder_entity::Entity::delete_many()
+        .filter(reminder_entity::Column::TodoId.eq(todo_id))
+        .exec(db)
+        .await
+        .with_context(|| format!("failed to clear reminders for todo {todo_id}"))?;
+
+    let now = Utc::now();
+    for spec in specs {
+        // 每个旧提醒最多被沿用一次
+        let previous = existing
+            .iter()
+            .position(|model| spec.same_trigger(model))
+            .map(|index| existing.swap_remove(index));
+
+        let last_triggered = if keep_state {
+            previous
+                .as_ref()
+                .and_then(|model| model.last_triggered_occurrence_at)
+        } else {
+            None
+        };
+        let created_at = previous.map(|model| model.created_at).unwrap_or(now);
+
+        reminder_entity::ActiveModel {
+            id: NotSet,
+            todo_id: Set(todo_id),
+            offset_minutes: Set(spec.offset_minutes),
+            related: Set(spec.related.clone()),
+            trigger_at: Set(spec.trigger_at),
+            action: Set(spec.action.clone()),
+            description: Set(spec.description.clone()),
+            last_triggered_occurrence_at: Set(last_triggered),
+            created_at: Set(created_at),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await
+        .with_context(|| format!("failed to insert reminder for todo {todo_id}"))?;
+    }
+
+    Ok(())
+}
+
+/// 更新主提醒（第一个相对到期时间的提醒）的偏移，没有时新建
+///
+/// 兼容只传 reminder_offset_minutes 的旧接口
+pub async fn set_primary_offset<C: ConnectionTrait>(
+    db: &C,
+    todo_id: i32,
+    offset_minutes: i32,
+) -> Result<()> {
+    let primary = load_reminders(db, todo_id)
+        .await?
+        .into_iter()
+        .find(|model| model.trigger_at.is_none() && model.related == RELATED_END);
+
+    let now = Utc::now();
+    match primary {
+        Some(model) => {
+            let mut active: reminder_entity::ActiveModel = model.into();
+            active.offset_minutes = Set(Some(offset_minutes));
+            active.last_triggered_occurrence_at = Set(None);
+            active.updated_at = Set(now);
+            active
+                .update(db)
+                .await
+                .with_context(|| format!("failed to update reminder for todo {todo_id}"))?;
+        }
+        None => {
+            create_reminder(db, todo_id, &ReminderSpec::relative(offset_minutes)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 新建一个提醒
+pub async fn create_reminder<C: ConnectionTrait>(
+    db: &C,
+    todo_id: i32,
+    spec: &ReminderSpec,
+) -> Result<reminder_entity::Model> {
+    let now = Utc::now();
+    reminder_entity::ActiveModel {
+        id: NotSet,
+        todo_id: Set(todo_id),
+        offset_minutes: Set(spec.offset_minutes),
+        related: Set(spec.related.clone()),
+        trigger_at: Set(spec.trigger_at),
+        action: Set(spec.action.clone()),
+        description: Set(spec.description.clone()),
+        last_triggered_occurrence_at: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .with_context(|| format!("failed to insert reminder for todo {todo_id}"))
+}
+
+/// 清除 Todo 所有提醒的触发状态（到期时间变化、完成状态切换时调用）
+pub async fn reset_trigger_state<C: ConnectionTrait>(db: &C, todo_id: i32) -> Result<()> {
+    reminder_entity::Entity::update_many()
+        .col_expr(
+            reminder_entity::Column::LastTriggeredOccurrenceAt,
+            sea_orm::sea_query::Expr::value(Option::<DateTime<Utc>>::None),
+        )
+        .filter(reminder_entity::Column::TodoId.eq(todo_id))
+        .exec(db)
+        .await
+        .with_context(|| format!("failed to reset reminders for todo {todo_id}"))?;
+
+    Ok(())
+}
+
+/// 记录提醒已对某个实例触发
+pub async fn mark_triggered(
+    db: &DatabaseConnection,
+    reminder_id: i32,
+    occurrence_at: DateTime<Utc>,
+) -> Result<()> {
+    let model = reminder_entity::Entity::find_by_id(reminder_id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load reminder {reminder_id}"))?
+        .ok_or_else(|| anyhow!("reminder {reminder_id} not found"))?;
+
+    let mut active: reminder_entity::ActiveModel = model.into();
+    active.last_triggered_occurrence_at = Set(Some(occurrence_at));
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to mark reminder {reminder_id} as triggered"))?;
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+// 保存的筛选条件（智能列表）
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+
+use super::query::{self, TodoQuery, TodoQueryPage};
+use crate::features::todo::data::saved_filter_entity;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub id: i32,
+    pub name: String,
+    pub query: TodoQuery,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl SavedFilter {
+    fn from_model(model: saved_filter_entity::Model) -> Result<Self> {
+        let query = serde_json::from_str(&model.query)
+            .with_context(|| format!("invalid query in saved filter {}", model.id))?;
+        Ok(Self {
+            id: model.id,
+            name: model.name,
+            query,
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
+        })
+    }
+}
+
+pub async fn list_filters(db: &DatabaseConnection) -> Result<Vec<SavedFilter>> {
+    saved_filter_entity::Entity::find()
+        .order_by_asc(saved_filter_entity::Column::Name)
+        .all(db)
+        .await
+        .context("failed to load saved filters")?
+        .into_iter()
+        .map(SavedFilter::from_model)
+        .collect()
+}
+
+/// 新建（id 为空）或更新保存的筛选条件，名称不能重复
+pub async fn save_filter(
+    db: &DatabaseConnection,
+    id: Option<i32>,
+    name: &str,
+    query: &TodoQuery,
+) -> Result<SavedFilter> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("filter name must not be empty");
+    }
+    query::validate(query)?;
+
+    let duplicate = saved_filter_entity::Entity::find()
+        .filter(saved_filter_entity::Column::Name.eq(name))
+        .one(db)
+        .await?;
+    if duplicate.is_some_and(|existing| Some(existing.id) != id) {
+        bail!("a saved filter named {name} already exists");
+    }
+
+    let now = Utc::now();
+    let serialized = serde_json::to_string(query).context("failed to serialize query")?;
+    let model = match id {
+        Some(id) => {
+            let existing = saved_filter_entity::Entity::find_by_id(id)
+                .one(db)
+                .await?
+                .ok_or_else(|| anyhow!("saved filter {id} not found"))?;
+            let mut active: saved_filter_entity::ActiveModel = existing.into();
+            active.name = Set(name.to_string());
+            active.query = Set(serialized);
+            active.updated_at = Set(now);
+            active.update(db).await
+        }
+        None => {
+            saved_filter_entity::ActiveModel {
+                id: NotSet,
+                name: Set(name.to_string()),
+                query: Set(serialized),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await
+        }
+    }
+    .context("failed to save filter")?;
+
+    SavedFilter::from_model(model)
+}
+
+pub async fn delete_filter(db: &DatabaseConnection, id: i32) -> Result<()> {
+    let result = saved_filter_entity::Entity::delete_by_id(id)
+        .exec(db)
+        .await
+        .with_context(|| format!("failed to delete saved filter {id}"))?;
+    if result.rows_affected == 0 {
+        bail!("saved filter {id} not found");
+    }
+    Ok(())
+}
+
+/// 执行保存的筛选条件，page / page_size 不为空时覆盖保存的分页设置
+pub async fn run_filter(
+    db: &DatabaseConnection,
+    id: i32,
+    page: Option<u64>,
+    page_size: Option<u64>,
+) -> Result<TodoQueryPage> {
+    let model = saved_filter_entity::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow!("saved filter {id} not found"))?;
+    let mut query = SavedFilter::from_model(model)?.query;
+    if let Some(page) = page {
+        query.page = page;
+    }
+    if page_size.is_some() {
+        query.page_size = page_size;
+    }
+
+    query::query_todos(db, &query).await
+}
@@ -3,25 +3,40 @@ use sea_orm::DatabaseConnection;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 
-use super::service;
+use super::{catch_up, service};
 use crate::features::todo::api::notifications;
 use crate::infrastructure::notification::NotificationManager;
 
+/// 提醒时间已过不超过该秒数的仍由调度器直接发送（同一时刻的多个提醒、调度延迟），
+/// 更早的视为错过，交给 `catch_up` 按补发策略处理
+const LATE_TOLERANCE_SECONDS: i64 = 60;
+
 /// 到期提醒调度器
 ///
 /// 工作流程：
-/// 1. 找到最近需要提醒的 Todo（due_date - reminder_offset_minutes 最早的）
+/// 0. 按补发策略处理已错过的提醒（启动、休眠唤醒、修改 RRULE 后），只调度提醒时间未过的提醒
+/// 1. 找到最近需要触发的提醒（每个 Todo 可有多个 VALARM，重复任务按 RRULE 展开实例）
 /// 2. 等待到提醒时间点
 /// 3. 发送统一通知（Toast + WebSocket）
-/// 4. 标记为已提醒（更新 reminder_last_triggered_at）
-/// 5. 自动 reschedule 找下一个需要提醒的 Todo
+/// 4. 标记该提醒对该实例已触发（更新提醒的 last_triggered_occurrence_at），稍后提醒则清除 snoozed_until
+/// 5. 自动 reschedule 找下一个需要提醒的实例（重复任务会重新挂上下一次实例）
 pub struct DueNotificationScheduler {
     db: DatabaseConnection,
     notification_manager: Arc<NotificationManager>,
-    next_reminder: Arc<RwLock<Option<(i32, DateTime<Utc>)>>>, // (todo_id, reminder_time)
+    next_reminder: Arc<RwLock<Option<ScheduledReminder>>>,
     reschedule_tx: mpsc::Sender<()>,
 }
 
+/// 已调度的提醒
+#[derive(Debug, Clone, Copy)]
+struct ScheduledReminder {
+    todo_id: i32,
+    /// None 表示稍后提醒（snooze）
+    reminder_id: Option<i32>,
+    occurrence_at: DateTime<Utc>,
+    reminder_time: DateTime<Utc>,
+}
+
 impl Clone for DueNotificationScheduler {
     fn clone(&self) -> Self {
         Self {
@@ -37,7 +52,7 @@ impl DueNotificationScheduler {
     /// 创建新的调度器
     pub fn new(db: DatabaseConnection, notification_manager: Arc<NotificationManager>) -> Self {
         let (reschedule_tx, mut reschedule_rx) = mpsc::channel::<()>(32);
-        let next_reminder = Arc::new(RwLock::new(None::<(i32, DateTime<Utc>)>));
+        let next_reminder = Arc::new(RwLock::new(None::<ScheduledReminder>));
 
         let scheduler = Self {
             db,
@@ -51,7 +66,7 @@ impl DueNotificationScheduler {
         tauri::async_runtime::spawn(async move {
             println!("[Scheduler] 后台任务已启动");
 
-            // 初始化时计算一次
+            // 初始化时计算一次（同时按策略处理应用关闭期间错过的提醒）
             scheduler_clone.schedule_next_reminder().await;
 
             loop {
@@ -86,14 +101,17 @@ impl DueNotificationScheduler {
     /// 等待到下次提醒时间
     async fn wait_until_next_reminder(&self) {
         loop {
-            let next = self.next_reminder.read().await.clone();
-            if let Some((todo_id, reminder_time)) = next {
+            let next = *self.next_reminder.read().await;
+            if let Some(reminder) = next {
                 let now = Utc::now();
-                if reminder_time > now {
-                    let duration = (reminder_time - now)
+                if reminder.reminder_time > now {
+                    let duration = (reminder.reminder_time - now)
                         .to_std()
                         .unwrap_or(std::time::Duration::from_secs(60));
-                    println!("[Scheduler] 等待 {:?} 后提醒 Todo#{}", duration, todo_id);
+                    println!(
+                        "[Scheduler] 等待 {:?} 后提醒 Todo#{}",
+                        duration, reminder.todo_id
+                    );
                     tokio::time::sleep(duration).await;
                     return;
                 }
@@ -108,25 +126,27 @@ impl DueNotificationScheduler {
 
     /// 计算并调度下次提醒
     async fn schedule_next_reminder(&self) {
+        let cutoff = Utc::now() - chrono::Duration::seconds(LATE_TOLERANCE_SECONDS);
+        if let Err(e) = catch_up::run_catch_up(&self.db, &self.notification_manager, cutoff).await {
+            eprintln!("[Scheduler] 处理错过的提醒时出错: {}", e);
+        }
+
         println!("[Scheduler] 查找下一个需要提醒的 Todo...");
-        match service::get_next_reminder_todo(&self.db).await {
-            Ok(Some(todo)) => {
-                // 计算提醒时间 = due_date - reminder_offset_minutes
-                if let Some(due_date) = todo.due_date {
-                    let offset_minutes = todo.reminder_offset_minutes;
-                    let reminder_time = due_date - chrono::Duration::minutes(offset_minutes as i64);
-
-                    *self.next_reminder.write().await = Some((todo.id, reminder_time));
-                    println!(
-                        "[Scheduler] 下次提醒: Todo#{} \"{}\" 在 {}",
-                        todo.id,
-                        todo.title,
-                        reminder_time.format("%Y-%m-%d %H:%M:%S")
-                    );
-                } else {
-                    eprintln!("[Scheduler] Todo#{} 缺少 due_date", todo.id);
-                    *self.next_reminder.write().await = None;
-                }
+        match service::get_next_reminder(&self.db, cutoff).await {
+            Ok(Some(pending)) => {
+                *self.next_reminder.write().await = Some(ScheduledReminder {
+                    todo_id: pending.todo.id,
+                    reminder_id: pending.reminder_id,
+                    occurrence_at: pending.occurrence_at,
+                    reminder_time: pending.remind_at,
+                });
+                println!(
+                    "[Scheduler] 下次提醒: Todo#{} \"{}\" 在 {}（实例到期 {}）",
+                    pending.todo.id,
+                    pending.todo.title,
+                    pending.remind_at.format("%Y-%m-%d %H:%M:%S"),
+                    pending.occurrence_at.format("%Y-%m-%d %H:%M:%S")
+                );
             }
             Ok(None) => {
                 *self.next_reminder.write().await = None;
@@ -141,21 +161,56 @@ impl DueNotificationScheduler {
 
     /// 发送提醒并自动重新调度
     async fn send_reminder_and_reschedule(&self) -> anyhow::Result<()> {
-        let next = self.next_reminder.read().await.clone();
+        let next = *self.next_reminder.read().await;
+
+        if let Some(reminder) = next {
+            let todo_id = reminder.todo_id;
+
+            // 休眠唤醒后才到期的等待已错过太久，交给 catch_up 按策略处理
+            let late = Utc::now() - reminder.reminder_time;
+            if late > chrono::Duration::seconds(LATE_TOLERANCE_SECONDS) {
+                println!("[Scheduler] Todo#{} 的提醒已错过，按补发策略处理", todo_id);
+                self.schedule_next_reminder().await;
+                return Ok(());
+            }
 
-        if let Some((todo_id, _)) = next {
             println!("[Scheduler] 发送提醒给 Todo#{}", todo_id);
 
             // 获取 Todo 详情
             let todo = service::get_todo_by_id(&self.db, todo_id).await?;
 
             // 统一发送 Toast + WebSocket 通知
-            notifications::notify_todo_due(&self.notification_manager, todo.id, &todo.title);
+            notifications::notify_todo_due(
+                &self.notification_manager,
+                todo.id,
+                &todo.title,
+                reminder.occurrence_at,
+            );
             println!("[Scheduler] 通知已发送 (Todo#{})", todo_id);
 
-            // 标记为已提醒
-            service::mark_todo_reminded(&self.db, todo_id).await?;
-            println!("[Scheduler] Todo#{} 已标记为已提醒", todo_id);
+            match reminder.reminder_id {
+                Some(reminder_id) => {
+                    // 标记该提醒对该实例已触发（重复任务随后会重新挂上下一次实例）
+                    service::mark_todo_reminded(
+                        &self.db,
+                        todo_id,
+                        reminder_id,
+                        reminder.occurrence_at,
+                    )
+                    .await?;
+                    println!(
+                        "[Scheduler] Todo#{} 的提醒#{} 对实例 {} 已标记为已触发",
+                        todo_id,
+                        reminder_id,
+                        reminder.occurrence_at.format("%Y-%m-%d %H:%M:%S")
+                    );
+                }
+                None => {
+                    // 稍后提醒只触发一次，需要再次推迟时由用户重新 snooze
+                    service::clear_todo_snooze(&self.db, todo_id).await?;
+                    println!("[Scheduler] Todo#{} 的稍后提醒已触发", todo_id);
+                }
+            }
 
             // 自动重新调度找下一个
             self.schedule_next_reminder().await;
@@ -1,7 +1,8 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{NotSet, Set},
@@ -9,25 +10,30 @@ use sea_orm::{
 };
 use uuid::Uuid;
 
-use super::{fractional_index, models::Todo};
-use crate::features::todo::data::entity;
+use super::{
+    dependencies, fractional_index,
+    journal::{self, JournalOperation, TodoChange},
+    models::Todo,
+    recurrence::{self, RecurrenceRule},
+    reminders::{self, ReminderSpec},
+    trash,
+};
+use crate::features::todo::data::{entity, reminder_entity};
 
 const DEFAULT_STATUS: &str = "NEEDS-ACTION";
 const COMPLETED_STATUS: &str = "COMPLETED";
 const DEFAULT_REMINDER_MINUTES: i32 = 15;
+const DEFAULT_SNOOZE_MINUTES: i64 = 10;
+const MAX_OVERDUE_OCCURRENCES: usize = 500;
 
 pub async fn list_todos(db: &DatabaseConnection) -> Result<Vec<Todo>> {
-    // 清理孤立的已删除项（deleted_at 不为空但没有 remote_url 的项）
-    // 这些可能是因为删除后 CalDAV 配置被移除导致的
-    cleanup_orphaned_deleted_todos(db).await?;
-
     let todos = entity::Entity::find()
         .filter(entity::Column::DeletedAt.is_null())
         .all(db)
         .await
         .context("failed to load todos")?;
 
-    Ok(todos.into_iter().map(Into::into).collect())
+    dependencies::with_dependencies(db, todos).await
 }
 
 pub async fn create_todo(db: &DatabaseConnection, title: Option<String>) -> Result<Todo> {
@@ -70,6 +76,11 @@ pub async fn create_todo(db: &DatabaseConnection, title: Option<String>) -> Resu
         timezone: Set(Some(local_timezone)), // 设置本地时区
         reminder_method: Set(Some("display".to_string())),
         reminder_last_triggered_at: Set(None),
+        last_reminded_occurrence_at: Set(None),
+        snoozed_until: Set(None),
+        snoozed_occurrence_at: Set(None),
+        missed_reminder_action: Set(None),
+        missed_reminder_handled_at: Set(None),
         completed_at: Set(None),
         notified: Set(false),
         dirty: Set(true),
@@ -77,6 +88,7 @@ pub async fn create_todo(db: &DatabaseConnection, title: Option<String>) -> Resu
         remote_etag: Set(None),
         remote_calendar_url: Set(None),
         sync_token: Set(None),
+        sync_snapshot: Set(None),
         last_synced_at: Set(None),
         deleted_at: Set(None),
         created_at: Set(now),
@@ -86,16 +98,32 @@ pub async fn create_todo(db: &DatabaseConnection, title: Option<String>) -> Resu
     .await
     .context("failed to insert todo")?;
 
+    // 默认创建一个到期前提醒
+    reminders::create_reminder(
+        db,
+        model.id,
+        &ReminderSpec::relative(DEFAULT_REMINDER_MINUTES),
+    )
+    .await?;
+
+    journal::record(
+        db,
+        JournalOperation::Create,
+        &model.title,
+        vec![TodoChange::created(&model)],
+    )
+    .await?;
+
     Ok(model.into())
 }
 
 pub async fn get_todo(db: &DatabaseConnection, id: i32) -> Result<Todo> {
-    entity::Entity::find_by_id(id)
+    let model = entity::Entity::find_by_id(id)
         .one(db)
         .await
         .with_context(|| format!("failed to load todo {id}"))?
-        .map(Into::into)
-        .ok_or_else(|| anyhow!("todo {id} not found"))
+        .ok_or_else(|| anyhow!("todo {id} not found"))?;
+    dependencies::to_todo(db, model).await
 }
 
 pub async fn update_todo(
@@ -110,15 +138,37 @@ pub async fn update_todo(
         .with_context(|| format!("failed to load todo {id}"))?
         .ok_or_else(|| anyhow!("todo {id} not found"))?;
 
-    let mut active: entity::ActiveModel = model.into();
     let now = Utc::now();
 
+    // 完成重复任务时，计算下一次实例（未完成 -> 完成 才滚动）
+    let next_instance = match completed {
+        Some(true) if !model.completed => next_recurrence_instance(&model, now),
+        _ => None,
+    };
+    let completion_toggled = next_instance.is_none() && completed.is_some();
+
+    let before = model.clone();
+    let mut active: entity::ActiveModel = model.into();
+
     if let Some(title) = title {
         let normalized = normalize_title(Some(title));
         active.title = Set(normalized.into_owned());
     }
 
-    if let Some(completed) = completed {
+    if let Some(instance) = next_instance {
+        // 重复任务：不标记完成，而是将时间滚动到下一次实例
+        active.due_date = Set(instance.due_date);
+        active.start_at = Set(instance.start_at);
+        active.recurrence_rule = Set(Some(instance.recurrence_rule));
+        active.completed = Set(false);
+        active.status = Set(DEFAULT_STATUS.to_string());
+        active.percent_complete = Set(Some(0));
+        active.completed_at = Set(None);
+        active.notified = Set(false);
+        active.reminder_last_triggered_at = Set(None);
+        active.snoozed_until = Set(None);
+        active.snoozed_occurrence_at = Set(None);
+    } else if let Some(completed) = completed {
         active.completed = Set(completed);
         if completed {
             active.status = Set(COMPLETED_STATUS.to_string());
@@ -126,12 +176,18 @@ pub async fn update_todo(
             active.completed_at = Set(Some(now));
             // 完成后清除提醒记录
             active.reminder_last_triggered_at = Set(None);
+            active.last_reminded_occurrence_at = Set(None);
+            active.snoozed_until = Set(None);
+            active.snoozed_occurrence_at = Set(None);
         } else {
             active.status = Set(DEFAULT_STATUS.to_string());
             active.percent_complete = Set(Some(0));
             active.completed_at = Set(None);
             // 取消完成时也清除提醒记录，允许重新提醒
             active.reminder_last_triggered_at = Set(None);
+            active.last_reminded_occurrence_at = Set(None);
+            active.snoozed_until = Set(None);
+            active.snoozed_occurrence_at = Set(None);
         }
     }
 
@@ -144,7 +200,19 @@ pub async fn update_todo(
         .await
         .with_context(|| format!("failed to update todo {id}"))?;
 
-    Ok(updated.into())
+    if completion_toggled {
+        reminders::reset_trigger_state(db, id).await?;
+    }
+
+    journal::record(
+        db,
+        JournalOperation::Update,
+        &updated.title,
+        vec![TodoChange::new(&before, &updated)],
+    )
+    .await?;
+
+    dependencies::to_todo(db, updated).await
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -171,6 +239,7 @@ pub async fn update_todo_details(
     let previous_due_date = model.due_date;
     let previous_reminder_offset = model.reminder_offset_minutes;
 
+    let before = model.clone();
     let mut active: entity::ActiveModel = model.into();
     let now = Utc::now();
 
@@ -191,22 +260,27 @@ pub async fn update_todo_details(
     }
 
     let parsed_due = parse_datetime_opt(due_date)?;
-    if parsed_due != previous_due_date {
+    let due_changed = parsed_due != previous_due_date;
+    if due_changed {
         active.due_date = Set(parsed_due);
         active.notified = Set(false);
         // 清除提醒记录，因为提醒时间可能变化
         active.reminder_last_triggered_at = Set(None);
+        active.last_reminded_occurrence_at = Set(None);
+        active.snoozed_until = Set(None);
+        active.snoozed_occurrence_at = Set(None);
     }
 
     active.recurrence_rule = Set(recurrence_rule);
 
-    if let Some(minutes) = reminder_offset_minutes {
-        if minutes != previous_reminder_offset {
-            active.reminder_offset_minutes = Set(minutes);
-            active.notified = Set(false);
-            // 清除提醒记录，因为提醒时间变化了
-            active.reminder_last_triggered_at = Set(None);
-        }
+    let changed_reminder_offset =
+        reminder_offset_minutes.filter(|minutes| *minutes != previous_reminder_offset);
+    if let Some(minutes) = changed_reminder_offset {
+        active.reminder_offset_minutes = Set(minutes);
+        active.notified = Set(false);
+        // 清除提醒记录，因为提醒时间变化了
+        active.reminder_last_triggered_at = Set(None);
+        active.last_reminded_occurrence_at = Set(None);
     }
 
     active.reminder_method = Set(reminder_method);
@@ -221,12 +295,25 @@ pub async fn update_todo_details(
         .await
         .with_context(|| format!("failed to update todo details {id}"))?;
 
-    Ok(updated.into())
+    if let Some(minutes) = changed_reminder_offset {
+        reminders::set_primary_offset(db, id, minutes).await?;
+    }
+    if due_changed {
+        reminders::reset_trigger_state(db, id).await?;
+    }
+
+    journal::record(
+        db,
+        JournalOperation::Update,
+        &updated.title,
+        vec![TodoChange::new(&before, &updated)],
+    )
+    .await?;
+
+    dependencies::to_todo(db, updated).await
 }
 
-/// 删除 todo：根据是否有远端同步配置决定删除策略
-/// - 如果 todo 有 remote_url（已同步到 CalDAV）：软删除，标记 deleted_at 和 dirty，等待同步删除远端
-/// - 如果 todo 没有 remote_url（纯本地）：直接硬删除
+/// 删除 todo：连同子任务移入回收站（见 `trash` 模块），可撤销或从回收站恢复
 pub async fn delete_todo(db: &DatabaseConnection, id: i32) -> Result<()> {
     let model = entity::Entity::find_by_id(id)
         .one(db)
@@ -234,29 +321,101 @@ pub async fn delete_todo(db: &DatabaseConnection, id: i32) -> Result<()> {
         .with_context(|| format!("failed to find todo {id}"))?
         .ok_or_else(|| anyhow!("todo {id} not found"))?;
 
-    // 如果有远端 URL，说明已同步到 CalDAV，需要软删除等待同步
-    if model.remote_url.is_some() {
-        let now = Utc::now();
-        let mut active: entity::ActiveModel = model.into();
-        active.deleted_at = Set(Some(now));
-        active.dirty = Set(true);
-        active.updated_at = Set(now);
-
-        active
-            .update(db)
-            .await
-            .with_context(|| format!("failed to mark todo {id} as deleted"))?;
-    } else {
-        // 纯本地 todo，直接硬删除
-        entity::Entity::delete_by_id(id)
-            .exec(db)
-            .await
-            .with_context(|| format!("failed to delete todo {id}"))?;
+    if model.deleted_at.is_some() {
+        return Ok(());
     }
 
+    let label = model.title.clone();
+    let trashed = trash::move_to_trash(db, model, Utc::now()).await?;
+
+    journal::record(
+        db,
+        JournalOperation::Delete,
+        &label,
+        trashed.iter().map(TodoChange::trashed).collect(),
+    )
+    .await?;
+
     Ok(())
 }
 
+/// 从回收站恢复 todo 及其子任务（见 `trash::restore`），返回恢复后的 todo
+pub async fn restore_todo(db: &DatabaseConnection, id: i32) -> Result<Todo> {
+    let restored = trash::restore(db, id, Utc::now()).await?;
+    let root = restored[0].1.clone();
+
+    journal::record(
+        db,
+        JournalOperation::Restore,
+        &root.title,
+        restored
+            .iter()
+            .map(|(before, after)| TodoChange::new(before, after))
+            .collect(),
+    )
+    .await?;
+
+    dependencies::to_todo(db, root).await
+}
+
+/// 重复任务的下一次实例
+struct RecurrenceInstance {
+    due_date: Option<DateTime<Utc>>,
+    start_at: DateTime<Utc>,
+    recurrence_rule: String,
+}
+
+/// 根据 RRULE 计算重复任务的下一次实例
+///
+/// - 以 due_date（没有则以 start_at）作为当前实例
+/// - 跳过已经过去的实例，保证下一次实例在 `now` 之后
+/// - start_at 与 due_date 同步平移，保持任务时长不变
+/// - 规则带 COUNT 时按跨过的实例数递减
+///
+/// 返回 None 表示不是重复任务、规则无法解析或规则已结束，调用方按普通任务完成
+fn next_recurrence_instance(
+    model: &entity::Model,
+    now: DateTime<Utc>,
+) -> Option<RecurrenceInstance> {
+    let raw_rule = model
+        .recurrence_rule
+        .as_deref()
+        .filter(|rule| !rule.trim().is_empty())?;
+
+    let rule = match RecurrenceRule::parse(raw_rule) {
+        Ok(rule) => rule,
+        Err(err) => {
+            eprintln!(
+                "[Service] Todo#{} 的重复规则无法解析，按普通任务完成: {}",
+                model.id, err
+            );
+            return None;
+        }
+    };
+
+    let tz = todo_timezone(model);
+    let anchor = model.due_date.unwrap_or(model.start_at);
+    let next = rule.next_occurrence(anchor, tz, now)?;
+    let delta = next.at - anchor;
+
+    let recurrence_rule = match rule.count {
+        Some(count) => recurrence::with_count(raw_rule, count.saturating_sub(next.skipped)),
+        None => raw_rule.to_string(),
+    };
+
+    println!(
+        "[Service] Todo#{} 为重复任务，下一次实例: {}",
+        model.id,
+        next.at.to_rfc3339()
+    );
+
+    Some(RecurrenceInstance {
+        due_date: model.due_date.map(|_| next.at),
+        start_at: model.start_at + delta,
+        recurrence_rule,
+    })
+}
+
 /// 获取指定任务的所有子任务
 pub async fn get_subtasks(db: &DatabaseConnection, parent_id: i32) -> Result<Vec<Todo>> {
     let todos = entity::Entity::find()
@@ -266,7 +425,7 @@ pub async fn get_subtasks(db: &DatabaseConnection, parent_id: i32) -> Result<Vec
         .await
         .with_context(|| format!("failed to load subtasks for parent {}", parent_id))?;
 
-    Ok(todos.into_iter().map(Into::into).collect())
+    dependencies::with_dependencies(db, todos).await
 }
 
 /// 更新任务的父任务关系
@@ -283,8 +442,9 @@ pub async fn update_parent(
             return Err(anyhow!("A task cannot be its own parent"));
         }
 
-        // 检查新父任务是否存在
+        // 检查新父任务是否存在（回收站中的 todo 不能作为父任务）
         let parent_exists = entity::Entity::find_by_id(new_parent_id)
+            .filter(entity::Column::DeletedAt.is_null())
             .one(db)
             .await?
             .is_some();
@@ -312,6 +472,7 @@ pub async fn update_parent(
         .with_context(|| format!("failed to find todo {}", id))?
         .ok_or_else(|| anyhow!("todo {} not found", id))?;
 
+    let before = model.clone();
     let mut active: entity::ActiveModel = model.into();
     active.parent_id = Set(parent_id);
     active.dirty = Set(true);
@@ -323,7 +484,15 @@ pub async fn update_parent(
         .await
         .with_context(|| format!("failed to update parent for todo {}", id))?;
 
-    Ok(updated.into())
+    journal::record(
+        db,
+        JournalOperation::Reparent,
+        &updated.title,
+        vec![TodoChange::new(&before, &updated)],
+    )
+    .await?;
+
+    dependencies::to_todo(db, updated).await
 }
 
 /// 更新任务的排序位置
@@ -340,12 +509,23 @@ pub async fn reorder_todo(
     );
     let now = Utc::now();
 
-    // 验证循环引用（如果改变了父任务）
+    // 验证新父任务存在且不会形成循环引用
     if let Some(new_parent_id) = new_parent_id {
         if new_parent_id == id {
             return Err(anyhow!("A task cannot be its own parent"));
         }
 
+        // 回收站中的 todo 不能作为父任务
+        let parent_exists = entity::Entity::find_by_id(new_parent_id)
+            .filter(entity::Column::DeletedAt.is_null())
+            .one(db)
+            .await?
+            .is_some();
+
+        if !parent_exists {
+            return Err(anyhow!("Parent task {} not found", new_parent_id));
+        }
+
         let mut current_parent_id = Some(new_parent_id);
         while let Some(pid) = current_parent_id {
             if pid == id {
@@ -390,13 +570,15 @@ pub async fn reorder_todo(
         new_order_index, before_order, after_order
     );
 
+    let before = model.clone();
+
     // 检查是否需要重新平衡
     if fractional_index::should_rebalance(new_order_index) {
         eprintln!(
             "[Reorder] Rebalancing order indices for parent_id={:?}, triggered by todo {}",
             new_parent_id, id
         );
-        rebalance_order_indices(db, new_parent_id).await?;
+        let mut changes = rebalance_order_indices(db, new_parent_id).await?;
         // 重新计算 order_index
         let before_order = if let Some(bid) = before_id {
             entity::Entity::find_by_id(bid)
@@ -428,7 +610,12 @@ pub async fn reorder_todo(
             .await
             .with_context(|| format!("failed to reorder todo {}", id))?;
 
-        return Ok(updated.into());
+        // 撤销时需要同时恢复同级任务重新平衡前的位置
+        changes.retain(|change| change.todo_id != id);
+        changes.push(TodoChange::new(&before, &updated));
+        journal::record(db, JournalOperation::Reorder, &updated.title, changes).await?;
+
+        return dependencies::to_todo(db, updated).await;
     }
 
     // 正常更新
@@ -444,11 +631,22 @@ pub async fn reorder_todo(
         .await
         .with_context(|| format!("failed to reorder todo {}", id))?;
 
-    Ok(updated.into())
+    journal::record(
+        db,
+        JournalOperation::Reorder,
+        &updated.title,
+        vec![TodoChange::new(&before, &updated)],
+    )
+    .await?;
+
+    dependencies::to_todo(db, updated).await
 }
 
-/// 重新平衡指定父任务下所有子任务的 order_index
-async fn rebalance_order_indices(db: &DatabaseConnection, parent_id: Option<i32>) -> Result<()> {
+/// 重新平衡指定父任务下所有子任务的 order_index，返回各任务的变化
+async fn rebalance_order_indices(
+    db: &DatabaseConnection,
+    parent_id: Option<i32>,
+) -> Result<Vec<TodoChange>> {
     use sea_orm::QueryOrder;
 
     let mut todos = if let Some(pid) = parent_id {
@@ -476,88 +674,271 @@ async fn rebalance_order_indices(db: &DatabaseConnection, parent_id: Option<i32>
         count, parent_id
     );
 
+    let mut changes = Vec::with_capacity(count);
     for (todo, new_index) in todos.iter_mut().zip(new_indices.iter()) {
         let mut active: entity::ActiveModel = todo.clone().into();
         active.order_index = Set(Some(new_index.clone()));
         active.dirty = Set(true);
         active.updated_at = Set(now);
         active.last_modified_at = Set(now);
-        active.update(db).await?;
+        let updated = active.update(db).await?;
+        changes.push(TodoChange::new(todo, &updated));
     }
 
     eprintln!("[Rebalance] Completed for parent_id={:?}", parent_id);
-    Ok(())
+    Ok(changes)
 }
 
-/// 获取下一个需要提醒的 Todo（用于调度器）
+/// 待发送的提醒（用于调度器）
+#[derive(Debug, Clone)]
+pub struct PendingReminder {
+    pub todo: entity::Model,
+    /// 触发的提醒 ID，None 表示稍后提醒（snooze）到期
+    pub reminder_id: Option<i32>,
+    /// 本次提醒对应的实例到期时间（重复任务为展开后的某个实例）
+    pub occurrence_at: DateTime<Utc>,
+    /// 提醒时间（相对提醒按实例时间与偏移计算，绝对提醒为固定时间）
+    pub remind_at: DateTime<Utc>,
+}
+
+/// 获取下一个需要发送的提醒（用于调度器）
 ///
 /// 查询条件：
 /// - 未删除
 /// - 未完成
-/// - 有 due_date
-/// - reminder_last_triggered_at 为空（未提醒过）
+/// - 存在尚未触发的提醒（每个 VALARM 单独计算，重复任务按 RRULE 展开，见 `next_reminder_trigger`）
+///   或存在稍后提醒（snoozed_until）
 ///
-/// 排序：按 (due_date - reminder_offset_minutes) 升序，即最早需要提醒的在前
-pub async fn get_next_reminder_todo(db: &DatabaseConnection) -> Result<Option<entity::Model>> {
-    // 不在数据库层过滤 reminder_last_triggered_at，因为可能是空字符串或 NULL
-    let todos = entity::Entity::find()
-        .filter(entity::Column::DeletedAt.is_null())
-        .filter(entity::Column::Completed.eq(false))
-        .filter(entity::Column::DueDate.is_not_null())
-        .all(db)
-        .await
-        .context("failed to query todos with reminders")?;
+/// 只返回提醒时间不早于 `now` 的提醒：更早的（休眠唤醒、修改 RRULE 后错过的实例）
+/// 由 `catch_up` 按补发策略处理，避免连续弹出过期的提醒
+///
+/// 排序：按提醒时间升序，即最早需要提醒的在前
+pub async fn get_next_reminder(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Option<PendingReminder>> {
+    let (todos, mut reminders_by_todo) = load_reminder_candidates(db).await?;
 
     println!(
-        "[Service] 查询到 {} 个未完成且有 due_date 的 Todo",
-        todos.len()
+        "[Service] 查询到 {} 个未完成的 Todo，其中 {} 个设置了提醒",
+        todos.len(),
+        reminders_by_todo.len()
     );
 
-    // 在内存中计算提醒时间并排序（因为 SQL 无法直接计算时间差）
-    let mut todos_with_reminder_time: Vec<_> = todos
-        .into_iter()
-        .filter_map(|todo| {
-            println!("[Service] Todo#{}: title=\"{}\", due_date={:?}, offset={}, reminder_last_triggered_at={:?}",
-                todo.id, todo.title, todo.due_date, todo.reminder_offset_minutes, todo.reminder_last_triggered_at);
-
-            // 过滤掉已经提醒过的（reminder_last_triggered_at 不为空）
-            if todo.reminder_last_triggered_at.is_some() {
-                println!("[Service]   -> 已提醒过，跳过");
-                return None;
-            }
+    // 在内存中展开实例并计算提醒时间（SQL 无法直接展开 RRULE）
+    let mut pending = Vec::new();
+    for todo in todos {
+        if let Some(snoozed) = snoozed_reminder(&todo).filter(|s| s.remind_at >= now) {
+            println!(
+                "[Service] Todo#{}: 稍后提醒时间 {}",
+                todo.id,
+                snoozed.remind_at.format("%Y-%m-%d %H:%M:%S")
+            );
+            pending.push(snoozed);
+        }
 
-            let due_date = todo.due_date?;
-            let offset = todo.reminder_offset_minutes;
-            let reminder_time = due_date - chrono::Duration::minutes(offset as i64);
+        let Some(todo_reminders) = reminders_by_todo.remove(&todo.id) else {
+            continue;
+        };
 
-            println!("[Service]   -> 计算出的提醒时间: {}", reminder_time.format("%Y-%m-%d %H:%M:%S"));
+        println!(
+            "[Service] Todo#{}: title=\"{}\", due_date={:?}, rrule={:?}, reminders={}",
+            todo.id,
+            todo.title,
+            todo.due_date,
+            todo.recurrence_rule,
+            todo_reminders.len()
+        );
 
-            Some((todo, reminder_time))
-        })
-        .collect();
+        for reminder in todo_reminders {
+            let Some((occurrence_at, remind_at)) =
+                next_upcoming_trigger(&todo, reminder.clone(), now)
+            else {
+                println!(
+                    "[Service]   -> 提醒#{} 没有尚未触发的实例，跳过",
+                    reminder.id
+                );
+                continue;
+            };
+
+            println!(
+                "[Service]   -> 提醒#{} 实例 {} 的提醒时间: {}",
+                reminder.id,
+                occurrence_at.format("%Y-%m-%d %H:%M:%S"),
+                remind_at.format("%Y-%m-%d %H:%M:%S")
+            );
+
+            pending.push(PendingReminder {
+                todo: todo.clone(),
+                reminder_id: Some(reminder.id),
+                occurrence_at,
+                remind_at,
+            });
+        }
+    }
 
-    println!(
-        "[Service] 有 {} 个 Todo 可以计算出提醒时间",
-        todos_with_reminder_time.len()
-    );
+    println!("[Service] 有 {} 个待触发的提醒", pending.len());
 
     // 按提醒时间排序
-    todos_with_reminder_time.sort_by_key(|(_, time)| *time);
+    pending.sort_by_key(|reminder| reminder.remind_at);
 
-    let result = todos_with_reminder_time
-        .into_iter()
-        .next()
-        .map(|(todo, _)| todo);
-    if let Some(ref todo) = result {
+    let result = pending.into_iter().next();
+    if let Some(ref reminder) = result {
         println!(
-            "[Service] 选中的下一个提醒 Todo: #{} \"{}\"",
-            todo.id, todo.title
+            "[Service] 选中的下一个提醒: Todo#{} \"{}\" 提醒#{:?}",
+            reminder.todo.id, reminder.todo.title, reminder.reminder_id
         );
     }
 
     Ok(result)
 }
 
+/// 获取所有已过提醒时间但尚未触发的提醒（用于启动时补发错过的提醒）
+///
+/// 重复任务会展开出所有错过的实例，每个提醒最多返回 `MAX_OVERDUE_OCCURRENCES` 个，按提醒时间升序
+pub async fn get_overdue_reminders(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<PendingReminder>> {
+    let (todos, mut reminders_by_todo) = load_reminder_candidates(db).await?;
+
+    let mut overdue = Vec::new();
+    for todo in todos {
+        if let Some(snoozed) = snoozed_reminder(&todo).filter(|s| s.remind_at <= now) {
+            overdue.push(snoozed);
+        }
+
+        for mut reminder in reminders_by_todo.remove(&todo.id).unwrap_or_default() {
+            for _ in 0..MAX_OVERDUE_OCCURRENCES {
+                let Some((occurrence_at, remind_at)) = next_reminder_trigger(&todo, &reminder)
+                else {
+                    break;
+                };
+                if remind_at > now {
+                    break;
+                }
+
+                overdue.push(PendingReminder {
+                    todo: todo.clone(),
+                    reminder_id: Some(reminder.id),
+                    occurrence_at,
+                    remind_at,
+                });
+                // 仅在内存中推进触发状态，继续展开后续错过的实例
+                reminder.last_triggered_occurrence_at = Some(occurrence_at);
+            }
+        }
+    }
+
+    overdue.sort_by_key(|reminder| reminder.remind_at);
+    Ok(overdue)
+}
+
+/// 加载可能需要提醒的 Todo（未删除、未完成）及其提醒
+async fn load_reminder_candidates(
+    db: &DatabaseConnection,
+) -> Result<(
+    Vec<entity::Model>,
+    HashMap<i32, Vec<reminder_entity::Model>>,
+)> {
+    let todos = entity::Entity::find()
+        .filter(entity::Column::DeletedAt.is_null())
+        .filter(entity::Column::Completed.eq(false))
+        .all(db)
+        .await
+        .context("failed to query todos with reminders")?;
+
+    let todo_ids: Vec<i32> = todos.iter().map(|todo| todo.id).collect();
+    let reminders_by_todo = reminders::load_reminders_for_todos(db, &todo_ids).await?;
+
+    Ok((todos, reminders_by_todo))
+}
+
+/// Todo 的稍后提醒（如有）
+fn snoozed_reminder(todo: &entity::Model) -> Option<PendingReminder> {
+    let snoozed_until = todo.snoozed_until?;
+    Some(PendingReminder {
+        todo: todo.clone(),
+        reminder_id: None,
+        occurrence_at: todo
+            .snoozed_occurrence_at
+            .or(todo.due_date)
+            .unwrap_or(snoozed_until),
+        remind_at: snoozed_until,
+    })
+}
+
+/// 单个提醒在 `now` 之后（含）的第一次触发，跳过提醒时间已过的实例（仅在内存中推进触发状态）
+fn next_upcoming_trigger(
+    todo: &entity::Model,
+    mut reminder: reminder_entity::Model,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    for _ in 0..MAX_OVERDUE_OCCURRENCES {
+        let (occurrence_at, remind_at) = next_reminder_trigger(todo, &reminder)?;
+        if remind_at >= now {
+            return Some((occurrence_at, remind_at));
+        }
+        reminder.last_triggered_occurrence_at = Some(occurrence_at);
+    }
+    None
+}
+
+/// 计算单个提醒下一次触发对应的（实例到期时间, 提醒时间）
+///
+/// - 绝对提醒只触发一次，实例时间取 due_date（没有则取触发时间）
+/// - 相对 END 提醒：实例到期时间 - offset
+/// - 相对 START 提醒：实例开始时间 - offset（开始时间与到期时间的间隔保持不变）
+fn next_reminder_trigger(
+    todo: &entity::Model,
+    reminder: &reminder_entity::Model,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if let Some(trigger_at) = reminder.trigger_at {
+        if reminder.last_triggered_occurrence_at.is_some() {
+            return None;
+        }
+        return Some((todo.due_date.unwrap_or(trigger_at), trigger_at));
+    }
+
+    let offset = chrono::Duration::minutes(reminder.offset_minutes? as i64);
+    let occurrence_at = next_unreminded_occurrence(todo, reminder.last_triggered_occurrence_at)?;
+    let anchor = if reminder.related == reminders::RELATED_START {
+        let due_date = todo.due_date?;
+        occurrence_at - (due_date - todo.start_at)
+    } else {
+        occurrence_at
+    };
+
+    Some((occurrence_at, anchor - offset))
+}
+
+/// 计算 Todo 下一个尚未提醒的实例到期时间
+///
+/// - 当前实例（due_date）尚未提醒时直接返回 due_date
+/// - 非重复任务只有一个实例，提醒过即结束
+/// - 重复任务从 due_date 起展开 RRULE，取 last_reminded 之后的第一个实例
+fn next_unreminded_occurrence(
+    todo: &entity::Model,
+    last_reminded: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    let due_date = todo.due_date?;
+    let Some(last_reminded) = last_reminded else {
+        return Some(due_date);
+    };
+    if last_reminded < due_date {
+        return Some(due_date);
+    }
+
+    let rule = todo
+        .recurrence_rule
+        .as_deref()
+        .filter(|rule| !rule.trim().is_empty())
+        .and_then(|rule| RecurrenceRule::parse(rule).ok())?;
+
+    rule.next_occurrence(due_date, todo_timezone(todo), last_reminded)
+        .map(|next| next.at)
+}
+
 /// 根据 ID 获取 Todo（用于调度器发送提醒）
 pub async fn get_todo_by_id(db: &DatabaseConnection, id: i32) -> Result<entity::Model> {
     entity::Entity::find_by_id(id)
@@ -567,16 +948,30 @@ pub async fn get_todo_by_id(db: &DatabaseConnection, id: i32) -> Result<entity::
         .ok_or_else(|| anyhow!("todo {id} not found"))
 }
 
-/// 标记 Todo 为已提醒（用于调度器）
-pub async fn mark_todo_reminded(db: &DatabaseConnection, id: i32) -> Result<()> {
+/// 标记 Todo 的某个提醒对某个实例已触发（用于调度器）
+///
+/// 按提醒、按实例记录触发状态，重启后不会重复提醒同一实例，也不会跳过后续实例
+pub async fn mark_todo_reminded(
+    db: &DatabaseConnection,
+    id: i32,
+    reminder_id: i32,
+    occurrence_at: DateTime<Utc>,
+) -> Result<()> {
+    reminders::mark_triggered(db, reminder_id, occurrence_at).await?;
+
     let model = entity::Entity::find_by_id(id)
         .one(db)
         .await
         .with_context(|| format!("failed to load todo {id}"))?
         .ok_or_else(|| anyhow!("todo {id} not found"))?;
 
+    let last_reminded = model
+        .last_reminded_occurrence_at
+        .map_or(occurrence_at, |previous| previous.max(occurrence_at));
+
     let mut active: entity::ActiveModel = model.into();
     active.reminder_last_triggered_at = Set(Some(Utc::now()));
+    active.last_reminded_occurrence_at = Set(Some(last_reminded));
 
     active
         .update(db)
@@ -586,47 +981,160 @@ pub async fn mark_todo_reminded(db: &DatabaseConnection, id: i32) -> Result<()>
     Ok(())
 }
 
-/// 清理所有待删除的 todo（公开函数，供清除 CalDAV 配置时调用）
+/// 稍后提醒：记录 snooze 时间，调度器到时会再次提醒
+///
+/// - 优先使用 `until`，其次 `minutes`，都未提供时默认 10 分钟后
+/// - 对应的实例取最近一次已提醒的实例（没有则取 due_date），该实例其余未触发的提醒标记为已触发
+/// - 仅保存在本地，不同步到 CalDAV
+pub async fn snooze_todo(
+    db: &DatabaseConnection,
+    id: i32,
+    minutes: Option<i32>,
+    until: Option<String>,
+) -> Result<Todo> {
+    let model = entity::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load todo {id}"))?
+        .ok_or_else(|| anyhow!("todo {id} not found"))?;
+
+    if model.completed {
+        return Err(anyhow!("todo {id} is already completed"));
+    }
+
+    let now = Utc::now();
+    let snoozed_until = match (parse_datetime_opt(until)?, minutes) {
+        (Some(until), _) => until,
+        (None, Some(minutes)) if minutes > 0 => now + chrono::Duration::minutes(minutes as i64),
+        (None, Some(minutes)) => return Err(anyhow!("invalid snooze minutes: {minutes}")),
+        (None, None) => now + chrono::Duration::minutes(DEFAULT_SNOOZE_MINUTES),
+    };
+    if snoozed_until <= now {
+        return Err(anyhow!("snooze time must be in the future"));
+    }
+
+    let occurrence_at = model.last_reminded_occurrence_at.or(model.due_date);
+
+    // 稍后提醒代替该实例其余尚未触发的提醒，原定时间不再重复提醒
+    if let Some(occurrence_at) = occurrence_at {
+        for reminder in reminders::load_reminders(db, id).await? {
+            let pending = next_reminder_trigger(&model, &reminder)
+                .is_some_and(|(next, _)| next <= occurrence_at);
+            if pending {
+                reminders::mark_triggered(db, reminder.id, occurrence_at).await?;
+            }
+        }
+    }
+
+    let mut active: entity::ActiveModel = model.into();
+    active.snoozed_until = Set(Some(snoozed_until));
+    active.snoozed_occurrence_at = Set(occurrence_at);
+    active.last_reminded_occurrence_at = Set(occurrence_at);
+    active.updated_at = Set(now);
+
+    let updated = active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to snooze todo {id}"))?;
+
+    dependencies::to_todo(db, updated).await
+}
+
+/// 清除 Todo 的稍后提醒（稍后提醒触发后由调度器调用）
+pub async fn clear_todo_snooze(db: &DatabaseConnection, id: i32) -> Result<()> {
+    let model = entity::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load todo {id}"))?
+        .ok_or_else(|| anyhow!("todo {id} not found"))?;
+
+    let mut active: entity::ActiveModel = model.into();
+    active.snoozed_until = Set(None);
+    active.snoozed_occurrence_at = Set(None);
+    active.reminder_last_triggered_at = Set(Some(Utc::now()));
+
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to clear snooze for todo {id}"))?;
+
+    Ok(())
+}
+
+/// 记录启动时对错过提醒的处理结果（coalesced / dropped / fired）
+pub async fn record_missed_reminder_action(
+    db: &DatabaseConnection,
+    id: i32,
+    action: &str,
+    handled_at: DateTime<Utc>,
+) -> Result<()> {
+    let model = entity::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load todo {id}"))?
+        .ok_or_else(|| anyhow!("todo {id} not found"))?;
+
+    let mut active: entity::ActiveModel = model.into();
+    active.missed_reminder_action = Set(Some(action.to_string()));
+    active.missed_reminder_handled_at = Set(Some(handled_at));
+
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to record missed reminder action for todo {id}"))?;
+
+    Ok(())
+}
+
+/// 放弃所有待推送的远端删除（公开函数，供清除 CalDAV 配置时调用）
+///
+/// 这些 todo 清除远端信息后留在回收站中
 pub async fn cleanup_pending_deletes(db: &DatabaseConnection) -> Result<()> {
     let pending_deletes = entity::Entity::find()
         .filter(entity::Column::DeletedAt.is_not_null())
+        .filter(entity::Column::RemoteUrl.is_not_null())
         .all(db)
         .await
         .context("failed to query pending deleted todos")?;
 
+    let now = Utc::now();
     for model in pending_deletes {
-        entity::Entity::delete_by_id(model.id)
-            .exec(db)
-            .await
-            .with_context(|| format!("failed to cleanup pending deleted todo {}", model.id))?;
+        trash::detach_from_remote(db, model, now).await?;
     }
 
     Ok(())
 }
 
-/// 清理孤立的已删除 todo（deleted_at 不为空但没有 remote_url）
-/// 这些通常是因为用户在标记删除后移除了 CalDAV 配置，导致无法完成同步删除
-async fn cleanup_orphaned_deleted_todos(db: &DatabaseConnection) -> Result<()> {
-    use sea_orm::sea_query::Expr;
-
-    // 查找所有已标记删除但没有远端 URL 的 todo
-    let orphaned = entity::Entity::find()
-        .filter(entity::Column::DeletedAt.is_not_null())
-        .filter(
-            Expr::col(entity::Column::RemoteUrl)
-                .is_null()
-                .or(Expr::col(entity::Column::RemoteUrl).eq("")),
-        )
+/// 解除 todo 与已移除日历的关联（供删除 CalDAV 日历时调用）
+///
+/// 回收站中的 todo 只清除远端信息；其余 todo 清除远端信息并标记为 dirty，之后推送到默认日历
+pub async fn detach_calendar_todos(db: &DatabaseConnection, calendar_url: &str) -> Result<()> {
+    let models = entity::Entity::find()
+        .filter(entity::Column::RemoteCalendarUrl.eq(calendar_url))
         .all(db)
         .await
-        .context("failed to query orphaned deleted todos")?;
+        .context("failed to query todos of removed calendar")?;
+
+    let now = Utc::now();
+    for model in models {
+        if model.deleted_at.is_some() {
+            trash::detach_from_remote(db, model, now).await?;
+            continue;
+        }
 
-    // 彻底删除这些孤立项
-    for model in orphaned {
-        entity::Entity::delete_by_id(model.id)
-            .exec(db)
+        let id = model.id;
+        let mut active: entity::ActiveModel = model.into();
+        active.remote_url = Set(None);
+        active.remote_etag = Set(None);
+        active.remote_calendar_url = Set(None);
+        active.sync_token = Set(None);
+        active.sync_snapshot = Set(None);
+        active.dirty = Set(true);
+        active.updated_at = Set(now);
+        active
+            .update(db)
             .await
-            .with_context(|| format!("failed to cleanup orphaned deleted todo {}", model.id))?;
+            .with_context(|| format!("failed to detach todo {id} from removed calendar"))?;
     }
 
     Ok(())
@@ -645,7 +1153,7 @@ fn normalize_title(title: Option<String>) -> Cow<'static, str> {
     }
 }
 
-fn parse_datetime(value: &str) -> Result<DateTime<Utc>> {
+pub(super) fn parse_datetime(value: &str) -> Result<DateTime<Utc>> {
     chrono::DateTime::parse_from_rfc3339(value)
         .map(|dt| dt.with_timezone(&Utc))
         .with_context(|| format!("failed to parse datetime: {value}"))
@@ -658,6 +1166,15 @@ fn parse_datetime_opt(value: Option<String>) -> Result<Option<DateTime<Utc>>> {
     }
 }
 
+/// Todo 所在时区（用于展开重复规则），无法识别时使用 UTC
+fn todo_timezone(model: &entity::Model) -> Tz {
+    model
+        .timezone
+        .as_deref()
+        .and_then(|tz| tz.parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC)
+}
+
 /// 获取本地时区（尝试检测系统时区，失败则使用 Asia/Shanghai）
 fn get_local_timezone() -> String {
     // 尝试使用 iana-time-zone 检测系统时区
@@ -671,3 +1188,84 @@ fn get_local_timezone() -> String {
     // 默认使用 Asia/Shanghai (UTC+8)
     "Asia/Shanghai".to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::todo::core::reminders::ReminderSpec;
+    use crate::infrastructure::database::test_db::memory_db;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn snooze_replaces_remaining_reminders_of_occurrence() {
+        let db = memory_db().await;
+        let now = Utc::now();
+        let due_date = now + Duration::minutes(20);
+
+        let todo = create_todo(&db, Some("Call the bank".to_string()))
+            .await
+            .unwrap();
+        let mut active: entity::ActiveModel = get_todo_by_id(&db, todo.id).await.unwrap().into();
+        active.due_date = Set(Some(due_date));
+        active.update(&db).await.unwrap();
+        reminders::replace_reminders(
+            &db,
+            todo.id,
+            &[ReminderSpec::relative(30), ReminderSpec::relative(0)],
+            false,
+        )
+        .await
+        .unwrap();
+
+        // 提前 30 分钟的提醒已经弹出，用户选择 30 分钟后再提醒
+        let first = get_next_reminder(&db, now).await.unwrap().unwrap();
+        assert_eq!(first.occurrence_at, due_date);
+        mark_todo_reminded(
+            &db,
+            todo.id,
+            first.reminder_id.unwrap(),
+            first.occurrence_at,
+        )
+        .await
+        .unwrap();
+        snooze_todo(&db, todo.id, Some(30), None).await.unwrap();
+
+        // 到期时的提醒不再单独触发，只剩稍后提醒
+        let next = get_next_reminder(&db, now).await.unwrap().unwrap();
+        assert_eq!(next.reminder_id, None);
+        assert_eq!(next.occurrence_at, due_date);
+
+        let overdue = get_overdue_reminders(&db, now + Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].reminder_id, None);
+    }
+
+    #[tokio::test]
+    async fn next_reminder_skips_past_occurrences() {
+        let db = memory_db().await;
+        let now = Utc::now();
+        let due_date = now - Duration::days(3) + Duration::hours(1);
+
+        let todo = create_todo(&db, Some("Water plants".to_string()))
+            .await
+            .unwrap();
+        let mut active: entity::ActiveModel = get_todo_by_id(&db, todo.id).await.unwrap().into();
+        active.due_date = Set(Some(due_date));
+        active.recurrence_rule = Set(Some("FREQ=DAILY".to_string()));
+        active.update(&db).await.unwrap();
+        reminders::replace_reminders(&db, todo.id, &[ReminderSpec::relative(0)], false)
+            .await
+            .unwrap();
+
+        // 已过的实例不由调度器逐个发送，只调度之后的第一次
+        let next = get_next_reminder(&db, now).await.unwrap().unwrap();
+        assert_eq!(next.occurrence_at, due_date + Duration::days(3));
+        assert!(next.remind_at >= now);
+
+        // 过期的实例仍未标记，留给 catch_up 按策略处理
+        let overdue = get_overdue_reminders(&db, now).await.unwrap();
+        assert_eq!(overdue.len(), 3);
+    }
+}
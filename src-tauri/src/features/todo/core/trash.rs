@@ -0,0 +1,477 @@
+// 回收站
+//
+// 删除 todo 时不再直接删除记录，而是连同子任务一起标记 deleted_at 移入回收站：
+// - 已同步到 CalDAV 的 todo 同时标记 dirty，由同步删除远端资源，之后仍保留在回收站中
+// - 纯本地 todo 不标记 dirty（避免被当作待删除的远端资源处理）
+// 回收站中超过保留天数、且没有待推送删除的 todo 才会被彻底删除
+//
+// 从回收站恢复时连同子任务一起恢复并标记 dirty：远端删除已推送的作为新 todo 重新创建，
+// 尚未推送的改为推送更新（远端资源已不存在时由同步重新创建）
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait,
+    DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::Serialize;
+
+use super::{dependencies, fractional_index, models::Todo};
+use crate::features::settings::core::service::SettingService;
+use crate::features::todo::data::entity;
+
+const RETENTION_KEY: &str = "todo.trash_retention_days";
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+const MAX_RETENTION_DAYS: u32 = 3650;
+
+/// 回收站保留天数
+pub async fn get_retention_days(db: &DatabaseConnection) -> Result<u32> {
+    let raw =
+        SettingService::get_or_default(db, RETENTION_KEY, &DEFAULT_RETENTION_DAYS.to_string())
+            .await?;
+    Ok(raw.parse().unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+pub async fn set_retention_days(db: &DatabaseConnection, days: u32) -> Result<()> {
+    if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+        bail!("trash retention must be between 1 and {MAX_RETENTION_DAYS} days");
+    }
+    SettingService::set(db, RETENTION_KEY, &days.to_string()).await?;
+    Ok(())
+}
+
+/// 回收站中的一项：删除的 todo 及同在回收站中的子任务
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashedTodo {
+    pub todo: Todo,
+    pub deleted_at: String,
+    /// 超过保留天数后自动彻底删除的时间
+    pub purge_at: String,
+    /// 远端删除尚未推送，推送完成前不会被彻底删除
+    pub pending_remote_delete: bool,
+    pub subtasks: Vec<TrashedTodo>,
+}
+
+/// 列出回收站：父任务不在回收站中的 todo 作为顶层项，按删除时间从新到旧排列
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<TrashedTodo>> {
+    let retention = Duration::days(i64::from(get_retention_days(db).await?));
+    let models = entity::Entity::find()
+        .filter(entity::Column::DeletedAt.is_not_null())
+        .order_by_desc(entity::Column::DeletedAt)
+        .all(db)
+        .await
+        .context("failed to load trashed todos")?;
+
+    let ids: HashSet<i32> = models.iter().map(|model| model.id).collect();
+    let mut roots = Vec::new();
+    let mut children: HashMap<i32, Vec<entity::Model>> = HashMap::new();
+    for model in models {
+        match model.parent_id.filter(|parent_id| ids.contains(parent_id)) {
+            Some(parent_id) => children.entry(parent_id).or_default().push(model),
+            None => roots.push(model),
+        }
+    }
+
+    Ok(roots
+        .into_iter()
+        .map(|model| build_entry(model, &mut children, retention))
+        .collect())
+}
+
+fn build_entry(
+    model: entity::Model,
+    children: &mut HashMap<i32, Vec<entity::Model>>,
+    retention: Duration,
+) -> TrashedTodo {
+    let deleted_at = model.deleted_at.unwrap_or(model.updated_at);
+    let subtasks = children
+        .remove(&model.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|child| build_entry(child, children, retention))
+        .collect();
+
+    TrashedTodo {
+        deleted_at: deleted_at.to_rfc3339(),
+        purge_at: (deleted_at + retention).to_rfc3339(),
+        pending_remote_delete: model.dirty,
+        subtasks,
+        todo: model.into(),
+    }
+}
+
+/// 将 todo 及其未删除的子孙移入回收站，返回移入前的记录（父任务在前）
+pub(crate) async fn move_to_trash(
+    db: &DatabaseConnection,
+    model: entity::Model,
+    now: DateTime<Utc>,
+) -> Result<Vec<entity::Model>> {
+    let trashed = subtree(db, model, false).await?;
+    for model in &trashed {
+        trash_todo(db, model.clone(), now).await?;
+    }
+    Ok(trashed)
+}
+
+/// todo 及其子孙中同在（`trashed` 为 true）或同不在回收站中的部分，父任务在前
+async fn subtree(
+    db: &DatabaseConnection,
+    root: entity::Model,
+    trashed: bool,
+) -> Result<Vec<entity::Model>> {
+    let mut models = vec![root];
+    let mut index = 0;
+    while index < models.len() {
+        let mut query =
+            entity::Entity::find().filter(entity::Column::ParentId.eq(models[index].id));
+        query = if trashed {
+            query.filter(entity::Column::DeletedAt.is_not_null())
+        } else {
+            query.filter(entity::Column::DeletedAt.is_null())
+        };
+        let children = query
+            .all(db)
+            .await
+            .with_context(|| format!("failed to load subtasks of todo {}", models[index].id))?;
+        models.extend(children);
+        index += 1;
+    }
+    Ok(models)
+}
+
+async fn find_trashed(db: &DatabaseConnection, id: i32) -> Result<entity::Model> {
+    let model = entity::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to find todo {id}"))?
+        .ok_or_else(|| anyhow!("todo {id} not found"))?;
+    if model.deleted_at.is_none() {
+        bail!("todo {id} is not in trash");
+    }
+    Ok(model)
+}
+
+/// 从回收站恢复 todo 及其在回收站中的子孙，返回各 todo 恢复前后的记录（父任务在前）
+///
+/// 父任务已不存在或仍在回收站中时恢复为顶层任务，排在末尾
+pub(super) async fn restore(
+    db: &DatabaseConnection,
+    id: i32,
+    now: DateTime<Utc>,
+) -> Result<Vec<(entity::Model, entity::Model)>> {
+    let model = find_trashed(db, id).await?;
+    let parent_alive = match model.parent_id {
+        Some(parent_id) => entity::Entity::find_by_id(parent_id)
+            .filter(entity::Column::DeletedAt.is_null())
+            .one(db)
+            .await
+            .with_context(|| format!("failed to load parent of todo {id}"))?
+            .is_some(),
+        None => true,
+    };
+    let top_level_order = if parent_alive {
+        None
+    } else {
+        let last_order_index = entity::Entity::find()
+            .filter(entity::Column::ParentId.is_null())
+            .filter(entity::Column::DeletedAt.is_null())
+            .all(db)
+            .await
+            .context("failed to load top-level todos")?
+            .iter()
+            .filter_map(|todo| todo.order_index)
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Some(fractional_index::generate_key_between(
+            last_order_index,
+            None,
+        ))
+    };
+
+    let mut restored = Vec::new();
+    for before in subtree(db, model, true).await? {
+        let todo_id = before.id;
+        let mut active: entity::ActiveModel = before.clone().into();
+        if let Some(order_index) = top_level_order.filter(|_| todo_id == id) {
+            active.parent_id = Set(None);
+            active.order_index = Set(Some(order_index));
+        }
+        active.deleted_at = Set(None);
+        active.dirty = Set(true);
+        active.last_modified_at = Set(now);
+        active.updated_at = Set(now);
+
+        let after = active
+            .update(db)
+            .await
+            .with_context(|| format!("failed to restore todo {todo_id} from trash"))?;
+        restored.push((before, after));
+    }
+    Ok(restored)
+}
+
+/// 彻底删除回收站中的 todo 及其在回收站中的子孙，返回删除的数量
+///
+/// 远端删除尚未推送的不能彻底删除，否则下次同步会把远端资源当作新 todo 拉取回来
+pub async fn purge(db: &DatabaseConnection, id: i32) -> Result<usize> {
+    let model = find_trashed(db, id).await?;
+    let models = subtree(db, model, true).await?;
+    if let Some(pending) = models.iter().find(|model| model.dirty) {
+        bail!(
+            "todo {} is waiting for its deletion to be pushed to CalDAV",
+            pending.id
+        );
+    }
+    delete_permanently(db, &models).await
+}
+
+/// 清空回收站（跳过远端删除尚未推送的 todo），返回删除的数量
+pub async fn empty(db: &DatabaseConnection) -> Result<usize> {
+    let models = entity::Entity::find()
+        .filter(entity::Column::DeletedAt.is_not_null())
+        .filter(entity::Column::Dirty.eq(false))
+        .all(db)
+        .await
+        .context("failed to load trashed todos")?;
+    delete_permanently(db, &models).await
+}
+
+/// 将单个 todo 移入回收站
+pub(super) async fn trash_todo<C: ConnectionTrait>(
+    db: &C,
+    model: entity::Model,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let id = model.id;
+    let has_remote = model.remote_url.is_some();
+    let mut active: entity::ActiveModel = model.into();
+    active.deleted_at = Set(Some(now));
+    active.dirty = Set(has_remote);
+    active.updated_at = Set(now);
+
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to move todo {id} to trash"))?;
+    Ok(())
+}
+
+/// 回收站中的 todo 不再对应远端资源（远端已删除或放弃删除）：清除远端信息和 dirty 标记
+///
+/// 之后从回收站恢复时会作为新 todo 推送到远端
+pub(crate) async fn detach_from_remote(
+    db: &DatabaseConnection,
+    model: entity::Model,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let id = model.id;
+    let mut active: entity::ActiveModel = model.into();
+    active.remote_url = Set(None);
+    active.remote_etag = Set(None);
+    active.remote_calendar_url = Set(None);
+    active.sync_token = Set(None);
+    active.sync_snapshot = Set(None);
+    active.last_synced_at = Set(None);
+    active.dirty = Set(false);
+    active.updated_at = Set(now);
+
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to detach trashed todo {id} from remote"))?;
+    Ok(())
+}
+
+/// 彻底删除回收站中超过保留天数的 todo，返回删除的数量
+///
+/// 仍在等待推送远端删除（dirty）的 todo 保留到同步完成
+pub async fn purge_expired(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<usize> {
+    let days = get_retention_days(db).await?;
+    let cutoff = now - Duration::days(i64::from(days));
+
+    let expired = entity::Entity::find()
+        .filter(entity::Column::DeletedAt.lt(cutoff))
+        .filter(entity::Column::Dirty.eq(false))
+        .all(db)
+        .await
+        .context("failed to query expired trashed todos")?;
+
+    delete_permanently(db, &expired).await
+}
+
+async fn delete_permanently(db: &DatabaseConnection, models: &[entity::Model]) -> Result<usize> {
+    let ids: Vec<i32> = models.iter().map(|model| model.id).collect();
+    for model in models {
+        // 不在本次删除范围内的子任务（已恢复或仍待推送删除）不随父任务一起删除（外键级联），改为顶层任务
+        entity::Entity::update_many()
+            .col_expr(entity::Column::ParentId, Expr::value(Option::<i32>::None))
+            .filter(entity::Column::ParentId.eq(model.id))
+            .filter(entity::Column::Id.is_not_in(ids.clone()))
+            .exec(db)
+            .await
+            .with_context(|| format!("failed to detach subtasks of todo {}", model.id))?;
+
+        entity::Entity::delete_by_id(model.id)
+            .exec(db)
+            .await
+            .with_context(|| format!("failed to purge trashed todo {}", model.id))?;
+    }
+    dependencies::forget_uids(db, models.iter().map(|model| model.uid.clone()).collect()).await?;
+
+    Ok(models.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::todo::core::service;
+    use crate::features::todo::sync::ical_file::{self, IcsImportMode};
+    use crate::infrastructure::database::test_db::memory_db;
+
+    async fn create(db: &DatabaseConnection, title: &str, parent_id: Option<i32>) -> i32 {
+        let todo = service::create_todo(db, Some(title.to_string()))
+            .await
+            .unwrap();
+        if parent_id.is_some() {
+            service::update_parent(db, todo.id, parent_id)
+                .await
+                .unwrap();
+        }
+        todo.id
+    }
+
+    async fn load(db: &DatabaseConnection, id: i32) -> Option<entity::Model> {
+        entity::Entity::find_by_id(id).one(db).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn list_restore_and_purge_subtask_trees() {
+        let db = memory_db().await;
+        let parent = create(&db, "Parent", None).await;
+        let child = create(&db, "Child", Some(parent)).await;
+        let grandchild = create(&db, "Grandchild", Some(child)).await;
+        let other = create(&db, "Other", None).await;
+
+        service::delete_todo(&db, parent).await.unwrap();
+        service::delete_todo(&db, other).await.unwrap();
+
+        let trashed = list(&db).await.unwrap();
+        assert_eq!(trashed.len(), 2);
+        let parent_entry = trashed
+            .iter()
+            .find(|entry| entry.todo.id == parent)
+            .unwrap();
+        assert!(!parent_entry.pending_remote_delete);
+        assert_eq!(parent_entry.subtasks[0].todo.id, child);
+        assert_eq!(parent_entry.subtasks[0].subtasks[0].todo.id, grandchild);
+
+        // 父任务仍在回收站中：恢复的子任务改为顶层任务，孙任务跟随恢复
+        let restored = service::restore_todo(&db, child).await.unwrap();
+        assert_eq!(restored.parent_id, None);
+        let grandchild_model = load(&db, grandchild).await.unwrap();
+        assert!(grandchild_model.deleted_at.is_none());
+        assert!(grandchild_model.dirty);
+        assert_eq!(grandchild_model.parent_id, Some(child));
+        assert!(service::restore_todo(&db, child).await.is_err());
+
+        // 彻底删除父任务不影响已恢复的子任务
+        assert_eq!(purge(&db, parent).await.unwrap(), 1);
+        assert!(load(&db, parent).await.is_none());
+        assert!(load(&db, child).await.is_some());
+
+        // 远端删除尚未推送的不能彻底删除，清空回收站时跳过
+        let mut active: entity::ActiveModel = load(&db, other).await.unwrap().into();
+        active.remote_url = Set(Some("https://example.com/other.ics".to_string()));
+        active.dirty = Set(true);
+        active.update(&db).await.unwrap();
+        assert!(purge(&db, other).await.is_err());
+        assert_eq!(empty(&db).await.unwrap(), 0);
+        assert!(list(&db).await.unwrap()[0].pending_remote_delete);
+    }
+
+    #[tokio::test]
+    async fn trashed_todos_cannot_become_parents() {
+        let db = memory_db().await;
+        let parent = create(&db, "Parent", None).await;
+        let child = create(&db, "Child", None).await;
+        service::delete_todo(&db, parent).await.unwrap();
+
+        assert!(service::update_parent(&db, child, Some(parent))
+            .await
+            .is_err());
+        assert_eq!(load(&db, child).await.unwrap().parent_id, None);
+
+        // 跳过已存在的条目时，回收站中的 todo 也不会成为导入子任务的父任务
+        let parent_uid = load(&db, parent).await.unwrap().uid;
+        let ics = format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:test\r\n\
+             BEGIN:VTODO\r\nUID:{parent_uid}\r\nSUMMARY:Parent\r\nEND:VTODO\r\n\
+             BEGIN:VTODO\r\nUID:imported-child\r\nSUMMARY:Imported\r\n\
+             RELATED-TO:{parent_uid}\r\nEND:VTODO\r\nEND:VCALENDAR\r\n"
+        );
+        let summary = ical_file::import_ics(&db, &ics, IcsImportMode::Skip)
+            .await
+            .unwrap();
+        assert_eq!((summary.created, summary.skipped), (1, 1));
+
+        let imported = entity::Entity::find()
+            .filter(entity::Column::Uid.eq("imported-child"))
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.parent_id, None);
+        assert!(load(&db, parent).await.unwrap().deleted_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn reorder_rejects_trashed_or_missing_parents() {
+        let db = memory_db().await;
+        let parent = create(&db, "Parent", None).await;
+        let child = create(&db, "Child", None).await;
+        service::delete_todo(&db, parent).await.unwrap();
+
+        assert!(service::reorder_todo(&db, child, None, None, Some(parent))
+            .await
+            .is_err());
+        assert!(
+            service::reorder_todo(&db, child, None, None, Some(parent + 100))
+                .await
+                .is_err()
+        );
+        assert_eq!(load(&db, child).await.unwrap().parent_id, None);
+    }
+
+    #[tokio::test]
+    async fn purge_keeps_recent_and_pending_deletes() {
+        let db = memory_db().await;
+        set_retention_days(&db, 7).await.unwrap();
+
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (title, age_days, pending) in [
+            ("old", 10, false),
+            ("recent", 1, false),
+            ("pending", 10, true),
+        ] {
+            let id = create(&db, title, None).await;
+            service::delete_todo(&db, id).await.unwrap();
+            let mut active: entity::ActiveModel = load(&db, id).await.unwrap().into();
+            active.deleted_at = Set(Some(now - Duration::days(age_days)));
+            active.dirty = Set(pending);
+            active.update(&db).await.unwrap();
+            ids.push(id);
+        }
+
+        assert_eq!(purge_expired(&db, now).await.unwrap(), 1);
+        let remaining: Vec<i32> = entity::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|todo| todo.id)
+            .collect();
+        assert_eq!(remaining, ids[1..].to_vec());
+    }
+}
@@ -0,0 +1,49 @@
+use sea_orm::{ConnectionTrait, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+/// 添加错过提醒处理记录字段（missed_reminder_action / missed_reminder_handled_at）的迁移
+#[derive(Debug, Clone, Copy)]
+pub struct AddMissedReminderMigration;
+
+impl MigrationName for AddMissedReminderMigration {
+    fn name(&self) -> &str {
+        "m20251212_000001_add_missed_reminder"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for AddMissedReminderMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        // 字段已存在时忽略错误
+        for column in ["missed_reminder_action", "missed_reminder_handled_at"] {
+            let _ = db
+                .execute(Statement::from_string(
+                    backend,
+                    format!("ALTER TABLE todos ADD COLUMN {column} TEXT"),
+                ))
+                .await;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        for column in ["missed_reminder_action", "missed_reminder_handled_at"] {
+            let _ = db
+                .execute(Statement::from_string(
+                    backend,
+                    format!("ALTER TABLE todos DROP COLUMN {column}"),
+                ))
+                .await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use sea_orm::{ConnectionTrait, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+/// 为推送队列添加原日历资源字段（source_calendar_url / source_href / source_etag）的迁移
+///
+/// todo 移动到其他日历后，原日历上的资源在目标日历上创建成功后才删除
+#[derive(Debug, Clone, Copy)]
+pub struct AddOutboxSourceMigration;
+
+const COLUMNS: [&str; 3] = ["source_calendar_url", "source_href", "source_etag"];
+
+impl MigrationName for AddOutboxSourceMigration {
+    fn name(&self) -> &str {
+        "m20260105_000001_add_outbox_source"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for AddOutboxSourceMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        // 字段已存在时忽略错误
+        for column in COLUMNS {
+            let _ = db
+                .execute(Statement::from_string(
+                    backend,
+                    format!("ALTER TABLE caldav_outbox ADD COLUMN {column} TEXT"),
+                ))
+                .await;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        for column in COLUMNS {
+            let _ = db
+                .execute(Statement::from_string(
+                    backend,
+                    format!("ALTER TABLE caldav_outbox DROP COLUMN {column}"),
+                ))
+                .await;
+        }
+
+        Ok(())
+    }
+}
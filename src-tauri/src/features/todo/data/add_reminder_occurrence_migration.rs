@@ -0,0 +1,61 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+/// 添加 last_reminded_occurrence_at 字段的迁移
+///
+/// 重复任务的每个实例都需要单独提醒，因此提醒状态按实例到期时间记录
+#[derive(Debug, Clone, Copy)]
+pub struct AddReminderOccurrenceMigration;
+
+impl MigrationName for AddReminderOccurrenceMigration {
+    fn name(&self) -> &str {
+        "m20251201_000001_add_reminder_occurrence"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for AddReminderOccurrenceMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        // 字段已存在时忽略错误
+        let _ = db
+            .execute(Statement::from_string(
+                backend,
+                "ALTER TABLE todos ADD COLUMN last_reminded_occurrence_at TEXT".to_owned(),
+            ))
+            .await;
+
+        // 已提醒过的旧数据：视为当前 due_date 对应的实例已提醒，避免升级后重复提醒
+        db.execute(Statement::from_string(
+            backend,
+            "UPDATE todos SET last_reminded_occurrence_at = due_date \
+             WHERE last_reminded_occurrence_at IS NULL \
+             AND reminder_last_triggered_at IS NOT NULL \
+             AND due_date IS NOT NULL"
+                .to_owned(),
+        ))
+        .await
+        .context("failed to backfill last_reminded_occurrence_at")
+        .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        let _ = db
+            .execute(Statement::from_string(
+                backend,
+                "ALTER TABLE todos DROP COLUMN last_reminded_occurrence_at".to_owned(),
+            ))
+            .await;
+
+        Ok(())
+    }
+}
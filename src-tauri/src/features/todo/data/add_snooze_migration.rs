@@ -0,0 +1,49 @@
+use sea_orm::{ConnectionTrait, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+/// 添加稍后提醒字段（snoozed_until / snoozed_occurrence_at）的迁移
+#[derive(Debug, Clone, Copy)]
+pub struct AddSnoozeMigration;
+
+impl MigrationName for AddSnoozeMigration {
+    fn name(&self) -> &str {
+        "m20251210_000001_add_snooze"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for AddSnoozeMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        // 字段已存在时忽略错误
+        for column in ["snoozed_until", "snoozed_occurrence_at"] {
+            let _ = db
+                .execute(Statement::from_string(
+                    backend,
+                    format!("ALTER TABLE todos ADD COLUMN {column} TEXT"),
+                ))
+                .await;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        for column in ["snoozed_until", "snoozed_occurrence_at"] {
+            let _ = db
+                .execute(Statement::from_string(
+                    backend,
+                    format!("ALTER TABLE todos DROP COLUMN {column}"),
+                ))
+                .await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Todo 依赖：todo_id 需要等 UID 为 depends_on_uid 的 todo 完成后才能开始
+///
+/// 按 UID 关联（对应 iCalendar 的 RELATED-TO;RELTYPE=DEPENDS-ON），被依赖的 todo 不在本地时记录也会保留
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "todo_dependencies")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub todo_id: i32,
+    pub depends_on_uid: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::entity::Entity",
+        from = "Column::TodoId",
+        to = "super::entity::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Todo,
+}
+
+impl Related<super::entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Todo.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
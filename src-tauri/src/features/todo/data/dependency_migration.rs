@@ -0,0 +1,52 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+use super::dependency_entity;
+
+/// Todo 依赖迁移
+///
+/// 创建 todo_dependencies 表，同一依赖关系只记录一次
+#[derive(Debug, Clone, Copy)]
+pub struct DependencyMigration;
+
+impl MigrationName for DependencyMigration {
+    fn name(&self) -> &str {
+        "m20260101_000001_todo_dependencies"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for DependencyMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        let mut create_dependencies = schema.create_table_from_entity(dependency_entity::Entity);
+        create_dependencies.if_not_exists();
+        db.execute(backend.build(&create_dependencies))
+            .await
+            .context("failed to create todo_dependencies table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        db.execute(Statement::from_string(
+            backend,
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_todo_dependencies_pair \
+             ON todo_dependencies(todo_id, depends_on_uid)"
+                .to_owned(),
+        ))
+        .await
+        .context("failed to create todo_dependencies index")
+        .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(dependency_entity::Entity).to_owned())
+            .await
+    }
+}
@@ -29,6 +29,15 @@ pub struct Model {
     pub timezone: Option<String>,
     pub reminder_method: Option<String>,
     pub reminder_last_triggered_at: Option<DateTimeUtc>,
+    /// 最近一次已提醒的实例到期时间（重复任务按实例记录提醒状态）
+    pub last_reminded_occurrence_at: Option<DateTimeUtc>,
+    /// 稍后提醒：到达该时间时再次提醒（为空表示没有稍后提醒）
+    pub snoozed_until: Option<DateTimeUtc>,
+    /// 稍后提醒对应的实例到期时间
+    pub snoozed_occurrence_at: Option<DateTimeUtc>,
+    /// 启动时对错过提醒的处理结果：coalesced（合并通知）/ dropped（丢弃）/ fired（逐个补发）
+    pub missed_reminder_action: Option<String>,
+    pub missed_reminder_handled_at: Option<DateTimeUtc>,
     pub completed_at: Option<DateTimeUtc>,
     #[sea_orm(default_value = false)]
     pub notified: bool,
@@ -38,6 +47,9 @@ pub struct Model {
     pub remote_etag: Option<String>,
     pub remote_calendar_url: Option<String>,
     pub sync_token: Option<String>,
+    /// 最近一次同步时的 iCalendar 内容（字段级三方合并的基准版本，
+    /// 也是推送时的模板，用于保留其他客户端写入的属性）
+    pub sync_snapshot: Option<String>,
     pub last_synced_at: Option<DateTimeUtc>,
     pub deleted_at: Option<DateTimeUtc>,
     pub created_at: DateTimeUtc,
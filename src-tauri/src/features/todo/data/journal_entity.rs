@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 撤销 / 重做日志条目（一次用户操作）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "todo_journal")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// create | update | delete | reorder | reparent
+    pub operation: String,
+    /// 操作对象的标题（用于界面显示"撤销删除 xxx"）
+    pub label: String,
+    /// 受影响 todo 在操作前后的状态（TodoChange 列表的 JSON）
+    #[sea_orm(column_type = "Text")]
+    pub changes: String,
+    /// 已撤销（可重做）
+    #[sea_orm(default_value = false)]
+    pub undone: bool,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,42 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+use super::journal_entity;
+
+/// 撤销 / 重做日志迁移
+///
+/// 创建 todo_journal 表
+#[derive(Debug, Clone, Copy)]
+pub struct JournalMigration;
+
+impl MigrationName for JournalMigration {
+    fn name(&self) -> &str {
+        "m20251231_000001_todo_journal"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for JournalMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        let mut create_journal = schema.create_table_from_entity(journal_entity::Entity);
+        create_journal.if_not_exists();
+        db.execute(backend.build(&create_journal))
+            .await
+            .context("failed to create todo_journal table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(journal_entity::Entity).to_owned())
+            .await
+    }
+}
@@ -2,7 +2,25 @@
 //
 // 负责数据持久化：数据库实体定义、数据库迁移
 
+pub mod add_missed_reminder_migration;
 pub mod add_order_index_migration;
+pub mod add_outbox_source_migration;
+pub mod add_reminder_occurrence_migration;
+pub mod add_snooze_migration;
 pub mod add_subtask_migration;
+pub mod dependency_entity;
+pub mod dependency_migration;
 pub mod entity;
+pub mod journal_entity;
+pub mod journal_migration;
 pub mod migration;
+pub mod outbox_entity;
+pub mod outbox_migration;
+pub mod reminder_entity;
+pub mod reminder_migration;
+pub mod saved_filter_entity;
+pub mod saved_filter_migration;
+pub mod sync_conflict_entity;
+pub mod sync_conflict_migration;
+pub mod sync_history_entity;
+pub mod sync_history_migration;
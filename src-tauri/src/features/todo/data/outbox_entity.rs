@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// CalDAV 待推送操作（outbox）
+///
+/// 每个 dirty 的 Todo 对应一条记录（create / update / delete），记录重试次数和下次重试时间；
+/// 永久失败（412、403、404）的记录状态为 failed，需要用户重试或放弃
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "caldav_outbox")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub todo_id: i32,
+    pub operation: String,
+    #[sea_orm(default_value = "pending")]
+    pub status: String,
+    #[sea_orm(default_value = 0)]
+    pub attempts: i32,
+    pub next_attempt_at: DateTimeUtc,
+    pub last_error: Option<String>,
+    /// todo 移动到其他日历后，等待删除的原日历资源（目标日历上创建成功后删除）
+    pub source_calendar_url: Option<String>,
+    pub source_href: Option<String>,
+    pub source_etag: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::entity::Entity",
+        from = "Column::TodoId",
+        to = "super::entity::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Todo,
+}
+
+impl Related<super::entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Todo.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,42 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+use super::outbox_entity;
+
+/// CalDAV 推送队列迁移
+///
+/// 创建 caldav_outbox 表，记录每个待推送 Todo 的重试状态
+#[derive(Debug, Clone, Copy)]
+pub struct OutboxMigration;
+
+impl MigrationName for OutboxMigration {
+    fn name(&self) -> &str {
+        "m20251220_000001_caldav_outbox"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for OutboxMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        let mut create_outbox = schema.create_table_from_entity(outbox_entity::Entity);
+        create_outbox.if_not_exists();
+        db.execute(backend.build(&create_outbox))
+            .await
+            .context("failed to create caldav_outbox table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(outbox_entity::Entity).to_owned())
+            .await
+    }
+}
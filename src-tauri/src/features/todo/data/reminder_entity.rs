@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Todo 提醒（对应 iCalendar 的 VALARM，每个 Todo 可以有多个）
+///
+/// - 相对提醒：offset_minutes 为相对到期时间（related = "END"）或开始时间（related = "START"）
+///   提前的分钟数，负数表示之后
+/// - 绝对提醒：trigger_at 为固定的提醒时间
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "todo_reminders")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub todo_id: i32,
+    pub offset_minutes: Option<i32>,
+    #[sea_orm(default_value = "END")]
+    pub related: String,
+    pub trigger_at: Option<DateTimeUtc>,
+    #[sea_orm(default_value = "DISPLAY")]
+    pub action: String,
+    pub description: Option<String>,
+    /// 最近一次已触发的实例到期时间（重复任务按实例记录触发状态）
+    pub last_triggered_occurrence_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::entity::Entity",
+        from = "Column::TodoId",
+        to = "super::entity::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Todo,
+}
+
+impl Related<super::entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Todo.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
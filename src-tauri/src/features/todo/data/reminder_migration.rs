@@ -0,0 +1,76 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+use super::reminder_entity;
+
+/// Todo 多提醒（todo_reminders 表）迁移
+///
+/// 首次创建表时，将旧的单一 remind_before_minutes 迁移为一条相对提醒
+#[derive(Debug, Clone, Copy)]
+pub struct TodoReminderMigration;
+
+impl MigrationName for TodoReminderMigration {
+    fn name(&self) -> &str {
+        "m20251205_000001_create_todo_reminders"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for TodoReminderMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        let table_exists = db
+            .query_one(Statement::from_string(
+                backend,
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'todo_reminders'"
+                    .to_owned(),
+            ))
+            .await?
+            .is_some();
+
+        let mut create_reminders = schema.create_table_from_entity(reminder_entity::Entity);
+        create_reminders.if_not_exists();
+        db.execute(backend.build(&create_reminders))
+            .await
+            .context("failed to create todo_reminders table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        let _ = db
+            .execute(Statement::from_string(
+                backend,
+                "CREATE INDEX IF NOT EXISTS idx_todo_reminders_todo_id ON todo_reminders(todo_id)"
+                    .to_owned(),
+            ))
+            .await;
+
+        // 仅在首次建表时迁移旧数据，避免用户删除全部提醒后又被补回
+        if !table_exists {
+            db.execute(Statement::from_string(
+                backend,
+                "INSERT INTO todo_reminders \
+                 (todo_id, offset_minutes, related, trigger_at, action, description, \
+                  last_triggered_occurrence_at, created_at, updated_at) \
+                 SELECT id, remind_before_minutes, 'END', NULL, 'DISPLAY', NULL, \
+                  last_reminded_occurrence_at, created_at, updated_at \
+                 FROM todos WHERE remind_before_minutes >= 0"
+                    .to_owned(),
+            ))
+            .await
+            .context("failed to migrate todo reminders")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(reminder_entity::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+use super::saved_filter_entity;
+
+/// 保存的筛选条件迁移
+///
+/// 创建 todo_saved_filters 表
+#[derive(Debug, Clone, Copy)]
+pub struct SavedFilterMigration;
+
+impl MigrationName for SavedFilterMigration {
+    fn name(&self) -> &str {
+        "m20251228_000001_todo_saved_filters"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for SavedFilterMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        let mut create_filters = schema.create_table_from_entity(saved_filter_entity::Entity);
+        create_filters.if_not_exists();
+        db.execute(backend.build(&create_filters))
+            .await
+            .context("failed to create todo_saved_filters table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(saved_filter_entity::Entity).to_owned())
+            .await
+    }
+}
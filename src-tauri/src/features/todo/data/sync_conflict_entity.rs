@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// CalDAV 同步冲突（本地和远端修改了同一字段）
+///
+/// 字段值为便于展示的文本形式；冲突解决前该 Todo 保留本地值且不会推送到远端
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "todo_sync_conflicts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub todo_id: i32,
+    pub field: String,
+    pub base_value: Option<String>,
+    pub local_value: Option<String>,
+    pub remote_value: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::entity::Entity",
+        from = "Column::TodoId",
+        to = "super::entity::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Todo,
+}
+
+impl Related<super::entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Todo.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,69 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema, Statement};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+use super::sync_conflict_entity;
+
+/// CalDAV 字段级合并迁移
+///
+/// 添加同步快照字段（todos.sync_snapshot）并创建冲突表（todo_sync_conflicts）
+#[derive(Debug, Clone, Copy)]
+pub struct SyncConflictMigration;
+
+impl MigrationName for SyncConflictMigration {
+    fn name(&self) -> &str {
+        "m20251215_000001_sync_conflicts"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for SyncConflictMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        // 字段已存在时忽略错误
+        let _ = db
+            .execute(Statement::from_string(
+                backend,
+                "ALTER TABLE todos ADD COLUMN sync_snapshot TEXT".to_owned(),
+            ))
+            .await;
+
+        let mut create_conflicts = schema.create_table_from_entity(sync_conflict_entity::Entity);
+        create_conflicts.if_not_exists();
+        db.execute(backend.build(&create_conflicts))
+            .await
+            .context("failed to create todo_sync_conflicts table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        let _ = db
+            .execute(Statement::from_string(
+                backend,
+                "CREATE INDEX IF NOT EXISTS idx_todo_sync_conflicts_todo_id \
+                 ON todo_sync_conflicts(todo_id)"
+                    .to_owned(),
+            ))
+            .await;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(sync_conflict_entity::Entity).to_owned())
+            .await?;
+
+        let db = manager.get_connection();
+        let _ = db
+            .execute(Statement::from_string(
+                db.get_database_backend(),
+                "ALTER TABLE todos DROP COLUMN sync_snapshot".to_owned(),
+            ))
+            .await;
+
+        Ok(())
+    }
+}
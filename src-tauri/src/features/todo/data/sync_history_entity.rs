@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// CalDAV 同步历史
+///
+/// 每次同步（不含被跳过的同步）一条记录：触发原因、起止时间、各项计数、错误信息，
+/// 以及各日历的结果和逐条 todo 操作（JSON）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "caldav_sync_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub reason: String,
+    /// success / error
+    pub status: String,
+    pub started_at: DateTimeUtc,
+    pub finished_at: DateTimeUtc,
+    #[sea_orm(default_value = 0)]
+    pub created: i32,
+    #[sea_orm(default_value = 0)]
+    pub updated: i32,
+    #[sea_orm(default_value = 0)]
+    pub pushed: i32,
+    #[sea_orm(default_value = 0)]
+    pub deleted: i32,
+    #[sea_orm(default_value = 0)]
+    pub conflicts: i32,
+    #[sea_orm(default_value = 0)]
+    pub failed: i32,
+    pub error: Option<String>,
+    /// 各日历的同步结果（JSON 数组）
+    #[sea_orm(column_type = "Text")]
+    pub calendars: String,
+    /// 逐条 todo 操作（JSON 数组）
+    #[sea_orm(column_type = "Text")]
+    pub actions: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,53 @@
+use anyhow::Context;
+use sea_orm::{ConnectionTrait, Schema};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::MigrationTrait;
+
+use super::sync_history_entity;
+
+/// CalDAV 同步历史迁移
+///
+/// 创建 caldav_sync_history 表，并为按时间倒序分页查询建立索引
+#[derive(Debug, Clone, Copy)]
+pub struct SyncHistoryMigration;
+
+impl MigrationName for SyncHistoryMigration {
+    fn name(&self) -> &str {
+        "m20251222_000001_caldav_sync_history"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for SyncHistoryMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        let mut create_history = schema.create_table_from_entity(sync_history_entity::Entity);
+        create_history.if_not_exists();
+        db.execute(backend.build(&create_history))
+            .await
+            .context("failed to create caldav_sync_history table")
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_caldav_sync_history_started_at")
+                    .table(sync_history_entity::Entity)
+                    .col(sync_history_entity::Column::StartedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(sync_history_entity::Entity).to_owned())
+            .await
+    }
+}
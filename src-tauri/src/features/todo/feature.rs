@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::OnceCell;
 
 use anyhow::Result;
@@ -8,8 +9,16 @@ use sea_orm_migration::MigrationTrait;
 use crate::core::{AppState, Feature};
 use crate::infrastructure::database::DatabaseRegistry;
 
-use super::core::scheduler::DueNotificationScheduler;
-use super::data::{add_order_index_migration, add_subtask_migration, migration};
+use super::core::{scheduler::DueNotificationScheduler, trash};
+use super::data::{
+    add_missed_reminder_migration, add_order_index_migration, add_outbox_source_migration,
+    add_reminder_occurrence_migration, add_snooze_migration, add_subtask_migration,
+    dependency_migration, journal_migration, migration, outbox_migration, reminder_migration,
+    saved_filter_migration, sync_conflict_migration, sync_history_migration,
+};
+
+/// 检查回收站中过期 todo 的间隔
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 /// Todo Feature
 ///
@@ -63,6 +72,72 @@ impl Feature for TodoFeature {
             let migration = add_order_index_migration::AddOrderIndexMigration;
             Box::pin(async move { migration.up(manager).await })
         });
+
+        // 注册重复提醒实例状态迁移
+        registry.register_migration("add_reminder_occurrence_migration", |manager| {
+            let migration = add_reminder_occurrence_migration::AddReminderOccurrenceMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册多提醒表迁移
+        registry.register_migration("todo_reminder_migration", |manager| {
+            let migration = reminder_migration::TodoReminderMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册稍后提醒字段迁移
+        registry.register_migration("add_snooze_migration", |manager| {
+            let migration = add_snooze_migration::AddSnoozeMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册错过提醒处理记录字段迁移
+        registry.register_migration("add_missed_reminder_migration", |manager| {
+            let migration = add_missed_reminder_migration::AddMissedReminderMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册 CalDAV 同步快照与冲突表迁移
+        registry.register_migration("sync_conflict_migration", |manager| {
+            let migration = sync_conflict_migration::SyncConflictMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册 CalDAV 推送队列迁移
+        registry.register_migration("outbox_migration", |manager| {
+            let migration = outbox_migration::OutboxMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册 CalDAV 同步历史迁移
+        registry.register_migration("sync_history_migration", |manager| {
+            let migration = sync_history_migration::SyncHistoryMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册保存的筛选条件迁移
+        registry.register_migration("saved_filter_migration", |manager| {
+            let migration = saved_filter_migration::SavedFilterMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册撤销 / 重做日志迁移
+        registry.register_migration("journal_migration", |manager| {
+            let migration = journal_migration::JournalMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册任务依赖迁移
+        registry.register_migration("dependency_migration", |manager| {
+            let migration = dependency_migration::DependencyMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
+
+        // 注册推送队列原日历资源字段迁移
+        registry.register_migration("add_outbox_source_migration", |manager| {
+            let migration = add_outbox_source_migration::AddOutboxSourceMigration;
+            Box::pin(async move { migration.up(manager).await })
+        });
     }
 
     fn command_names(&self) -> Vec<&'static str> {
@@ -75,10 +150,48 @@ impl Feature for TodoFeature {
             "get_subtasks",
             "update_todo_parent",
             "reorder_todo",
+            "get_todo_dependencies",
+            "add_todo_dependency",
+            "remove_todo_dependency",
+            "list_next_actionable_todos",
+            "get_todo_reminders",
+            "set_todo_reminders",
+            "snooze_todo",
+            "get_reminder_catch_up_policy",
+            "set_reminder_catch_up_policy",
+            "query_todos",
+            "list_todo_filters",
+            "save_todo_filter",
+            "delete_todo_filter",
+            "run_todo_filter",
+            "export_todos_ics",
+            "import_todos_ics",
+            "undo_todo_change",
+            "redo_todo_change",
+            "get_todo_history",
+            "get_todo_trash_retention",
+            "set_todo_trash_retention",
+            "list_todo_trash",
+            "restore_todo",
+            "purge_todo",
+            "empty_todo_trash",
             "get_caldav_status",
             "save_caldav_config",
             "clear_caldav_config",
             "sync_caldav_now",
+            "dry_run_caldav_sync",
+            "list_caldav_calendars",
+            "add_caldav_calendar",
+            "update_caldav_calendar",
+            "remove_caldav_calendar",
+            "move_todo_to_calendar",
+            "discover_caldav_calendars",
+            "list_todo_sync_conflicts",
+            "resolve_todo_sync_conflict",
+            "list_caldav_outbox",
+            "retry_caldav_outbox_item",
+            "discard_caldav_outbox_item",
+            "list_caldav_sync_history",
         ]
     }
 
@@ -91,6 +204,20 @@ impl Feature for TodoFeature {
     }
 
     async fn initialize(&self, app_state: &AppState) -> Result<()> {
+        // 彻底删除回收站中超过保留天数的 todo：启动时执行一次，之后定期检查
+        let db = app_state.db().clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(TRASH_PURGE_INTERVAL);
+            loop {
+                interval.tick().await;
+                match trash::purge_expired(&db, chrono::Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(count) => println!("[TodoFeature] Purged {count} expired todos from trash"),
+                    Err(err) => eprintln!("[TodoFeature] Failed to purge expired trash: {err:#}"),
+                }
+            }
+        });
+
         // 创建到期通知调度器
         let scheduler = Arc::new(DueNotificationScheduler::new(
             app_state.db().clone(),
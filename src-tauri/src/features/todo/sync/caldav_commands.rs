@@ -1,14 +1,22 @@
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
-use super::{CalDavConfig, CalDavConfigService, CalDavSyncEvent};
+use super::client::{CalDavClient, DiscoveredCalendar};
+use super::conflicts::{self, ConflictResolution};
+use super::dry_run::SyncPlan;
+use super::history::{self, SyncHistoryPage};
+use super::outbox;
+use super::{CalDavAuth, CalDavCalendarConfig, CalDavConfig, CalDavConfigService, CalDavSyncEvent};
 use crate::core::AppState;
+use crate::features::todo::core::models::{CalDavOutboxItem, TodoSyncConflict};
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateCalDavConfigPayload {
     pub url: String,
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub auth: CalDavAuth,
 }
 
 #[derive(Debug, Serialize)]
@@ -19,6 +27,62 @@ pub struct CalDavStatus {
     pub last_sync_at: Option<String>,
     pub last_error: Option<String>,
     pub syncing: bool,
+    pub calendars: Vec<CalDavCalendarStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalDavCalendarStatus {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub username: String,
+    pub enabled: bool,
+    pub is_default: bool,
+    /// 认证方式：password / oauth2
+    pub auth_type: &'static str,
+    pub sync_interval_minutes: u64,
+    pub last_sync_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalDavCalendarPayload {
+    pub name: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub auth: CalDavAuth,
+    pub sync_interval_minutes: Option<u64>,
+    pub enabled: Option<bool>,
+    pub is_default: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCalDavCalendarPayload {
+    pub id: String,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub username: Option<String>,
+    /// 为空时保留原密码
+    pub password: Option<String>,
+    /// 为空时保留原认证方式
+    pub auth: Option<CalDavAuth>,
+    pub sync_interval_minutes: Option<u64>,
+    pub enabled: Option<bool>,
+    pub is_default: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveSyncConflictPayload {
+    pub conflict_id: i32,
+    pub resolution: ConflictResolution,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveTodoToCalendarPayload {
+    pub todo_id: i32,
+    pub calendar_id: String,
 }
 
 /// 获取 CalDAV 同步状态
@@ -49,6 +113,8 @@ pub async fn get_caldav_status(state: State<'_, AppState>) -> Result<CalDavStatu
     // 获取同步状态
     let syncing = state.caldav_sync_manager().is_running();
 
+    let calendars = list_caldav_calendars(state).await?;
+
     Ok(CalDavStatus {
         configured,
         url,
@@ -56,6 +122,7 @@ pub async fn get_caldav_status(state: State<'_, AppState>) -> Result<CalDavStatu
         last_sync_at,
         last_error,
         syncing,
+        calendars,
     })
 }
 
@@ -69,6 +136,7 @@ pub async fn save_caldav_config(
         url: payload.url.trim().to_string(),
         username: payload.username.trim().to_string(),
         password: payload.password,
+        auth: payload.auth,
     };
 
     if !config.is_valid() {
@@ -131,6 +199,16 @@ pub async fn sync_caldav_now(state: State<'_, AppState>) -> Result<CalDavSyncEve
         .map_err(|e| e.to_string())
 }
 
+/// CalDAV 同步试运行：返回同步将执行的操作及字段变化，不写入本地或远端
+#[tauri::command]
+pub async fn dry_run_caldav_sync(state: State<'_, AppState>) -> Result<SyncPlan, String> {
+    state
+        .caldav_sync_manager()
+        .sync_now_dry_run()
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 获取 CalDAV 同步间隔（分钟）
 #[tauri::command]
 pub async fn get_caldav_sync_interval(state: State<'_, AppState>) -> Result<u64, String> {
@@ -145,10 +223,7 @@ pub async fn set_caldav_sync_interval(
     state: State<'_, AppState>,
     minutes: u64,
 ) -> Result<(), String> {
-    // 验证范围（最小1分钟，最大1440分钟即24小时）
-    if minutes < 1 || minutes > 1440 {
-        return Err("同步间隔必须在 1-1440 分钟之间".to_string());
-    }
+    validate_sync_interval(minutes)?;
 
     CalDavConfigService::set_sync_interval_minutes(state.db(), minutes)
         .await
@@ -161,3 +236,320 @@ pub async fn set_caldav_sync_interval(
 
     Ok(())
 }
+
+/// 获取所有 CalDAV 日历及其同步状态
+#[tauri::command]
+pub async fn list_caldav_calendars(
+    state: State<'_, AppState>,
+) -> Result<Vec<CalDavCalendarStatus>, String> {
+    let calendars = CalDavConfigService::list_calendars(state.db())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut statuses = Vec::with_capacity(calendars.len());
+    for calendar in calendars {
+        let last_sync_at = CalDavConfigService::get_calendar_last_sync(state.db(), &calendar.id)
+            .await
+            .map_err(|e| e.to_string())?
+            .map(|dt| dt.to_rfc3339());
+        let last_error = CalDavConfigService::get_calendar_last_error(state.db(), &calendar.id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        statuses.push(CalDavCalendarStatus {
+            id: calendar.id,
+            name: calendar.name,
+            url: calendar.url,
+            username: calendar.username,
+            enabled: calendar.enabled,
+            is_default: calendar.is_default,
+            auth_type: calendar.auth.kind(),
+            sync_interval_minutes: calendar.sync_interval_minutes,
+            last_sync_at,
+            last_error,
+        });
+    }
+
+    Ok(statuses)
+}
+
+/// 添加 CalDAV 日历
+#[tauri::command]
+pub async fn add_caldav_calendar(
+    state: State<'_, AppState>,
+    payload: CalDavCalendarPayload,
+) -> Result<Vec<CalDavCalendarStatus>, String> {
+    let mut calendar = CalDavCalendarConfig::new(
+        payload.name.trim().to_string(),
+        CalDavConfig {
+            url: payload.url.trim().to_string(),
+            username: payload.username.trim().to_string(),
+            password: payload.password,
+            auth: payload.auth,
+        },
+    );
+    calendar.sync_interval_minutes = CalDavConfigService::default_sync_interval_minutes(state.db())
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(minutes) = payload.sync_interval_minutes {
+        validate_sync_interval(minutes)?;
+        calendar.sync_interval_minutes = minutes;
+    }
+    if let Some(enabled) = payload.enabled {
+        calendar.enabled = enabled;
+    }
+    if let Some(is_default) = payload.is_default {
+        calendar.is_default = is_default;
+    }
+
+    if !calendar.is_valid() {
+        return Err("CalDAV 配置信息不完整".to_string());
+    }
+
+    CalDavConfigService::save_calendar(state.db(), &calendar)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    crate::features::todo::api::notifications::notify_caldav_config_saved(state.notification());
+
+    use super::sync::SyncReason;
+    let manager = state.caldav_sync_manager();
+    manager.restart_scheduler();
+    manager.trigger(SyncReason::ConfigUpdated);
+
+    list_caldav_calendars(state).await
+}
+
+/// 更新 CalDAV 日历
+#[tauri::command]
+pub async fn update_caldav_calendar(
+    state: State<'_, AppState>,
+    payload: UpdateCalDavCalendarPayload,
+) -> Result<Vec<CalDavCalendarStatus>, String> {
+    let mut calendar = CalDavConfigService::get_calendar(state.db(), &payload.id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("CalDAV 日历不存在: {}", payload.id))?;
+
+    let previous_connection = calendar.connection();
+
+    if let Some(name) = payload.name {
+        calendar.name = name.trim().to_string();
+    }
+    if let Some(url) = payload.url {
+        calendar.url = url.trim().to_string();
+    }
+    if let Some(username) = payload.username {
+        calendar.username = username.trim().to_string();
+    }
+    if let Some(password) = payload.password.filter(|p| !p.is_empty()) {
+        calendar.password = password;
+    }
+    if let Some(auth) = payload.auth {
+        calendar.auth = auth;
+    }
+    if let Some(minutes) = payload.sync_interval_minutes {
+        validate_sync_interval(minutes)?;
+        calendar.sync_interval_minutes = minutes;
+    }
+    if let Some(enabled) = payload.enabled {
+        calendar.enabled = enabled;
+    }
+    if let Some(is_default) = payload.is_default {
+        calendar.is_default = is_default;
+    }
+
+    if !calendar.is_valid() {
+        return Err("CalDAV 配置信息不完整".to_string());
+    }
+
+    CalDavConfigService::save_calendar(state.db(), &calendar)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 连接信息变化后重新进行完整同步
+    if previous_connection.url != calendar.url || previous_connection.username != calendar.username
+    {
+        CalDavConfigService::set_calendar_last_sync(state.db(), &calendar.id, None)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    use super::sync::SyncReason;
+    let manager = state.caldav_sync_manager();
+    manager.restart_scheduler();
+    manager.trigger(SyncReason::ConfigUpdated);
+
+    list_caldav_calendars(state).await
+}
+
+/// 删除 CalDAV 日历
+///
+/// 该日历下的 todo 解除关联，之后推送到默认日历
+#[tauri::command]
+pub async fn remove_caldav_calendar(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Vec<CalDavCalendarStatus>, String> {
+    let removed = CalDavConfigService::remove_calendar(state.db(), &id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(calendar) = removed {
+        let calendar_url = CalDavClient::new(&calendar.connection())
+            .map_err(|e| e.to_string())?
+            .calendar_url()
+            .to_string();
+
+        crate::features::todo::core::service::detach_calendar_todos(state.db(), &calendar_url)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        use super::sync::SyncReason;
+        let manager = state.caldav_sync_manager();
+        manager.restart_scheduler();
+        manager.trigger(SyncReason::ConfigUpdated);
+    }
+
+    list_caldav_calendars(state).await
+}
+
+/// 将 todo 移动到另一个 CalDAV 日历
+#[tauri::command]
+pub async fn move_todo_to_calendar(
+    state: State<'_, AppState>,
+    payload: MoveTodoToCalendarPayload,
+) -> Result<(), String> {
+    let manager = state.caldav_sync_manager();
+    manager
+        .move_todo_to_calendar(payload.todo_id, &payload.calendar_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    use super::sync::SyncReason;
+    manager.trigger(SyncReason::DataChanged);
+
+    Ok(())
+}
+
+/// CalDAV 服务发现：列出账户下支持任务（VTODO）的日历，供设置界面选择
+#[tauri::command]
+pub async fn discover_caldav_calendars(
+    payload: UpdateCalDavConfigPayload,
+) -> Result<Vec<DiscoveredCalendar>, String> {
+    let config = CalDavConfig {
+        url: payload.url.trim().to_string(),
+        username: payload.username.trim().to_string(),
+        password: payload.password,
+        auth: payload.auth,
+    };
+
+    if !config.is_valid() {
+        return Err("CalDAV 配置信息不完整".to_string());
+    }
+
+    let client = CalDavClient::new(&config).map_err(|e| e.to_string())?;
+    client
+        .discover_calendars()
+        .await
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// 获取未解决的 CalDAV 同步冲突（不传 todo_id 时返回全部）
+#[tauri::command]
+pub async fn list_todo_sync_conflicts(
+    state: State<'_, AppState>,
+    todo_id: Option<i32>,
+) -> Result<Vec<TodoSyncConflict>, String> {
+    conflicts::list_conflicts(state.db(), todo_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 解决 CalDAV 同步冲突：保留本地值或采用远端值
+///
+/// 该 todo 的冲突全部解决后触发同步，将合并结果推送到远端
+#[tauri::command]
+pub async fn resolve_todo_sync_conflict(
+    state: State<'_, AppState>,
+    payload: ResolveSyncConflictPayload,
+) -> Result<Vec<TodoSyncConflict>, String> {
+    let has_remaining =
+        conflicts::resolve_conflict(state.db(), payload.conflict_id, payload.resolution)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    if !has_remaining {
+        use super::sync::SyncReason;
+        state.caldav_sync_manager().trigger(SyncReason::DataChanged);
+    }
+
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+
+    list_todo_sync_conflicts(state, None).await
+}
+
+/// 获取 CalDAV 推送队列（等待推送、等待重试和永久失败的条目）
+#[tauri::command]
+pub async fn list_caldav_outbox(
+    state: State<'_, AppState>,
+) -> Result<Vec<CalDavOutboxItem>, String> {
+    outbox::list_entries(state.db())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 立即重试推送队列中的条目
+#[tauri::command]
+pub async fn retry_caldav_outbox_item(
+    state: State<'_, AppState>,
+    id: i32,
+) -> Result<Vec<CalDavOutboxItem>, String> {
+    outbox::retry_entry(state.db(), id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    use super::sync::SyncReason;
+    state.caldav_sync_manager().trigger(SyncReason::Manual);
+
+    list_caldav_outbox(state).await
+}
+
+/// 放弃推送队列中的条目（本地修改不再推送到远端）
+#[tauri::command]
+pub async fn discard_caldav_outbox_item(
+    state: State<'_, AppState>,
+    id: i32,
+) -> Result<Vec<CalDavOutboxItem>, String> {
+    outbox::discard_entry(state.db(), id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(scheduler) = state.todo_scheduler() {
+        scheduler.reschedule().await;
+    }
+
+    list_caldav_outbox(state).await
+}
+
+/// 分页获取 CalDAV 同步历史（最新的在前，page 从 0 开始）
+#[tauri::command]
+pub async fn list_caldav_sync_history(
+    state: State<'_, AppState>,
+    page: Option<u64>,
+    page_size: Option<u64>,
+) -> Result<SyncHistoryPage, String> {
+    history::list_runs(state.db(), page.unwrap_or(0), page_size)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 验证同步间隔范围（最小1分钟，最大1440分钟即24小时）
+fn validate_sync_interval(minutes: u64) -> Result<(), String> {
+    if !(1..=1440).contains(&minutes) {
+        return Err("同步间隔必须在 1-1440 分钟之间".to_string());
+    }
+    Ok(())
+}
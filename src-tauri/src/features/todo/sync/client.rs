@@ -1,7 +1,11 @@
 use std::{
     borrow::Cow,
     collections::HashMap,
-    sync::atomic::{AtomicU32, Ordering},
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
     time::Duration,
 };
 
@@ -14,7 +18,8 @@ use serde::Serialize;
 use url::Url;
 use uuid::Uuid;
 
-use super::config::CalDavConfig;
+use super::config::{CalDavAuth, CalDavConfig, OAuth2Credentials};
+use super::oauth;
 
 const USER_AGENT: &str = "pet-focus-caldav/0.1";
 const CALENDAR_QUERY_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
@@ -30,8 +35,56 @@ const CALENDAR_QUERY_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
     </cal:filter>
 </cal:calendar-query>
 "#;
+const PRINCIPAL_PROPFIND_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+    <d:prop>
+        <d:current-user-principal />
+    </d:prop>
+</d:propfind>
+"#;
+const CALENDAR_HOME_PROPFIND_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+    <d:prop>
+        <cal:calendar-home-set />
+    </d:prop>
+</d:propfind>
+"#;
+const CALENDAR_LIST_PROPFIND_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav" xmlns:ical="http://apple.com/ns/ical/">
+    <d:prop>
+        <d:resourcetype />
+        <d:displayname />
+        <cal:calendar-description />
+        <cal:supported-calendar-component-set />
+        <ical:calendar-color />
+    </d:prop>
+</d:propfind>
+"#;
+const WELL_KNOWN_CALDAV_PATH: &str = "/.well-known/caldav";
+const COLLECTION_STATE_PROPFIND_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+    <d:prop>
+        <cs:getctag />
+        <d:sync-token />
+    </d:prop>
+</d:propfind>
+"#;
+const ETAG_QUERY_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<cal:calendar-query xmlns:cal="urn:ietf:params:xml:ns:caldav" xmlns:d="DAV:">
+    <d:prop>
+        <d:getetag />
+    </d:prop>
+    <cal:filter>
+        <cal:comp-filter name="VCALENDAR">
+            <cal:comp-filter name="VTODO" />
+        </cal:comp-filter>
+    </cal:filter>
+</cal:calendar-query>
+"#;
+/// calendar-multiget 每批请求的资源数
+const MULTIGET_BATCH_SIZE: usize = 100;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct CalDavItem {
     pub uid: String,
     pub summary: String,
@@ -46,9 +99,12 @@ pub struct CalDavItem {
     pub completed_at: Option<DateTime<Utc>>,
     pub last_modified: Option<DateTime<Utc>>,
     pub reminder_minutes: Option<i32>,
+    pub alarms: Vec<CalDavAlarm>,
     pub timezone: Option<String>,
     pub recurrence_rule: Option<String>,
     pub related_to: Option<String>, // 父任务的 UID (用于子任务)
+    /// 依赖的 todo UID（RELATED-TO;RELTYPE=DEPENDS-ON）
+    pub depends_on: Vec<String>,
 }
 
 impl CalDavItem {
@@ -57,6 +113,25 @@ impl CalDavItem {
     }
 }
 
+/// VTODO 中的一个 VALARM
+#[derive(Debug, Clone, Serialize)]
+pub struct CalDavAlarm {
+    pub trigger: AlarmTrigger,
+    pub action: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum AlarmTrigger {
+    /// 相对触发：minutes_before 为提前的分钟数（负数表示之后），related_start 表示相对 DTSTART
+    Relative {
+        minutes_before: i32,
+        related_start: bool,
+    },
+    /// 绝对触发（TRIGGER;VALUE=DATE-TIME）
+    Absolute(DateTime<Utc>),
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct RemoteTodo {
     pub href: String,
@@ -65,18 +140,82 @@ pub struct RemoteTodo {
     pub raw_ical: String,
 }
 
+/// 服务发现找到的日历集合
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredCalendar {
+    pub url: String,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub color: Option<String>,
+}
+
+/// 日历集合的变更标识（CalendarServer getctag 与 RFC 6578 sync-token）
+#[derive(Debug, Clone, Default)]
+pub struct CollectionState {
+    pub ctag: Option<String>,
+    pub sync_token: Option<String>,
+}
+
+/// 远端资源的 href 与 ETag（不含日历数据）
+#[derive(Debug, Clone)]
+pub struct RemoteEtag {
+    pub href: String,
+    pub etag: Option<String>,
+}
+
+/// sync-collection 报告的结果
+#[derive(Debug, Clone, Default)]
+pub struct SyncCollectionResult {
+    pub sync_token: Option<String>,
+    pub changed: Vec<RemoteEtag>,
+    pub removed: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct UploadResult {
     pub href: String,
     pub etag: Option<String>,
 }
 
+/// 服务器返回的非成功 HTTP 响应，保留状态码供调用方判断失败类型
+#[derive(Debug)]
+pub struct CalDavHttpError {
+    pub status: StatusCode,
+    message: String,
+}
+
+impl CalDavHttpError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// 错误链中的 HTTP 状态码，网络错误等没有响应的失败返回 None
+    pub fn status_of(err: &anyhow::Error) -> Option<StatusCode> {
+        err.chain()
+            .find_map(|cause| cause.downcast_ref::<Self>())
+            .map(|err| err.status)
+    }
+}
+
+impl fmt::Display for CalDavHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CalDavHttpError {}
+
 #[derive(Debug)]
 pub struct CalDavClient {
     http: Client,
     calendar_url: Url,
     username: String,
     password: String,
+    /// 当前认证信息（OAuth2 刷新后更新，调用方通过 `auth()` 取回并保存）
+    auth: Mutex<CalDavAuth>,
     nonce_count: AtomicU32,
 }
 
@@ -99,6 +238,7 @@ impl CalDavClient {
             calendar_url,
             username: config.username.clone(),
             password: config.password.clone(),
+            auth: Mutex::new(config.auth.clone()),
             nonce_count: AtomicU32::new(0),
         })
     }
@@ -107,6 +247,14 @@ impl CalDavClient {
         &self.calendar_url
     }
 
+    /// 当前认证信息（包含请求过程中刷新的 OAuth2 token）
+    pub fn auth(&self) -> CalDavAuth {
+        self.auth
+            .lock()
+            .map(|auth| auth.clone())
+            .unwrap_or_default()
+    }
+
     pub async fn fetch_todos(&self) -> Result<Vec<RemoteTodo>> {
         eprintln!("[CalDAV] Fetching todos from: {}", self.calendar_url);
         eprintln!("[CalDAV] Username: {}", self.username);
@@ -138,7 +286,11 @@ impl CalDavClient {
         if !status.is_success() {
             let text = response.text().await.unwrap_or_default();
             eprintln!("[CalDAV] Error response body: {}", text);
-            return Err(anyhow!("CalDAV REPORT failed: {status} {text}"));
+            return Err(CalDavHttpError::new(
+                status,
+                format!("CalDAV REPORT failed: {status} {text}"),
+            )
+            .into());
         }
 
         let xml = response
@@ -175,6 +327,148 @@ impl CalDavClient {
         Ok(todos)
     }
 
+    /// 获取集合的 ctag 和 sync-token（服务器不支持时为 None）
+    pub async fn fetch_collection_state(&self) -> Result<CollectionState> {
+        let responses = self
+            .propfind(&self.calendar_url, "0", COLLECTION_STATE_PROPFIND_XML)
+            .await?;
+
+        Ok(responses
+            .into_iter()
+            .next()
+            .map(|response| CollectionState {
+                ctag: response.ctag,
+                sync_token: response.sync_token,
+            })
+            .unwrap_or_default())
+    }
+
+    /// RFC 6578 sync-collection：获取自 `sync_token` 以来变化的资源
+    ///
+    /// `sync_token` 为 None 时进行初始同步，返回集合中的所有资源。
+    /// 令牌失效时服务器返回 403/409，由调用方回退到 ETag 列表
+    pub async fn sync_collection(&self, sync_token: Option<&str>) -> Result<SyncCollectionResult> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:sync-collection xmlns:d="DAV:">
+    <d:sync-token>{}</d:sync-token>
+    <d:sync-level>1</d:sync-level>
+    <d:prop>
+        <d:getetag />
+    </d:prop>
+</d:sync-collection>
+"#,
+            quick_xml::escape::escape(sync_token.unwrap_or_default())
+        );
+
+        let xml = self
+            .send_xml_request(b"REPORT", &self.calendar_url, "0", &body)
+            .await?;
+        let multistatus =
+            parse_propfind(&xml).with_context(|| "failed to parse sync-collection response")?;
+
+        let mut result = SyncCollectionResult {
+            sync_token: multistatus.sync_token,
+            ..Default::default()
+        };
+        for response in multistatus.responses {
+            let href = self.resolve_href(&response.href)?;
+            // 集合自身不是成员资源
+            if href == self.calendar_url {
+                continue;
+            }
+
+            let removed = response
+                .status
+                .as_deref()
+                .is_some_and(|status| status.contains(" 404"));
+            if removed {
+                result.removed.push(href.to_string());
+            } else {
+                result.changed.push(RemoteEtag {
+                    href: href.to_string(),
+                    etag: response.etag,
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 列出集合中所有 VTODO 的 href 和 ETag（不获取日历数据）
+    pub async fn list_etags(&self) -> Result<Vec<RemoteEtag>> {
+        let xml = self
+            .send_xml_request(b"REPORT", &self.calendar_url, "1", ETAG_QUERY_XML)
+            .await?;
+        let multistatus =
+            parse_propfind(&xml).with_context(|| "failed to parse CalDAV ETag listing")?;
+
+        let mut etags = Vec::with_capacity(multistatus.responses.len());
+        for response in multistatus.responses {
+            let href = self.resolve_href(&response.href)?;
+            if href == self.calendar_url {
+                continue;
+            }
+            etags.push(RemoteEtag {
+                href: href.to_string(),
+                etag: response.etag,
+            });
+        }
+
+        Ok(etags)
+    }
+
+    /// calendar-multiget：按 href 批量获取 VTODO
+    ///
+    /// 非 VTODO 资源（例如同一日历中的 VEVENT）会被跳过
+    pub async fn fetch_todos_by_href(&self, hrefs: &[String]) -> Result<Vec<RemoteTodo>> {
+        let mut todos = Vec::with_capacity(hrefs.len());
+
+        for batch in hrefs.chunks(MULTIGET_BATCH_SIZE) {
+            let mut body = String::from(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<cal:calendar-multiget xmlns:cal="urn:ietf:params:xml:ns:caldav" xmlns:d="DAV:">
+    <d:prop>
+        <d:getetag />
+        <cal:calendar-data />
+    </d:prop>
+"#,
+            );
+            for href in batch {
+                let resource = self.resolve_href(href)?;
+                body.push_str(&format!(
+                    "    <d:href>{}</d:href>\n",
+                    quick_xml::escape::escape(resource.path())
+                ));
+            }
+            body.push_str("</cal:calendar-multiget>\n");
+
+            let xml = self
+                .send_xml_request(b"REPORT", &self.calendar_url, "1", &body)
+                .await?;
+            let items = parse_multistatus(&xml)
+                .with_context(|| "failed to parse CalDAV multiget response")?;
+
+            for item in items {
+                if !item.calendar_data.contains("BEGIN:VTODO") {
+                    continue;
+                }
+
+                let absolute_href = self.resolve_href(&item.href)?;
+                let parsed = parse_ical_todo(&item.calendar_data)
+                    .with_context(|| format!("failed to parse VTODO from {}", absolute_href))?;
+                todos.push(RemoteTodo {
+                    href: absolute_href.to_string(),
+                    etag: item.etag,
+                    item: parsed,
+                    raw_ical: item.calendar_data,
+                });
+            }
+        }
+
+        Ok(todos)
+    }
+
     pub async fn create_todo(&self, uid: &str, ics: &str) -> Result<UploadResult> {
         let resource = self
             .calendar_url
@@ -205,7 +499,11 @@ impl CalDavClient {
         let status = response.status();
         if !status.is_success() {
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("CalDAV GET failed: {status} {text}"));
+            return Err(CalDavHttpError::new(
+                status,
+                format!("CalDAV GET failed: {status} {text}"),
+            )
+            .into());
         }
 
         // 从响应头获取 ETag
@@ -246,12 +544,159 @@ impl CalDavClient {
         if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("CalDAV DELETE failed: {status} {text}"));
+            return Err(CalDavHttpError::new(
+                status,
+                format!("CalDAV DELETE failed: {status} {text}"),
+            )
+            .into());
         }
 
         Ok(())
     }
 
+    /// 服务发现（RFC 6764 / RFC 4791）
+    ///
+    /// 以配置的 URL 为入口：查找 current-user-principal（失败时尝试 /.well-known/caldav），
+    /// 再查找 calendar-home-set，最后列出其中支持 VTODO 的日历集合
+    pub async fn discover_calendars(&self) -> Result<Vec<DiscoveredCalendar>> {
+        let principal = match self.find_principal(&self.calendar_url).await {
+            Ok(Some(principal)) => principal,
+            result => {
+                if let Err(err) = result {
+                    eprintln!("[CalDAV] Principal lookup failed on configured URL: {err:#}");
+                }
+                let well_known = self
+                    .calendar_url
+                    .join(WELL_KNOWN_CALDAV_PATH)
+                    .context("failed to build well-known CalDAV URL")?;
+                let context_url = self.resolve_well_known(&well_known).await?;
+                self.find_principal(&context_url)
+                    .await?
+                    .unwrap_or(context_url)
+            }
+        };
+        eprintln!("[CalDAV] Discovered principal: {}", principal);
+
+        let home = self
+            .propfind(&principal, "0", CALENDAR_HOME_PROPFIND_XML)
+            .await?
+            .into_iter()
+            .find_map(|response| response.calendar_home_set)
+            .map(|href| principal.join(&href))
+            .transpose()
+            .context("invalid calendar-home-set href")?
+            .unwrap_or_else(|| principal.clone());
+        eprintln!("[CalDAV] Discovered calendar home: {}", home);
+
+        let mut calendars = Vec::new();
+        for response in self
+            .propfind(&home, "1", CALENDAR_LIST_PROPFIND_XML)
+            .await?
+        {
+            // 未声明 supported-calendar-component-set 时视为支持所有组件
+            let supports_todo = response.components.is_empty()
+                || response
+                    .components
+                    .iter()
+                    .any(|component| component.eq_ignore_ascii_case("VTODO"));
+            if !response.is_calendar || !supports_todo {
+                continue;
+            }
+
+            let url = home
+                .join(&response.href)
+                .with_context(|| format!("invalid calendar href: {}", response.href))?;
+            calendars.push(DiscoveredCalendar {
+                url: url.to_string(),
+                display_name: response.display_name,
+                description: response.description,
+                color: response.color,
+            });
+        }
+
+        Ok(calendars)
+    }
+
+    /// 在给定 URL 上查找 current-user-principal
+    async fn find_principal(&self, url: &Url) -> Result<Option<Url>> {
+        let principal = self
+            .propfind(url, "0", PRINCIPAL_PROPFIND_XML)
+            .await?
+            .into_iter()
+            .find_map(|response| response.current_user_principal);
+
+        principal
+            .map(|href| url.join(&href))
+            .transpose()
+            .context("invalid current-user-principal href")
+    }
+
+    /// 跟随 /.well-known/caldav 的重定向，返回实际的上下文路径
+    async fn resolve_well_known(&self, well_known: &Url) -> Result<Url> {
+        let response = self
+            .send_authenticated_request(Method::GET, well_known, &[], None)
+            .await?;
+
+        let context_url = response.url().clone();
+        if !response.status().is_success() && context_url == *well_known {
+            let status = response.status();
+            return Err(CalDavHttpError::new(
+                status,
+                format!("CalDAV service discovery failed: {well_known} returned {status}"),
+            )
+            .into());
+        }
+
+        eprintln!("[CalDAV] Well-known context path: {}", context_url);
+        Ok(context_url)
+    }
+
+    async fn propfind(&self, url: &Url, depth: &str, body: &str) -> Result<Vec<PropfindResponse>> {
+        let xml = self.send_xml_request(b"PROPFIND", url, depth, body).await?;
+        let multistatus =
+            parse_propfind(&xml).with_context(|| "failed to parse CalDAV PROPFIND response")?;
+        Ok(multistatus.responses)
+    }
+
+    /// 发送 PROPFIND / REPORT 等 XML 请求，返回响应正文
+    async fn send_xml_request(
+        &self,
+        method: &[u8],
+        url: &Url,
+        depth: &str,
+        body: &str,
+    ) -> Result<String> {
+        let method = Method::from_bytes(method)
+            .map_err(|err| anyhow!("failed to create WebDAV method: {err}"))?;
+
+        let headers = [
+            (
+                header::HeaderName::from_static("content-type"),
+                "application/xml; charset=utf-8".to_string(),
+            ),
+            (header::HeaderName::from_static("depth"), depth.to_string()),
+        ];
+
+        let response = self
+            .send_authenticated_request(method.clone(), url, &headers, Some(body))
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(CalDavHttpError::new(
+                status,
+                format!("CalDAV {method} {url} failed: {status} {text}"),
+            )
+            .into());
+        }
+
+        response
+            .text()
+            .await
+            .with_context(|| format!("failed to read CalDAV {method} response"))
+    }
+
     fn resolve_href(&self, href: &str) -> Result<Url> {
         // 如果是完整URL，直接解析
         if href.starts_with("http://") || href.starts_with("https://") {
@@ -286,6 +731,38 @@ impl CalDavClient {
     ) -> Result<reqwest::Response> {
         eprintln!("[CalDAV Auth] Sending request: {} {}", method, url);
 
+        let response = match self.auth() {
+            CalDavAuth::Password => {
+                self.send_password_request(method, url, headers, body)
+                    .await?
+            }
+            CalDavAuth::OAuth2(credentials) => {
+                self.send_bearer_request(method, url, headers, body, credentials)
+                    .await?
+            }
+        };
+
+        if response.status() == StatusCode::FORBIDDEN {
+            let text = response.text().await.unwrap_or_default();
+            eprintln!("[CalDAV Auth] Got 403 Forbidden: {}", text);
+            return Err(CalDavHttpError::new(
+                StatusCode::FORBIDDEN,
+                format!("CalDAV request forbidden (403): {text}"),
+            )
+            .into());
+        }
+
+        Ok(response)
+    }
+
+    /// 用户名密码认证：先使用 Basic，服务器返回 401 时按 Digest 质询重试
+    async fn send_password_request(
+        &self,
+        method: Method,
+        url: &Url,
+        headers: &[(header::HeaderName, String)],
+        body: Option<&str>,
+    ) -> Result<reqwest::Response> {
         let request = self.build_request(method.clone(), url, headers, body, None)?;
         let mut response = self
             .http
@@ -318,21 +795,81 @@ impl CalDavClient {
             if retry_status == StatusCode::UNAUTHORIZED {
                 let text = response.text().await.unwrap_or_default();
                 eprintln!("[CalDAV Auth] Authentication failed after retry: {}", text);
-                return Err(anyhow!(
-                    "CalDAV credentials rejected with 401 Unauthorized: {text}"
-                ));
+                return Err(CalDavHttpError::new(
+                    StatusCode::UNAUTHORIZED,
+                    format!("CalDAV credentials rejected with 401 Unauthorized: {text}"),
+                )
+                .into());
             }
         }
 
-        if status == StatusCode::FORBIDDEN {
-            let text = response.text().await.unwrap_or_default();
-            eprintln!("[CalDAV Auth] Got 403 Forbidden: {}", text);
-            return Err(anyhow!("CalDAV request forbidden (403): {text}"));
+        Ok(response)
+    }
+
+    /// OAuth2 认证：token 缺失或即将过期时先刷新，服务器返回 401 时刷新后重试一次
+    async fn send_bearer_request(
+        &self,
+        method: Method,
+        url: &Url,
+        headers: &[(header::HeaderName, String)],
+        body: Option<&str>,
+        mut credentials: OAuth2Credentials,
+    ) -> Result<reqwest::Response> {
+        if oauth::needs_refresh(&credentials, Utc::now()) {
+            credentials = self.refresh_oauth_token(&credentials).await?;
+        }
+
+        let bearer = format!(
+            "Bearer {}",
+            credentials.access_token.as_deref().unwrap_or("")
+        );
+        let request = self.build_request(method.clone(), url, headers, body, Some(&bearer))?;
+        let mut response = self
+            .http
+            .execute(request)
+            .await
+            .with_context(|| format!("failed to execute {method} {url}"))?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            eprintln!("[CalDAV Auth] Got 401 with bearer token, refreshing access token...");
+            credentials = self.refresh_oauth_token(&credentials).await?;
+
+            let bearer = format!(
+                "Bearer {}",
+                credentials.access_token.as_deref().unwrap_or("")
+            );
+            let retry_request =
+                self.build_request(method.clone(), url, headers, body, Some(&bearer))?;
+            response = self.http.execute(retry_request).await.with_context(|| {
+                format!("failed to execute bearer-authenticated {method} {url}")
+            })?;
+
+            if response.status() == StatusCode::UNAUTHORIZED {
+                let text = response.text().await.unwrap_or_default();
+                return Err(CalDavHttpError::new(
+                    StatusCode::UNAUTHORIZED,
+                    format!("CalDAV OAuth2 token rejected with 401 Unauthorized: {text}"),
+                )
+                .into());
+            }
         }
 
         Ok(response)
     }
 
+    async fn refresh_oauth_token(
+        &self,
+        credentials: &OAuth2Credentials,
+    ) -> Result<OAuth2Credentials> {
+        let refreshed = oauth::refresh_access_token(&self.http, credentials).await?;
+        eprintln!("[CalDAV Auth] OAuth2 access token refreshed");
+
+        if let Ok(mut auth) = self.auth.lock() {
+            *auth = CalDavAuth::OAuth2(refreshed.clone());
+        }
+        Ok(refreshed)
+    }
+
     fn build_request(
         &self,
         method: Method,
@@ -485,7 +1022,11 @@ impl CalDavClient {
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("CalDAV PUT failed: {status} {text}"));
+            return Err(CalDavHttpError::new(
+                status,
+                format!("CalDAV PUT failed: {status} {text}"),
+            )
+            .into());
         }
 
         let etag = response
@@ -753,7 +1294,151 @@ enum Field {
     CalendarData,
 }
 
-fn parse_ical_todo(ics: &str) -> Result<CalDavItem> {
+/// PROPFIND / REPORT 的 multistatus 响应
+#[derive(Debug, Default)]
+struct PropfindMultistatus {
+    responses: Vec<PropfindResponse>,
+    /// sync-collection 返回的新 sync-token（位于 multistatus 顶层）
+    sync_token: Option<String>,
+}
+
+/// multistatus 中的一个 response（只保留服务发现和增量同步需要的属性）
+#[derive(Debug, Default)]
+struct PropfindResponse {
+    href: String,
+    /// response 级别的状态（sync-collection 中 404 表示资源已删除）
+    status: Option<String>,
+    etag: Option<String>,
+    ctag: Option<String>,
+    sync_token: Option<String>,
+    current_user_principal: Option<String>,
+    calendar_home_set: Option<String>,
+    is_calendar: bool,
+    components: Vec<String>,
+    display_name: Option<String>,
+    description: Option<String>,
+    color: Option<String>,
+}
+
+fn parse_propfind(xml: &str) -> Result<PropfindMultistatus> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut multistatus = PropfindMultistatus::default();
+    let mut current: Option<PropfindResponse> = None;
+    // 当前元素路径（local name），用于区分不同属性下的 href
+    let mut path: Vec<Vec<u8>> = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => {
+                let name = element.local_name().as_ref().to_vec();
+                if name == b"response" {
+                    current = Some(PropfindResponse::default());
+                }
+                if let Some(response) = current.as_mut() {
+                    mark_propfind_element(response, &path, &element);
+                }
+                path.push(name);
+            }
+            Ok(Event::Empty(element)) => {
+                if let Some(response) = current.as_mut() {
+                    mark_propfind_element(response, &path, &element);
+                }
+            }
+            Ok(Event::Text(data)) => {
+                let text = reader
+                    .decoder()
+                    .decode(data.as_ref())
+                    .unwrap_or(Cow::Borrowed(""))
+                    .trim()
+                    .to_string();
+                match current.as_mut() {
+                    Some(response) => apply_propfind_text(response, &path, text),
+                    None if path.last().map(Vec::as_slice) == Some(b"sync-token") => {
+                        multistatus.sync_token = Some(text).filter(|token| !token.is_empty());
+                    }
+                    None => {}
+                }
+            }
+            Ok(Event::CData(data)) => {
+                if let Some(response) = current.as_mut() {
+                    let text = String::from_utf8_lossy(data.as_ref()).trim().to_string();
+                    apply_propfind_text(response, &path, text);
+                }
+            }
+            Ok(Event::End(element)) => {
+                path.pop();
+                if element.local_name().as_ref() == b"response" {
+                    if let Some(response) = current.take() {
+                        if !response.href.is_empty() {
+                            multistatus.responses.push(response);
+                        }
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(err) => return Err(err.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(multistatus)
+}
+
+/// 处理 resourcetype 中的 calendar 以及 supported-calendar-component-set 中的 comp
+fn mark_propfind_element(
+    response: &mut PropfindResponse,
+    path: &[Vec<u8>],
+    element: &quick_xml::events::BytesStart,
+) {
+    let parent = path.last().map(Vec::as_slice);
+    match (parent, element.local_name().as_ref()) {
+        (Some(b"resourcetype"), b"calendar") => response.is_calendar = true,
+        (Some(b"supported-calendar-component-set"), b"comp") => {
+            if let Ok(Some(attr)) = element.try_get_attribute("name") {
+                response
+                    .components
+                    .push(String::from_utf8_lossy(&attr.value).to_string());
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply_propfind_text(response: &mut PropfindResponse, path: &[Vec<u8>], text: String) {
+    if text.is_empty() {
+        return;
+    }
+
+    let mut names = path.iter().rev().map(Vec::as_slice);
+    let element = names.next();
+    let parent = names.next();
+
+    match (element, parent) {
+        (Some(b"href"), Some(b"response")) => response.href = text,
+        (Some(b"status"), Some(b"response")) => response.status = Some(text),
+        (Some(b"getetag"), _) => response.etag = Some(text),
+        (Some(b"getctag"), _) => response.ctag = Some(text),
+        (Some(b"sync-token"), _) => response.sync_token = Some(text),
+        (Some(b"href"), Some(b"current-user-principal")) => {
+            response.current_user_principal = Some(text)
+        }
+        (Some(b"href"), Some(b"calendar-home-set")) => {
+            // 可能有多个 calendar-home-set，取第一个
+            response.calendar_home_set.get_or_insert(text);
+        }
+        (Some(b"displayname"), _) => response.display_name = Some(text),
+        (Some(b"calendar-description"), _) => response.description = Some(text),
+        (Some(b"calendar-color"), _) => response.color = Some(text),
+        _ => {}
+    }
+}
+
+/// 解析 iCalendar 文本中的第一个 VTODO
+pub fn parse_ical_todo(ics: &str) -> Result<CalDavItem> {
     use ical::parser::ical::IcalParser;
 
     let mut parser = IcalParser::new(ics.as_bytes());
@@ -807,14 +1492,47 @@ fn parse_ical_todo(ics: &str) -> Result<CalDavItem> {
     let (last_modified, _) = get_datetime_property(&todo.properties, "LAST-MODIFIED");
     let recurrence_rule = get_property_value(&todo.properties, "RRULE");
 
-    let reminder_minutes = todo
+    let alarms: Vec<CalDavAlarm> = todo
         .alarms
         .iter()
-        .find_map(|alarm| get_property_value(&alarm.properties, "TRIGGER"))
-        .and_then(parse_trigger_offset);
-
-    // 解析 RELATED-TO 字段（用于子任务）
-    let related_to = get_property_value(&todo.properties, "RELATED-TO");
+        .filter_map(|alarm| parse_alarm(&alarm.properties))
+        .collect();
+
+    // 兼容单一提醒字段：取第一个相对到期时间提前的提醒
+    let reminder_minutes = alarms.iter().find_map(|alarm| match alarm.trigger {
+        AlarmTrigger::Relative {
+            minutes_before,
+            related_start: false,
+        } if minutes_before >= 0 => Some(minutes_before),
+        _ => None,
+    });
+
+    // 解析 RELATED-TO 字段：未指定 RELTYPE 或 PARENT 为父任务（用于子任务），DEPENDS-ON 为依赖
+    let mut related_to = None;
+    let mut depends_on: Vec<String> = Vec::new();
+    for prop in todo
+        .properties
+        .iter()
+        .filter(|prop| prop.name.eq_ignore_ascii_case("RELATED-TO"))
+    {
+        let Some(value) = prop.value.clone().filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        match property_parameter(prop, "RELTYPE") {
+            None => {
+                related_to.get_or_insert(value);
+            }
+            Some(reltype) if reltype.eq_ignore_ascii_case("PARENT") => {
+                related_to.get_or_insert(value);
+            }
+            Some(reltype) if reltype.eq_ignore_ascii_case("DEPENDS-ON") => {
+                if value != uid && !depends_on.contains(&value) {
+                    depends_on.push(value);
+                }
+            }
+            Some(_) => {}
+        }
+    }
 
     Ok(CalDavItem {
         uid,
@@ -830,9 +1548,11 @@ fn parse_ical_todo(ics: &str) -> Result<CalDavItem> {
         completed_at,
         last_modified,
         reminder_minutes,
+        alarms,
         timezone,
         recurrence_rule,
         related_to,
+        depends_on,
     })
 }
 
@@ -851,16 +1571,19 @@ fn get_property_parameter(
     properties
         .iter()
         .find(|prop| prop.name.eq_ignore_ascii_case(name))
-        .and_then(|prop| prop.params.as_ref())
-        .and_then(|params| {
-            params.iter().find_map(|(param_name, values)| {
-                if param_name.eq_ignore_ascii_case(parameter) {
-                    values.first().cloned()
-                } else {
-                    None
-                }
-            })
+        .and_then(|prop| property_parameter(prop, parameter))
+}
+
+fn property_parameter(prop: &ical::property::Property, parameter: &str) -> Option<String> {
+    prop.params.as_ref().and_then(|params| {
+        params.iter().find_map(|(param_name, values)| {
+            if param_name.eq_ignore_ascii_case(parameter) {
+                values.first().cloned()
+            } else {
+                None
+            }
         })
+    })
 }
 
 fn get_datetime_property(
@@ -920,20 +1643,145 @@ fn parse_ical_datetime(value: &str, tzid: Option<&str>) -> Result<DateTime<Utc>>
     Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
 }
 
-fn parse_trigger_offset(value: String) -> Option<i32> {
+fn parse_alarm(properties: &[ical::property::Property]) -> Option<CalDavAlarm> {
+    let value = get_property_value(properties, "TRIGGER")?;
+    let value_type = get_property_parameter(properties, "TRIGGER", "VALUE");
+
+    let trigger = if value_type
+        .as_deref()
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE-TIME"))
+    {
+        AlarmTrigger::Absolute(parse_ical_datetime(value.trim(), None).ok()?)
+    } else {
+        // 未指定 RELATED 时按到期时间处理（与旧版本及多数任务客户端的行为一致）
+        let related_start = get_property_parameter(properties, "TRIGGER", "RELATED")
+            .is_some_and(|v| v.eq_ignore_ascii_case("START"));
+        AlarmTrigger::Relative {
+            minutes_before: -parse_trigger_duration(&value)?,
+            related_start,
+        }
+    };
+
+    let action = get_property_value(properties, "ACTION")
+        .map(|action| action.trim().to_ascii_uppercase())
+        .filter(|action| !action.is_empty())
+        .unwrap_or_else(|| "DISPLAY".to_string());
+    let description = get_property_value(properties, "DESCRIPTION");
+
+    Some(CalDavAlarm {
+        trigger,
+        action,
+        description,
+    })
+}
+
+/// 解析 iCalendar DURATION（如 `-PT15M`、`-P1DT2H`、`PT0S`），返回带符号的分钟数
+pub(super) fn parse_trigger_duration(value: &str) -> Option<i32> {
     let trimmed = value.trim();
-    if !trimmed.starts_with('-') {
+    let (sign, rest) = match trimmed.as_bytes().first()? {
+        b'-' => (-1, &trimmed[1..]),
+        b'+' => (1, &trimmed[1..]),
+        _ => (1, trimmed),
+    };
+    let rest = rest.strip_prefix('P')?;
+
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for ch in rest.chars() {
+        match ch {
+            '0'..='9' => number.push(ch),
+            'T' if number.is_empty() => in_time = true,
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let amount = number.parse::<i64>().ok()?;
+                number.clear();
+                let unit = match (ch, in_time) {
+                    ('W', false) => 7 * 24 * 3600,
+                    ('D', false) => 24 * 3600,
+                    ('H', true) => 3600,
+                    ('M', true) => 60,
+                    ('S', true) => 1,
+                    _ => return None,
+                };
+                seconds = seconds.checked_add(amount.checked_mul(unit)?)?;
+            }
+            _ => return None,
+        }
+    }
+    if !number.is_empty() {
         return None;
     }
 
-    let trimmed = trimmed.trim_start_matches('-');
-    if let Some(minutes) = trimmed.strip_prefix("PT").and_then(|v| v.strip_suffix('M')) {
-        return minutes.parse::<i32>().ok();
+    i32::try_from(sign * seconds / 60).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm(lines: &str) -> Option<CalDavAlarm> {
+        let properties: Vec<_> = ical::PropertyParser::from_reader(lines.as_bytes())
+            .map(|property| property.unwrap())
+            .collect();
+        parse_alarm(&properties)
     }
 
-    if let Some(hours) = trimmed.strip_prefix("PT").and_then(|v| v.strip_suffix('H')) {
-        return hours.parse::<i32>().ok().map(|h| h * 60);
+    #[test]
+    fn test_parse_trigger_duration() {
+        assert_eq!(parse_trigger_duration("-P1D"), Some(-1440));
+        assert_eq!(parse_trigger_duration("-PT15M"), Some(-15));
+        assert_eq!(parse_trigger_duration("PT0S"), Some(0));
+        assert_eq!(parse_trigger_duration("-P1DT2H"), Some(-1560));
+        assert_eq!(parse_trigger_duration("+PT30M"), Some(30));
+        assert_eq!(parse_trigger_duration("-P1W"), Some(-10080));
+
+        assert_eq!(parse_trigger_duration("-PT15"), None);
+        assert_eq!(parse_trigger_duration("-P1H"), None);
+        assert_eq!(parse_trigger_duration("15M"), None);
+        assert_eq!(parse_trigger_duration(""), None);
     }
 
-    None
+    #[test]
+    fn test_parse_alarm() {
+        let parsed = alarm("TRIGGER:-PT15M\nACTION:DISPLAY\n").unwrap();
+        assert!(matches!(
+            parsed.trigger,
+            AlarmTrigger::Relative {
+                minutes_before: 15,
+                related_start: false
+            }
+        ));
+        assert_eq!(parsed.action, "DISPLAY");
+
+        let parsed = alarm("TRIGGER;RELATED=END:-P1DT2H\nACTION:AUDIO\n").unwrap();
+        assert!(matches!(
+            parsed.trigger,
+            AlarmTrigger::Relative {
+                minutes_before: 1560,
+                related_start: false
+            }
+        ));
+
+        // 缺少 ACTION 时按 DISPLAY 处理
+        let parsed = alarm("TRIGGER;RELATED=START:PT0S\n").unwrap();
+        assert!(matches!(
+            parsed.trigger,
+            AlarmTrigger::Relative {
+                minutes_before: 0,
+                related_start: true
+            }
+        ));
+        assert_eq!(parsed.action, "DISPLAY");
+
+        let parsed =
+            alarm("TRIGGER;VALUE=DATE-TIME:20260102T030405Z\nACTION:email\nDESCRIPTION:Standup\n")
+                .unwrap();
+        let expected = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+        assert!(matches!(parsed.trigger, AlarmTrigger::Absolute(at) if at == expected));
+        assert_eq!(parsed.action, "EMAIL");
+        assert_eq!(parsed.description.as_deref(), Some("Standup"));
+
+        assert!(alarm("ACTION:DISPLAY\n").is_none());
+        assert!(alarm("TRIGGER:soon\nACTION:DISPLAY\n").is_none());
+    }
 }
@@ -1,62 +1,466 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
-use sea_orm::DatabaseConnection;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::features::settings::core::service::SettingService;
+use crate::features::settings::data::entity as setting_entity;
+use crate::infrastructure::secrets::SecretStore;
 
-const CONFIG_KEY: &str = "caldav.config";
-const LAST_SYNC_KEY: &str = "caldav.last_sync";
-const LAST_ERROR_KEY: &str = "caldav.last_error";
-const SYNC_INTERVAL_KEY: &str = "caldav.sync_interval_minutes";
+const CALENDARS_KEY: &str = "caldav.calendars";
 const DEFAULT_SYNC_INTERVAL_MINUTES: u64 = 15;
+/// 同步间隔设置，新添加的日历以此为默认值（沿用旧版单日历配置的键，旧配置迁移时读取）
+const SYNC_INTERVAL_KEY: &str = "caldav.sync_interval_minutes";
+
+// 旧版单日历配置，首次读取日历列表时迁移为默认日历
+const LEGACY_CONFIG_KEY: &str = "caldav.config";
+const LEGACY_LAST_SYNC_KEY: &str = "caldav.last_sync";
+const LEGACY_LAST_ERROR_KEY: &str = "caldav.last_error";
+const LEGACY_CALENDAR_ID: &str = "default";
 
+/// CalDAV 连接信息（日历集合 URL + 凭据）
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CalDavConfig {
     pub url: String,
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub auth: CalDavAuth,
 }
 
 impl CalDavConfig {
     pub fn is_valid(&self) -> bool {
-        !self.url.trim().is_empty() && !self.username.trim().is_empty()
+        if self.url.trim().is_empty() {
+            return false;
+        }
+        match &self.auth {
+            CalDavAuth::Password => !self.username.trim().is_empty(),
+            CalDavAuth::OAuth2(credentials) => credentials.is_valid(),
+        }
+    }
+}
+
+/// CalDAV 认证方式
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CalDavAuth {
+    /// 用户名 + 密码（包括应用专用密码），按服务器要求使用 Basic 或 Digest
+    #[default]
+    Password,
+    /// OAuth2 Bearer token，过期或被拒绝（401）时使用 refresh token 刷新
+    #[serde(rename = "oauth2")]
+    OAuth2(OAuth2Credentials),
+}
+
+impl CalDavAuth {
+    /// 认证方式名称（不含凭据，用于状态展示）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CalDavAuth::Password => "password",
+            CalDavAuth::OAuth2(_) => "oauth2",
+        }
     }
 }
 
+/// OAuth2 凭据（刷新后写回日历配置）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OAuth2Credentials {
+    /// 令牌端点（refresh_token 授权）
+    pub token_endpoint: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    #[serde(default)]
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// access token 过期时间（令牌端点未返回有效期时为空）
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl OAuth2Credentials {
+    pub fn is_valid(&self) -> bool {
+        let has_token = self
+            .access_token
+            .as_deref()
+            .is_some_and(|token| !token.is_empty())
+            || self
+                .refresh_token
+                .as_deref()
+                .is_some_and(|token| !token.is_empty());
+        has_token && !self.token_endpoint.trim().is_empty() && !self.client_id.trim().is_empty()
+    }
+}
+
+/// 一个已配置的 CalDAV 日历（每个日历有独立的凭据、同步间隔和同步状态）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CalDavCalendarConfig {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    #[serde(default = "default_sync_interval_minutes")]
+    pub sync_interval_minutes: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 默认日历：尚未关联日历的本地新建 todo 推送到这里
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default)]
+    pub auth: CalDavAuth,
+}
+
+impl CalDavCalendarConfig {
+    pub fn new(name: String, connection: CalDavConfig) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            url: connection.url,
+            username: connection.username,
+            password: connection.password,
+            sync_interval_minutes: DEFAULT_SYNC_INTERVAL_MINUTES,
+            enabled: true,
+            is_default: false,
+            auth: connection.auth,
+        }
+    }
+
+    pub fn connection(&self) -> CalDavConfig {
+        CalDavConfig {
+            url: self.url.clone(),
+            username: self.username.clone(),
+            password: self.password.clone(),
+            auth: self.auth.clone(),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.connection().is_valid()
+    }
+}
+
+fn default_sync_interval_minutes() -> u64 {
+    DEFAULT_SYNC_INTERVAL_MINUTES
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn last_sync_key(calendar_id: &str) -> String {
+    format!("caldav.calendar.{calendar_id}.last_sync")
+}
+
+fn last_error_key(calendar_id: &str) -> String {
+    format!("caldav.calendar.{calendar_id}.last_error")
+}
+
+fn sync_state_key(calendar_id: &str) -> String {
+    format!("caldav.calendar.{calendar_id}.sync_state")
+}
+
+fn secrets_key(calendar_id: &str) -> String {
+    format!("caldav.calendar.{calendar_id}.secrets")
+}
+
+/// 日历的敏感字段（密码、OAuth2 密钥和令牌）
+///
+/// 加密后单独保存在 `caldav.calendar.{id}.secrets`，日历列表中只保留空值
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct CalendarSecrets {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    client_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    access_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+}
+
+impl CalendarSecrets {
+    /// 从日历中取出敏感字段（日历中对应字段被清空）
+    fn take(calendar: &mut CalDavCalendarConfig) -> Self {
+        let mut secrets = Self {
+            password: std::mem::take(&mut calendar.password),
+            ..Self::default()
+        };
+        if let CalDavAuth::OAuth2(credentials) = &mut calendar.auth {
+            secrets.client_secret = credentials.client_secret.take();
+            secrets.access_token = credentials.access_token.take();
+            secrets.refresh_token = credentials.refresh_token.take();
+        }
+        secrets
+    }
+
+    /// 将敏感字段填回日历
+    fn apply(self, calendar: &mut CalDavCalendarConfig) {
+        calendar.password = self.password;
+        if let CalDavAuth::OAuth2(credentials) = &mut calendar.auth {
+            credentials.client_secret = self.client_secret;
+            credentials.access_token = self.access_token;
+            credentials.refresh_token = self.refresh_token;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// 日历集合的增量同步状态
+///
+/// 记录集合 URL，日历地址变更后旧状态自动失效
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CalendarSyncState {
+    pub collection_url: String,
+    pub ctag: Option<String>,
+    pub sync_token: Option<String>,
+}
+
+/// 解密日历凭据
+fn decrypt_secrets(encrypted: &str) -> Result<CalendarSecrets> {
+    let payload = SecretStore::global()?.decrypt(encrypted)?;
+    serde_json::from_str(&payload).context("failed to deserialize CalDAV credentials")
+}
+
 pub struct CalDavConfigService;
 
 impl CalDavConfigService {
-    pub async fn get_config(db: &DatabaseConnection) -> Result<Option<CalDavConfig>> {
-        let data = SettingService::get(db, CONFIG_KEY).await?;
-        if let Some(raw) = data {
-            let config: CalDavConfig = serde_json::from_str(&raw)
-                .with_context(|| "failed to deserialize CalDAV configuration")?;
-            if config.is_valid() {
-                return Ok(Some(config));
+    /// 获取所有已配置的日历（默认日历在前），凭据从加密存储中解密填回
+    ///
+    /// 日历列表中仍有明文凭据（旧版本写入）时，将其移入加密存储
+    pub async fn list_calendars(db: &DatabaseConnection) -> Result<Vec<CalDavCalendarConfig>> {
+        let raw = match SettingService::get(db, CALENDARS_KEY).await? {
+            Some(raw) => raw,
+            None => return Self::migrate_legacy_config(db).await,
+        };
+
+        let mut calendars: Vec<CalDavCalendarConfig> =
+            serde_json::from_str(&raw).with_context(|| "failed to deserialize CalDAV calendars")?;
+
+        let mut has_plaintext = false;
+        for calendar in calendars.iter_mut() {
+            let plaintext = CalendarSecrets::take(calendar);
+            if !plaintext.is_empty() {
+                has_plaintext = true;
+                plaintext.apply(calendar);
+            } else if let Some(encrypted) =
+                SettingService::get(db, &secrets_key(&calendar.id)).await?
+            {
+                match decrypt_secrets(&encrypted) {
+                    Ok(secrets) => secrets.apply(calendar),
+                    Err(err) => {
+                        // 密钥文件丢失或数据库来自其他设备：保留密文，提示用户重新输入凭据
+                        eprintln!("[CalDAV] 无法读取日历 {} 的凭据: {err:#}", calendar.id);
+                        let message = format!(
+                            "Saved credentials could not be decrypted, please enter them again: {err:#}"
+                        );
+                        Self::set_calendar_last_error(db, &calendar.id, Some(&message)).await?;
+                    }
+                }
             }
         }
-        Ok(None)
+
+        if has_plaintext {
+            Self::store_calendars(db, calendars.clone()).await?;
+            println!("[CalDAV] 已将日历凭据迁移到加密存储");
+        }
+
+        calendars.sort_by_key(|calendar| !calendar.is_default);
+        Ok(calendars)
     }
 
-    pub async fn set_config(db: &DatabaseConnection, config: &CalDavConfig) -> Result<()> {
-        let payload = serde_json::to_string(config)
-            .with_context(|| "failed to serialize CalDAV configuration")?;
-        SettingService::set(db, CONFIG_KEY, &payload).await?;
-        // 重置状态
-        let _ = SettingService::delete(db, LAST_ERROR_KEY).await?;
-        Ok(())
+    /// 凭据无法解密的日历名称（密钥文件丢失，或恢复了其他设备的备份）
+    pub async fn calendars_with_unreadable_credentials(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for calendar in Self::list_calendars(db).await? {
+            if let Some(encrypted) = SettingService::get(db, &secrets_key(&calendar.id)).await? {
+                if decrypt_secrets(&encrypted).is_err() {
+                    names.push(calendar.name);
+                }
+            }
+        }
+        Ok(names)
     }
 
-    pub async fn clear_config(db: &DatabaseConnection) -> Result<()> {
-        let _ = SettingService::delete(db, CONFIG_KEY).await?;
-        let _ = SettingService::delete(db, LAST_SYNC_KEY).await?;
-        let _ = SettingService::delete(db, LAST_ERROR_KEY).await?;
+    pub async fn get_calendar(
+        db: &DatabaseConnection,
+        id: &str,
+    ) -> Result<Option<CalDavCalendarConfig>> {
+        Ok(Self::list_calendars(db)
+            .await?
+            .into_iter()
+            .find(|calendar| calendar.id == id))
+    }
+
+    /// 默认日历（没有标记默认时取第一个）
+    pub async fn default_calendar(db: &DatabaseConnection) -> Result<Option<CalDavCalendarConfig>> {
+        Ok(Self::list_calendars(db).await?.into_iter().next())
+    }
+
+    /// 新增或更新日历（按 id 匹配）
+    ///
+    /// 保证最多一个默认日历；列表中只有一个日历时它自动成为默认日历
+    pub async fn save_calendar(
+        db: &DatabaseConnection,
+        calendar: &CalDavCalendarConfig,
+    ) -> Result<()> {
+        if !calendar.is_valid() {
+            return Err(anyhow!("CalDAV calendar configuration is incomplete"));
+        }
+
+        let mut calendars = Self::list_calendars(db).await?;
+        if calendar.is_default {
+            for existing in calendars.iter_mut() {
+                existing.is_default = false;
+            }
+        }
+
+        match calendars
+            .iter_mut()
+            .find(|existing| existing.id == calendar.id)
+        {
+            Some(existing) => *existing = calendar.clone(),
+            None => calendars.push(calendar.clone()),
+        }
+
+        Self::store_calendars(db, calendars).await
+    }
+
+    /// 更新日历的认证信息（OAuth2 刷新 token 后写回），日历不存在时忽略
+    pub async fn set_calendar_auth(
+        db: &DatabaseConnection,
+        id: &str,
+        auth: &CalDavAuth,
+    ) -> Result<()> {
+        let mut calendars = Self::list_calendars(db).await?;
+        let Some(calendar) = calendars.iter_mut().find(|calendar| calendar.id == id) else {
+            return Ok(());
+        };
+        calendar.auth = auth.clone();
+
+        Self::store_calendars(db, calendars).await
+    }
+
+    /// 删除日历及其同步状态，返回被删除的日历
+    pub async fn remove_calendar(
+        db: &DatabaseConnection,
+        id: &str,
+    ) -> Result<Option<CalDavCalendarConfig>> {
+        let mut calendars = Self::list_calendars(db).await?;
+        let Some(index) = calendars.iter().position(|calendar| calendar.id == id) else {
+            return Ok(None);
+        };
+        let removed = calendars.remove(index);
+
+        Self::store_calendars(db, calendars).await?;
+        let _ = SettingService::delete(db, &last_sync_key(id)).await?;
+        let _ = SettingService::delete(db, &last_error_key(id)).await?;
+        let _ = SettingService::delete(db, &sync_state_key(id)).await?;
+        let _ = SettingService::delete(db, &secrets_key(id)).await?;
+
+        Ok(Some(removed))
+    }
+
+    /// 保存日历列表：凭据加密后按日历单独保存，列表中不含明文凭据
+    async fn store_calendars(
+        db: &DatabaseConnection,
+        mut calendars: Vec<CalDavCalendarConfig>,
+    ) -> Result<()> {
+        if !calendars.is_empty() && !calendars.iter().any(|calendar| calendar.is_default) {
+            calendars[0].is_default = true;
+        }
+
+        for calendar in calendars.iter_mut() {
+            let secrets = CalendarSecrets::take(calendar);
+            if secrets.is_empty() {
+                // 无法解密的凭据不是用户清除的，保留密文（恢复原密钥文件后仍可读取）
+                let key = secrets_key(&calendar.id);
+                if let Some(encrypted) = SettingService::get(db, &key).await? {
+                    if decrypt_secrets(&encrypted).is_ok() {
+                        let _ = SettingService::delete(db, &key).await?;
+                    }
+                }
+                continue;
+            }
+
+            let payload = serde_json::to_string(&secrets)
+                .with_context(|| "failed to serialize CalDAV credentials")?;
+            let encrypted = SecretStore::global()?.encrypt(&payload).with_context(|| {
+                format!("failed to encrypt credentials of calendar {}", calendar.id)
+            })?;
+            SettingService::set(db, &secrets_key(&calendar.id), &encrypted).await?;
+        }
+
+        let payload = serde_json::to_string(&calendars)
+            .with_context(|| "failed to serialize CalDAV calendars")?;
+        SettingService::set(db, CALENDARS_KEY, &payload).await?;
         Ok(())
     }
 
-    pub async fn get_last_sync(db: &DatabaseConnection) -> Result<Option<DateTime<Utc>>> {
-        let value = SettingService::get(db, LAST_SYNC_KEY).await?;
+    /// 将旧版单日历配置迁移为默认日历（包括同步间隔和同步状态）
+    async fn migrate_legacy_config(db: &DatabaseConnection) -> Result<Vec<CalDavCalendarConfig>> {
+        let Some(raw) = SettingService::get(db, LEGACY_CONFIG_KEY).await? else {
+            return Ok(Vec::new());
+        };
+
+        let legacy: CalDavConfig = serde_json::from_str(&raw)
+            .with_context(|| "failed to deserialize CalDAV configuration")?;
+        if !legacy.is_valid() {
+            return Ok(Vec::new());
+        }
+
+        let sync_interval_minutes = Self::default_sync_interval_minutes(db).await?;
+
+        let calendar = CalDavCalendarConfig {
+            id: LEGACY_CALENDAR_ID.to_string(),
+            name: "CalDAV".to_string(),
+            sync_interval_minutes,
+            is_default: true,
+            ..CalDavCalendarConfig::new(String::new(), legacy)
+        };
+
+        if let Some(value) = SettingService::get(db, LEGACY_LAST_SYNC_KEY).await? {
+            SettingService::set(db, &last_sync_key(&calendar.id), &value).await?;
+        }
+        if let Some(value) = SettingService::get(db, LEGACY_LAST_ERROR_KEY).await? {
+            SettingService::set(db, &last_error_key(&calendar.id), &value).await?;
+        }
+
+        Self::store_calendars(db, vec![calendar.clone()]).await?;
+
+        // 旧配置中有明文密码：启用 secure_delete，删除时覆写被释放的页面内容，
+        // 避免密码残留在数据库文件的空闲页中（该设置只对当前连接生效，因此在同一事务中删除）
+        let txn = db.begin().await?;
+        txn.execute_unprepared("PRAGMA secure_delete = ON").await?;
+        setting_entity::Entity::delete_many()
+            .filter(setting_entity::Column::Key.is_in([
+                LEGACY_CONFIG_KEY,
+                LEGACY_LAST_SYNC_KEY,
+                LEGACY_LAST_ERROR_KEY,
+            ]))
+            .exec(&txn)
+            .await
+            .context("failed to remove legacy CalDAV configuration")?;
+        txn.commit().await?;
+
+        println!("[CalDAV] 已将旧版 CalDAV 配置迁移为默认日历");
+        Ok(vec![calendar])
+    }
+
+    pub async fn get_calendar_last_sync(
+        db: &DatabaseConnection,
+        id: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let value = SettingService::get(db, &last_sync_key(id)).await?;
         if let Some(raw) = value {
             let parsed = DateTime::parse_from_rfc3339(&raw)
                 .with_context(|| format!("failed to parse CalDAV last sync time: {raw}"))?;
@@ -65,49 +469,157 @@ impl CalDavConfigService {
         Ok(None)
     }
 
-    pub async fn set_last_sync(
+    pub async fn set_calendar_last_sync(
         db: &DatabaseConnection,
+        id: &str,
         value: Option<DateTime<Utc>>,
     ) -> Result<()> {
         if let Some(timestamp) = value {
-            SettingService::set(db, LAST_SYNC_KEY, &timestamp.to_rfc3339()).await?;
+            SettingService::set(db, &last_sync_key(id), &timestamp.to_rfc3339()).await?;
         } else {
-            let _ = SettingService::delete(db, LAST_SYNC_KEY).await?;
+            let _ = SettingService::delete(db, &last_sync_key(id)).await?;
         }
         Ok(())
     }
 
-    pub async fn get_last_error(db: &DatabaseConnection) -> Result<Option<String>> {
-        Ok(SettingService::get(db, LAST_ERROR_KEY).await?)
+    pub async fn get_calendar_last_error(
+        db: &DatabaseConnection,
+        id: &str,
+    ) -> Result<Option<String>> {
+        Ok(SettingService::get(db, &last_error_key(id)).await?)
     }
 
-    pub async fn set_last_error(db: &DatabaseConnection, value: Option<&str>) -> Result<()> {
+    pub async fn set_calendar_last_error(
+        db: &DatabaseConnection,
+        id: &str,
+        value: Option<&str>,
+    ) -> Result<()> {
         match value {
             Some(message) if !message.is_empty() => {
-                SettingService::set(db, LAST_ERROR_KEY, message).await?;
+                SettingService::set(db, &last_error_key(id), message).await?;
             }
             _ => {
-                let _ = SettingService::delete(db, LAST_ERROR_KEY).await?;
+                let _ = SettingService::delete(db, &last_error_key(id)).await?;
             }
         }
         Ok(())
     }
 
+    pub async fn get_calendar_sync_state(
+        db: &DatabaseConnection,
+        id: &str,
+    ) -> Result<Option<CalendarSyncState>> {
+        match SettingService::get(db, &sync_state_key(id)).await? {
+            Some(raw) => serde_json::from_str(&raw)
+                .map(Some)
+                .with_context(|| "failed to deserialize CalDAV sync state"),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_calendar_sync_state(
+        db: &DatabaseConnection,
+        id: &str,
+        value: Option<&CalendarSyncState>,
+    ) -> Result<()> {
+        if let Some(state) = value {
+            let payload = serde_json::to_string(state)
+                .with_context(|| "failed to serialize CalDAV sync state")?;
+            SettingService::set(db, &sync_state_key(id), &payload).await?;
+        } else {
+            let _ = SettingService::delete(db, &sync_state_key(id)).await?;
+        }
+        Ok(())
+    }
+
+    // ===== 旧版单日历接口：作用于默认日历 =====
+
+    pub async fn get_config(db: &DatabaseConnection) -> Result<Option<CalDavConfig>> {
+        Ok(Self::default_calendar(db)
+            .await?
+            .filter(|calendar| calendar.is_valid())
+            .map(|calendar| calendar.connection()))
+    }
+
+    pub async fn set_config(db: &DatabaseConnection, config: &CalDavConfig) -> Result<()> {
+        let calendar = match Self::default_calendar(db).await? {
+            Some(existing) => CalDavCalendarConfig {
+                url: config.url.clone(),
+                username: config.username.clone(),
+                password: config.password.clone(),
+                auth: config.auth.clone(),
+                ..existing
+            },
+            None => CalDavCalendarConfig {
+                is_default: true,
+                sync_interval_minutes: Self::default_sync_interval_minutes(db).await?,
+                ..CalDavCalendarConfig::new("CalDAV".to_string(), config.clone())
+            },
+        };
+
+        Self::save_calendar(db, &calendar).await?;
+        // 重置状态
+        Self::set_calendar_last_error(db, &calendar.id, None).await?;
+        Ok(())
+    }
+
+    /// 清除所有日历配置
+    pub async fn clear_config(db: &DatabaseConnection) -> Result<()> {
+        for calendar in Self::list_calendars(db).await? {
+            Self::remove_calendar(db, &calendar.id).await?;
+        }
+        let _ = SettingService::delete(db, CALENDARS_KEY).await?;
+        Ok(())
+    }
+
+    pub async fn get_last_sync(db: &DatabaseConnection) -> Result<Option<DateTime<Utc>>> {
+        match Self::default_calendar(db).await? {
+            Some(calendar) => Self::get_calendar_last_sync(db, &calendar.id).await,
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_last_sync(
+        db: &DatabaseConnection,
+        value: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        if let Some(calendar) = Self::default_calendar(db).await? {
+            Self::set_calendar_last_sync(db, &calendar.id, value).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_last_error(db: &DatabaseConnection) -> Result<Option<String>> {
+        match Self::default_calendar(db).await? {
+            Some(calendar) => Self::get_calendar_last_error(db, &calendar.id).await,
+            None => Ok(None),
+        }
+    }
+
     pub async fn get_sync_interval_minutes(db: &DatabaseConnection) -> Result<u64> {
-        let raw = SettingService::get(db, SYNC_INTERVAL_KEY).await?;
-        if let Some(raw_value) = raw {
-            if let Ok(parsed) = raw_value.parse::<u64>() {
-                if parsed > 0 {
-                    return Ok(parsed);
-                }
-            }
+        match Self::default_calendar(db).await? {
+            Some(calendar) => Ok(calendar.sync_interval_minutes.max(1)),
+            None => Self::default_sync_interval_minutes(db).await,
         }
-        Ok(DEFAULT_SYNC_INTERVAL_MINUTES)
     }
 
+    /// 设置默认日历的同步间隔，同时作为之后添加的日历的默认值（没有日历时只保存该默认值）
     pub async fn set_sync_interval_minutes(db: &DatabaseConnection, minutes: u64) -> Result<()> {
         let minutes = minutes.max(1);
         SettingService::set(db, SYNC_INTERVAL_KEY, &minutes.to_string()).await?;
+        if let Some(mut calendar) = Self::default_calendar(db).await? {
+            calendar.sync_interval_minutes = minutes;
+            Self::save_calendar(db, &calendar).await?;
+        }
         Ok(())
     }
+
+    /// 新日历的默认同步间隔（分钟）
+    pub async fn default_sync_interval_minutes(db: &DatabaseConnection) -> Result<u64> {
+        Ok(SettingService::get(db, SYNC_INTERVAL_KEY)
+            .await?
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_SYNC_INTERVAL_MINUTES))
+    }
 }
@@ -0,0 +1,182 @@
+// CalDAV 同步冲突
+//
+// 三方合并时本地和远端修改了同一字段会记录冲突（todo_sync_conflicts 表），
+// 冲突字段暂时保留本地值，存在未解决冲突的 todo 不会推送到远端。
+// 用户选择保留本地值或采用远端值后删除冲突，全部解决后 todo 正常推送
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+
+use super::client::parse_ical_todo;
+use super::merge::{self, FieldConflict};
+use super::sync::{apply_item_to_active, apply_remote_alarms, build_ical_from_model};
+use crate::features::todo::core::models::TodoSyncConflict;
+use crate::features::todo::data::{entity, sync_conflict_entity};
+
+/// 冲突的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// 保留本地值（之后推送到远端）
+    Local,
+    /// 采用远端值
+    Remote,
+}
+
+/// 获取未解决的冲突（todo_id 为 None 时返回全部）
+pub async fn list_conflicts(
+    db: &DatabaseConnection,
+    todo_id: Option<i32>,
+) -> Result<Vec<TodoSyncConflict>> {
+    let mut query = sync_conflict_entity::Entity::find();
+    if let Some(todo_id) = todo_id {
+        query = query.filter(sync_conflict_entity::Column::TodoId.eq(todo_id));
+    }
+
+    let conflicts = query
+        .order_by_asc(sync_conflict_entity::Column::TodoId)
+        .order_by_asc(sync_conflict_entity::Column::Id)
+        .all(db)
+        .await
+        .context("failed to load sync conflicts")?;
+
+    Ok(conflicts.into_iter().map(Into::into).collect())
+}
+
+/// 某个 todo 尚未解决的冲突字段
+pub async fn conflict_fields(db: &DatabaseConnection, todo_id: i32) -> Result<Vec<String>> {
+    Ok(sync_conflict_entity::Entity::find()
+        .filter(sync_conflict_entity::Column::TodoId.eq(todo_id))
+        .all(db)
+        .await
+        .with_context(|| format!("failed to load sync conflicts for todo {todo_id}"))?
+        .into_iter()
+        .map(|conflict| conflict.field)
+        .collect())
+}
+
+/// 存在未解决冲突的 todo（同步时暂不推送）
+pub async fn conflicted_todo_ids(db: &DatabaseConnection) -> Result<HashSet<i32>> {
+    Ok(sync_conflict_entity::Entity::find()
+        .all(db)
+        .await
+        .context("failed to load sync conflicts")?
+        .into_iter()
+        .map(|conflict| conflict.todo_id)
+        .collect())
+}
+
+/// 用本次合并的结果替换某个 todo 的冲突记录（同一字段保留创建时间）
+pub async fn replace_conflicts(
+    db: &DatabaseConnection,
+    todo_id: i32,
+    conflicts: &[FieldConflict],
+) -> Result<()> {
+    let existing = sync_conflict_entity::Entity::find()
+        .filter(sync_conflict_entity::Column::TodoId.eq(todo_id))
+        .all(db)
+        .await
+        .with_context(|| format!("failed to load sync conflicts for todo {todo_id}"))?;
+
+    sync_conflict_entity::Entity::delete_many()
+        .filter(sync_conflict_entity::Column::TodoId.eq(todo_id))
+        .exec(db)
+        .await
+        .with_context(|| format!("failed to clear sync conflicts for todo {todo_id}"))?;
+
+    let now = Utc::now();
+    for conflict in conflicts {
+        let created_at = existing
+            .iter()
+            .find(|model| model.field == conflict.field)
+            .map(|model| model.created_at)
+            .unwrap_or(now);
+
+        sync_conflict_entity::ActiveModel {
+            id: NotSet,
+            todo_id: Set(todo_id),
+            field: Set(conflict.field.to_string()),
+            base_value: Set(conflict.base.clone()),
+            local_value: Set(conflict.local.clone()),
+            remote_value: Set(conflict.remote.clone()),
+            created_at: Set(created_at),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await
+        .with_context(|| format!("failed to record sync conflict for todo {todo_id}"))?;
+    }
+
+    Ok(())
+}
+
+/// 解决一个冲突，返回该 todo 是否还有未解决的冲突
+///
+/// 采用远端值时从同步快照（即最近一次拉取的远端版本）中取该字段写入本地；
+/// 两种方式处理后 todo 都保持 dirty，冲突全部解决后推送到远端
+pub async fn resolve_conflict(
+    db: &DatabaseConnection,
+    conflict_id: i32,
+    resolution: ConflictResolution,
+) -> Result<bool> {
+    let conflict = sync_conflict_entity::Entity::find_by_id(conflict_id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load sync conflict {conflict_id}"))?
+        .ok_or_else(|| anyhow!("sync conflict {conflict_id} not found"))?;
+
+    let todo = entity::Entity::find_by_id(conflict.todo_id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load todo {}", conflict.todo_id))?
+        .ok_or_else(|| anyhow!("todo {} not found", conflict.todo_id))?;
+
+    if resolution == ConflictResolution::Remote {
+        let snapshot = todo
+            .sync_snapshot
+            .as_deref()
+            .ok_or_else(|| anyhow!("todo {} has no sync snapshot", todo.id))?;
+        let remote = parse_ical_todo(snapshot)
+            .with_context(|| format!("failed to parse sync snapshot of todo {}", todo.id))?;
+        let mut local = parse_ical_todo(&build_ical_from_model(db, &todo).await)
+            .with_context(|| format!("failed to build local version of todo {}", todo.id))?;
+        merge::copy_field(&mut local, &remote, &conflict.field);
+
+        let now = Utc::now();
+        let mut active: entity::ActiveModel = todo.clone().into();
+        let keep_reminder_state = apply_item_to_active(db, &mut active, &local, now).await;
+        active.last_modified_at = Set(now);
+        active.dirty = Set(true);
+        active
+            .update(db)
+            .await
+            .with_context(|| format!("failed to apply remote value to todo {}", todo.id))?;
+
+        if conflict.field == merge::FIELD_ALARMS {
+            apply_remote_alarms(db, todo.id, &local, keep_reminder_state, false).await?;
+        }
+    } else {
+        let mut active: entity::ActiveModel = todo.clone().into();
+        active.dirty = Set(true);
+        active.updated_at = Set(Utc::now());
+        active
+            .update(db)
+            .await
+            .with_context(|| format!("failed to mark todo {} dirty", todo.id))?;
+    }
+
+    sync_conflict_entity::Entity::delete_by_id(conflict_id)
+        .exec(db)
+        .await
+        .with_context(|| format!("failed to delete sync conflict {conflict_id}"))?;
+
+    Ok(!conflict_fields(db, todo.id).await?.is_empty())
+}
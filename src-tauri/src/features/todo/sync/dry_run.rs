@@ -0,0 +1,309 @@
+// CalDAV 同步试运行
+//
+// 按与正式同步相同的规则规划一个日历的同步：读取本地数据和远端变化，
+// 列出将要执行的创建、更新、合并、推送和删除操作及每个字段的前后值。
+// 只发送只读请求（PROPFIND / REPORT），不写入本地数据库和远端，也不修改同步状态
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+
+use super::client::{parse_ical_todo, CalDavClient, CalDavItem, RemoteTodo};
+use super::config::CalDavCalendarConfig;
+use super::sync::{belongs_to_calendar, build_ical_from_model, load_sync_inputs, SyncInputs};
+use super::{conflicts, merge, outbox};
+use crate::features::todo::data::entity;
+
+/// 试运行规划出的操作
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedAction {
+    /// 远端新增，在本地创建
+    CreateLocal,
+    /// 远端修改，覆盖本地
+    UpdateLocal,
+    /// 本地和远端都有修改，逐字段合并
+    MergeLocal,
+    /// 远端已删除，删除本地
+    DeleteLocal,
+    /// 本地新增，在远端创建
+    CreateRemote,
+    /// 本地修改，推送到远端
+    UpdateRemote,
+    /// 本地已删除，删除远端
+    DeleteRemote,
+}
+
+/// 字段的前后值（文本形式与同步冲突一致）
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedChange {
+    pub action: PlannedAction,
+    /// 本地 todo（远端新增时为空）
+    pub todo_id: Option<i32>,
+    pub uid: String,
+    pub summary: String,
+    pub href: Option<String>,
+    /// 有变化的字段：本地操作为本地的前后值，远端操作为远端的前后值
+    pub fields: Vec<FieldChange>,
+    /// 合并时将产生冲突的字段
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarSyncPlan {
+    pub calendar_id: String,
+    pub calendar_name: String,
+    pub changes: Vec<PlannedChange>,
+    /// 规划失败时的错误信息
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncPlan {
+    pub planned_at: String,
+    pub calendars: Vec<CalendarSyncPlan>,
+}
+
+/// 规划一个日历的同步
+pub async fn plan_calendar_sync(
+    db: &DatabaseConnection,
+    client: &CalDavClient,
+    calendar: &CalDavCalendarConfig,
+) -> Result<Vec<PlannedChange>> {
+    let now = Utc::now();
+    let calendar_url = client.calendar_url().as_str();
+    let SyncInputs {
+        mut by_href,
+        mut by_uid,
+        changes,
+        ..
+    } = load_sync_inputs(db, client, calendar).await?;
+
+    let mut planned = Vec::new();
+
+    // 拉取：与 synchronize_database 相同，先按 href 再按 UID 匹配本地 todo
+    for remote in &changes.changed {
+        let existing = if let Some(existing) = by_href.remove(&remote.href) {
+            by_uid.remove(&existing.uid);
+            Some(existing)
+        } else if let Some(existing) = by_uid.remove(&remote.item.uid) {
+            if let Some(href) = existing.remote_url.as_ref() {
+                by_href.remove(href);
+            }
+            Some(existing)
+        } else {
+            None
+        };
+
+        match existing {
+            Some(existing) => {
+                if let Some(change) = plan_remote_update(db, &existing, remote, now).await? {
+                    planned.push(change);
+                }
+            }
+            None => planned.push(PlannedChange {
+                action: PlannedAction::CreateLocal,
+                todo_id: None,
+                uid: remote.item.uid.clone(),
+                summary: remote.item.summary.clone(),
+                href: Some(remote.href.clone()),
+                fields: field_changes(None, Some(&remote.item)),
+                conflicts: Vec::new(),
+            }),
+        }
+    }
+
+    // 推送：只包含推送队列中到期的条目，有未解决冲突的 todo 不推送
+    let outbox_entries = outbox::entries_by_todo(db).await?;
+    let conflicted = conflicts::conflicted_todo_ids(db).await?;
+    let dirty_locals = entity::Entity::find()
+        .filter(entity::Column::Dirty.eq(true))
+        .all(db)
+        .await
+        .context("failed to load dirty todos")?;
+
+    for model in dirty_locals
+        .iter()
+        .filter(|model| belongs_to_calendar(model, calendar_url, calendar.is_default))
+    {
+        let operation = outbox::operation_for(model);
+        let due = outbox_entries
+            .get(&model.id)
+            .is_none_or(|entry| entry.operation != operation || outbox::is_due(entry, now));
+        if !due {
+            continue;
+        }
+
+        let base = model
+            .sync_snapshot
+            .as_deref()
+            .and_then(|snapshot| parse_ical_todo(snapshot).ok());
+
+        if model.deleted_at.is_some() {
+            planned.push(PlannedChange {
+                fields: field_changes(base.as_ref(), None),
+                ..local_change(model, PlannedAction::DeleteRemote)
+            });
+        } else if !conflicted.contains(&model.id) {
+            let local = local_item(db, model).await?;
+            let action = if model.remote_url.is_some() {
+                PlannedAction::UpdateRemote
+            } else {
+                PlannedAction::CreateRemote
+            };
+            planned.push(PlannedChange {
+                fields: field_changes(base.as_ref(), Some(&local)),
+                ..local_change(model, action)
+            });
+        }
+    }
+
+    // 远端已删除的本地 todo
+    for (href, model) in &by_href {
+        if model.deleted_at.is_none() && changes.removed.contains(href) {
+            let local = local_item(db, model).await?;
+            planned.push(PlannedChange {
+                fields: field_changes(Some(&local), None),
+                ..local_change(model, PlannedAction::DeleteLocal)
+            });
+        }
+    }
+
+    Ok(planned)
+}
+
+/// 与 update_local_from_remote 相同的判断：本地已删除或本地更新更晚（无快照）时不修改本地
+async fn plan_remote_update(
+    db: &DatabaseConnection,
+    existing: &entity::Model,
+    remote: &RemoteTodo,
+    now: DateTime<Utc>,
+) -> Result<Option<PlannedChange>> {
+    if existing.deleted_at.is_some() {
+        return Ok(None);
+    }
+
+    let local = local_item(db, existing).await?;
+
+    if existing.dirty {
+        if let Some(snapshot) = existing.sync_snapshot.as_deref() {
+            let base = parse_ical_todo(snapshot).with_context(|| {
+                format!("failed to parse sync snapshot of todo {}", existing.id)
+            })?;
+            let held_fields = conflicts::conflict_fields(db, existing.id).await?;
+            let outcome = merge::three_way_merge(&base, &local, &remote.item, &held_fields);
+
+            return Ok(Some(PlannedChange {
+                href: Some(remote.href.clone()),
+                fields: field_changes(Some(&local), Some(&outcome.item)),
+                conflicts: outcome
+                    .conflicts
+                    .iter()
+                    .map(|conflict| conflict.field.to_string())
+                    .collect(),
+                ..local_change(existing, PlannedAction::MergeLocal)
+            }));
+        }
+
+        if remote.item.last_modified.unwrap_or(now) <= existing.last_modified_at {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(PlannedChange {
+        href: Some(remote.href.clone()),
+        fields: field_changes(Some(&local), Some(&remote.item)),
+        ..local_change(existing, PlannedAction::UpdateLocal)
+    }))
+}
+
+fn local_change(model: &entity::Model, action: PlannedAction) -> PlannedChange {
+    PlannedChange {
+        action,
+        todo_id: Some(model.id),
+        uid: model.uid.clone(),
+        summary: model.title.clone(),
+        href: model.remote_url.clone(),
+        fields: Vec::new(),
+        conflicts: Vec::new(),
+    }
+}
+
+/// 本地 todo 的 CalDAV 表示（与推送内容一致）
+async fn local_item(db: &DatabaseConnection, model: &entity::Model) -> Result<CalDavItem> {
+    parse_ical_todo(&build_ical_from_model(db, model).await)
+        .with_context(|| format!("failed to build local version of todo {}", model.id))
+}
+
+/// 列出前后值不同的字段（不存在的一方视为全部为空）
+pub fn field_changes(before: Option<&CalDavItem>, after: Option<&CalDavItem>) -> Vec<FieldChange> {
+    merge::MERGE_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let before = before.and_then(|item| merge::field_value(item, field));
+            let after = after.and_then(|item| merge::field_value(item, field));
+            (before != after).then(|| FieldChange {
+                field: field.to_string(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(summary: &str) -> CalDavItem {
+        CalDavItem {
+            uid: "todo-1".to_string(),
+            summary: summary.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_field_changes_only_lists_changed_fields() {
+        let before = item("Old");
+        let mut after = item("New");
+        after.location = Some("Office".to_string());
+
+        let changes = field_changes(Some(&before), Some(&after));
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    field: merge::FIELD_SUMMARY.to_string(),
+                    before: Some("Old".to_string()),
+                    after: Some("New".to_string()),
+                },
+                FieldChange {
+                    field: merge::FIELD_LOCATION.to_string(),
+                    before: None,
+                    after: Some("Office".to_string()),
+                },
+            ]
+        );
+        assert!(field_changes(Some(&before), Some(&before)).is_empty());
+    }
+
+    #[test]
+    fn test_field_changes_for_deleted_item() {
+        let changes = field_changes(Some(&item("Gone")), None);
+        assert!(changes
+            .iter()
+            .all(|change| change.before.is_some() && change.after.is_none()));
+        assert!(changes
+            .iter()
+            .any(|change| change.field == merge::FIELD_SUMMARY));
+    }
+}
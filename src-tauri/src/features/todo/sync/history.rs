@@ -0,0 +1,300 @@
+// CalDAV 同步历史
+//
+// 每次同步结束后记录一条历史：触发原因、起止时间、各项计数、错误信息、各日历结果，
+// 以及逐条 todo 的操作（创建、更新、推送、删除），用于事后排查“任务不见了”等问题。
+// 被跳过的同步（未配置、未到间隔、正在同步）不记录。
+// 历史保留最近 RETENTION_DAYS 天、最多 MAX_ENTRIES 条，每次写入后清理
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+
+use super::dry_run::PlannedAction;
+use super::sync::{CalDavSyncEvent, CalendarSyncResult, SyncOutcome};
+use crate::features::todo::data::{entity, sync_history_entity};
+
+/// 历史保留天数
+const RETENTION_DAYS: i64 = 30;
+/// 历史最多保留条数
+const MAX_ENTRIES: usize = 500;
+/// 分页查询的默认和最大每页条数
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+const STATUS_SUCCESS: &str = "success";
+const STATUS_ERROR: &str = "error";
+
+/// 同步中对一个 todo 执行的操作
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SyncHistoryAction {
+    pub calendar_id: String,
+    pub action: PlannedAction,
+    /// 本地 todo（从远端新建时为空）
+    pub todo_id: Option<i32>,
+    pub uid: String,
+    pub summary: String,
+    /// 操作失败时的错误信息（推送失败已记录到推送队列）
+    pub error: Option<String>,
+}
+
+impl SyncHistoryAction {
+    pub fn for_todo(calendar_id: &str, action: PlannedAction, model: &entity::Model) -> Self {
+        Self {
+            calendar_id: calendar_id.to_string(),
+            action,
+            todo_id: Some(model.id),
+            uid: model.uid.clone(),
+            summary: model.title.clone(),
+            error: None,
+        }
+    }
+}
+
+/// 一次同步的历史记录
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncHistoryEntry {
+    pub id: i32,
+    pub reason: String,
+    /// success / error
+    pub status: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub created: i32,
+    pub updated: i32,
+    pub pushed: i32,
+    pub deleted: i32,
+    pub conflicts: i32,
+    pub failed: i32,
+    pub error: Option<String>,
+    pub calendars: serde_json::Value,
+    pub actions: Vec<SyncHistoryAction>,
+}
+
+impl SyncHistoryEntry {
+    fn from_model(model: sync_history_entity::Model) -> Self {
+        Self {
+            id: model.id,
+            reason: model.reason,
+            status: model.status,
+            started_at: model.started_at.to_rfc3339(),
+            finished_at: model.finished_at.to_rfc3339(),
+            created: model.created,
+            updated: model.updated,
+            pushed: model.pushed,
+            deleted: model.deleted,
+            conflicts: model.conflicts,
+            failed: model.failed,
+            error: model.error,
+            calendars: serde_json::from_str(&model.calendars).unwrap_or_default(),
+            actions: serde_json::from_str(&model.actions).unwrap_or_default(),
+        }
+    }
+}
+
+/// 分页查询结果（按开始时间倒序，page 从 0 开始）
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncHistoryPage {
+    pub entries: Vec<SyncHistoryEntry>,
+    pub page: u64,
+    pub page_size: u64,
+    pub total: u64,
+}
+
+/// 各日历成功同步的计数之和（失败的日历没有计数）
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Totals {
+    created: usize,
+    updated: usize,
+    pushed: usize,
+    deleted: usize,
+    conflicts: usize,
+    failed: usize,
+}
+
+fn totals(calendars: &[CalendarSyncResult]) -> Totals {
+    calendars
+        .iter()
+        .fold(Totals::default(), |mut totals, result| {
+            if let SyncOutcome::Success {
+                created,
+                updated,
+                pushed,
+                deleted,
+                conflicts,
+                failed,
+                ..
+            } = &result.outcome
+            {
+                totals.created += created;
+                totals.updated += updated;
+                totals.pushed += pushed;
+                totals.deleted += deleted;
+                totals.conflicts += conflicts;
+                totals.failed += failed;
+            }
+            totals
+        })
+}
+
+/// 记录一次同步，被跳过的同步不记录
+pub async fn record_run(
+    db: &DatabaseConnection,
+    event: &CalDavSyncEvent,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    actions: &[SyncHistoryAction],
+) -> Result<()> {
+    let (status, error) = match &event.outcome {
+        SyncOutcome::Success { .. } => (STATUS_SUCCESS, None),
+        SyncOutcome::Error { message } => (STATUS_ERROR, Some(message.clone())),
+        SyncOutcome::Skipped { .. } => return Ok(()),
+    };
+    let totals = totals(&event.calendars);
+    let calendars = serde_json::to_string(&event.calendars)
+        .context("failed to serialize CalDAV sync results")?;
+    let actions =
+        serde_json::to_string(actions).context("failed to serialize CalDAV sync actions")?;
+
+    sync_history_entity::ActiveModel {
+        id: NotSet,
+        reason: Set(event.reason.as_str().to_string()),
+        status: Set(status.to_string()),
+        started_at: Set(started_at),
+        finished_at: Set(finished_at),
+        created: Set(totals.created as i32),
+        updated: Set(totals.updated as i32),
+        pushed: Set(totals.pushed as i32),
+        deleted: Set(totals.deleted as i32),
+        conflicts: Set(totals.conflicts as i32),
+        failed: Set(totals.failed as i32),
+        error: Set(error),
+        calendars: Set(calendars),
+        actions: Set(actions),
+    }
+    .insert(db)
+    .await
+    .context("failed to record CalDAV sync history")?;
+
+    prune(db, finished_at).await
+}
+
+/// 删除超过保留期限或超出条数上限的历史
+async fn prune(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<()> {
+    sync_history_entity::Entity::delete_many()
+        .filter(sync_history_entity::Column::StartedAt.lt(now - Duration::days(RETENTION_DAYS)))
+        .exec(db)
+        .await
+        .context("failed to prune expired CalDAV sync history")?;
+
+    let ids: Vec<i32> = sync_history_entity::Entity::find()
+        .select_only()
+        .column(sync_history_entity::Column::Id)
+        .order_by_desc(sync_history_entity::Column::StartedAt)
+        .order_by_desc(sync_history_entity::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await
+        .context("failed to load CalDAV sync history")?;
+    let overflow: Vec<i32> = ids.into_iter().skip(MAX_ENTRIES).collect();
+    if !overflow.is_empty() {
+        sync_history_entity::Entity::delete_many()
+            .filter(sync_history_entity::Column::Id.is_in(overflow))
+            .exec(db)
+            .await
+            .context("failed to prune CalDAV sync history")?;
+    }
+
+    Ok(())
+}
+
+/// 分页查询同步历史（最新的在前）
+pub async fn list_runs(
+    db: &DatabaseConnection,
+    page: u64,
+    page_size: Option<u64>,
+) -> Result<SyncHistoryPage> {
+    let page_size = page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let paginator = sync_history_entity::Entity::find()
+        .order_by_desc(sync_history_entity::Column::StartedAt)
+        .order_by_desc(sync_history_entity::Column::Id)
+        .paginate(db, page_size);
+    let total = paginator
+        .num_items()
+        .await
+        .context("failed to count CalDAV sync history")?;
+    let entries = paginator
+        .fetch_page(page)
+        .await
+        .context("failed to load CalDAV sync history")?
+        .into_iter()
+        .map(SyncHistoryEntry::from_model)
+        .collect();
+
+    Ok(SyncHistoryPage {
+        entries,
+        page,
+        page_size,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(outcome: SyncOutcome) -> CalendarSyncResult {
+        CalendarSyncResult {
+            calendar_id: "cal".to_string(),
+            calendar_name: "Cal".to_string(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_totals_skip_failed_calendars() {
+        let calendars = vec![
+            result(SyncOutcome::Success {
+                synced_at: String::new(),
+                created: 2,
+                updated: 1,
+                pushed: 3,
+                deleted: 1,
+                conflicts: 0,
+                failed: 1,
+            }),
+            result(SyncOutcome::Error {
+                message: "timeout".to_string(),
+            }),
+            result(SyncOutcome::Success {
+                synced_at: String::new(),
+                created: 1,
+                updated: 0,
+                pushed: 0,
+                deleted: 2,
+                conflicts: 1,
+                failed: 0,
+            }),
+        ];
+
+        assert_eq!(
+            totals(&calendars),
+            Totals {
+                created: 3,
+                updated: 1,
+                pushed: 3,
+                deleted: 3,
+                conflicts: 1,
+                failed: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,359 @@
+// iCalendar (.ics) 文件导入导出
+//
+// 导出：每个 todo 使用与 CalDAV 推送相同的序列化（build_ical_from_model），合并为一个 VCALENDAR。
+// 导入：按 UID 拆分文件，逐个用 parse_ical_todo 解析；UID 已存在时按选项跳过、覆盖或另存副本。
+// 父子关系（RELATED-TO）在全部条目写入后再建立，父任务在文件中出现的先后不影响结果；
+// 另存副本时子任务指向父任务的副本
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::features::todo::core::{dependencies, fractional_index};
+use crate::features::todo::data::entity;
+
+use super::{
+    client::{parse_ical_todo, CalDavItem},
+    ical_patch,
+    sync::{apply_item_to_active, apply_remote_alarms, build_ical_from_model},
+};
+
+/// 导出范围
+#[derive(Debug, Clone, Deserialize)]
+pub struct IcsExportFilter {
+    /// 只导出这些 todo 及其子任务，为空时导出全部
+    #[serde(default)]
+    pub ids: Option<Vec<i32>>,
+    /// 是否包含已完成的 todo
+    #[serde(default = "default_include_completed")]
+    pub include_completed: bool,
+}
+
+impl Default for IcsExportFilter {
+    fn default() -> Self {
+        Self {
+            ids: None,
+            include_completed: default_include_completed(),
+        }
+    }
+}
+
+fn default_include_completed() -> bool {
+    true
+}
+
+/// 导入时 UID 已存在的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IcsImportMode {
+    /// 保留本地 todo
+    #[default]
+    Skip,
+    /// 用文件中的内容覆盖本地 todo
+    Overwrite,
+    /// 以新的 UID 另存为新 todo
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IcsImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// 解析或写入失败的条目的错误信息
+    pub errors: Vec<String>,
+}
+
+/// 导出 todo 为一个 VCALENDAR
+pub async fn export_ics(db: &DatabaseConnection, filter: &IcsExportFilter) -> Result<String> {
+    let todos = entity::Entity::find()
+        .filter(entity::Column::DeletedAt.is_null())
+        .all(db)
+        .await
+        .context("failed to load todos for export")?;
+
+    let selected = filter.ids.as_ref().map(|ids| with_descendants(&todos, ids));
+    let mut calendars = Vec::new();
+    for todo in &todos {
+        if selected.as_ref().is_some_and(|ids| !ids.contains(&todo.id)) {
+            continue;
+        }
+        if !filter.include_completed && todo.completed {
+            continue;
+        }
+        calendars.push(build_ical_from_model(db, todo).await);
+    }
+
+    Ok(ical_patch::merge_calendars(
+        calendars.iter().map(String::as_str),
+    ))
+}
+
+/// 选中的 todo 及其所有子孙
+fn with_descendants(todos: &[entity::Model], ids: &[i32]) -> HashSet<i32> {
+    let mut selected: HashSet<i32> = ids.iter().copied().collect();
+    loop {
+        let before = selected.len();
+        for todo in todos {
+            if todo
+                .parent_id
+                .is_some_and(|parent| selected.contains(&parent))
+            {
+                selected.insert(todo.id);
+            }
+        }
+        if selected.len() == before {
+            return selected;
+        }
+    }
+}
+
+/// 导入 .ics 文件中的 VTODO
+///
+/// 新建和覆盖的 todo 标记为 dirty，配置了 CalDAV 时随下次同步推送
+pub async fn import_ics(
+    db: &DatabaseConnection,
+    content: &str,
+    mode: IcsImportMode,
+) -> Result<IcsImportSummary> {
+    let calendars =
+        ical_patch::split_todos(content).ok_or_else(|| anyhow!("invalid iCalendar file"))?;
+    let now = Utc::now();
+    let mut summary = IcsImportSummary::default();
+
+    // 文件中的 UID → 本地 todo（跳过的条目也记录，其子任务仍挂到本地已有的父任务下，回收站中的除外）
+    let mut local_ids: HashMap<String, i32> = HashMap::new();
+    // 需要建立父子关系的 todo：(本地 id, 父任务 UID)
+    let mut pending_parents: Vec<(i32, Option<String>)> = Vec::new();
+    let mut created_ids: Vec<i32> = Vec::new();
+
+    for calendar in &calendars {
+        let item = match parse_ical_todo(calendar) {
+            Ok(item) => item,
+            Err(err) => {
+                summary.failed += 1;
+                summary.errors.push(format!("{err:#}"));
+                continue;
+            }
+        };
+        let existing = entity::Entity::find()
+            .filter(entity::Column::Uid.eq(&item.uid))
+            .one(db)
+            .await
+            .with_context(|| format!("failed to look up todo {}", item.uid))?;
+
+        // 父任务统一在最后建立，避免 apply_item_to_active 按数据库中的旧 UID 挂接
+        let mut detached = CalDavItem {
+            related_to: None,
+            ..item.clone()
+        };
+        let result = match (existing, mode) {
+            (Some(existing), IcsImportMode::Skip) => {
+                // 回收站中的 todo 不作为本次导入条目的父任务
+                if existing.deleted_at.is_none() {
+                    local_ids.insert(item.uid.clone(), existing.id);
+                }
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(existing), IcsImportMode::Overwrite) => {
+                overwrite_todo(db, existing, &detached, now).await
+            }
+            (Some(_), IcsImportMode::Duplicate) => {
+                detached.uid = Uuid::new_v4().to_string();
+                insert_todo(db, &detached, now).await
+            }
+            (None, _) => insert_todo(db, &detached, now).await,
+        };
+
+        match result {
+            Ok((id, is_new)) => {
+                if is_new {
+                    summary.created += 1;
+                    created_ids.push(id);
+                } else {
+                    summary.updated += 1;
+                }
+                local_ids.insert(item.uid.clone(), id);
+                pending_parents.push((id, item.related_to.clone()));
+            }
+            Err(err) => {
+                summary.failed += 1;
+                summary.errors.push(format!("{}: {err:#}", item.summary));
+            }
+        }
+    }
+
+    link_parents(db, &local_ids, &pending_parents).await?;
+    assign_order_indexes(db, &created_ids).await?;
+
+    Ok(summary)
+}
+
+/// 新建 todo，返回 (id, true)
+async fn insert_todo(
+    db: &DatabaseConnection,
+    item: &CalDavItem,
+    now: DateTime<Utc>,
+) -> Result<(i32, bool)> {
+    let mut active = entity::ActiveModel {
+        id: NotSet,
+        parent_id: Set(None),
+        ..Default::default()
+    };
+    apply_item_to_active(db, &mut active, item, now).await;
+    active.dirty = Set(true);
+    active.created_at = Set(now);
+
+    let inserted = active
+        .insert(db)
+        .await
+        .with_context(|| format!("failed to insert todo {}", item.uid))?;
+    apply_remote_alarms(db, inserted.id, item, false, true).await?;
+    dependencies::replace_uids(db, inserted.id, &item.depends_on).await?;
+
+    Ok((inserted.id, true))
+}
+
+/// 用导入内容覆盖本地 todo（已删除的会恢复），远端关联保持不变，返回 (id, false)
+async fn overwrite_todo(
+    db: &DatabaseConnection,
+    existing: entity::Model,
+    item: &CalDavItem,
+    now: DateTime<Utc>,
+) -> Result<(i32, bool)> {
+    let id = existing.id;
+    let mut active: entity::ActiveModel = existing.into();
+    apply_item_to_active(db, &mut active, item, now).await;
+    active.deleted_at = Set(None);
+    active.dirty = Set(true);
+    active.updated_at = Set(now);
+
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to overwrite todo {}", item.uid))?;
+    apply_remote_alarms(db, id, item, false, false).await?;
+    dependencies::replace_uids(db, id, &item.depends_on).await?;
+
+    Ok((id, false))
+}
+
+/// 按 RELATED-TO 建立父子关系：优先使用本次导入的条目，其次是本地已有的 todo
+async fn link_parents(
+    db: &DatabaseConnection,
+    local_ids: &HashMap<String, i32>,
+    pending: &[(i32, Option<String>)],
+) -> Result<()> {
+    let mut parents: HashMap<i32, i32> = HashMap::new();
+    for (id, parent_uid) in pending {
+        let Some(parent_uid) = parent_uid else {
+            continue;
+        };
+        let parent_id = match local_ids.get(parent_uid) {
+            Some(parent_id) => Some(*parent_id),
+            None => entity::Entity::find()
+                .filter(entity::Column::Uid.eq(parent_uid))
+                .filter(entity::Column::DeletedAt.is_null())
+                .one(db)
+                .await?
+                .map(|parent| parent.id),
+        };
+        match parent_id {
+            Some(parent_id) if parent_id != *id => {
+                parents.insert(*id, parent_id);
+            }
+            _ => println!(
+                "[ics] parent {parent_uid} not found for todo {id}, importing as top level"
+            ),
+        }
+    }
+
+    for (id, _) in pending {
+        let Some(&parent_id) = parents.get(id) else {
+            continue;
+        };
+        if creates_cycle(db, &parents, *id, parent_id).await? {
+            println!("[ics] ignoring cyclic RELATED-TO for todo {id}");
+            parents.remove(id);
+            continue;
+        }
+        entity::ActiveModel {
+            id: Set(*id),
+            parent_id: Set(Some(parent_id)),
+            ..Default::default()
+        }
+        .update(db)
+        .await
+        .with_context(|| format!("failed to set parent of todo {id}"))?;
+    }
+
+    Ok(())
+}
+
+/// 沿父任务链向上查找，判断把 parent_id 设为 id 的父任务是否会成环
+async fn creates_cycle(
+    db: &DatabaseConnection,
+    parents: &HashMap<i32, i32>,
+    id: i32,
+    parent_id: i32,
+) -> Result<bool> {
+    let mut visited = HashSet::from([id]);
+    let mut current = Some(parent_id);
+    while let Some(todo_id) = current {
+        if !visited.insert(todo_id) {
+            return Ok(true);
+        }
+        current = match parents.get(&todo_id) {
+            Some(parent) => Some(*parent),
+            None => entity::Entity::find_by_id(todo_id)
+                .one(db)
+                .await?
+                .and_then(|todo| todo.parent_id),
+        };
+    }
+    Ok(false)
+}
+
+/// 新建的 todo 依次追加到各自父任务下的末尾
+async fn assign_order_indexes(db: &DatabaseConnection, created_ids: &[i32]) -> Result<()> {
+    for id in created_ids {
+        let Some(todo) = entity::Entity::find_by_id(*id).one(db).await? else {
+            continue;
+        };
+        let mut query = entity::Entity::find().filter(entity::Column::DeletedAt.is_null());
+        query = match todo.parent_id {
+            Some(parent_id) => query.filter(entity::Column::ParentId.eq(parent_id)),
+            None => query.filter(entity::Column::ParentId.is_null()),
+        };
+        let max_order_index = query
+            .all(db)
+            .await?
+            .iter()
+            .filter(|sibling| sibling.id != todo.id)
+            .filter_map(|sibling| sibling.order_index)
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        entity::ActiveModel {
+            id: Set(todo.id),
+            order_index: Set(Some(fractional_index::generate_key_between(
+                max_order_index,
+                None,
+            ))),
+            ..Default::default()
+        }
+        .update(db)
+        .await
+        .with_context(|| format!("failed to set order of todo {}", todo.id))?;
+    }
+    Ok(())
+}
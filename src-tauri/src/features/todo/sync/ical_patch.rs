@@ -0,0 +1,623 @@
+// 推送时保留其他客户端写入的 iCalendar 内容
+//
+// build_ical_from_model 只生成 pet-focus 管理的属性。推送前以最近一次同步的 iCalendar
+// （sync_snapshot）为模板合并：
+// - VTODO 中 pet-focus 管理的属性替换为新生成的值，其余属性（X- 属性、ATTACH、ATTENDEE、
+//   GEO、URL、CLASS 等）原样保留
+// - 触发时间和动作未变的 VALARM 保留模板中的完整内容，其余 VALARM 按本地提醒重新生成
+// - 模板中的其他组件（重复实例的覆盖、其他时区等）原样保留
+// - 没有收件人（ATTENDEE）的 EMAIL 提醒不符合 RFC 5545，按 DISPLAY 写出
+//
+// .ics 文件导入导出也在这里按组件合并和拆分日历
+
+use super::client::parse_trigger_duration;
+
+/// VTODO 中由 pet-focus 生成的属性
+const OWNED_TODO_PROPERTIES: [&str; 15] = [
+    "UID",
+    "DTSTAMP",
+    "LAST-MODIFIED",
+    "SUMMARY",
+    "DESCRIPTION",
+    "STATUS",
+    "PERCENT-COMPLETE",
+    "PRIORITY",
+    "LOCATION",
+    "CATEGORIES",
+    "DUE",
+    "DTSTART",
+    "RRULE",
+    "COMPLETED",
+    "RELATED-TO",
+];
+
+/// 单行最大长度（RFC 5545 3.1，按字节计）
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Debug, Clone, Default)]
+struct Component {
+    name: String,
+    /// 展开折叠后的属性行
+    properties: Vec<String>,
+    components: Vec<Component>,
+}
+
+/// 以 `template` 为模板合并新生成的 iCalendar
+///
+/// 模板无法解析或不包含 VTODO 时返回 None，由调用方直接使用生成的内容
+pub fn patch_ical(template: &str, generated: &str) -> Option<String> {
+    let template = parse_calendar(template)?;
+    let generated = parse_calendar(generated)?;
+    let generated_todo = generated
+        .components
+        .iter()
+        .find(|component| component.name == "VTODO")?;
+    let master_index = template.components.iter().position(|component| {
+        component.name == "VTODO" && property(component, "RECURRENCE-ID").is_none()
+    })?;
+
+    let generated_tzids: Vec<&str> = generated
+        .components
+        .iter()
+        .filter(|component| component.name == "VTIMEZONE")
+        .filter_map(|component| property(component, "TZID"))
+        .map(property_value)
+        .collect();
+
+    let mut calendar = Component {
+        name: "VCALENDAR".to_string(),
+        properties: merge_properties(&generated.properties, &template.properties, |_| false),
+        components: generated
+            .components
+            .iter()
+            .filter(|component| component.name == "VTIMEZONE")
+            .cloned()
+            .collect(),
+    };
+
+    for (index, component) in template.components.iter().enumerate() {
+        if index == master_index {
+            calendar
+                .components
+                .push(patch_todo(component, generated_todo));
+            continue;
+        }
+
+        let replaced_timezone = component.name == "VTIMEZONE"
+            && property(component, "TZID")
+                .map(property_value)
+                .is_some_and(|tzid| generated_tzids.contains(&tzid));
+        if !replaced_timezone {
+            calendar.components.push(component.clone());
+        }
+    }
+
+    let mut output = String::new();
+    write_component(&mut output, &calendar);
+    Some(output)
+}
+
+/// 将没有 ATTENDEE 的 EMAIL 提醒改为 DISPLAY
+///
+/// RFC 5545 要求 EMAIL 提醒至少有一个收件人，本地创建的 EMAIL 提醒没有收件人；
+/// 从模板保留的、带收件人的 EMAIL 提醒不变。无法解析或无需修改时原样返回
+pub fn display_email_alarms_without_attendee(ics: String) -> String {
+    let Some(mut calendar) = parse_calendar(&ics) else {
+        return ics;
+    };
+
+    let mut changed = false;
+    for alarm in calendar
+        .components
+        .iter_mut()
+        .filter(|component| component.name == "VTODO")
+        .flat_map(|todo| todo.components.iter_mut())
+        .filter(|component| component.name == "VALARM")
+    {
+        let is_email = property(alarm, "ACTION")
+            .is_some_and(|line| property_value(line).trim().eq_ignore_ascii_case("EMAIL"));
+        if !is_email || property(alarm, "ATTENDEE").is_some() {
+            continue;
+        }
+
+        // DISPLAY 提醒不能带 SUMMARY（EMAIL 提醒的邮件主题）
+        alarm
+            .properties
+            .retain(|line| property_name(line) != "SUMMARY");
+        for line in &mut alarm.properties {
+            if property_name(line) == "ACTION" {
+                *line = "ACTION:DISPLAY".to_string();
+            }
+        }
+        changed = true;
+    }
+
+    if !changed {
+        return ics;
+    }
+    let mut output = String::new();
+    write_component(&mut output, &calendar);
+    output
+}
+
+/// 将多个 VCALENDAR 合并为一个
+///
+/// 日历属性取第一个可解析的日历，同一 TZID 的 VTIMEZONE 只保留一份并放在最前面；
+/// 无法解析的日历被跳过
+pub fn merge_calendars<'a>(calendars: impl IntoIterator<Item = &'a str>) -> String {
+    let mut merged = Component {
+        name: "VCALENDAR".to_string(),
+        ..Default::default()
+    };
+    let mut timezones: Vec<Component> = Vec::new();
+
+    for calendar in calendars.into_iter().filter_map(parse_calendar) {
+        if merged.properties.is_empty() {
+            merged.properties = calendar.properties;
+        }
+        for component in calendar.components {
+            if component.name != "VTIMEZONE" {
+                merged.components.push(component);
+                continue;
+            }
+            let tzid = property(&component, "TZID").map(property_value);
+            let known = timezones
+                .iter()
+                .any(|timezone| property(timezone, "TZID").map(property_value) == tzid);
+            if !known {
+                timezones.push(component);
+            }
+        }
+    }
+
+    if merged.properties.is_empty() {
+        merged.properties = vec![
+            "VERSION:2.0".to_string(),
+            "PRODID:-//pet-focus//EN".to_string(),
+        ];
+    }
+    timezones.append(&mut merged.components);
+    merged.components = timezones;
+
+    let mut output = String::new();
+    write_component(&mut output, &merged);
+    output
+}
+
+/// 按 UID 拆分日历中的 VTODO
+///
+/// 每个 UID 生成一个日历，包含该 UID 的所有 VTODO（主实例在前，之后是重复实例的覆盖）
+/// 和原日历中的全部 VTIMEZONE。没有 UID 的 VTODO 各自单独成为一个日历。
+/// 无法解析时返回 None
+pub fn split_todos(ics: &str) -> Option<Vec<String>> {
+    let calendar = parse_calendar(ics)?;
+    let timezones: Vec<&Component> = calendar
+        .components
+        .iter()
+        .filter(|component| component.name == "VTIMEZONE")
+        .collect();
+
+    let mut groups: Vec<(Option<&str>, Vec<&Component>)> = Vec::new();
+    for todo in calendar
+        .components
+        .iter()
+        .filter(|component| component.name == "VTODO")
+    {
+        let uid = property(todo, "UID").map(property_value);
+        let position = uid.and_then(|uid| {
+            groups
+                .iter()
+                .position(|(existing, _)| *existing == Some(uid))
+        });
+        match position {
+            Some(index) => groups[index].1.push(todo),
+            None => groups.push((uid, vec![todo])),
+        }
+    }
+
+    Some(
+        groups
+            .into_iter()
+            .map(|(_, mut todos)| {
+                // 主实例（没有 RECURRENCE-ID）排在前面，解析时取第一个 VTODO
+                todos.sort_by_key(|todo| property(todo, "RECURRENCE-ID").is_some());
+                let single = Component {
+                    name: calendar.name.clone(),
+                    properties: calendar.properties.clone(),
+                    components: timezones
+                        .iter()
+                        .chain(todos.iter())
+                        .map(|component| (*component).clone())
+                        .collect(),
+                };
+                let mut output = String::new();
+                write_component(&mut output, &single);
+                output
+            })
+            .collect(),
+    )
+}
+
+fn patch_todo(template: &Component, generated: &Component) -> Component {
+    let mut todo = Component {
+        name: template.name.clone(),
+        properties: merge_properties(&generated.properties, &template.properties, |line| {
+            is_owned_property(line)
+        }),
+        components: Vec::new(),
+    };
+
+    let mut template_alarms: Vec<Option<&Component>> = template
+        .components
+        .iter()
+        .filter(|component| component.name == "VALARM")
+        .map(Some)
+        .collect();
+
+    for alarm in generated
+        .components
+        .iter()
+        .filter(|component| component.name == "VALARM")
+    {
+        let key = alarm_key(alarm);
+        let matched = template_alarms
+            .iter_mut()
+            .find(|candidate| candidate.is_some_and(|candidate| alarm_key(candidate) == key))
+            .and_then(Option::take);
+        todo.components.push(matched.unwrap_or(alarm).clone());
+    }
+
+    todo.components.extend(
+        template
+            .components
+            .iter()
+            .filter(|component| component.name != "VALARM")
+            .cloned(),
+    );
+
+    todo
+}
+
+/// 生成的属性在前，模板中未被生成、且不属于 `owned` 的属性在后
+fn merge_properties(
+    generated: &[String],
+    template: &[String],
+    owned: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let generated_keys: Vec<String> = generated.iter().map(|line| property_key(line)).collect();
+
+    generated
+        .iter()
+        .cloned()
+        .chain(
+            template
+                .iter()
+                .filter(|line| !owned(line) && !generated_keys.contains(&property_key(line)))
+                .cloned(),
+        )
+        .collect()
+}
+
+/// RELATED-TO 只管理父任务和依赖关系，其他 RELTYPE（SIBLING、CHILD 等）保留
+fn is_owned_property(line: &str) -> bool {
+    let name = property_name(line);
+    if !OWNED_TODO_PROPERTIES.contains(&name.as_str()) {
+        return false;
+    }
+    if name == "RELATED-TO" {
+        return matches!(relation_type(line).as_str(), "PARENT" | "DEPENDS-ON");
+    }
+    true
+}
+
+/// 属性名；RELATED-TO 按关系类型区分
+fn property_key(line: &str) -> String {
+    let name = property_name(line);
+    if name == "RELATED-TO" {
+        format!("{name};{}", relation_type(line))
+    } else {
+        name
+    }
+}
+
+/// RELATED-TO 的 RELTYPE，缺省为 PARENT
+fn relation_type(line: &str) -> String {
+    property_param(line, "RELTYPE")
+        .unwrap_or("PARENT")
+        .to_ascii_uppercase()
+}
+
+/// 用于匹配 VALARM 的动作和触发时间（相对触发统一换算为分钟）
+///
+/// 缺省值与解析时一致：ACTION 按 DISPLAY，RELATED 按到期时间（END）
+fn alarm_key(alarm: &Component) -> Option<(String, String)> {
+    let action = property(alarm, "ACTION")
+        .map(|line| property_value(line).trim().to_ascii_uppercase())
+        .unwrap_or_else(|| "DISPLAY".to_string());
+    let trigger = property(alarm, "TRIGGER")?;
+    let value = property_value(trigger);
+
+    let is_absolute =
+        property_param(trigger, "VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE-TIME"));
+    let trigger = if is_absolute {
+        value.to_string()
+    } else {
+        let related = property_param(trigger, "RELATED")
+            .unwrap_or("END")
+            .to_ascii_uppercase();
+        format!("{}:{}", related, parse_trigger_duration(value)?)
+    };
+
+    Some((action, trigger))
+}
+
+fn property<'a>(component: &'a Component, name: &str) -> Option<&'a str> {
+    component
+        .properties
+        .iter()
+        .find(|line| property_name(line) == name)
+        .map(String::as_str)
+}
+
+/// 属性名到第一个未被引号包围的 `:` 之前为名称和参数
+fn split_property(line: &str) -> (&str, &str) {
+    let mut quoted = false;
+    for (index, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return (&line[..index], &line[index + 1..]),
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+fn property_name(line: &str) -> String {
+    let (head, _) = split_property(line);
+    head.split(';').next().unwrap_or(head).to_ascii_uppercase()
+}
+
+fn property_value(line: &str) -> &str {
+    split_property(line).1
+}
+
+fn property_param<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let (head, _) = split_property(line);
+    head.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.eq_ignore_ascii_case(name)
+            .then(|| value.trim_matches('"'))
+    })
+}
+
+/// 展开折叠行并解析组件结构，根组件必须是 VCALENDAR
+fn parse_calendar(ics: &str) -> Option<Component> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut root = None;
+
+    for line in unfold_lines(ics) {
+        let name = property_name(&line);
+        let value = property_value(&line).trim().to_ascii_uppercase();
+        match name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: value,
+                ..Default::default()
+            }),
+            "END" => {
+                let component = stack.pop()?;
+                if component.name != value {
+                    return None;
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => root = Some(component),
+                }
+            }
+            _ => stack.last_mut()?.properties.push(line),
+        }
+    }
+
+    root.filter(|root| stack.is_empty() && root.name == "VCALENDAR")
+}
+
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(continuation) = raw.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(continuation);
+                continue;
+            }
+        }
+        if !raw.trim().is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+fn write_component(output: &mut String, component: &Component) {
+    write_line(output, &format!("BEGIN:{}", component.name));
+    for line in &component.properties {
+        write_line(output, line);
+    }
+    for child in &component.components {
+        write_component(output, child);
+    }
+    write_line(output, &format!("END:{}", component.name));
+}
+
+/// 写入一行，超过 75 字节时折叠（不拆分 UTF-8 字符）
+fn write_line(output: &mut String, line: &str) {
+    let mut rest = line;
+    let mut limit = MAX_LINE_OCTETS;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        output.push_str(&rest[..split]);
+        output.push_str("\r\n ");
+        rest = &rest[split..];
+        // 续行开头的空格占一个字节
+        limit = MAX_LINE_OCTETS - 1;
+    }
+    output.push_str(rest);
+    output.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+PRODID:-//Mozilla.org/NONSGML Mozilla Calendar V1.1//EN\r\n\
+X-WR-CALNAME:Work\r\n\
+BEGIN:VTODO\r\n\
+UID:todo-1\r\n\
+SUMMARY:Old title\r\n\
+CLASS:PRIVATE\r\n\
+X-MOZ-GENERATION:3\r\n\
+ATTENDEE;CN=\"Doe: Jane\":mailto:jane@example.com\r\n\
+RELATED-TO;RELTYPE=SIBLING:todo-2\r\n\
+RELATED-TO:old-parent\r\n\
+RELATED-TO;RELTYPE=DEPENDS-ON:old-dependency\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT15M\r\n\
+ACTION:DISPLAY\r\n\
+DESCRIPTION:Custom text\r\n\
+X-MOZ-LASTACK:20240301T080000Z\r\n\
+END:VALARM\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT1H\r\n\
+ACTION:DISPLAY\r\n\
+END:VALARM\r\n\
+END:VTODO\r\n\
+END:VCALENDAR\r\n";
+
+    const GENERATED: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+PRODID:-//pet-focus//EN\r\n\
+BEGIN:VTODO\r\n\
+UID:todo-1\r\n\
+SUMMARY:New title\r\n\
+RELATED-TO:new-parent\r\n\
+RELATED-TO;RELTYPE=DEPENDS-ON:todo-3\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER;RELATED=END:-PT15M\r\n\
+ACTION:DISPLAY\r\n\
+DESCRIPTION:Reminder\r\n\
+END:VALARM\r\n\
+END:VTODO\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_owned_properties_are_replaced_and_unknown_kept() {
+        let patched = patch_ical(TEMPLATE, GENERATED).unwrap();
+
+        assert!(patched.contains("SUMMARY:New title\r\n"));
+        assert!(!patched.contains("Old title"));
+        assert!(patched.contains("PRODID:-//pet-focus//EN\r\n"));
+        assert!(patched.contains("X-WR-CALNAME:Work\r\n"));
+        assert!(patched.contains("CLASS:PRIVATE\r\n"));
+        assert!(patched.contains("X-MOZ-GENERATION:3\r\n"));
+        assert!(patched.contains("ATTENDEE;CN=\"Doe: Jane\":mailto:jane@example.com\r\n"));
+        assert!(patched.contains("RELATED-TO;RELTYPE=SIBLING:todo-2\r\n"));
+        assert!(patched.contains("RELATED-TO:new-parent\r\n"));
+        assert!(patched.contains("RELATED-TO;RELTYPE=DEPENDS-ON:todo-3\r\n"));
+        assert!(!patched.contains("old-parent"));
+        assert!(!patched.contains("old-dependency"));
+    }
+
+    #[test]
+    fn test_matching_alarm_keeps_details_and_removed_alarm_is_dropped() {
+        let patched = patch_ical(TEMPLATE, GENERATED).unwrap();
+
+        assert_eq!(patched.matches("BEGIN:VALARM").count(), 1);
+        assert!(patched.contains("DESCRIPTION:Custom text\r\n"));
+        assert!(patched.contains("X-MOZ-LASTACK:20240301T080000Z\r\n"));
+        assert!(!patched.contains("-PT1H"));
+    }
+
+    #[test]
+    fn test_email_alarm_without_attendee_is_written_as_display() {
+        let alarms = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VTODO\r\n\
+UID:todo-1\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT15M\r\n\
+ACTION:EMAIL\r\n\
+DESCRIPTION:Reminder\r\n\
+SUMMARY:Report\r\n\
+END:VALARM\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT1H\r\n\
+ACTION:EMAIL\r\n\
+DESCRIPTION:Reminder\r\n\
+SUMMARY:Report\r\n\
+ATTENDEE:mailto:jane@example.com\r\n\
+END:VALARM\r\n\
+END:VTODO\r\n\
+END:VCALENDAR\r\n";
+
+        let fixed = display_email_alarms_without_attendee(alarms.to_string());
+        assert_eq!(fixed.matches("ACTION:DISPLAY\r\n").count(), 1);
+        assert_eq!(fixed.matches("ACTION:EMAIL\r\n").count(), 1);
+        assert_eq!(fixed.matches("SUMMARY:Report\r\n").count(), 1);
+        assert!(fixed.contains("ATTENDEE:mailto:jane@example.com\r\n"));
+
+        // 无需修改时原样返回
+        assert_eq!(display_email_alarms_without_attendee(fixed.clone()), fixed);
+    }
+
+    #[test]
+    fn test_long_lines_are_unfolded_and_refolded() {
+        let value = "a".repeat(100);
+        let template = TEMPLATE.replace(
+            "CLASS:PRIVATE\r\n",
+            &format!("X-LONG:{}\r\n {}\r\n", &value[..50], &value[50..]),
+        );
+
+        let patched = patch_ical(&template, GENERATED).unwrap();
+        assert!(patched
+            .split("\r\n")
+            .all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(unfold_lines(&patched).contains(&format!("X-LONG:{value}")));
+    }
+
+    #[test]
+    fn test_merge_and_split_round_trip() {
+        let first = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//pet-focus//EN\r\n\
+BEGIN:VTIMEZONE\r\nTZID:Asia/Shanghai\r\nEND:VTIMEZONE\r\n\
+BEGIN:VTODO\r\nUID:a\r\nSUMMARY:A\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let second = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Other//EN\r\n\
+BEGIN:VTIMEZONE\r\nTZID:Asia/Shanghai\r\nEND:VTIMEZONE\r\n\
+BEGIN:VTODO\r\nUID:b\r\nRECURRENCE-ID:20250102T000000Z\r\nSUMMARY:B override\r\nEND:VTODO\r\n\
+BEGIN:VTODO\r\nUID:b\r\nSUMMARY:B\r\nEND:VTODO\r\n\
+BEGIN:VTODO\r\nSUMMARY:No UID\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+
+        let merged = merge_calendars([first, second, "not a calendar"]);
+        assert!(merged.starts_with(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//pet-focus//EN\r\nBEGIN:VTIMEZONE\r\n"
+        ));
+        assert_eq!(merged.matches("BEGIN:VTIMEZONE").count(), 1);
+        assert_eq!(merged.matches("BEGIN:VTODO").count(), 4);
+
+        let parts = split_todos(&merged).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|part| part.contains("TZID:Asia/Shanghai")));
+        let b = &parts[1];
+        assert!(b.find("SUMMARY:B\r\n").unwrap() < b.find("SUMMARY:B override").unwrap());
+        assert!(parts[2].contains("SUMMARY:No UID"));
+        assert!(split_todos("BEGIN:VTODO\r\nEND:VTODO\r\n").is_none());
+    }
+
+    #[test]
+    fn test_invalid_template_is_rejected() {
+        assert!(patch_ical("BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\n", GENERATED).is_none());
+        assert!(patch_ical("not an icalendar", GENERATED).is_none());
+    }
+}
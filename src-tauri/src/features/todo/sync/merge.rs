@@ -0,0 +1,295 @@
+// CalDAV 字段级三方合并
+//
+// 以最近一次同步的版本（快照）为基准，分别判断本地和远端修改了哪些字段：
+// - 只有一方修改的字段采用修改方的值
+// - 双方都修改且结果不同的字段记为冲突，暂时保留本地值，等待用户处理
+// 所有版本都先解析为 CalDavItem 再比较，本地版本由本地 todo 生成的 iCalendar 解析得到
+
+use super::client::{AlarmTrigger, CalDavItem};
+
+pub const FIELD_SUMMARY: &str = "summary";
+pub const FIELD_DESCRIPTION: &str = "description";
+pub const FIELD_STATUS: &str = "status";
+pub const FIELD_PRIORITY: &str = "priority";
+pub const FIELD_LOCATION: &str = "location";
+pub const FIELD_CATEGORIES: &str = "categories";
+pub const FIELD_START: &str = "start";
+pub const FIELD_DUE: &str = "due";
+pub const FIELD_TIMEZONE: &str = "timezone";
+pub const FIELD_RECURRENCE_RULE: &str = "recurrence_rule";
+pub const FIELD_RELATED_TO: &str = "related_to";
+pub const FIELD_DEPENDS_ON: &str = "depends_on";
+pub const FIELD_ALARMS: &str = "alarms";
+
+/// 参与合并的字段
+pub const MERGE_FIELDS: [&str; 13] = [
+    FIELD_SUMMARY,
+    FIELD_DESCRIPTION,
+    FIELD_STATUS,
+    FIELD_PRIORITY,
+    FIELD_LOCATION,
+    FIELD_CATEGORIES,
+    FIELD_START,
+    FIELD_DUE,
+    FIELD_TIMEZONE,
+    FIELD_RECURRENCE_RULE,
+    FIELD_RELATED_TO,
+    FIELD_DEPENDS_ON,
+    FIELD_ALARMS,
+];
+
+/// 双方都修改了的字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldConflict {
+    pub field: &'static str,
+    pub base: Option<String>,
+    pub local: Option<String>,
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    /// 合并结果（冲突字段保留本地值）
+    pub item: CalDavItem,
+    /// 合并结果中是否包含需要推送到远端的本地修改
+    pub local_changes: bool,
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// 三方合并
+///
+/// `held_fields` 为尚未解决的冲突字段：只要本地和远端的值仍不相同，就继续作为冲突保留
+pub fn three_way_merge(
+    base: &CalDavItem,
+    local: &CalDavItem,
+    remote: &CalDavItem,
+    held_fields: &[String],
+) -> MergeOutcome {
+    let mut merged = remote.clone();
+    let mut local_changes = false;
+    let mut conflicts = Vec::new();
+
+    for field in MERGE_FIELDS {
+        let base_value = field_value(base, field);
+        let local_value = field_value(local, field);
+        let remote_value = field_value(remote, field);
+
+        let local_changed = local_value != base_value && !is_implicit_local_value(base, field);
+        let remote_changed = remote_value != base_value;
+        let held = held_fields.iter().any(|held| held == field);
+
+        if local_value != remote_value && (held || (local_changed && remote_changed)) {
+            copy_field(&mut merged, local, field);
+            local_changes = true;
+            conflicts.push(FieldConflict {
+                field,
+                base: base_value,
+                local: local_value,
+                remote: remote_value,
+            });
+        } else if local_changed && !remote_changed {
+            copy_field(&mut merged, local, field);
+            local_changes = true;
+        }
+    }
+
+    merged.last_modified = local.last_modified.max(remote.last_modified);
+
+    MergeOutcome {
+        item: merged,
+        local_changes,
+        conflicts,
+    }
+}
+
+/// 本地总会生成、而基准版本中缺失的字段（DTSTART、时区、VALARM）
+///
+/// 远端没有这些属性时本地会补上默认值，不应视为本地修改
+fn is_implicit_local_value(base: &CalDavItem, field: &str) -> bool {
+    match field {
+        FIELD_START => base.start.is_none(),
+        FIELD_TIMEZONE => base.timezone.is_none(),
+        FIELD_ALARMS => base.alarms.is_empty(),
+        _ => false,
+    }
+}
+
+/// 字段的可比较文本形式（也用于展示冲突）
+pub fn field_value(item: &CalDavItem, field: &str) -> Option<String> {
+    match field {
+        FIELD_SUMMARY => Some(item.summary.clone()),
+        FIELD_DESCRIPTION => non_empty(item.description.as_deref()),
+        FIELD_STATUS => Some(if item.is_completed() {
+            "COMPLETED".to_string()
+        } else {
+            item.status
+                .clone()
+                .unwrap_or_else(|| "NEEDS-ACTION".to_string())
+        }),
+        // PRIORITY:0 表示未定义
+        FIELD_PRIORITY => item
+            .priority
+            .filter(|priority| *priority > 0)
+            .map(|priority| priority.to_string()),
+        FIELD_LOCATION => non_empty(item.location.as_deref()),
+        FIELD_CATEGORIES => non_empty(Some(item.categories.join(",")).as_deref()),
+        FIELD_START => item.start.map(|start| start.to_rfc3339()),
+        FIELD_DUE => item.due.map(|due| due.to_rfc3339()),
+        FIELD_TIMEZONE => non_empty(item.timezone.as_deref()),
+        FIELD_RECURRENCE_RULE => non_empty(item.recurrence_rule.as_deref()),
+        FIELD_RELATED_TO => non_empty(item.related_to.as_deref()),
+        FIELD_DEPENDS_ON => {
+            let mut uids = item.depends_on.clone();
+            uids.sort();
+            non_empty(Some(uids.join(",")).as_deref())
+        }
+        FIELD_ALARMS => {
+            let mut alarms: Vec<String> = item
+                .alarms
+                .iter()
+                .map(|alarm| {
+                    let trigger = match &alarm.trigger {
+                        AlarmTrigger::Relative {
+                            minutes_before,
+                            related_start,
+                        } => format!(
+                            "{}m before {}",
+                            minutes_before,
+                            if *related_start { "START" } else { "END" }
+                        ),
+                        AlarmTrigger::Absolute(at) => at.to_rfc3339(),
+                    };
+                    format!("{} {}", alarm.action, trigger)
+                })
+                .collect();
+            alarms.sort();
+            non_empty(Some(alarms.join("; ")).as_deref())
+        }
+        _ => None,
+    }
+}
+
+/// 将 `source` 的某个字段复制到 `target`
+pub fn copy_field(target: &mut CalDavItem, source: &CalDavItem, field: &str) {
+    match field {
+        FIELD_SUMMARY => target.summary = source.summary.clone(),
+        FIELD_DESCRIPTION => target.description = source.description.clone(),
+        FIELD_STATUS => {
+            target.status = source.status.clone();
+            target.percent_complete = source.percent_complete;
+            target.completed_at = source.completed_at;
+        }
+        FIELD_PRIORITY => target.priority = source.priority,
+        FIELD_LOCATION => target.location = source.location.clone(),
+        FIELD_CATEGORIES => target.categories = source.categories.clone(),
+        FIELD_START => target.start = source.start,
+        FIELD_DUE => target.due = source.due,
+        FIELD_TIMEZONE => target.timezone = source.timezone.clone(),
+        FIELD_RECURRENCE_RULE => target.recurrence_rule = source.recurrence_rule.clone(),
+        FIELD_RELATED_TO => target.related_to = source.related_to.clone(),
+        FIELD_DEPENDS_ON => target.depends_on = source.depends_on.clone(),
+        FIELD_ALARMS => {
+            target.alarms = source.alarms.clone();
+            target.reminder_minutes = source.reminder_minutes;
+        }
+        _ => {}
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn base_item() -> CalDavItem {
+        CalDavItem {
+            uid: "todo-1".to_string(),
+            summary: "Write report".to_string(),
+            status: Some("NEEDS-ACTION".to_string()),
+            start: Some(utc("2024-03-01T09:00:00Z")),
+            due: Some(utc("2024-03-05T09:00:00Z")),
+            last_modified: Some(utc("2024-03-01T09:00:00Z")),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_non_overlapping_edits_are_combined() {
+        let base = base_item();
+        let mut local = base.clone();
+        local.due = Some(utc("2024-03-06T09:00:00Z"));
+        let mut remote = base.clone();
+        remote.summary = "Write final report".to_string();
+
+        let outcome = three_way_merge(&base, &local, &remote, &[]);
+        assert!(outcome.conflicts.is_empty());
+        assert!(outcome.local_changes);
+        assert_eq!(outcome.item.summary, "Write final report");
+        assert_eq!(outcome.item.due, Some(utc("2024-03-06T09:00:00Z")));
+    }
+
+    #[test]
+    fn test_same_field_edits_conflict_and_keep_local() {
+        let base = base_item();
+        let mut local = base.clone();
+        local.summary = "Local title".to_string();
+        let mut remote = base.clone();
+        remote.summary = "Remote title".to_string();
+
+        let outcome = three_way_merge(&base, &local, &remote, &[]);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].field, FIELD_SUMMARY);
+        assert_eq!(outcome.conflicts[0].remote.as_deref(), Some("Remote title"));
+        assert_eq!(outcome.item.summary, "Local title");
+    }
+
+    #[test]
+    fn test_identical_edits_do_not_conflict() {
+        let base = base_item();
+        let mut local = base.clone();
+        local.status = Some("COMPLETED".to_string());
+        let remote = local.clone();
+
+        let outcome = three_way_merge(&base, &local, &remote, &[]);
+        assert!(outcome.conflicts.is_empty());
+        assert!(!outcome.local_changes);
+    }
+
+    #[test]
+    fn test_implicit_local_start_is_not_a_change() {
+        let mut base = base_item();
+        base.start = None;
+        let local = base_item();
+        let mut remote = base.clone();
+        remote.start = Some(utc("2024-03-02T09:00:00Z"));
+
+        let outcome = three_way_merge(&base, &local, &remote, &[]);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.item.start, Some(utc("2024-03-02T09:00:00Z")));
+    }
+
+    #[test]
+    fn test_held_field_stays_in_conflict() {
+        let base = base_item();
+        let mut local = base.clone();
+        local.summary = "Local title".to_string();
+
+        // 远端版本已成为新的基准，字段仍有未解决的冲突
+        let outcome = three_way_merge(&base, &local, &base, &[FIELD_SUMMARY.to_string()]);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.item.summary, "Local title");
+    }
+}
@@ -1,7 +1,17 @@
 pub mod caldav_commands;
 pub mod client;
 pub mod config;
+pub mod conflicts;
+pub mod dry_run;
+pub mod history;
+pub mod ical_file;
+pub mod ical_patch;
+pub mod merge;
+pub mod oauth;
+pub mod outbox;
 pub mod sync;
+#[cfg(all(test, not(any(target_os = "android", target_os = "ios"))))]
+mod test_server;
 
-pub use config::{CalDavConfig, CalDavConfigService};
+pub use config::{CalDavAuth, CalDavCalendarConfig, CalDavConfig, CalDavConfigService};
 pub use sync::{CalDavSyncEvent, CalDavSyncManager};
@@ -0,0 +1,214 @@
+// CalDAV OAuth2 认证
+//
+// 使用 refresh token 向配置的令牌端点换取新的 access token（RFC 6749 第 6 节）。
+// 令牌端点未返回新的 refresh token 时沿用原来的
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::config::OAuth2Credentials;
+
+/// 距过期不足该时间（秒）时提前刷新
+const EXPIRY_MARGIN_SECONDS: i64 = 60;
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+/// 发送请求前是否需要刷新：没有 access token，或即将过期且有 refresh token
+pub fn needs_refresh(credentials: &OAuth2Credentials, now: DateTime<Utc>) -> bool {
+    let has_access_token = credentials
+        .access_token
+        .as_deref()
+        .is_some_and(|token| !token.is_empty());
+    if !has_access_token {
+        return true;
+    }
+
+    credentials.refresh_token.is_some()
+        && credentials
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now + Duration::seconds(EXPIRY_MARGIN_SECONDS))
+}
+
+/// 使用 refresh token 获取新的 access token，返回更新后的凭据
+pub async fn refresh_access_token(
+    http: &Client,
+    credentials: &OAuth2Credentials,
+) -> Result<OAuth2Credentials> {
+    let refresh_token = credentials
+        .refresh_token
+        .as_deref()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| anyhow!("OAuth2 access token expired and no refresh token is configured"))?;
+
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", credentials.client_id.as_str()),
+    ];
+    if let Some(secret) = credentials.client_secret.as_deref() {
+        form.push(("client_secret", secret));
+    }
+
+    let response = http
+        .post(&credentials.token_endpoint)
+        .form(&form)
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "failed to reach OAuth2 token endpoint {}",
+                credentials.token_endpoint
+            )
+        })?;
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(anyhow!("OAuth2 token refresh failed: {status} {text}"));
+    }
+
+    let token: TokenResponse =
+        serde_json::from_str(&text).context("failed to parse OAuth2 token response")?;
+
+    Ok(apply_token_response(credentials, token, Utc::now()))
+}
+
+fn apply_token_response(
+    credentials: &OAuth2Credentials,
+    token: TokenResponse,
+    now: DateTime<Utc>,
+) -> OAuth2Credentials {
+    OAuth2Credentials {
+        access_token: Some(token.access_token),
+        refresh_token: token
+            .refresh_token
+            .or_else(|| credentials.refresh_token.clone()),
+        expires_at: token
+            .expires_in
+            .map(|seconds| now + Duration::seconds(seconds)),
+        ..credentials.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn credentials(token_endpoint: String) -> OAuth2Credentials {
+        OAuth2Credentials {
+            token_endpoint,
+            client_id: "pet-focus".to_string(),
+            client_secret: None,
+            access_token: Some("old-access".to_string()),
+            refresh_token: Some("refresh-1".to_string()),
+            expires_at: None,
+        }
+    }
+
+    /// 只处理一个请求的令牌端点，返回收到的请求正文
+    async fn mock_token_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/token", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, form)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if form.len() >= length || read == 0 {
+                        break;
+                    }
+                }
+            }
+
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+
+            let text = String::from_utf8_lossy(&request).to_string();
+            text.split_once("\r\n\r\n")
+                .map(|(_, form)| form.to_string())
+                .unwrap_or_default()
+        });
+
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = Utc::now();
+        let mut creds = credentials(String::new());
+        assert!(!needs_refresh(&creds, now));
+
+        creds.expires_at = Some(now + Duration::seconds(30));
+        assert!(needs_refresh(&creds, now));
+
+        creds.expires_at = Some(now + Duration::hours(1));
+        assert!(!needs_refresh(&creds, now));
+
+        creds.access_token = None;
+        assert!(needs_refresh(&creds, now));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_against_mock_token_server() {
+        let (endpoint, server) = mock_token_server(
+            "200 OK",
+            r#"{"access_token":"new-access","token_type":"Bearer","expires_in":3600}"#,
+        )
+        .await;
+
+        let refreshed = refresh_access_token(&Client::new(), &credentials(endpoint))
+            .await
+            .unwrap();
+        let form = server.await.unwrap();
+
+        assert!(form.contains("grant_type=refresh_token"));
+        assert!(form.contains("refresh_token=refresh-1"));
+        assert_eq!(refreshed.access_token.as_deref(), Some("new-access"));
+        // 未返回新的 refresh token 时沿用原来的
+        assert_eq!(refreshed.refresh_token.as_deref(), Some("refresh-1"));
+        assert!(refreshed.expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejected_by_token_server() {
+        let (endpoint, server) =
+            mock_token_server("400 Bad Request", r#"{"error":"invalid_grant"}"#).await;
+
+        let err = refresh_access_token(&Client::new(), &credentials(endpoint))
+            .await
+            .unwrap_err();
+        server.await.unwrap();
+
+        assert!(err.to_string().contains("invalid_grant"));
+    }
+}
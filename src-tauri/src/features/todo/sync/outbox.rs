@@ -0,0 +1,510 @@
+// CalDAV 推送队列（outbox）
+//
+// 每次同步前根据 dirty 标记整理队列：每个 dirty 的 todo 对应一条 create / update / delete 记录。
+// 推送失败不再中断整次同步，而是记录到对应条目：
+// - 临时失败（网络、服务器错误等）按指数退避安排下次重试
+// - 永久失败（412、403、404）标记为 failed，不再自动重试，由用户选择重试或放弃
+//
+// todo 移动到其他日历时，条目同时记录原日历上的资源：目标日历上创建成功后，
+// 原日历同步时带 If-Match 删除该资源，删除完成前条目一直保留
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::StatusCode;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+
+use super::client::CalDavHttpError;
+use crate::features::todo::core::models::CalDavOutboxItem;
+use crate::features::todo::data::{entity, outbox_entity};
+
+pub const OPERATION_CREATE: &str = "create";
+pub const OPERATION_UPDATE: &str = "update";
+pub const OPERATION_DELETE: &str = "delete";
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_FAILED: &str = "failed";
+
+/// 首次重试间隔（分钟），之后每次翻倍
+const BACKOFF_BASE_MINUTES: i64 = 1;
+/// 重试间隔上限（分钟）
+const BACKOFF_MAX_MINUTES: i64 = 6 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// 稍后重试可能成功
+    Transient,
+    /// 重试无法成功，需要用户处理
+    Permanent,
+}
+
+/// 根据 HTTP 状态判断失败类型：412（版本冲突）、403（无权限）、404（资源不存在）为永久失败，
+/// 其他状态码和没有响应的网络错误为临时失败
+pub fn classify_failure(err: &anyhow::Error) -> FailureKind {
+    match CalDavHttpError::status_of(err) {
+        Some(StatusCode::PRECONDITION_FAILED | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND) => {
+            FailureKind::Permanent
+        }
+        _ => FailureKind::Transient,
+    }
+}
+
+/// 第 `attempts` 次失败后的重试间隔
+pub fn backoff_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let minutes = BACKOFF_BASE_MINUTES.saturating_mul(1 << exponent);
+    Duration::minutes(minutes.min(BACKOFF_MAX_MINUTES))
+}
+
+/// dirty todo 对应的推送操作
+pub fn operation_for(model: &entity::Model) -> &'static str {
+    if model.deleted_at.is_some() {
+        OPERATION_DELETE
+    } else if model.remote_url.is_some() {
+        OPERATION_UPDATE
+    } else {
+        OPERATION_CREATE
+    }
+}
+
+/// 根据 dirty 标记整理队列
+///
+/// 为新的 dirty todo 添加条目，已不再 dirty 的条目删除。
+/// 操作类型变化、或失败后 todo 又被修改过的条目重新开始计数
+pub async fn reconcile(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<()> {
+    let dirty = entity::Entity::find()
+        .filter(entity::Column::Dirty.eq(true))
+        .all(db)
+        .await
+        .context("failed to load dirty todos")?;
+    let dirty_ids: HashSet<i32> = dirty.iter().map(|model| model.id).collect();
+
+    let mut entries: HashMap<i32, outbox_entity::Model> = HashMap::new();
+    for entry in outbox_entity::Entity::find()
+        .all(db)
+        .await
+        .context("failed to load CalDAV outbox")?
+    {
+        if dirty_ids.contains(&entry.todo_id) || entry.source_href.is_some() {
+            entries.insert(entry.todo_id, entry);
+        } else {
+            outbox_entity::Entity::delete_by_id(entry.id)
+                .exec(db)
+                .await
+                .with_context(|| format!("failed to remove outbox entry {}", entry.id))?;
+        }
+    }
+
+    for model in &dirty {
+        let operation = operation_for(model);
+        match entries.remove(&model.id) {
+            Some(entry)
+                if entry.operation == operation
+                    && !(entry.status == STATUS_FAILED && model.updated_at > entry.updated_at) => {}
+            Some(entry) => {
+                let mut active: outbox_entity::ActiveModel = entry.into();
+                active.operation = Set(operation.to_string());
+                active.status = Set(STATUS_PENDING.to_string());
+                active.attempts = Set(0);
+                active.next_attempt_at = Set(now);
+                active.last_error = Set(None);
+                active.updated_at = Set(now);
+                active.update(db).await.with_context(|| {
+                    format!("failed to update outbox entry for todo {}", model.id)
+                })?;
+            }
+            None => {
+                outbox_entity::ActiveModel {
+                    id: NotSet,
+                    todo_id: Set(model.id),
+                    operation: Set(operation.to_string()),
+                    status: Set(STATUS_PENDING.to_string()),
+                    attempts: Set(0),
+                    next_attempt_at: Set(now),
+                    last_error: Set(None),
+                    source_calendar_url: Set(None),
+                    source_href: Set(None),
+                    source_etag: Set(None),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(db)
+                .await
+                .with_context(|| format!("failed to enqueue todo {} for CalDAV push", model.id))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 当前队列，按 todo_id 索引
+pub async fn entries_by_todo(
+    db: &DatabaseConnection,
+) -> Result<HashMap<i32, outbox_entity::Model>> {
+    Ok(outbox_entity::Entity::find()
+        .all(db)
+        .await
+        .context("failed to load CalDAV outbox")?
+        .into_iter()
+        .map(|entry| (entry.todo_id, entry))
+        .collect())
+}
+
+/// 条目当前是否可以推送（未永久失败且已到重试时间）
+pub fn is_due(entry: &outbox_entity::Model, now: DateTime<Utc>) -> bool {
+    entry.status == STATUS_PENDING && entry.next_attempt_at <= now
+}
+
+/// 推送成功，移除条目（还需删除原日历上的资源时保留条目并重置重试状态）
+pub async fn record_success(
+    db: &DatabaseConnection,
+    entry: outbox_entity::Model,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if entry.source_href.is_none() {
+        return remove_entry(db, entry.id).await;
+    }
+    reset_entry(db, entry, now).await
+}
+
+/// 记录 todo 移动到其他日历，返回移动后 todo 应关联的远端资源（href, etag）
+///
+/// 原日历上的资源（`track_source` 为 false 时不记录）在目标日历上创建成功后删除；
+/// 同步前多次移动时保留最初的原日历资源，移回该日历时直接继续使用该资源
+pub async fn record_move(
+    db: &DatabaseConnection,
+    model: &entity::Model,
+    target_calendar_url: &str,
+    track_source: bool,
+    now: DateTime<Utc>,
+) -> Result<Option<(String, Option<String>)>> {
+    let existing = outbox_entity::Entity::find()
+        .filter(outbox_entity::Column::TodoId.eq(model.id))
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load outbox entry of todo {}", model.id))?;
+
+    let pending = existing.as_ref().and_then(|entry| {
+        Some((
+            entry.source_calendar_url.clone()?,
+            entry.source_href.clone()?,
+            entry.source_etag.clone(),
+        ))
+    });
+    let current = match (&model.remote_calendar_url, &model.remote_url) {
+        (Some(url), Some(href)) if track_source => {
+            Some((url.clone(), href.clone(), model.remote_etag.clone()))
+        }
+        _ => None,
+    };
+    let mut source = pending.or(current);
+    let restored = match &source {
+        Some((url, href, etag)) if url == target_calendar_url => {
+            let restored = (href.clone(), etag.clone());
+            source = None;
+            Some(restored)
+        }
+        _ => None,
+    };
+    let operation = if restored.is_some() {
+        OPERATION_UPDATE
+    } else {
+        OPERATION_CREATE
+    };
+    let (source_calendar_url, source_href, source_etag) = match source {
+        Some((url, href, etag)) => (Some(url), Some(href), etag),
+        None => (None, None, None),
+    };
+
+    let mut active = match existing {
+        Some(entry) => entry.into(),
+        None => outbox_entity::ActiveModel {
+            id: NotSet,
+            todo_id: Set(model.id),
+            created_at: Set(now),
+            ..Default::default()
+        },
+    };
+    active.operation = Set(operation.to_string());
+    active.status = Set(STATUS_PENDING.to_string());
+    active.attempts = Set(0);
+    active.next_attempt_at = Set(now);
+    active.last_error = Set(None);
+    active.source_calendar_url = Set(source_calendar_url);
+    active.source_href = Set(source_href);
+    active.source_etag = Set(source_etag);
+    active.updated_at = Set(now);
+    active
+        .save(db)
+        .await
+        .with_context(|| format!("failed to record move of todo {}", model.id))?;
+
+    Ok(restored)
+}
+
+/// 等待删除的原日历资源位于该日历上的条目
+pub async fn source_removals(
+    db: &DatabaseConnection,
+    calendar_url: &str,
+) -> Result<Vec<outbox_entity::Model>> {
+    outbox_entity::Entity::find()
+        .filter(outbox_entity::Column::SourceCalendarUrl.eq(calendar_url))
+        .filter(outbox_entity::Column::SourceHref.is_not_null())
+        .order_by_asc(outbox_entity::Column::Id)
+        .all(db)
+        .await
+        .context("failed to load pending CalDAV source removals")
+}
+
+/// 原日历上的资源已删除：todo 仍有待推送的修改时保留条目，否则移除
+pub async fn record_source_removed(
+    db: &DatabaseConnection,
+    entry: outbox_entity::Model,
+    todo_dirty: bool,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if !todo_dirty {
+        return remove_entry(db, entry.id).await;
+    }
+
+    let entry_id = entry.id;
+    let mut active: outbox_entity::ActiveModel = entry.into();
+    active.source_calendar_url = Set(None);
+    active.source_href = Set(None);
+    active.source_etag = Set(None);
+    active.updated_at = Set(now);
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to update outbox entry {entry_id}"))?;
+    Ok(())
+}
+
+/// 推送失败，记录错误并安排重试（永久失败时不再自动重试）
+pub async fn record_failure(
+    db: &DatabaseConnection,
+    entry: outbox_entity::Model,
+    err: &anyhow::Error,
+    now: DateTime<Utc>,
+) -> Result<FailureKind> {
+    let kind = classify_failure(err);
+    let attempts = entry.attempts + 1;
+    let entry_id = entry.id;
+
+    let mut active: outbox_entity::ActiveModel = entry.into();
+    active.attempts = Set(attempts);
+    active.last_error = Set(Some(format!("{err:#}")));
+    active.updated_at = Set(now);
+    match kind {
+        FailureKind::Transient => {
+            active.next_attempt_at = Set(now + backoff_delay(attempts));
+        }
+        FailureKind::Permanent => {
+            active.status = Set(STATUS_FAILED.to_string());
+        }
+    }
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to record failure of outbox entry {entry_id}"))?;
+
+    Ok(kind)
+}
+
+/// 获取队列中的全部条目（含 todo 标题）
+pub async fn list_entries(db: &DatabaseConnection) -> Result<Vec<CalDavOutboxItem>> {
+    let entries = outbox_entity::Entity::find()
+        .order_by_asc(outbox_entity::Column::CreatedAt)
+        .order_by_asc(outbox_entity::Column::Id)
+        .all(db)
+        .await
+        .context("failed to load CalDAV outbox")?;
+
+    let todo_ids: Vec<i32> = entries.iter().map(|entry| entry.todo_id).collect();
+    let titles: HashMap<i32, String> = entity::Entity::find()
+        .filter(entity::Column::Id.is_in(todo_ids))
+        .all(db)
+        .await
+        .context("failed to load todos of CalDAV outbox")?
+        .into_iter()
+        .map(|todo| (todo.id, todo.title))
+        .collect();
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let title = titles.get(&entry.todo_id).cloned();
+            CalDavOutboxItem::from_model(entry, title)
+        })
+        .collect())
+}
+
+/// 重试条目：重置失败状态和重试计数，下次同步时立即推送
+pub async fn retry_entry(db: &DatabaseConnection, entry_id: i32) -> Result<()> {
+    let entry = find_entry(db, entry_id).await?;
+    reset_entry(db, entry, Utc::now()).await
+}
+
+/// 放弃条目：不再推送该 todo 的本地修改
+///
+/// 清除 dirty 标记，本地内容保留；delete 不再删除远端资源，本地 todo 仍留在回收站中；
+/// 移动到其他日历后未删除的原日历资源也保留，下次同步该日历时作为新 todo 拉取
+pub async fn discard_entry(db: &DatabaseConnection, entry_id: i32) -> Result<()> {
+    let entry = find_entry(db, entry_id).await?;
+
+    if let Some(todo) = entity::Entity::find_by_id(entry.todo_id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load todo {}", entry.todo_id))?
+    {
+        let mut active: entity::ActiveModel = todo.into();
+        active.dirty = Set(false);
+        active.updated_at = Set(Utc::now());
+        active
+            .update(db)
+            .await
+            .with_context(|| format!("failed to clear dirty flag of todo {}", entry.todo_id))?;
+    }
+
+    remove_entry(db, entry_id).await
+}
+
+/// 重置失败状态和重试计数，下次同步时立即推送
+async fn reset_entry(
+    db: &DatabaseConnection,
+    entry: outbox_entity::Model,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let entry_id = entry.id;
+    let mut active: outbox_entity::ActiveModel = entry.into();
+    active.status = Set(STATUS_PENDING.to_string());
+    active.attempts = Set(0);
+    active.next_attempt_at = Set(now);
+    active.updated_at = Set(now);
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to reset outbox entry {entry_id}"))?;
+    Ok(())
+}
+
+async fn remove_entry(db: &DatabaseConnection, entry_id: i32) -> Result<()> {
+    outbox_entity::Entity::delete_by_id(entry_id)
+        .exec(db)
+        .await
+        .with_context(|| format!("failed to remove outbox entry {entry_id}"))?;
+    Ok(())
+}
+
+async fn find_entry(db: &DatabaseConnection, entry_id: i32) -> Result<outbox_entity::Model> {
+    outbox_entity::Entity::find_by_id(entry_id)
+        .one(db)
+        .await
+        .with_context(|| format!("failed to load outbox entry {entry_id}"))?
+        .ok_or_else(|| anyhow!("outbox entry {entry_id} not found"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::todo::core::service;
+    use crate::infrastructure::database::test_db::memory_db;
+
+    fn http_error(status: StatusCode) -> anyhow::Error {
+        CalDavHttpError::new(status, format!("CalDAV PUT failed: {status}")).into()
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        assert_eq!(
+            classify_failure(&http_error(StatusCode::PRECONDITION_FAILED)),
+            FailureKind::Permanent
+        );
+        assert_eq!(
+            classify_failure(&http_error(StatusCode::FORBIDDEN)),
+            FailureKind::Permanent
+        );
+        assert_eq!(
+            classify_failure(&http_error(StatusCode::SERVICE_UNAVAILABLE)),
+            FailureKind::Transient
+        );
+        // 外层附加的上下文不影响判断
+        assert_eq!(
+            classify_failure(&http_error(StatusCode::NOT_FOUND).context("failed to push todo")),
+            FailureKind::Permanent
+        );
+        // 只按状态码判断，错误信息中的数字不影响结果
+        assert_eq!(
+            classify_failure(&anyhow!(
+                "error sending request for url (https://example.com/404.ics)"
+            )),
+            FailureKind::Transient
+        );
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_limit() {
+        assert_eq!(backoff_delay(1), Duration::minutes(1));
+        assert_eq!(backoff_delay(2), Duration::minutes(2));
+        assert_eq!(backoff_delay(5), Duration::minutes(16));
+        assert_eq!(backoff_delay(30), Duration::minutes(BACKOFF_MAX_MINUTES));
+    }
+
+    #[tokio::test]
+    async fn test_failed_entry_is_reset_after_todo_changes() {
+        let db = memory_db().await;
+        let todo = service::create_todo(&db, Some("Report".to_string()))
+            .await
+            .unwrap();
+        let now = Utc::now();
+
+        reconcile(&db, now).await.unwrap();
+        let entry = entries_by_todo(&db)
+            .await
+            .unwrap()
+            .remove(&todo.id)
+            .unwrap();
+        let kind = record_failure(
+            &db,
+            entry,
+            &http_error(StatusCode::PRECONDITION_FAILED),
+            now,
+        )
+        .await
+        .unwrap();
+        assert_eq!(kind, FailureKind::Permanent);
+
+        // todo 没有变化时保持失败状态
+        reconcile(&db, now).await.unwrap();
+        let entry = entries_by_todo(&db)
+            .await
+            .unwrap()
+            .remove(&todo.id)
+            .unwrap();
+        assert_eq!(entry.status, STATUS_FAILED);
+        assert_eq!(entry.attempts, 1);
+
+        // 失败后再次修改 todo，条目重新开始计数
+        let mut active: entity::ActiveModel =
+            service::get_todo_by_id(&db, todo.id).await.unwrap().into();
+        active.title = Set("Weekly report".to_string());
+        active.updated_at = Set(now + Duration::minutes(1));
+        active.update(&db).await.unwrap();
+
+        reconcile(&db, now + Duration::minutes(1)).await.unwrap();
+        let entry = entries_by_todo(&db)
+            .await
+            .unwrap()
+            .remove(&todo.id)
+            .unwrap();
+        assert_eq!(entry.status, STATUS_PENDING);
+        assert_eq!(entry.attempts, 0);
+        assert_eq!(entry.last_error, None);
+    }
+}
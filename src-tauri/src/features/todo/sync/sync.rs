@@ -1,8 +1,13 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
+use reqwest::StatusCode;
 use sea_orm::{
     ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
 };
@@ -10,17 +15,34 @@ use serde::Serialize;
 use serde_json::json;
 use tauri::{AppHandle, Manager, Wry};
 use tokio::sync::Mutex;
+use tokio::time::Instant;
 
-use crate::features::todo::core::fractional_index;
-use crate::features::todo::data::entity;
+use crate::features::todo::core::{
+    dependencies, fractional_index,
+    reminders::{self, ReminderSpec},
+    trash,
+};
+use crate::features::todo::data::{entity, reminder_entity};
 
 use super::{
-    client::{CalDavClient, CalDavItem, RemoteTodo},
-    config::CalDavConfigService,
+    client::{
+        parse_ical_todo, AlarmTrigger, CalDavAlarm, CalDavClient, CalDavHttpError, CalDavItem,
+        CollectionState, RemoteEtag, RemoteTodo, UploadResult,
+    },
+    config::{CalDavCalendarConfig, CalDavConfigService, CalendarSyncState},
+    conflicts,
+    dry_run::{self, CalendarSyncPlan, PlannedAction, SyncPlan},
+    history::{self, SyncHistoryAction},
+    ical_patch, merge, outbox,
 };
 
 const SYNC_EVENT: &str = "caldav-sync-event";
 const DEFAULT_REMINDER_MINUTES: i32 = 15;
+const DEFAULT_SCHEDULER_POLL_MINUTES: i64 = 15;
+/// 本地修改后等待该时间内没有新的修改再推送
+const PUSH_DEBOUNCE: Duration = Duration::from_secs(5);
+/// 连续修改时最长等待时间，避免持续编辑导致一直不推送
+const PUSH_MAX_DELAY: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -32,6 +54,18 @@ pub enum SyncReason {
     ConfigUpdated,
 }
 
+impl SyncReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncReason::Startup => "startup",
+            SyncReason::Manual => "manual",
+            SyncReason::Scheduled => "scheduled",
+            SyncReason::DataChanged => "data_changed",
+            SyncReason::ConfigUpdated => "config_updated",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "status", rename_all = "snake_case")]
 pub enum SyncOutcome {
@@ -41,6 +75,10 @@ pub enum SyncOutcome {
         updated: usize,
         pushed: usize,
         deleted: usize,
+        /// 本次同步新产生的字段冲突数
+        conflicts: usize,
+        /// 推送失败的条目数（已记录到推送队列，稍后重试或等待用户处理）
+        failed: usize,
     },
     Skipped {
         reason: String,
@@ -53,6 +91,16 @@ pub enum SyncOutcome {
 #[derive(Debug, Clone, Serialize)]
 pub struct CalDavSyncEvent {
     pub reason: SyncReason,
+    /// 汇总结果（任一日历失败时为 Error）
+    pub outcome: SyncOutcome,
+    /// 各日历的同步结果
+    pub calendars: Vec<CalendarSyncResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarSyncResult {
+    pub calendar_id: String,
+    pub calendar_name: String,
     pub outcome: SyncOutcome,
 }
 
@@ -68,6 +116,25 @@ struct CalDavSyncInner {
     guard: Mutex<()>,
     running: std::sync::atomic::AtomicBool,
     scheduler_restart: Arc<tokio::sync::Notify>,
+    /// 各日历最近一次尝试同步的时间（失败时也记录，避免定时同步反复重试）
+    last_attempts: std::sync::Mutex<HashMap<String, DateTime<Utc>>>,
+    /// 等待推送的本地修改
+    pending_push: std::sync::Mutex<Option<PendingPush>>,
+    push_requested: tokio::sync::Notify,
+}
+
+/// 尚未推送的本地修改：记录第一次和最近一次修改的时间
+#[derive(Debug, Clone, Copy)]
+struct PendingPush {
+    first_requested: Instant,
+    last_requested: Instant,
+}
+
+impl PendingPush {
+    /// 推送时间：最近一次修改后 PUSH_DEBOUNCE，且不晚于第一次修改后 PUSH_MAX_DELAY
+    fn deadline(&self) -> Instant {
+        (self.last_requested + PUSH_DEBOUNCE).min(self.first_requested + PUSH_MAX_DELAY)
+    }
 }
 
 impl CalDavSyncManager {
@@ -79,10 +146,14 @@ impl CalDavSyncManager {
                 guard: Mutex::new(()),
                 running: std::sync::atomic::AtomicBool::new(false),
                 scheduler_restart: Arc::new(tokio::sync::Notify::new()),
+                last_attempts: std::sync::Mutex::new(HashMap::new()),
+                pending_push: std::sync::Mutex::new(None),
+                push_requested: tokio::sync::Notify::new(),
             }),
         };
 
         manager.spawn_scheduler();
+        manager.spawn_push_debouncer();
         manager.trigger(SyncReason::Startup);
 
         manager
@@ -103,22 +174,80 @@ impl CalDavSyncManager {
         });
     }
 
+    /// 本地数据修改后安排推送
+    ///
+    /// 修改已标记为 dirty，这里只负责触发同步：短时间内的多次修改合并为一次同步，
+    /// 既能尽快推送到其他设备，又不会频繁请求服务器
+    pub fn schedule_push(&self) {
+        let now = Instant::now();
+        if let Ok(mut pending) = self.inner.pending_push.lock() {
+            let first_requested = pending.map_or(now, |pending| pending.first_requested);
+            *pending = Some(PendingPush {
+                first_requested,
+                last_requested: now,
+            });
+        }
+        self.inner.push_requested.notify_one();
+    }
+
     pub async fn sync_now(&self, reason: SyncReason) -> Result<CalDavSyncEvent> {
         self.sync_internal(reason).await
     }
 
+    /// 试运行：规划所有启用日历的同步，不写入本地或远端
+    ///
+    /// 与正式同步共用锁，正在同步时等待其完成后再规划
+    pub async fn sync_now_dry_run(&self) -> Result<SyncPlan> {
+        let _lock = self.inner.guard.lock().await;
+
+        let calendars: Vec<_> = CalDavConfigService::list_calendars(self.db())
+            .await?
+            .into_iter()
+            .filter(|calendar| calendar.enabled && calendar.is_valid())
+            .collect();
+
+        let mut plans = Vec::new();
+        for calendar in calendars {
+            let result = match CalDavClient::new(&calendar.connection()) {
+                Ok(client) => {
+                    let result = dry_run::plan_calendar_sync(self.db(), &client, &calendar).await;
+                    self.persist_refreshed_auth(&calendar, &client).await;
+                    result
+                }
+                Err(err) => Err(err),
+            };
+
+            let (changes, error) = match result {
+                Ok(changes) => (changes, None),
+                Err(err) => (Vec::new(), Some(format!("{err:#}"))),
+            };
+            plans.push(CalendarSyncPlan {
+                calendar_id: calendar.id,
+                calendar_name: calendar.name,
+                changes,
+                error,
+            });
+        }
+
+        Ok(SyncPlan {
+            planned_at: Utc::now().to_rfc3339(),
+            calendars: plans,
+        })
+    }
+
     fn spawn_scheduler(&self) {
         let manager = self.clone();
         tauri::async_runtime::spawn(async move {
             loop {
-                let interval = CalDavConfigService::get_sync_interval_minutes(manager.db())
-                    .await
-                    .unwrap_or(15);
+                let delay = manager.next_scheduled_sync_delay().await;
 
-                println!("📅 CalDAV scheduler: next sync in {} minutes", interval);
+                println!(
+                    "📅 CalDAV scheduler: next sync in {} seconds",
+                    delay.as_secs()
+                );
 
                 tokio::select! {
-                    _ = tokio::time::sleep(Duration::from_secs(interval * 60)) => {
+                    _ = tokio::time::sleep(delay) => {
                         println!("🔄 CalDAV: Starting scheduled sync...");
                         match manager.sync_internal(SyncReason::Scheduled).await {
                             Ok(_) => println!("✅ CalDAV: Scheduled sync completed"),
@@ -134,6 +263,85 @@ impl CalDavSyncManager {
         });
     }
 
+    /// 等待推送请求，到期后同步一次（同步期间的新修改在同步结束后再次安排）
+    fn spawn_push_debouncer(&self) {
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                manager.inner.push_requested.notified().await;
+
+                loop {
+                    let deadline = match manager.inner.pending_push.lock() {
+                        Ok(pending) => pending.map(|pending| pending.deadline()),
+                        Err(_) => None,
+                    };
+                    let Some(deadline) = deadline else {
+                        break;
+                    };
+
+                    if Instant::now() < deadline {
+                        // 等待期间的新修改会推迟截止时间，醒来后重新计算
+                        tokio::time::sleep_until(deadline).await;
+                        continue;
+                    }
+
+                    if let Ok(mut pending) = manager.inner.pending_push.lock() {
+                        pending.take();
+                    }
+                    if let Err(err) = manager.sync_internal(SyncReason::DataChanged).await {
+                        eprintln!("CalDAV push after local changes failed: {err}");
+                    }
+                    break;
+                }
+            }
+        });
+    }
+
+    /// 距离下一个日历需要定时同步的时间（没有日历时按默认间隔轮询）
+    async fn next_scheduled_sync_delay(&self) -> Duration {
+        let now = Utc::now();
+        let calendars = CalDavConfigService::list_calendars(self.db())
+            .await
+            .unwrap_or_default();
+
+        let mut next_due: Option<DateTime<Utc>> = None;
+        for calendar in calendars.iter().filter(|c| c.enabled && c.is_valid()) {
+            let due_at = self.calendar_due_at(calendar, now).await;
+            next_due = Some(next_due.map_or(due_at, |current| current.min(due_at)));
+        }
+
+        let next_due =
+            next_due.unwrap_or(now + chrono::Duration::minutes(DEFAULT_SCHEDULER_POLL_MINUTES));
+        (next_due - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+            .max(Duration::from_secs(1))
+    }
+
+    /// 日历下一次定时同步的时间：最近一次同步（或尝试）+ 该日历的同步间隔
+    async fn calendar_due_at(
+        &self,
+        calendar: &CalDavCalendarConfig,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        let interval = chrono::Duration::minutes(calendar.sync_interval_minutes.max(1) as i64);
+        let last_sync = CalDavConfigService::get_calendar_last_sync(self.db(), &calendar.id)
+            .await
+            .ok()
+            .flatten();
+        let last_attempt = self
+            .inner
+            .last_attempts
+            .lock()
+            .ok()
+            .and_then(|attempts| attempts.get(&calendar.id).copied());
+
+        match last_sync.max(last_attempt) {
+            Some(last) => last + interval,
+            None => now + interval,
+        }
+    }
+
     /// 重启调度器（用于同步间隔更改后立即生效）
     pub fn restart_scheduler(&self) {
         self.inner.scheduler_restart.notify_one();
@@ -155,65 +363,243 @@ impl CalDavSyncManager {
                 outcome: SyncOutcome::Skipped {
                     reason: "sync_already_running".to_string(),
                 },
+                calendars: Vec::new(),
             });
         }
 
-        let result = self.perform_sync(reason).await;
+        let started_at = Utc::now();
+        let mut actions = Vec::new();
+        let result = self.perform_sync(reason, &mut actions).await;
 
         self.inner
             .running
             .store(false, std::sync::atomic::Ordering::SeqCst);
 
-        match &result {
-            Ok(event) => self.emit_event(event),
-            Err(err) => {
-                let event = CalDavSyncEvent {
-                    reason,
-                    outcome: SyncOutcome::Error {
-                        message: err.to_string(),
-                    },
-                };
-                self.emit_event(&event);
-                return Ok(event);
-            }
+        let event = result.unwrap_or_else(|err| CalDavSyncEvent {
+            reason,
+            outcome: SyncOutcome::Error {
+                message: err.to_string(),
+            },
+            calendars: Vec::new(),
+        });
+        self.emit_event(&event);
+
+        if let Err(err) =
+            history::record_run(self.db(), &event, started_at, Utc::now(), &actions).await
+        {
+            eprintln!("[CalDAV] Failed to record sync history: {err:#}");
         }
 
-        result
+        Ok(event)
     }
 
-    async fn perform_sync(&self, reason: SyncReason) -> Result<CalDavSyncEvent> {
-        let config = match CalDavConfigService::get_config(self.db()).await? {
-            Some(config) => config,
-            None => {
-                CalDavConfigService::set_last_error(self.db(), None).await?;
-                return Ok(CalDavSyncEvent {
-                    reason,
-                    outcome: SyncOutcome::Skipped {
-                        reason: "missing_configuration".to_string(),
-                    },
-                });
+    /// 同步所有启用的日历（定时同步只同步已到间隔的日历）
+    ///
+    /// 对 todo 执行的操作追加到 `actions`（同步中途失败时也保留已执行的部分）
+    async fn perform_sync(
+        &self,
+        reason: SyncReason,
+        actions: &mut Vec<SyncHistoryAction>,
+    ) -> Result<CalDavSyncEvent> {
+        let calendars: Vec<_> = CalDavConfigService::list_calendars(self.db())
+            .await?
+            .into_iter()
+            .filter(|calendar| calendar.enabled && calendar.is_valid())
+            .collect();
+
+        if calendars.is_empty() {
+            return Ok(CalDavSyncEvent {
+                reason,
+                outcome: SyncOutcome::Skipped {
+                    reason: "missing_configuration".to_string(),
+                },
+                calendars: Vec::new(),
+            });
+        }
+
+        let now = Utc::now();
+        let mut results = Vec::new();
+        let mut errors = Vec::new();
+        let (mut created, mut updated, mut pushed, mut deleted, mut conflicts, mut failed) =
+            (0, 0, 0, 0, 0, 0);
+
+        for calendar in calendars {
+            if matches!(reason, SyncReason::Scheduled)
+                && self.calendar_due_at(&calendar, now).await > now
+            {
+                continue;
             }
-        };
 
-        let client = CalDavClient::new(&config)?;
-        let summary = synchronize_database(self.db(), &client).await?;
-        let synced_at = summary.synced_at.to_rfc3339();
+            if let Ok(mut attempts) = self.inner.last_attempts.lock() {
+                attempts.insert(calendar.id.clone(), now);
+            }
 
-        CalDavConfigService::set_last_sync(self.db(), Some(summary.synced_at)).await?;
-        CalDavConfigService::set_last_error(self.db(), None).await?;
+            let outcome = match self.sync_calendar(&calendar, actions).await {
+                Ok(summary) => {
+                    CalDavConfigService::set_calendar_last_sync(
+                        self.db(),
+                        &calendar.id,
+                        Some(summary.synced_at),
+                    )
+                    .await?;
+                    CalDavConfigService::set_calendar_last_error(self.db(), &calendar.id, None)
+                        .await?;
+
+                    created += summary.created;
+                    updated += summary.updated;
+                    pushed += summary.pushed;
+                    deleted += summary.deleted;
+                    conflicts += summary.conflicts;
+                    failed += summary.failed;
+
+                    SyncOutcome::Success {
+                        synced_at: summary.synced_at.to_rfc3339(),
+                        created: summary.created,
+                        updated: summary.updated,
+                        pushed: summary.pushed,
+                        deleted: summary.deleted,
+                        conflicts: summary.conflicts,
+                        failed: summary.failed,
+                    }
+                }
+                Err(err) => {
+                    let message = format!("{err:#}");
+                    eprintln!(
+                        "❌ CalDAV sync failed for calendar {}: {}",
+                        calendar.name, message
+                    );
+                    CalDavConfigService::set_calendar_last_error(
+                        self.db(),
+                        &calendar.id,
+                        Some(&message),
+                    )
+                    .await?;
+                    errors.push(format!("{}: {}", calendar.name, message));
 
-        let event = CalDavSyncEvent {
-            reason,
-            outcome: SyncOutcome::Success {
-                synced_at,
-                created: summary.created,
-                updated: summary.updated,
-                pushed: summary.pushed,
-                deleted: summary.deleted,
-            },
+                    SyncOutcome::Error { message }
+                }
+            };
+
+            results.push(CalendarSyncResult {
+                calendar_id: calendar.id,
+                calendar_name: calendar.name,
+                outcome,
+            });
+        }
+
+        if results.is_empty() {
+            return Ok(CalDavSyncEvent {
+                reason,
+                outcome: SyncOutcome::Skipped {
+                    reason: "no_calendar_due".to_string(),
+                },
+                calendars: results,
+            });
+        }
+
+        let outcome = if errors.is_empty() {
+            SyncOutcome::Success {
+                synced_at: now.to_rfc3339(),
+                created,
+                updated,
+                pushed,
+                deleted,
+                conflicts,
+                failed,
+            }
+        } else {
+            SyncOutcome::Error {
+                message: errors.join("; "),
+            }
         };
 
-        Ok(event)
+        Ok(CalDavSyncEvent {
+            reason,
+            outcome,
+            calendars: results,
+        })
+    }
+
+    async fn sync_calendar(
+        &self,
+        calendar: &CalDavCalendarConfig,
+        actions: &mut Vec<SyncHistoryAction>,
+    ) -> Result<SyncSummary> {
+        let client = CalDavClient::new(&calendar.connection())?;
+        let result = synchronize_database(self.db(), &client, calendar, actions).await;
+        self.persist_refreshed_auth(calendar, &client).await;
+        result
+    }
+
+    /// 保存请求过程中刷新的认证信息（OAuth2 token），同步失败时也保存
+    async fn persist_refreshed_auth(&self, calendar: &CalDavCalendarConfig, client: &CalDavClient) {
+        let auth = client.auth();
+        if auth == calendar.auth {
+            return;
+        }
+
+        if let Err(err) =
+            CalDavConfigService::set_calendar_auth(self.db(), &calendar.id, &auth).await
+        {
+            eprintln!(
+                "[CalDAV] Failed to save refreshed credentials for calendar {}: {err:#}",
+                calendar.name
+            );
+        }
+    }
+
+    /// 将 todo 移动到另一个日历
+    ///
+    /// todo 标记为 dirty，下次同步时作为新条目推送到目标日历；原日历上的远端条目通过推送队列，
+    /// 在目标日历上创建成功后带 If-Match 删除（原日历已不存在时不删除）
+    pub async fn move_todo_to_calendar(&self, todo_id: i32, calendar_id: &str) -> Result<()> {
+        let _lock = self.inner.guard.lock().await;
+
+        let target = CalDavConfigService::get_calendar(self.db(), calendar_id)
+            .await?
+            .ok_or_else(|| anyhow!("CalDAV calendar {calendar_id} not found"))?;
+        let target_url = CalDavClient::new(&target.connection())?
+            .calendar_url()
+            .to_string();
+
+        let model = entity::Entity::find_by_id(todo_id)
+            .one(self.db())
+            .await
+            .with_context(|| format!("failed to load todo {todo_id}"))?
+            .ok_or_else(|| anyhow!("todo {todo_id} not found"))?;
+
+        if model.remote_calendar_url.as_deref() == Some(target_url.as_str()) {
+            return Ok(());
+        }
+
+        let mut track_source = false;
+        if let (Some(_), Some(source_url)) = (&model.remote_url, &model.remote_calendar_url) {
+            track_source = self.find_calendar_client(source_url).await?.is_some();
+            if !track_source {
+                eprintln!(
+                    "[CalDAV] Source calendar {} is no longer configured, skipping remote delete for todo {}",
+                    source_url, todo_id
+                );
+            }
+        }
+
+        move_to_calendar(self.db(), model, target_url, track_source)
+            .await
+            .with_context(|| format!("failed to move todo {todo_id} to calendar {calendar_id}"))
+    }
+
+    /// 根据日历集合 URL 找到已配置的日历并创建客户端
+    async fn find_calendar_client(
+        &self,
+        calendar_url: &str,
+    ) -> Result<Option<(CalDavCalendarConfig, CalDavClient)>> {
+        for calendar in CalDavConfigService::list_calendars(self.db()).await? {
+            let client = CalDavClient::new(&calendar.connection())?;
+            if client.calendar_url().as_str() == calendar_url {
+                return Ok(Some((calendar, client)));
+            }
+        }
+        Ok(None)
     }
 
     fn emit_event(&self, event: &CalDavSyncEvent) {
@@ -234,6 +620,7 @@ impl CalDavSyncManager {
                     updated,
                     pushed,
                     deleted,
+                    conflicts,
                     ..
                 } => {
                     eprintln!(
@@ -260,6 +647,13 @@ impl CalDavSyncManager {
                         *deleted,
                     );
 
+                    if *conflicts > 0 {
+                        crate::features::todo::api::notifications::notify_sync_conflicts(
+                            state.notification(),
+                            *conflicts,
+                        );
+                    }
+
                     // CalDAV 同步可能修改了待办的提醒时间，需要重新规划
                     if let Some(scheduler) = state.todo_scheduler() {
                         let scheduler = scheduler.clone();
@@ -288,17 +682,56 @@ struct SyncSummary {
     updated: usize,
     pushed: usize,
     deleted: usize,
+    conflicts: usize,
+    failed: usize,
 }
 
-async fn synchronize_database(
+/// 本次同步需要拉取的远端变化
+#[derive(Default)]
+pub(super) struct RemoteChanges {
+    /// 新增或修改的 todo（已获取完整数据）
+    pub(super) changed: Vec<RemoteTodo>,
+    /// 远端已删除的资源 href
+    pub(super) removed: HashSet<String>,
+    /// 处理完成后保存的集合 sync-token
+    pub(super) sync_token: Option<String>,
+}
+
+/// 同步一个日历前读取的本地和远端状态（只读，不修改任何数据）
+pub(super) struct SyncInputs {
+    /// 属于该日历、已关联远端资源的本地 todo
+    pub(super) by_href: HashMap<String, entity::Model>,
+    /// 属于该日历的全部本地 todo
+    pub(super) by_uid: HashMap<String, entity::Model>,
+    pub(super) collection_state: CollectionState,
+    pub(super) changes: RemoteChanges,
+}
+
+/// todo 是否由该日历同步：remote_calendar_url 与日历一致，或尚未关联日历（仅默认日历处理）
+pub(super) fn belongs_to_calendar(
+    model: &entity::Model,
+    calendar_url: &str,
+    is_default: bool,
+) -> bool {
+    match model.remote_calendar_url.as_deref() {
+        Some(url) => url == calendar_url,
+        None => is_default,
+    }
+}
+
+/// 读取本地 todo 并获取远端变化
+///
+/// 拉取远端变化时按代价从低到高选择：ctag 未变化时跳过；有 sync-token 时使用
+/// sync-collection；否则列出所有 ETag。两种方式都只 multiget ETag 变化的资源
+pub(super) async fn load_sync_inputs(
     db: &DatabaseConnection,
     client: &CalDavClient,
-) -> Result<SyncSummary> {
-    let now = Utc::now();
-
-    let remote_todos = client.fetch_todos().await?;
+    calendar: &CalDavCalendarConfig,
+) -> Result<SyncInputs> {
+    let calendar_url = client.calendar_url().as_str();
 
     let mut local_models = entity::Entity::find().all(db).await?;
+    local_models.retain(|model| belongs_to_calendar(model, calendar_url, calendar.is_default));
     let mut by_href: HashMap<String, entity::Model> = HashMap::new();
     let mut by_uid: HashMap<String, entity::Model> = HashMap::new();
 
@@ -309,96 +742,417 @@ async fn synchronize_database(
         by_uid.insert(model.uid.clone(), model);
     }
 
+    let stored_state = CalDavConfigService::get_calendar_sync_state(db, &calendar.id)
+        .await?
+        .filter(|state| state.collection_url == calendar_url)
+        .unwrap_or_default();
+    let collection_state = client.fetch_collection_state().await.unwrap_or_else(|err| {
+        eprintln!("[CalDAV] Failed to fetch collection state, assuming changed: {err:#}");
+        CollectionState::default()
+    });
+
+    let mut changes =
+        if collection_state.ctag.is_some() && collection_state.ctag == stored_state.ctag {
+            eprintln!("[CalDAV] Collection ctag unchanged, skipping remote fetch");
+            RemoteChanges {
+                sync_token: stored_state.sync_token.clone(),
+                ..Default::default()
+            }
+        } else {
+            fetch_remote_changes(client, &stored_state, &collection_state, &by_href).await?
+        };
+    // 已移动到其他日历、等待删除的资源不再属于该日历
+    let moved_out: HashSet<String> = outbox::source_removals(db, calendar_url)
+        .await?
+        .into_iter()
+        .filter_map(|entry| entry.source_href)
+        .collect();
+    changes
+        .changed
+        .retain(|remote| !moved_out.contains(&remote.href));
+
+    Ok(SyncInputs {
+        by_href,
+        by_uid,
+        collection_state,
+        changes,
+    })
+}
+
+/// 同步一个日历
+///
+/// 只处理属于该日历的本地 todo，远端变化的获取方式见 [`load_sync_inputs`]；
+/// 对每个 todo 执行的操作追加到 `actions`
+async fn synchronize_database(
+    db: &DatabaseConnection,
+    client: &CalDavClient,
+    calendar: &CalDavCalendarConfig,
+    actions: &mut Vec<SyncHistoryAction>,
+) -> Result<SyncSummary> {
+    let now = Utc::now();
+
+    let calendar_url = client.calendar_url().as_str();
+    let SyncInputs {
+        mut by_href,
+        mut by_uid,
+        collection_state,
+        changes,
+    } = load_sync_inputs(db, client, calendar).await?;
+
     let mut created = 0usize;
     let mut updated = 0usize;
+    let mut conflicts = 0usize;
     let mut new_todos_to_create: Vec<&RemoteTodo> = Vec::new();
 
     // 第一遍：区分需要更新和需要创建的 todos
-    for remote in &remote_todos {
-        if let Some(existing) = by_href.remove(&remote.href) {
+    for remote in &changes.changed {
+        let existing = if let Some(existing) = by_href.remove(&remote.href) {
             by_uid.remove(&existing.uid);
-            update_local_from_remote(db, existing, remote, now, client).await?;
-            updated += 1;
+            existing
         } else if let Some(existing) = by_uid.remove(&remote.item.uid) {
             if let Some(href) = existing.remote_url.clone() {
                 by_href.remove(&href);
             }
-            update_local_from_remote(db, existing, remote, now, client).await?;
-            updated += 1;
+            existing
         } else {
             new_todos_to_create.push(remote);
-        }
+            continue;
+        };
+
+        let action = if existing.dirty {
+            PlannedAction::MergeLocal
+        } else {
+            PlannedAction::UpdateLocal
+        };
+        actions.push(SyncHistoryAction {
+            summary: remote.item.summary.clone(),
+            ..SyncHistoryAction::for_todo(&calendar.id, action, &existing)
+        });
+        conflicts += update_local_from_remote(db, existing, remote, now, client).await?;
+        updated += 1;
     }
 
     // 第二遍：批量创建新 todos，按 parent_id 分组并均匀分配 order_index
     if !new_todos_to_create.is_empty() {
         create_local_todos_with_uniform_order(db, &new_todos_to_create, now, client).await?;
         created = new_todos_to_create.len();
+        actions.extend(new_todos_to_create.iter().map(|remote| SyncHistoryAction {
+            calendar_id: calendar.id.clone(),
+            action: PlannedAction::CreateLocal,
+            todo_id: None,
+            uid: remote.item.uid.clone(),
+            summary: remote.item.summary.clone(),
+            error: None,
+        }));
     }
 
     let mut pushed = 0usize;
+    let mut pushed_ids = HashSet::new();
     let mut deleted = 0usize;
+    let mut failed = 0usize;
+
+    outbox::reconcile(db, now).await?;
+    let mut outbox_entries = outbox::entries_by_todo(db).await?;
 
     let dirty_locals = entity::Entity::find()
         .filter(entity::Column::Dirty.eq(true))
         .all(db)
         .await?;
+    // 有未解决冲突的 todo 暂不推送，避免覆盖远端的修改
+    let conflicted = conflicts::conflicted_todo_ids(db).await?;
+
+    for model in dirty_locals
+        .into_iter()
+        .filter(|model| belongs_to_calendar(model, calendar_url, calendar.is_default))
+    {
+        // 永久失败或未到重试时间的条目本次跳过
+        let Some(entry) = outbox_entries
+            .remove(&model.id)
+            .filter(|entry| outbox::is_due(entry, now))
+        else {
+            continue;
+        };
 
-    for model in dirty_locals {
+        let todo_id = model.id;
+        let mut action = SyncHistoryAction::for_todo(
+            &calendar.id,
+            match outbox::operation_for(&model) {
+                outbox::OPERATION_DELETE => PlannedAction::DeleteRemote,
+                outbox::OPERATION_UPDATE => PlannedAction::UpdateRemote,
+                _ => PlannedAction::CreateRemote,
+            },
+            &model,
+        );
         // 如果是待删除的项（deleted_at 不为空）
-        if model.deleted_at.is_some() {
-            delete_remote_todo(db, client, model).await?;
-            deleted += 1;
+        let result = if model.deleted_at.is_some() {
+            delete_remote_todo(db, client, model).await.map(|_| {
+                deleted += 1;
+            })
+        } else if !conflicted.contains(&model.id) {
+            match push_local_to_remote(db, client, model, now).await {
+                Ok(PushOutcome::Pushed) => {
+                    pushed += 1;
+                    Ok(())
+                }
+                // 未推送：条目留在 outbox 中，冲突解决后再推送
+                Ok(PushOutcome::Conflicted(new_conflicts)) => {
+                    conflicts += new_conflicts;
+                    continue;
+                }
+                Err(err) => Err(err),
+            }
         } else {
-            push_local_to_remote(db, client, model, now).await?;
-            pushed += 1;
+            continue;
+        };
+
+        match result {
+            Ok(()) => {
+                pushed_ids.insert(todo_id);
+                outbox::record_success(db, entry, now).await?;
+            }
+            Err(err) => {
+                let message = format!("{err:#}");
+                let kind = outbox::record_failure(db, entry, &err, now).await?;
+                eprintln!("[CalDAV] Push of todo {todo_id} failed ({kind:?}): {message}");
+                failed += 1;
+                action.error = Some(message);
+            }
         }
+        actions.push(action);
     }
 
-    // 检测远端已删除但本地仍存在的 todo（不在 remote_todos 中但有 remote_url 的本地项）
-    // 这些应该从本地删除
-    let remote_hrefs: std::collections::HashSet<String> =
-        remote_todos.iter().map(|r| r.href.clone()).collect();
+    // 已移动到其他日历的 todo：在目标日历上创建后（或已在本地删除时），删除原日历上的资源。
+    // 带 If-Match 原 ETag，远端在此之后有修改时删除失败（412），由用户决定重试或放弃
+    for entry in outbox::source_removals(db, calendar_url).await? {
+        if !outbox::is_due(&entry, now) {
+            continue;
+        }
+        let Some(model) = entity::Entity::find_by_id(entry.todo_id).one(db).await? else {
+            continue;
+        };
+        if model.remote_url.is_none() && model.deleted_at.is_none() {
+            continue;
+        }
+
+        let href = entry.source_href.clone().unwrap_or_default();
+        let mut action =
+            SyncHistoryAction::for_todo(&calendar.id, PlannedAction::DeleteRemote, &model);
+        match client
+            .delete_todo(&href, entry.source_etag.as_deref())
+            .await
+        {
+            Ok(()) => {
+                outbox::record_source_removed(db, entry, model.dirty, now).await?;
+                deleted += 1;
+            }
+            Err(err) => {
+                let message = format!("{err:#}");
+                let kind = outbox::record_failure(db, entry, &err, now).await?;
+                eprintln!(
+                    "[CalDAV] Removal of moved todo {} from {href} failed ({kind:?}): {message}",
+                    model.id
+                );
+                failed += 1;
+                action.error = Some(message);
+            }
+        }
+        actions.push(action);
+    }
 
+    // 远端已删除但本地仍存在的 todo，连同子任务移入回收站（可从回收站恢复）
+    let mut trashed_ids = HashSet::new();
     for (href, local_model) in by_href {
-        // 如果本地有 remote_url 但远端已不存在，且未被标记删除
-        if local_model.deleted_at.is_none() && !remote_hrefs.contains(&href) {
-            // 远端已删除，删除本地记录
-            entity::Entity::delete_by_id(local_model.id)
-                .exec(db)
-                .await
-                .with_context(|| {
-                    format!(
-                        "failed to delete local todo {} (removed from remote)",
-                        local_model.id
-                    )
-                })?;
+        // 未被标记删除的才处理（已标记的在上面推送删除时已清理）；
+        // 本次已推送的（包括远端不存在而重新创建的）以本地为准
+        if local_model.deleted_at.is_none()
+            && !trashed_ids.contains(&local_model.id)
+            && !pushed_ids.contains(&local_model.id)
+            && changes.removed.contains(&href)
+        {
+            for model in trash::move_to_trash(db, local_model.clone(), now).await? {
+                trashed_ids.insert(model.id);
+                // 远端资源已不存在的不再推送删除；仍存在的子任务随父任务删除
+                let removed = model
+                    .remote_url
+                    .as_ref()
+                    .is_some_and(|url| changes.removed.contains(url));
+                if removed {
+                    trash::detach_from_remote(db, model, now).await?;
+                }
+            }
             deleted += 1;
+            actions.push(SyncHistoryAction::for_todo(
+                &calendar.id,
+                PlannedAction::DeleteLocal,
+                &local_model,
+            ));
         }
     }
 
+    CalDavConfigService::set_calendar_sync_state(
+        db,
+        &calendar.id,
+        Some(&CalendarSyncState {
+            collection_url: calendar_url.to_string(),
+            ctag: collection_state.ctag,
+            sync_token: changes.sync_token.clone(),
+        }),
+    )
+    .await?;
+
+    // 记录已同步的 todo 对应的集合 sync-token
+    if let Some(token) = changes.sync_token {
+        entity::Entity::update_many()
+            .col_expr(
+                entity::Column::SyncToken,
+                sea_orm::sea_query::Expr::value(token),
+            )
+            .filter(entity::Column::RemoteCalendarUrl.eq(calendar_url))
+            .filter(entity::Column::Dirty.eq(false))
+            .exec(db)
+            .await
+            .context("failed to record CalDAV sync token on todos")?;
+    }
+
     Ok(SyncSummary {
         synced_at: now,
         created,
         updated,
         pushed,
         deleted,
+        conflicts,
+        failed,
     })
 }
 
+/// 将 todo 关联到目标日历，原日历上的资源记录到推送队列（`track_source` 为 false 时不删除）
+async fn move_to_calendar(
+    db: &DatabaseConnection,
+    model: entity::Model,
+    target_url: String,
+    track_source: bool,
+) -> Result<()> {
+    let now = Utc::now();
+    let restored = outbox::record_move(db, &model, &target_url, track_source, now).await?;
+    let (remote_url, remote_etag) = match restored {
+        Some((href, etag)) => (Some(href), etag),
+        None => (None, None),
+    };
+
+    let mut active: entity::ActiveModel = model.into();
+    active.remote_url = Set(remote_url);
+    active.remote_etag = Set(remote_etag);
+    active.remote_calendar_url = Set(Some(target_url));
+    active.sync_token = Set(None);
+    active.sync_snapshot = Set(None);
+    active.dirty = Set(true);
+    active.updated_at = Set(now);
+    active.update(db).await?;
+
+    Ok(())
+}
+
+/// 获取自上次同步以来的远端变化
+///
+/// 优先使用 sync-collection；服务器不支持或令牌失效时回退到 ETag 列表，
+/// 此时本地有而列表中没有的资源视为已删除
+async fn fetch_remote_changes(
+    client: &CalDavClient,
+    stored_state: &CalendarSyncState,
+    collection_state: &CollectionState,
+    known: &HashMap<String, entity::Model>,
+) -> Result<RemoteChanges> {
+    let incremental = match stored_state.sync_token.as_deref() {
+        Some(token) => match client.sync_collection(Some(token)).await {
+            Ok(result) => Some(result),
+            Err(err) => {
+                eprintln!("[CalDAV] sync-collection failed, falling back to ETag listing: {err:#}");
+                None
+            }
+        },
+        None => None,
+    };
+
+    let (listing, removed, sync_token) = match incremental {
+        Some(result) => (
+            result.changed,
+            result.removed.into_iter().collect::<HashSet<_>>(),
+            result
+                .sync_token
+                .or_else(|| collection_state.sync_token.clone()),
+        ),
+        None => {
+            let listing = client.list_etags().await?;
+            let listed: HashSet<&str> = listing.iter().map(|item| item.href.as_str()).collect();
+            let removed = known
+                .keys()
+                .filter(|href| !listed.contains(href.as_str()))
+                .cloned()
+                .collect();
+            (listing, removed, collection_state.sync_token.clone())
+        }
+    };
+
+    let changed_hrefs: Vec<String> = listing
+        .into_iter()
+        .filter(|remote| !is_known_version(known, remote))
+        .map(|remote| remote.href)
+        .collect();
+
+    eprintln!(
+        "[CalDAV] Remote changes: {} changed, {} removed",
+        changed_hrefs.len(),
+        removed.len()
+    );
+
+    let changed = if changed_hrefs.is_empty() {
+        Vec::new()
+    } else {
+        client.fetch_todos_by_href(&changed_hrefs).await?
+    };
+
+    Ok(RemoteChanges {
+        changed,
+        removed,
+        sync_token,
+    })
+}
+
+/// 本地是否已有该远端资源的同一版本（按 ETag 比较）
+fn is_known_version(known: &HashMap<String, entity::Model>, remote: &RemoteEtag) -> bool {
+    let local_etag = known
+        .get(&remote.href)
+        .and_then(|model| model.remote_etag.as_deref());
+
+    match (local_etag, remote.etag.as_deref()) {
+        (Some(local), Some(remote)) => comparable_etag(local) == comparable_etag(remote),
+        _ => false,
+    }
+}
+
+fn comparable_etag(etag: &str) -> &str {
+    etag.trim().trim_start_matches("W/").trim_matches('"')
+}
+
+/// 用远端版本更新本地 todo，返回新产生的冲突数
 async fn update_local_from_remote(
     db: &DatabaseConnection,
     existing: entity::Model,
     remote: &RemoteTodo,
     now: DateTime<Utc>,
     client: &CalDavClient,
-) -> Result<()> {
+) -> Result<usize> {
     // 如果本地已标记删除，跳过（等待同步删除到远端）
     if existing.deleted_at.is_some() {
-        return Ok(());
+        return Ok(0);
+    }
+
+    // 本地有未推送的修改：有同步快照时逐字段三方合并
+    if existing.dirty && existing.sync_snapshot.is_some() {
+        return merge_local_with_remote(db, existing, remote, now, client).await;
     }
 
-    // Last-Write-Wins 策略：比较时间戳决定是否覆盖本地
+    // 没有快照（旧数据）时使用 Last-Write-Wins 策略：比较时间戳决定是否覆盖本地
     if existing.dirty {
         let local_modified = existing.last_modified_at;
         let remote_modified = remote.item.last_modified.unwrap_or(now);
@@ -420,19 +1174,71 @@ async fn update_local_from_remote(
                 remote_modified.to_rfc3339(),
                 existing.id
             );
-            return Ok(());
+            return Ok(0);
         }
     }
 
     let mut active: entity::ActiveModel = existing.clone().into();
-    apply_remote_to_active(db, &mut active, &remote.item, remote, now, client).await;
+    let keep_reminder_state =
+        apply_remote_to_active(db, &mut active, &remote.item, remote, now, client).await;
 
     active
         .update(db)
         .await
         .with_context(|| format!("failed to update local todo {} from CalDAV", existing.id))?;
 
-    Ok(())
+    apply_remote_alarms(db, existing.id, &remote.item, keep_reminder_state, false).await?;
+    dependencies::replace_uids(db, existing.id, &remote.item.depends_on).await?;
+
+    Ok(0)
+}
+
+/// 以同步快照为基准，将本地未推送的修改与远端版本逐字段合并
+///
+/// 合并后远端版本成为新的快照；仍有本地修改时 todo 保持 dirty，稍后推送。
+/// 双方修改了同一字段时记录冲突，返回冲突数
+async fn merge_local_with_remote(
+    db: &DatabaseConnection,
+    existing: entity::Model,
+    remote: &RemoteTodo,
+    now: DateTime<Utc>,
+    client: &CalDavClient,
+) -> Result<usize> {
+    let snapshot = existing.sync_snapshot.as_deref().unwrap_or_default();
+    let base = parse_ical_todo(snapshot)
+        .with_context(|| format!("failed to parse sync snapshot of todo {}", existing.id))?;
+    let local = parse_ical_todo(&build_ical_from_model(db, &existing).await)
+        .with_context(|| format!("failed to build local version of todo {}", existing.id))?;
+    let held_fields = conflicts::conflict_fields(db, existing.id).await?;
+
+    let outcome = merge::three_way_merge(&base, &local, &remote.item, &held_fields);
+    if !outcome.conflicts.is_empty() {
+        eprintln!(
+            "⚠️  Sync conflict on todo {}: fields {:?}",
+            existing.id,
+            outcome
+                .conflicts
+                .iter()
+                .map(|conflict| conflict.field)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    let mut active: entity::ActiveModel = existing.clone().into();
+    let keep_reminder_state =
+        apply_remote_to_active(db, &mut active, &outcome.item, remote, now, client).await;
+    active.dirty = Set(outcome.local_changes);
+
+    active
+        .update(db)
+        .await
+        .with_context(|| format!("failed to merge local todo {} with CalDAV", existing.id))?;
+
+    apply_remote_alarms(db, existing.id, &outcome.item, keep_reminder_state, false).await?;
+    dependencies::replace_uids(db, existing.id, &outcome.item.depends_on).await?;
+    conflicts::replace_conflicts(db, existing.id, &outcome.conflicts).await?;
+
+    Ok(outcome.conflicts.len())
 }
 
 /// 批量创建新 todos，按 parent_id 分组并在 0 到最小值之间均匀分配 order_index
@@ -500,12 +1306,14 @@ async fn create_local_todos_with_uniform_order(
 
     // 第四步：插入所有 active models
     for (remote, active) in remotes.iter().zip(active_models) {
-        active.insert(db).await.with_context(|| {
+        let inserted = active.insert(db).await.with_context(|| {
             format!(
                 "failed to insert local todo from CalDAV resource {}",
                 remote.href
             )
         })?;
+        apply_remote_alarms(db, inserted.id, &remote.item, false, true).await?;
+        dependencies::replace_uids(db, inserted.id, &remote.item.depends_on).await?;
     }
 
     Ok(())
@@ -554,17 +1362,19 @@ async fn create_local_from_remote(
     let order_index = fractional_index::generate_key_between(None, min_order_index);
     active.order_index = Set(Some(order_index));
 
-    active.insert(db).await.with_context(|| {
+    let inserted = active.insert(db).await.with_context(|| {
         format!(
             "failed to insert local todo from CalDAV resource {}",
             remote.href
         )
     })?;
+    apply_remote_alarms(db, inserted.id, &remote.item, false, true).await?;
+    dependencies::replace_uids(db, inserted.id, &remote.item.depends_on).await?;
 
     Ok(())
 }
 
-/// 删除远端 CalDAV todo 资源，并从本地数据库彻底移除
+/// 删除远端 CalDAV todo 资源，本地记录保留在回收站中
 async fn delete_remote_todo(
     db: &DatabaseConnection,
     client: &CalDavClient,
@@ -588,28 +1398,19 @@ async fn delete_remote_todo(
                 let err_msg = err.to_string();
                 eprintln!("Failed to delete remote todo {}: {}", model.id, err_msg);
 
-                // 检查错误类型
-                let is_not_found = err_msg.contains("404") || err_msg.contains("Not Found");
-                let is_precondition_failed =
-                    err_msg.contains("412") || err_msg.contains("Precondition Failed");
-
-                if is_not_found {
+                if is_not_found(&err) {
                     eprintln!("Remote todo {} not found (404), already deleted", model.id);
-                } else if is_precondition_failed {
+                } else if is_precondition_failed(&err) {
                     eprintln!(
                         "ETag mismatch (412) for todo {}, trying without ETag",
                         model.id
                     );
                     // 第二次尝试：不使用 ETag 强制删除
                     if let Err(retry_err) = client.delete_todo(href, None).await {
-                        let retry_msg = retry_err.to_string();
                         // 再次检查是否是 404
-                        if !retry_msg.contains("404") && !retry_msg.contains("Not Found") {
+                        if !is_not_found(&retry_err) {
                             return Err(retry_err).with_context(|| {
-                                format!(
-                                    "failed to force delete remote todo {}: {}",
-                                    model.id, retry_msg
-                                )
+                                format!("failed to force delete remote todo {}", model.id)
                             });
                         }
                         eprintln!("Remote todo {} not found on retry (404)", model.id);
@@ -624,50 +1425,93 @@ async fn delete_remote_todo(
         }
     }
 
-    // 从本地数据库彻底删除
-    entity::Entity::delete_by_id(model.id)
-        .exec(db)
-        .await
-        .with_context(|| format!("failed to delete todo {} from local database", model.id))?;
+    // 本地记录留在回收站中，不再关联远端资源
+    let id = model.id;
+    trash::detach_from_remote(db, model, Utc::now()).await?;
 
-    eprintln!("Successfully deleted local todo {}", model.id);
+    eprintln!("Remote todo {id} deleted, local copy kept in trash");
     Ok(())
 }
 
+/// 推送单个 todo 的结果
+enum PushOutcome {
+    /// 已推送到远端（或以远端版本为准，无需再推送）
+    Pushed,
+    /// 与远端合并时产生冲突（冲突字段数），未推送
+    Conflicted(usize),
+}
+
 /// 将本地 todo 推送到远端 CalDAV 服务器（创建或更新）
 async fn push_local_to_remote(
     db: &DatabaseConnection,
     client: &CalDavClient,
-    model: entity::Model,
+    mut model: entity::Model,
     now: DateTime<Utc>,
-) -> Result<()> {
-    let body = build_ical_from_model(db, &model).await;
+) -> Result<PushOutcome> {
+    let mut body = build_ical_from_model(db, &model).await;
 
-    let upload = if let Some(href) = &model.remote_url {
+    let upload = if let Some(href) = model.remote_url.clone() {
         // 第一次尝试：使用 ETag 进行乐观锁更新
-        let update_result = client
-            .update_todo(href, &body, model.remote_etag.as_deref())
-            .await;
+        let update_result = match client
+            .update_todo(&href, &body, model.remote_etag.as_deref())
+            .await
+        {
+            Err(err) if is_not_found(&err) => recreate_remote_todo(client, &model, &body).await,
+            result => result,
+        };
 
         match update_result {
             Ok(upload) => upload,
             Err(err) => {
-                let err_msg = err.to_string();
-                let is_412 = err_msg.contains("412") || err_msg.contains("Precondition Failed");
+                if !is_precondition_failed(&err) {
+                    // 其他错误，直接返回
+                    return Err(err)
+                        .with_context(|| format!("failed to upload todo {} to CalDAV", model.id));
+                }
+
+                // 获取远端最新版本
+                let remote_todo = match client.get_todo(&href).await {
+                    Ok(remote_todo) => remote_todo,
+                    // 远端资源已不存在（If-Match 不匹配任何版本）
+                    Err(err) if is_not_found(&err) => {
+                        let upload = recreate_remote_todo(client, &model, &body).await?;
+                        record_upload(db, client, model, body, upload, now).await?;
+                        return Ok(PushOutcome::Pushed);
+                    }
+                    Err(err) => {
+                        return Err(err).context("failed to fetch remote todo after 412 conflict")
+                    }
+                };
 
-                if is_412 {
-                    // 遇到 412 冲突，应用 Last-Write-Wins 策略
+                if model.sync_snapshot.is_some() {
+                    // 有同步快照：与远端逐字段合并后用新的 ETag 重新推送
                     eprintln!(
-                        "⚠️  412 Conflict detected for todo {}, applying Last-Write-Wins strategy",
+                        "⚠️  412 Conflict detected for todo {}, merging with remote version",
                         model.id
                     );
+                    let conflicts =
+                        merge_local_with_remote(db, model.clone(), &remote_todo, now, client)
+                            .await?;
+                    if conflicts > 0 {
+                        return Ok(PushOutcome::Conflicted(conflicts));
+                    }
 
-                    // 获取远端最新版本
-                    let remote_todo = client
-                        .get_todo(href)
+                    model = entity::Entity::find_by_id(model.id)
+                        .one(db)
                         .await
-                        .context("failed to fetch remote todo after 412 conflict")?;
+                        .context("failed to reload todo after merge")?
+                        .ok_or_else(|| anyhow!("todo {} disappeared during merge", model.id))?;
+                    if !model.dirty {
+                        return Ok(PushOutcome::Pushed);
+                    }
 
+                    body = build_ical_from_model(db, &model).await;
+                    client
+                        .update_todo(&href, &body, model.remote_etag.as_deref())
+                        .await
+                        .context("failed to push merged todo after 412")?
+                } else {
+                    // 没有快照（旧数据）：应用 Last-Write-Wins 策略
                     let local_modified = model.last_modified_at;
                     let remote_modified = remote_todo.item.last_modified.unwrap_or(now);
 
@@ -680,7 +1524,7 @@ async fn push_local_to_remote(
                             model.id
                         );
                         client
-                            .update_todo(href, &body, None) // 不使用 ETag 强制更新
+                            .update_todo(&href, &body, None) // 不使用 ETag 强制更新
                             .await
                             .context("failed to force push local todo after 412")?
                     } else {
@@ -694,7 +1538,7 @@ async fn push_local_to_remote(
 
                         // 用远端版本覆盖本地
                         let mut active: entity::ActiveModel = model.clone().into();
-                        apply_remote_to_active(
+                        let keep_reminder_state = apply_remote_to_active(
                             db,
                             &mut active,
                             &remote_todo.item,
@@ -707,13 +1551,19 @@ async fn push_local_to_remote(
                             .update(db)
                             .await
                             .context("failed to update local with remote after 412")?;
+                        apply_remote_alarms(
+                            db,
+                            model.id,
+                            &remote_todo.item,
+                            keep_reminder_state,
+                            false,
+                        )
+                        .await?;
+                        dependencies::replace_uids(db, model.id, &remote_todo.item.depends_on)
+                            .await?;
 
-                        return Ok(());
+                        return Ok(PushOutcome::Pushed);
                     }
-                } else {
-                    // 其他错误，直接返回
-                    return Err(err)
-                        .with_context(|| format!("failed to upload todo {} to CalDAV", model.id));
                 }
             }
         }
@@ -724,11 +1574,39 @@ async fn push_local_to_remote(
             .with_context(|| format!("failed to create todo {} on CalDAV", model.id))?
     };
 
+    record_upload(db, client, model, body, upload, now).await?;
+    Ok(PushOutcome::Pushed)
+}
+
+/// 远端资源已不存在（例如恢复了待推送删除的 todo，而远端已被其他客户端删除）：按 UID 重新创建
+async fn recreate_remote_todo(
+    client: &CalDavClient,
+    model: &entity::Model,
+    body: &str,
+) -> Result<UploadResult> {
+    eprintln!("Remote todo {} no longer exists, re-creating it", model.id);
+    client
+        .create_todo(&model.uid, body)
+        .await
+        .with_context(|| format!("failed to re-create todo {} on CalDAV", model.id))
+}
+
+/// 记录推送结果
+async fn record_upload(
+    db: &DatabaseConnection,
+    client: &CalDavClient,
+    model: entity::Model,
+    body: String,
+    upload: UploadResult,
+    now: DateTime<Utc>,
+) -> Result<()> {
     let mut active: entity::ActiveModel = model.into();
     active.dirty = Set(false);
-    active.remote_url = Set(Some(upload.href.clone()));
+    active.remote_url = Set(Some(upload.href));
     active.remote_calendar_url = Set(Some(client.calendar_url().to_string()));
-    active.remote_etag = Set(upload.etag.clone());
+    active.remote_etag = Set(upload.etag);
+    // 推送的内容即为新的合并基准
+    active.sync_snapshot = Set(Some(body));
     active.last_synced_at = Set(Some(now));
     active.last_modified_at = Set(now);
     active.updated_at = Set(now);
@@ -737,10 +1615,20 @@ async fn push_local_to_remote(
         .update(db)
         .await
         .context("failed to persist local todo after CalDAV upload")?;
-
     Ok(())
 }
 
+fn is_not_found(err: &anyhow::Error) -> bool {
+    CalDavHttpError::status_of(err) == Some(StatusCode::NOT_FOUND)
+}
+
+fn is_precondition_failed(err: &anyhow::Error) -> bool {
+    CalDavHttpError::status_of(err) == Some(StatusCode::PRECONDITION_FAILED)
+}
+
+/// 将远端版本写入本地 ActiveModel（字段和同步状态）
+///
+/// 返回提醒触发状态是否可以保留（到期时间与提醒偏移均未变化）
 async fn apply_remote_to_active(
     db: &DatabaseConnection,
     active: &mut entity::ActiveModel,
@@ -748,7 +1636,30 @@ async fn apply_remote_to_active(
     remote: &RemoteTodo,
     now: DateTime<Utc>,
     client: &CalDavClient,
-) {
+) -> bool {
+    let reminder_unchanged = apply_item_to_active(db, active, item, now).await;
+
+    active.dirty = Set(false);
+    active.remote_url = Set(Some(remote.href.clone()));
+    active.remote_etag = Set(remote.etag.clone());
+    active.remote_calendar_url = Set(Some(client.calendar_url().to_string()));
+    active.sync_token = Set(None);
+    active.sync_snapshot = Set(Some(remote.raw_ical.clone()));
+    active.last_synced_at = Set(Some(now));
+    active.deleted_at = Set(None);
+
+    reminder_unchanged
+}
+
+/// 将 VTODO 的字段写入本地 ActiveModel（不修改同步状态）
+///
+/// 返回提醒触发状态是否可以保留（到期时间与提醒偏移均未变化）
+pub(super) async fn apply_item_to_active(
+    db: &DatabaseConnection,
+    active: &mut entity::ActiveModel,
+    item: &CalDavItem,
+    now: DateTime<Utc>,
+) -> bool {
     let status = item.status.clone().unwrap_or_else(|| {
         if item.is_completed() {
             "COMPLETED"
@@ -785,6 +1696,15 @@ async fn apply_remote_to_active(
     };
 
     let reminder_minutes = item.reminder_minutes.unwrap_or(existing_reminder).max(0);
+    let effective_reminder_minutes = if reminder_minutes <= 0 {
+        DEFAULT_REMINDER_MINUTES
+    } else {
+        reminder_minutes
+    };
+
+    // 到期时间与提醒偏移未变化时保留提醒状态，避免每次同步后重复提醒同一实例
+    let reminder_unchanged = active.due_date.try_as_ref() == Some(&item.due)
+        && active.reminder_offset_minutes.try_as_ref() == Some(&effective_reminder_minutes);
 
     active.uid = Set(item.uid.clone());
     active.title = Set(item.summary.clone());
@@ -803,16 +1723,14 @@ async fn apply_remote_to_active(
     active.timezone = Set(item.timezone.clone().or(existing_timezone));
     let reminder_method = existing_method.unwrap_or_else(|| "display".to_string());
     active.reminder_method = Set(Some(reminder_method));
-    active.reminder_last_triggered_at = Set(None);
+    if !reminder_unchanged {
+        active.reminder_last_triggered_at = Set(None);
+        active.last_reminded_occurrence_at = Set(None);
+        active.snoozed_until = Set(None);
+        active.snoozed_occurrence_at = Set(None);
+        active.notified = Set(false);
+    }
     active.completed_at = Set(item.completed_at);
-    active.notified = Set(false);
-    active.dirty = Set(false);
-    active.remote_url = Set(Some(remote.href.clone()));
-    active.remote_etag = Set(remote.etag.clone());
-    active.remote_calendar_url = Set(Some(client.calendar_url().to_string()));
-    active.sync_token = Set(None);
-    active.last_synced_at = Set(Some(now));
-    active.deleted_at = Set(None);
     active.updated_at = Set(now);
 
     // 处理 RELATED-TO (子任务关系)
@@ -851,6 +1769,59 @@ async fn apply_remote_to_active(
             active.reminder_method = Set(Some("display".to_string()));
         }
     }
+
+    reminder_unchanged
+}
+
+/// 用远端的 VALARM 替换本地提醒（需在 Todo 写入数据库之后调用）
+///
+/// 远端没有 VALARM 时，已有 Todo 保留本地提醒，新建的 Todo 使用默认提醒
+pub(super) async fn apply_remote_alarms(
+    db: &DatabaseConnection,
+    todo_id: i32,
+    item: &CalDavItem,
+    keep_state: bool,
+    is_new: bool,
+) -> Result<()> {
+    let specs = if !item.alarms.is_empty() {
+        item.alarms.iter().map(reminder_spec_from_alarm).collect()
+    } else if is_new {
+        vec![ReminderSpec::relative(DEFAULT_REMINDER_MINUTES)]
+    } else {
+        if !keep_state {
+            reminders::reset_trigger_state(db, todo_id).await?;
+        }
+        return Ok(());
+    };
+
+    reminders::replace_reminders(db, todo_id, &specs, keep_state)
+        .await
+        .with_context(|| format!("failed to apply CalDAV alarms to todo {todo_id}"))
+}
+
+fn reminder_spec_from_alarm(alarm: &CalDavAlarm) -> ReminderSpec {
+    let (offset_minutes, related, trigger_at) = match alarm.trigger {
+        AlarmTrigger::Relative {
+            minutes_before,
+            related_start,
+        } => {
+            let related = if related_start {
+                reminders::RELATED_START
+            } else {
+                reminders::RELATED_END
+            };
+            (Some(minutes_before), related, None)
+        }
+        AlarmTrigger::Absolute(at) => (None, reminders::RELATED_END, Some(at)),
+    };
+
+    ReminderSpec {
+        offset_minutes,
+        related: related.to_string(),
+        trigger_at,
+        action: alarm.action.clone(),
+        description: alarm.description.clone(),
+    }
 }
 
 fn serialize_tags(tags: &[String]) -> Option<String> {
@@ -861,7 +1832,10 @@ fn serialize_tags(tags: &[String]) -> Option<String> {
     }
 }
 
-async fn build_ical_from_model(db: &DatabaseConnection, model: &entity::Model) -> String {
+pub(super) async fn build_ical_from_model(
+    db: &DatabaseConnection,
+    model: &entity::Model,
+) -> String {
     let mut lines = Vec::<String>::new();
     let stamp = Utc::now();
 
@@ -961,19 +1935,90 @@ async fn build_ical_from_model(db: &DatabaseConnection, model: &entity::Model) -
         }
     }
 
-    if model.reminder_offset_minutes > 0 {
-        lines.push("BEGIN:VALARM".to_string());
-        lines.push(format!("TRIGGER:-PT{}M", model.reminder_offset_minutes));
-        lines.push("ACTION:DISPLAY".to_string());
-        lines.push("DESCRIPTION:Reminder".to_string());
-        lines.push("END:VALARM".to_string());
+    // 处理依赖关系 (RELATED-TO;RELTYPE=DEPENDS-ON)
+    match dependencies::dependency_uids(db, model.id).await {
+        Ok(uids) => {
+            for uid in uids {
+                lines.push(format!(
+                    "RELATED-TO;RELTYPE=DEPENDS-ON:{}",
+                    escape_ical_value(&uid)
+                ));
+            }
+        }
+        Err(err) => {
+            eprintln!(
+                "  -> Warning: failed to load dependencies for todo UID={}: {}",
+                model.uid, err
+            );
+        }
+    }
+
+    // 每个提醒对应一个 VALARM
+    match reminders::load_reminders(db, model.id).await {
+        Ok(todo_reminders) => {
+            for reminder in &todo_reminders {
+                push_valarm(&mut lines, reminder, &model.title);
+            }
+        }
+        Err(err) => {
+            eprintln!(
+                "  -> Warning: failed to load reminders for todo UID={}: {}",
+                model.uid, err
+            );
+        }
     }
 
     lines.push("END:VTODO".to_string());
     lines.push("END:VCALENDAR".to_string());
     lines.push(String::new());
 
-    lines.join("\r\n")
+    let generated = lines.join("\r\n");
+
+    // 以最近一次同步的版本为模板，保留其他客户端写入的属性和组件
+    let ics = model
+        .sync_snapshot
+        .as_deref()
+        .and_then(|template| ical_patch::patch_ical(template, &generated))
+        .unwrap_or(generated);
+    ical_patch::display_email_alarms_without_attendee(ics)
+}
+
+fn push_valarm(lines: &mut Vec<String>, reminder: &reminder_entity::Model, summary: &str) {
+    let trigger = match (reminder.trigger_at, reminder.offset_minutes) {
+        (Some(at), _) => format!("TRIGGER;VALUE=DATE-TIME:{}", format_datetime(&at)),
+        (None, Some(minutes_before)) => format!(
+            "TRIGGER;RELATED={}:{}",
+            reminder.related,
+            format_trigger_duration(minutes_before)
+        ),
+        (None, None) => return,
+    };
+
+    lines.push("BEGIN:VALARM".to_string());
+    lines.push(trigger);
+    lines.push(format!("ACTION:{}", reminder.action));
+    let description = reminder.description.as_deref().unwrap_or("Reminder");
+    lines.push(format!("DESCRIPTION:{}", escape_ical_value(description)));
+    if reminder.action == "EMAIL" {
+        lines.push(format!("SUMMARY:{}", escape_ical_value(summary)));
+    }
+    lines.push("END:VALARM".to_string());
+}
+
+/// 格式化提醒偏移为 iCalendar DURATION（提前为负，如 `-PT15M`、`-P1D`）
+fn format_trigger_duration(minutes_before: i32) -> String {
+    let sign = if minutes_before > 0 { "-" } else { "" };
+    let minutes = minutes_before.unsigned_abs();
+
+    if minutes == 0 {
+        "PT0S".to_string()
+    } else if minutes.is_multiple_of(1440) {
+        format!("{sign}P{}D", minutes / 1440)
+    } else if minutes.is_multiple_of(60) {
+        format!("{sign}PT{}H", minutes / 60)
+    } else {
+        format!("{sign}PT{}M", minutes)
+    }
 }
 
 /// 格式化 UTC 时间为 iCalendar 格式（带 Z 后缀表示 UTC）
@@ -1025,3 +2070,581 @@ fn escape_ical_value(value: &str) -> String {
         .replace(',', "\\,")
         .replace(';', "\\;")
 }
+
+#[cfg(all(test, not(any(target_os = "android", target_os = "ios"))))]
+mod tests {
+    use super::*;
+    use crate::features::todo::core::service;
+    use crate::features::todo::sync::client::parse_trigger_duration;
+    use crate::features::todo::sync::config::CalDavConfig;
+    use crate::features::todo::sync::test_server::FakeCalDavServer;
+    use crate::infrastructure::database::test_db::memory_db;
+    use chrono::TimeZone;
+
+    const USERNAME: &str = "user";
+    const PASSWORD: &str = "secret";
+
+    struct Harness {
+        db: DatabaseConnection,
+        server: FakeCalDavServer,
+        client: CalDavClient,
+        calendar: CalDavCalendarConfig,
+    }
+
+    impl Harness {
+        async fn start() -> Self {
+            Self::start_with_password(PASSWORD).await
+        }
+
+        async fn start_with_password(password: &str) -> Self {
+            let db = memory_db().await;
+
+            let server = FakeCalDavServer::start(USERNAME, PASSWORD).await;
+            let mut calendar = CalDavCalendarConfig::new(
+                "Tasks".to_string(),
+                CalDavConfig {
+                    url: server.calendar_url(),
+                    username: USERNAME.to_string(),
+                    password: password.to_string(),
+                    auth: Default::default(),
+                },
+            );
+            calendar.is_default = true;
+            let client = CalDavClient::new(&calendar.connection()).unwrap();
+
+            Self {
+                db,
+                server,
+                client,
+                calendar,
+            }
+        }
+
+        async fn sync(&self) -> Result<SyncSummary> {
+            synchronize_database(&self.db, &self.client, &self.calendar, &mut Vec::new()).await
+        }
+
+        async fn todo_by_uid(&self, uid: &str) -> Option<entity::Model> {
+            entity::Entity::find()
+                .filter(entity::Column::Uid.eq(uid))
+                .one(&self.db)
+                .await
+                .unwrap()
+        }
+    }
+
+    fn vtodo(uid: &str, summary: &str, description: Option<&str>) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//Fake//CalDAV//EN".to_string(),
+            "BEGIN:VTODO".to_string(),
+            format!("UID:{uid}"),
+            "DTSTAMP:20250101T000000Z".to_string(),
+            "LAST-MODIFIED:20250101T000000Z".to_string(),
+            format!("SUMMARY:{summary}"),
+            "STATUS:NEEDS-ACTION".to_string(),
+        ];
+        if let Some(description) = description {
+            lines.push(format!("DESCRIPTION:{description}"));
+        }
+        lines.extend(["END:VTODO".to_string(), "END:VCALENDAR".to_string()]);
+        lines.join("\r\n") + "\r\n"
+    }
+
+    #[test]
+    fn test_format_trigger_duration_round_trips() {
+        assert_eq!(format_trigger_duration(1440), "-P1D");
+        assert_eq!(format_trigger_duration(15), "-PT15M");
+        assert_eq!(format_trigger_duration(0), "PT0S");
+        assert_eq!(format_trigger_duration(120), "-PT2H");
+        assert_eq!(format_trigger_duration(-30), "PT30M");
+
+        // 解析出的分钟数按提前量写回后再解析，结果不变（-P1DT2H 写回为 -PT26H）
+        for value in ["-P1D", "-PT15M", "PT0S", "-P1DT2H", "PT30M"] {
+            let minutes = parse_trigger_duration(value).unwrap();
+            assert_eq!(
+                parse_trigger_duration(&format_trigger_duration(-minutes)),
+                Some(minutes)
+            );
+        }
+    }
+
+    #[test]
+    fn test_valarms_round_trip_through_ical() {
+        let at = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+        let reminder = |offset_minutes: Option<i32>,
+                        related: &str,
+                        trigger_at: Option<DateTime<Utc>>,
+                        action: &str| reminder_entity::Model {
+            id: 0,
+            todo_id: 0,
+            offset_minutes,
+            related: related.to_string(),
+            trigger_at,
+            action: action.to_string(),
+            description: Some("Check in".to_string()),
+            last_triggered_occurrence_at: None,
+            created_at: at,
+            updated_at: at,
+        };
+
+        let mut lines: Vec<String> = [
+            "BEGIN:VCALENDAR",
+            "VERSION:2.0",
+            "PRODID:-//pet-focus//EN",
+            "BEGIN:VTODO",
+            "UID:alarms",
+            "SUMMARY:Alarms",
+        ]
+        .map(str::to_string)
+        .to_vec();
+        for model in [
+            reminder(Some(1440), reminders::RELATED_END, None, "DISPLAY"),
+            reminder(Some(1560), reminders::RELATED_START, None, "AUDIO"),
+            reminder(Some(0), reminders::RELATED_END, None, "DISPLAY"),
+            reminder(None, reminders::RELATED_END, Some(at), "EMAIL"),
+        ] {
+            push_valarm(&mut lines, &model, "Alarms");
+        }
+        lines.extend(["END:VTODO".to_string(), "END:VCALENDAR".to_string()]);
+        let ics = ical_patch::display_email_alarms_without_attendee(lines.join("\r\n") + "\r\n");
+
+        let alarms = parse_ical_todo(&ics).unwrap().alarms;
+        assert_eq!(alarms.len(), 4);
+        assert!(matches!(
+            alarms[0].trigger,
+            AlarmTrigger::Relative {
+                minutes_before: 1440,
+                related_start: false
+            }
+        ));
+        assert!(matches!(
+            alarms[1].trigger,
+            AlarmTrigger::Relative {
+                minutes_before: 1560,
+                related_start: true
+            }
+        ));
+        assert_eq!(alarms[1].action, "AUDIO");
+        assert!(matches!(
+            alarms[2].trigger,
+            AlarmTrigger::Relative {
+                minutes_before: 0,
+                related_start: false
+            }
+        ));
+        assert!(matches!(alarms[3].trigger, AlarmTrigger::Absolute(trigger) if trigger == at));
+        assert_eq!(alarms[3].description.as_deref(), Some("Check in"));
+        // 没有收件人的 EMAIL 提醒按 DISPLAY 写出
+        assert_eq!(alarms[3].action, "DISPLAY");
+        assert!(!ics.contains("SUMMARY:Alarms\r\nEND:VALARM"));
+    }
+
+    #[tokio::test]
+    async fn test_initial_sync_pulls_remote_and_pushes_local() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Remote task", None));
+        let local = service::create_todo(&harness.db, Some("Local task".to_string()))
+            .await
+            .unwrap();
+
+        let summary = harness.sync().await.unwrap();
+        assert_eq!((summary.created, summary.pushed), (1, 1));
+
+        let remote = harness.todo_by_uid("remote-1").await.unwrap();
+        assert_eq!(remote.title, "Remote task");
+        assert!(!remote.dirty);
+        assert_eq!(
+            remote.remote_url.as_deref(),
+            Some(harness.server.resource_url("remote-1.ics").as_str())
+        );
+
+        let pushed = harness.todo_by_uid(&local.uid).await.unwrap();
+        assert!(!pushed.dirty);
+        assert!(pushed.remote_etag.is_some());
+        let ics = harness.server.get(&format!("{}.ics", local.uid)).unwrap();
+        assert!(ics.contains("SUMMARY:Local task"));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_sync_uses_ctag_and_sync_collection() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Original", None));
+        harness.sync().await.unwrap();
+        harness.server.take_requests();
+
+        // ctag 未变化时不发起 REPORT
+        let summary = harness.sync().await.unwrap();
+        assert_eq!(summary.updated, 0);
+        assert!(!harness
+            .server
+            .take_requests()
+            .iter()
+            .any(|request| request.starts_with("REPORT")));
+
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Renamed", None));
+        let summary = harness.sync().await.unwrap();
+        assert_eq!(summary.updated, 1);
+        let requests = harness.server.take_requests();
+        assert!(requests.contains(&"REPORT sync-collection".to_string()));
+        assert!(!requests.contains(&"REPORT calendar-query".to_string()));
+        assert_eq!(
+            harness.todo_by_uid("remote-1").await.unwrap().title,
+            "Renamed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_update_is_pushed_with_etag() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Original", None));
+        harness.sync().await.unwrap();
+
+        let todo = harness.todo_by_uid("remote-1").await.unwrap();
+        let old_etag = todo.remote_etag.clone();
+        service::update_todo(&harness.db, todo.id, Some("Edited".to_string()), None)
+            .await
+            .unwrap();
+
+        let summary = harness.sync().await.unwrap();
+        assert_eq!(summary.pushed, 1);
+        assert!(harness
+            .server
+            .get("remote-1.ics")
+            .unwrap()
+            .contains("SUMMARY:Edited"));
+        let todo = harness.todo_by_uid("remote-1").await.unwrap();
+        assert!(!todo.dirty);
+        assert_ne!(todo.remote_etag, old_etag);
+    }
+
+    #[tokio::test]
+    async fn test_deletes_propagate_both_ways() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Deleted here", None));
+        harness
+            .server
+            .put("remote-2.ics", &vtodo("remote-2", "Deleted there", None));
+        harness.sync().await.unwrap();
+
+        let todo = harness.todo_by_uid("remote-1").await.unwrap();
+        service::delete_todo(&harness.db, todo.id).await.unwrap();
+        harness.server.delete("remote-2.ics");
+
+        let summary = harness.sync().await.unwrap();
+        assert_eq!(summary.deleted, 2);
+        assert!(harness.server.resource_names().is_empty());
+        // 本地删除的留在回收站中，不再关联远端资源
+        let trashed = harness.todo_by_uid("remote-1").await.unwrap();
+        assert!(trashed.deleted_at.is_some());
+        assert!(trashed.remote_url.is_none());
+        assert!(!trashed.dirty);
+        // 远端删除的同样移入回收站
+        let trashed = harness.todo_by_uid("remote-2").await.unwrap();
+        assert!(trashed.deleted_at.is_some());
+        assert!(trashed.remote_url.is_none());
+        assert!(!trashed.dirty);
+    }
+
+    #[tokio::test]
+    async fn test_restored_todos_are_recreated_on_remote() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Delete pushed", None));
+        harness
+            .server
+            .put("remote-2.ics", &vtodo("remote-2", "Delete pending", None));
+        harness.sync().await.unwrap();
+
+        // remote-1 的删除已推送后恢复：作为新 todo 创建
+        let first = harness.todo_by_uid("remote-1").await.unwrap();
+        service::delete_todo(&harness.db, first.id).await.unwrap();
+        harness.sync().await.unwrap();
+        assert!(harness.server.get("remote-1.ics").is_none());
+        service::restore_todo(&harness.db, first.id).await.unwrap();
+
+        // remote-2 删除尚未推送时恢复，而远端已被其他客户端删除：重新创建
+        let second = harness.todo_by_uid("remote-2").await.unwrap();
+        service::delete_todo(&harness.db, second.id).await.unwrap();
+        harness.server.delete("remote-2.ics");
+        service::restore_todo(&harness.db, second.id).await.unwrap();
+
+        let summary = harness.sync().await.unwrap();
+        assert_eq!((summary.pushed, summary.deleted), (2, 0));
+        for (uid, title) in [
+            ("remote-1", "Delete pushed"),
+            ("remote-2", "Delete pending"),
+        ] {
+            let todo = harness.todo_by_uid(uid).await.unwrap();
+            assert!(todo.deleted_at.is_none());
+            assert!(!todo.dirty);
+            assert!(todo.remote_url.is_some());
+            assert!(harness
+                .server
+                .get(&format!("{uid}.ics"))
+                .unwrap()
+                .contains(&format!("SUMMARY:{title}")));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dependencies_map_to_related_to() {
+        let harness = Harness::start().await;
+        let dependent = vtodo("remote-1", "Deploy", None).replace(
+            "END:VTODO",
+            "RELATED-TO;RELTYPE=DEPENDS-ON:remote-2\r\n\
+             RELATED-TO;RELTYPE=DEPENDS-ON:elsewhere\r\n\
+             RELATED-TO;RELTYPE=SIBLING:remote-2\r\n\
+             END:VTODO",
+        );
+        harness.server.put("remote-1.ics", &dependent);
+        harness
+            .server
+            .put("remote-2.ics", &vtodo("remote-2", "Test", None));
+        harness.sync().await.unwrap();
+
+        // 不在本地的依赖保留但不阻塞，SIBLING 不是父任务
+        let deploy = harness.todo_by_uid("remote-1").await.unwrap();
+        let test = harness.todo_by_uid("remote-2").await.unwrap();
+        assert_eq!(deploy.parent_id, None);
+        let todo = service::get_todo(&harness.db, deploy.id).await.unwrap();
+        assert_eq!(todo.depends_on, vec![test.id]);
+        assert!(todo.blocked);
+
+        dependencies::remove_dependency(&harness.db, deploy.id, test.id)
+            .await
+            .unwrap();
+        assert_eq!(harness.sync().await.unwrap().pushed, 1);
+        let ics = harness.server.get("remote-1.ics").unwrap();
+        assert!(!ics.contains("DEPENDS-ON:remote-2"));
+        assert!(ics.contains("RELATED-TO;RELTYPE=DEPENDS-ON:elsewhere\r\n"));
+        assert!(ics.contains("RELATED-TO;RELTYPE=SIBLING:remote-2\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_moved_todo_is_removed_from_source_after_target_create() {
+        let harness = Harness::start().await;
+        let target_server = FakeCalDavServer::start(USERNAME, PASSWORD).await;
+        let target = CalDavCalendarConfig::new(
+            "Work".to_string(),
+            CalDavConfig {
+                url: target_server.calendar_url(),
+                username: USERNAME.to_string(),
+                password: PASSWORD.to_string(),
+                auth: Default::default(),
+            },
+        );
+        let target_client = CalDavClient::new(&target.connection()).unwrap();
+        let target_url = target_client.calendar_url().to_string();
+        let sync_target = || async {
+            synchronize_database(&harness.db, &target_client, &target, &mut Vec::new()).await
+        };
+
+        let moved = service::create_todo(&harness.db, Some("Moved".to_string()))
+            .await
+            .unwrap();
+        let edited = service::create_todo(&harness.db, Some("Edited".to_string()))
+            .await
+            .unwrap();
+        harness.sync().await.unwrap();
+        for id in [moved.id, edited.id] {
+            let model = entity::Entity::find_by_id(id)
+                .one(&harness.db)
+                .await
+                .unwrap()
+                .unwrap();
+            move_to_calendar(&harness.db, model, target_url.clone(), true)
+                .await
+                .unwrap();
+        }
+
+        // 目标日历上创建前不删除原资源，原日历上的修改也不会被拉取成新的 todo
+        let edited_name = format!("{}.ics", edited.uid);
+        let ics = harness.server.get(&edited_name).unwrap();
+        harness.server.put(
+            &edited_name,
+            &ics.replace("SUMMARY:Edited", "SUMMARY:Edited remotely"),
+        );
+        let summary = harness.sync().await.unwrap();
+        assert_eq!((summary.created, summary.deleted), (0, 0));
+        assert_eq!(harness.server.resource_names().len(), 2);
+
+        assert_eq!(sync_target().await.unwrap().pushed, 2);
+        let summary = harness.sync().await.unwrap();
+        assert_eq!((summary.deleted, summary.failed), (1, 1));
+        assert_eq!(harness.server.resource_names(), vec![edited_name]);
+        assert_eq!(target_server.resource_names().len(), 2);
+
+        // 远端有新修改的原资源保留，由用户重试或放弃
+        let entries = outbox::list_entries(&harness.db).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].todo_id, edited.id);
+        assert_eq!(entries[0].status, outbox::STATUS_FAILED);
+        assert_eq!(
+            entity::Entity::find().all(&harness.db).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_edits_merge_or_conflict() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("merge.ics", &vtodo("merge", "Original", None));
+        harness
+            .server
+            .put("conflict.ics", &vtodo("conflict", "Original", None));
+        harness.sync().await.unwrap();
+
+        // 不同字段：本地改标题，远端改描述，合并后推送
+        let merge = harness.todo_by_uid("merge").await.unwrap();
+        service::update_todo(&harness.db, merge.id, Some("Local title".to_string()), None)
+            .await
+            .unwrap();
+        harness.server.put(
+            "merge.ics",
+            &vtodo("merge", "Original", Some("Remote description")),
+        );
+
+        // 同一字段：双方都改标题，记录冲突且不推送
+        let conflict = harness.todo_by_uid("conflict").await.unwrap();
+        service::update_todo(&harness.db, conflict.id, Some("Local".to_string()), None)
+            .await
+            .unwrap();
+        harness
+            .server
+            .put("conflict.ics", &vtodo("conflict", "Remote", None));
+
+        let summary = harness.sync().await.unwrap();
+        assert_eq!(summary.conflicts, 1);
+
+        let merged_ics = harness.server.get("merge.ics").unwrap();
+        assert!(merged_ics.contains("SUMMARY:Local title"));
+        assert!(merged_ics.contains("DESCRIPTION:Remote description"));
+        let merge = harness.todo_by_uid("merge").await.unwrap();
+        assert!(!merge.dirty);
+        assert_eq!(merge.description.as_deref(), Some("Remote description"));
+
+        assert_eq!(
+            conflicts::conflict_fields(&harness.db, conflict.id)
+                .await
+                .unwrap(),
+            vec!["summary".to_string()]
+        );
+        assert!(harness
+            .server
+            .get("conflict.ics")
+            .unwrap()
+            .contains("SUMMARY:Remote"));
+    }
+
+    #[tokio::test]
+    async fn test_precondition_failed_conflict_stays_in_outbox() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Original", None));
+        harness.sync().await.unwrap();
+
+        // 双方都改了标题，推送时 412 后合并产生冲突
+        harness
+            .server
+            .put_silently("remote-1.ics", &vtodo("remote-1", "Remote", None));
+        let todo = harness.todo_by_uid("remote-1").await.unwrap();
+        service::update_todo(&harness.db, todo.id, Some("Local".to_string()), None)
+            .await
+            .unwrap();
+
+        let summary = harness.sync().await.unwrap();
+        assert_eq!((summary.pushed, summary.conflicts), (0, 1));
+        assert!(harness
+            .server
+            .get("remote-1.ics")
+            .unwrap()
+            .contains("SUMMARY:Remote"));
+
+        let entries = outbox::list_entries(&harness.db).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].todo_id, todo.id);
+        assert_eq!(entries[0].status, outbox::STATUS_PENDING);
+    }
+
+    #[tokio::test]
+    async fn test_precondition_failed_push_merges_and_retries() {
+        let harness = Harness::start().await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Original", None));
+        harness.sync().await.unwrap();
+
+        // 远端修改未体现在 ctag 上，只有推送时的 If-Match 能发现
+        harness.server.put_silently(
+            "remote-1.ics",
+            &vtodo("remote-1", "Original", Some("Remote description")),
+        );
+        let todo = harness.todo_by_uid("remote-1").await.unwrap();
+        service::update_todo(&harness.db, todo.id, Some("Edited".to_string()), None)
+            .await
+            .unwrap();
+
+        let summary = harness.sync().await.unwrap();
+        assert_eq!((summary.pushed, summary.failed), (1, 0));
+        let requests = harness.server.take_requests();
+        let remote_path = "/calendars/user/tasks/remote-1.ics";
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|request| **request == format!("PUT {remote_path}"))
+                .count(),
+            2
+        );
+        assert!(requests.contains(&format!("GET {remote_path}")));
+
+        let ics = harness.server.get("remote-1.ics").unwrap();
+        assert!(ics.contains("SUMMARY:Edited"));
+        assert!(ics.contains("DESCRIPTION:Remote description"));
+        assert!(!harness.todo_by_uid("remote-1").await.unwrap().dirty);
+    }
+
+    #[test]
+    fn test_status_checks_use_http_status() {
+        let plain = anyhow!("failed to upload /calendars/user/tasks/412-404.ics");
+        assert!(!is_not_found(&plain));
+        assert!(!is_precondition_failed(&plain));
+
+        let not_found: anyhow::Error =
+            CalDavHttpError::new(StatusCode::NOT_FOUND, "CalDAV GET failed").into();
+        assert!(is_not_found(&not_found.context("failed to fetch todo")));
+
+        let precondition_failed: anyhow::Error =
+            CalDavHttpError::new(StatusCode::PRECONDITION_FAILED, "CalDAV PUT failed").into();
+        assert!(is_precondition_failed(&precondition_failed));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_password_fails() {
+        let harness = Harness::start_with_password("wrong").await;
+        harness
+            .server
+            .put("remote-1.ics", &vtodo("remote-1", "Remote task", None));
+
+        let Err(err) = harness.sync().await else {
+            panic!("sync with a wrong password should fail");
+        };
+        assert!(format!("{err:#}").contains("401"));
+        assert!(harness.todo_by_uid("remote-1").await.is_none());
+    }
+}
@@ -0,0 +1,467 @@
+// 测试用 CalDAV 服务器
+//
+// 在进程内启动的最小 CalDAV 服务器，只实现同步引擎用到的部分：
+// - PROPFIND（集合的 getctag / sync-token）
+// - REPORT calendar-query / calendar-multiget / sync-collection
+// - GET、PUT（If-Match / If-None-Match）、DELETE
+// - Digest 认证（MD5，qop=auth），不接受 Basic
+// 测试可以直接修改服务器上的资源，模拟其他设备的修改
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use quick_xml::escape::escape;
+use tokio::net::TcpListener;
+
+const REALM: &str = "pet-focus-test";
+const NONCE: &str = "2f1b3c9d8e7a6b5c";
+const CALENDAR_PATH: &str = "/calendars/user/tasks/";
+const SYNC_TOKEN_PREFIX: &str = "http://fake-caldav/sync/";
+
+#[derive(Debug, Clone)]
+struct Resource {
+    ics: String,
+    etag: String,
+}
+
+#[derive(Debug, Default)]
+struct ServerState {
+    username: String,
+    password: String,
+    resources: BTreeMap<String, Resource>,
+    /// 集合版本：每次修改加一，用作 ctag 和 sync-token
+    version: u64,
+    /// 修改记录（版本, 资源路径），用于 sync-collection
+    changes: Vec<(u64, String)>,
+    /// 已处理的请求（方法 + 路径 + REPORT 类型），供测试断言
+    requests: Vec<String>,
+}
+
+impl ServerState {
+    fn store(&mut self, path: &str, ics: &str) -> String {
+        self.version += 1;
+        let etag = format!("\"{}-{:x}\"", self.version, md5::compute(ics.as_bytes()));
+        self.resources.insert(
+            path.to_string(),
+            Resource {
+                ics: ics.to_string(),
+                etag: etag.clone(),
+            },
+        );
+        self.changes.push((self.version, path.to_string()));
+        etag
+    }
+
+    fn remove(&mut self, path: &str) -> bool {
+        if self.resources.remove(path).is_none() {
+            return false;
+        }
+        self.version += 1;
+        self.changes.push((self.version, path.to_string()));
+        true
+    }
+
+    fn sync_token(&self) -> String {
+        format!("{SYNC_TOKEN_PREFIX}{}", self.version)
+    }
+}
+
+/// 进程内 CalDAV 服务器，随测试运行时结束
+#[derive(Clone)]
+pub struct FakeCalDavServer {
+    base_url: String,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl FakeCalDavServer {
+    pub async fn start(username: &str, password: &str) -> Self {
+        let state = Arc::new(Mutex::new(ServerState {
+            username: username.to_string(),
+            password: password.to_string(),
+            ..Default::default()
+        }));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let router = Router::new().fallback(handle).with_state(state.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Self { base_url, state }
+    }
+
+    /// 日历集合 URL（以 / 结尾）
+    pub fn calendar_url(&self) -> String {
+        format!("{}{CALENDAR_PATH}", self.base_url)
+    }
+
+    /// 资源的完整 URL
+    pub fn resource_url(&self, name: &str) -> String {
+        format!("{}{CALENDAR_PATH}{name}", self.base_url)
+    }
+
+    /// 模拟其他设备创建或修改资源
+    pub fn put(&self, name: &str, ics: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.store(&format!("{CALENDAR_PATH}{name}"), ics);
+    }
+
+    /// 修改资源但不更新 ctag 和修改记录（模拟 ctag 未及时更新的服务器）
+    pub fn put_silently(&self, name: &str, ics: &str) {
+        let mut state = self.state.lock().unwrap();
+        let path = format!("{CALENDAR_PATH}{name}");
+        let etag = format!("\"silent-{:x}\"", md5::compute(ics.as_bytes()));
+        state.resources.insert(
+            path,
+            Resource {
+                ics: ics.to_string(),
+                etag,
+            },
+        );
+    }
+
+    /// 模拟其他设备删除资源
+    pub fn delete(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.remove(&format!("{CALENDAR_PATH}{name}"));
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .resources
+            .get(&format!("{CALENDAR_PATH}{name}"))
+            .map(|resource| resource.ics.clone())
+    }
+
+    /// 集合中的资源名
+    pub fn resource_names(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .resources
+            .keys()
+            .map(|path| path.trim_start_matches(CALENDAR_PATH).to_string())
+            .collect()
+    }
+
+    /// 清空并返回已处理的请求记录（例如 "REPORT sync-collection"、"PUT /calendars/..."）
+    pub fn take_requests(&self) -> Vec<String> {
+        std::mem::take(&mut self.state.lock().unwrap().requests)
+    }
+}
+
+async fn handle(
+    State(state): State<Arc<Mutex<ServerState>>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let path = uri.path().to_string();
+
+    if !is_authorized(&state, &method, &path, &headers) {
+        let mut response = (StatusCode::UNAUTHORIZED, "authentication required").into_response();
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            HeaderValue::from_str(&format!(
+                "Digest realm=\"{REALM}\", nonce=\"{NONCE}\", qop=\"auth\", algorithm=MD5"
+            ))
+            .unwrap(),
+        );
+        return response;
+    }
+
+    let label = match method.as_str() {
+        "REPORT" => format!("REPORT {}", report_kind(&body)),
+        _ => format!("{method} {path}"),
+    };
+    state.requests.push(label);
+
+    match method.as_str() {
+        "PROPFIND" if path == CALENDAR_PATH => propfind_collection(&state),
+        "REPORT" if path == CALENDAR_PATH => match report_kind(&body) {
+            "calendar-multiget" => multiget(&state, &body),
+            "sync-collection" => sync_collection(&state, &body),
+            _ => calendar_query(&state, body.contains("calendar-data")),
+        },
+        "GET" => match state.resources.get(&path) {
+            Some(resource) => with_etag(
+                (StatusCode::OK, resource.ics.clone()).into_response(),
+                &resource.etag,
+            ),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+        "PUT" => put(&mut state, &path, &headers, &body),
+        "DELETE" => {
+            let current = state.resources.get(&path).map(|r| r.etag.clone());
+            match (current, if_match(&headers)) {
+                (None, _) => StatusCode::NOT_FOUND.into_response(),
+                (Some(etag), Some(expected)) if expected != etag => {
+                    StatusCode::PRECONDITION_FAILED.into_response()
+                }
+                _ => {
+                    state.remove(&path);
+                    StatusCode::NO_CONTENT.into_response()
+                }
+            }
+        }
+        _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    }
+}
+
+fn report_kind(body: &str) -> &'static str {
+    if body.contains("calendar-multiget") {
+        "calendar-multiget"
+    } else if body.contains("sync-collection") {
+        "sync-collection"
+    } else {
+        "calendar-query"
+    }
+}
+
+fn put(state: &mut ServerState, path: &str, headers: &HeaderMap, body: &str) -> Response {
+    if !path.starts_with(CALENDAR_PATH) || path == CALENDAR_PATH {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let current = state.resources.get(path).map(|r| r.etag.clone());
+    let create_only = headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| value == "*");
+    if create_only && current.is_some() {
+        return StatusCode::PRECONDITION_FAILED.into_response();
+    }
+    if let Some(expected) = if_match(headers) {
+        if current.as_deref() != Some(expected.as_str()) {
+            return StatusCode::PRECONDITION_FAILED.into_response();
+        }
+    }
+
+    let status = if current.is_some() {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::CREATED
+    };
+    let etag = state.store(path, body);
+    with_etag(status.into_response(), &etag)
+}
+
+fn if_match(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::IF_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+}
+
+fn with_etag(mut response: Response, etag: &str) -> Response {
+    response
+        .headers_mut()
+        .insert(header::ETAG, HeaderValue::from_str(etag).unwrap());
+    response
+}
+
+fn multistatus(body: String) -> Response {
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav" xmlns:cs="http://calendarserver.org/ns/">
+{body}</d:multistatus>
+"#
+    );
+    let mut response = (StatusCode::MULTI_STATUS, xml).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/xml; charset=utf-8"),
+    );
+    response
+}
+
+fn resource_response(path: &str, resource: &Resource, with_data: bool) -> String {
+    let data = if with_data {
+        format!(
+            "<cal:calendar-data>{}</cal:calendar-data>",
+            escape(resource.ics.as_str())
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "<d:response><d:href>{path}</d:href><d:propstat><d:prop><d:getetag>{}</d:getetag>{data}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>\n",
+        escape(resource.etag.as_str())
+    )
+}
+
+fn not_found_response(path: &str) -> String {
+    format!("<d:response><d:href>{path}</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>\n")
+}
+
+fn propfind_collection(state: &ServerState) -> Response {
+    multistatus(format!(
+        "<d:response><d:href>{CALENDAR_PATH}</d:href><d:propstat><d:prop><cs:getctag>{}</cs:getctag><d:sync-token>{}</d:sync-token></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>\n",
+        state.version,
+        state.sync_token()
+    ))
+}
+
+fn calendar_query(state: &ServerState, with_data: bool) -> Response {
+    let body: String = state
+        .resources
+        .iter()
+        .filter(|(_, resource)| resource.ics.contains("BEGIN:VTODO"))
+        .map(|(path, resource)| resource_response(path, resource, with_data))
+        .collect();
+    multistatus(body)
+}
+
+fn multiget(state: &ServerState, body: &str) -> Response {
+    let body: String = element_texts(body, "href")
+        .iter()
+        .map(|path| match state.resources.get(path) {
+            Some(resource) => resource_response(path, resource, true),
+            None => not_found_response(path),
+        })
+        .collect();
+    multistatus(body)
+}
+
+/// RFC 6578：返回令牌之后变化的资源，令牌无效时返回 403 valid-sync-token
+fn sync_collection(state: &ServerState, body: &str) -> Response {
+    let token = element_texts(body, "sync-token")
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let since = if token.is_empty() {
+        0
+    } else {
+        match token
+            .strip_prefix(SYNC_TOKEN_PREFIX)
+            .and_then(|version| version.parse::<u64>().ok())
+            .filter(|version| *version <= state.version)
+        {
+            Some(version) => version,
+            None => {
+                return (
+                    StatusCode::FORBIDDEN,
+                    r#"<d:error xmlns:d="DAV:"><d:valid-sync-token/></d:error>"#,
+                )
+                    .into_response()
+            }
+        }
+    };
+
+    let mut changed: Vec<&String> = state
+        .changes
+        .iter()
+        .filter(|(version, _)| *version > since)
+        .map(|(_, path)| path)
+        .collect();
+    changed.sort();
+    changed.dedup();
+
+    let mut body: String = changed
+        .into_iter()
+        .map(|path| match state.resources.get(path) {
+            Some(resource) => resource_response(path, resource, false),
+            None => not_found_response(path),
+        })
+        .collect();
+    body.push_str(&format!(
+        "<d:sync-token>{}</d:sync-token>\n",
+        state.sync_token()
+    ));
+    multistatus(body)
+}
+
+/// 提取所有 `<prefix:name>text</prefix:name>` 的文本（测试请求格式固定，不做完整 XML 解析）
+fn element_texts(xml: &str, name: &str) -> Vec<String> {
+    let open = format!(":{name}>");
+    let mut texts = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let is_close_tag = rest[..start]
+            .rfind('<')
+            .is_some_and(|tag_start| rest[tag_start..].starts_with("</"));
+        if is_close_tag {
+            rest = after;
+            continue;
+        }
+
+        let end = after.find("</").unwrap_or(after.len());
+        texts.push(
+            quick_xml::escape::unescape(after[..end].trim())
+                .map(|text| text.to_string())
+                .unwrap_or_default(),
+        );
+        rest = &after[end..];
+    }
+    texts
+}
+
+/// 校验 Digest 认证（RFC 7616，MD5 + qop=auth）
+fn is_authorized(state: &ServerState, method: &Method, path: &str, headers: &HeaderMap) -> bool {
+    let Some(value) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let Some(params) = value.strip_prefix("Digest ") else {
+        return false;
+    };
+
+    let params = parse_digest_params(params);
+    let get = |key: &str| params.get(key).map(String::as_str).unwrap_or_default();
+    if get("username") != state.username || get("realm") != REALM || get("nonce") != NONCE {
+        return false;
+    }
+    if get("uri") != path {
+        return false;
+    }
+
+    let ha1 = format!(
+        "{:x}",
+        md5::compute(format!("{}:{REALM}:{}", state.username, state.password))
+    );
+    let ha2 = format!("{:x}", md5::compute(format!("{method}:{}", get("uri"))));
+    let expected = format!(
+        "{:x}",
+        md5::compute(format!(
+            "{ha1}:{NONCE}:{}:{}:{}:{ha2}",
+            get("nc"),
+            get("cnonce"),
+            get("qop")
+        ))
+    );
+    get("response") == expected
+}
+
+fn parse_digest_params(input: &str) -> BTreeMap<String, String> {
+    let mut params = BTreeMap::new();
+    let mut rest = input.trim();
+    while !rest.is_empty() {
+        let Some((key, after)) = rest.split_once('=') else {
+            break;
+        };
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or_default())
+            }
+            None => {
+                let end = after.find(',').unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        params.insert(key.trim().to_string(), value.to_string());
+        rest = after.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+    params
+}
@@ -1,5 +1,7 @@
 mod connection;
 mod registry;
+#[cfg(test)]
+pub(crate) mod test_db;
 
-pub use connection::init_db;
+pub use connection::{app_data_dir, init_db};
 pub use registry::DatabaseRegistry;
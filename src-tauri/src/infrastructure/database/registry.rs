@@ -39,11 +39,34 @@ impl DatabaseRegistry {
         self.migrations.push((name, Box::new(migration_fn)));
     }
 
+    /// 已注册的 Migration 名称（按执行顺序），用于标识数据库表结构的版本
+    pub fn migration_names(&self) -> Vec<&'static str> {
+        self.migrations.iter().map(|(name, _)| *name).collect()
+    }
+
     /// 执行所有已注册的 Migrations
     pub async fn run_migrations(&self, db: &sea_orm::DatabaseConnection) -> Result<()> {
+        self.run_migrations_where(db, |_| true).await
+    }
+
+    /// 按注册顺序只执行 names 中列出的 Migrations（用于重建旧版本的表结构）
+    pub async fn run_named_migrations(
+        &self,
+        db: &sea_orm::DatabaseConnection,
+        names: &[String],
+    ) -> Result<()> {
+        self.run_migrations_where(db, |name| names.iter().any(|listed| listed == name))
+            .await
+    }
+
+    async fn run_migrations_where(
+        &self,
+        db: &sea_orm::DatabaseConnection,
+        filter: impl Fn(&str) -> bool,
+    ) -> Result<()> {
         let manager = SchemaManager::new(db);
 
-        for (name, migration_fn) in &self.migrations {
+        for (name, migration_fn) in self.migrations.iter().filter(|(name, _)| filter(name)) {
             println!("Running migration: {}", name);
             migration_fn(&manager)
                 .await
@@ -0,0 +1,20 @@
+// 测试用数据库
+//
+// 与应用启动时一样由各 Feature 注册 Migrations，测试中的表结构始终与真实数据库一致
+
+use sea_orm::{Database, DatabaseConnection};
+
+use super::DatabaseRegistry;
+
+/// 创建内存数据库并执行所有 Feature 注册的 Migrations
+pub async fn memory_db() -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+
+    let mut registry = DatabaseRegistry::new();
+    for feature in crate::init_features() {
+        feature.register_database(&mut registry);
+    }
+    registry.run_migrations(&db).await.unwrap();
+
+    db
+}
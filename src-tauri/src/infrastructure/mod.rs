@@ -1,5 +1,6 @@
 pub mod database;
 pub mod notification;
+pub mod secrets;
 
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 pub mod tray;
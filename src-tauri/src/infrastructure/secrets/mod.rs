@@ -0,0 +1,7 @@
+// 敏感信息存储
+//
+// 密码、令牌等凭据加密后再写入数据库，密钥保存在数据库之外的单独文件中
+
+mod store;
+
+pub use store::SecretStore;
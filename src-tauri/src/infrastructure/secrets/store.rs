@@ -0,0 +1,203 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::infrastructure::database::app_data_dir;
+
+const KEY_FILENAME: &str = "secrets.key";
+const KEY_LEN: usize = 32;
+/// 密文格式版本前缀：v1 = ChaCha20-Poly1305，正文为 base64(nonce || 密文 || tag)
+const CIPHERTEXT_PREFIX: &str = "v1:";
+
+static GLOBAL: OnceLock<SecretStore> = OnceLock::new();
+
+/// 加解密敏感信息
+///
+/// 密钥在首次使用时随机生成，保存在应用数据目录的 `secrets.key` 中（Unix 下权限为 0600），
+/// 与数据库分开存放：只拿到 SQLite 文件无法还原凭据
+pub struct SecretStore {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SecretStore {
+    /// 应用全局的密钥存储（密钥文件位于应用数据目录）
+    pub fn global() -> Result<&'static SecretStore> {
+        if let Some(store) = GLOBAL.get() {
+            return Ok(store);
+        }
+
+        let store = Self::open(&app_data_dir()?.join(KEY_FILENAME))?;
+        Ok(GLOBAL.get_or_init(|| store))
+    }
+
+    /// 使用指定的密钥文件，不存在时生成新密钥
+    pub fn open(key_path: &Path) -> Result<Self> {
+        let rng = SystemRandom::new();
+        let key_bytes = match fs::read(key_path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let mut bytes = vec![0u8; KEY_LEN];
+                rng.fill(&mut bytes)
+                    .map_err(|_| anyhow!("failed to generate secret key"))?;
+                write_key_file(key_path, &bytes)?;
+                bytes
+            }
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("failed to read secret key file {}", key_path.display())
+                })
+            }
+        };
+
+        if key_bytes.len() != KEY_LEN {
+            return Err(anyhow!(
+                "secret key file {} is corrupted",
+                key_path.display()
+            ));
+        }
+
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &key_bytes)
+            .map_err(|_| anyhow!("invalid secret key"))?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng,
+        })
+    }
+
+    /// 加密文本，每次使用随机 nonce（相同明文得到不同密文）
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce_bytes)
+            .map_err(|_| anyhow!("failed to generate nonce"))?;
+
+        let mut buffer = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::empty(),
+                &mut buffer,
+            )
+            .map_err(|_| anyhow!("failed to encrypt secret"))?;
+
+        let mut payload = nonce_bytes.to_vec();
+        payload.extend_from_slice(&buffer);
+        Ok(format!("{CIPHERTEXT_PREFIX}{}", STANDARD.encode(payload)))
+    }
+
+    /// 解密 [`encrypt`](Self::encrypt) 生成的密文
+    pub fn decrypt(&self, ciphertext: &str) -> Result<String> {
+        let encoded = ciphertext
+            .strip_prefix(CIPHERTEXT_PREFIX)
+            .ok_or_else(|| anyhow!("unsupported secret format"))?;
+        let payload = STANDARD
+            .decode(encoded)
+            .context("failed to decode secret")?;
+        if payload.len() < NONCE_LEN {
+            return Err(anyhow!("secret is truncated"));
+        }
+
+        let (nonce_bytes, sealed) = payload.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)
+            .map_err(|_| anyhow!("invalid secret nonce"))?;
+        let mut buffer = sealed.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut buffer)
+            .map_err(|_| anyhow!("failed to decrypt secret (wrong key or corrupted data)"))?;
+
+        String::from_utf8(plaintext.to_vec()).context("decrypted secret is not valid UTF-8")
+    }
+}
+
+/// 写入密钥文件，Unix 下创建时即限制为仅当前用户可读写
+fn write_key_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("failed to create secret key directory")?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("failed to create secret key file {}", path.display()))?;
+    std::io::Write::write_all(&mut file, bytes)
+        .with_context(|| format!("failed to write secret key file {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_key_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("pet-focus-secrets-{name}-{}", uuid::Uuid::new_v4()));
+        dir.join(KEY_FILENAME)
+    }
+
+    #[test]
+    fn test_round_trip_and_key_reuse() {
+        let path = temp_key_path("round-trip");
+        let store = SecretStore::open(&path).unwrap();
+
+        let first = store.encrypt("app-password").unwrap();
+        let second = store.encrypt("app-password").unwrap();
+        assert_ne!(first, second);
+        assert!(!first.contains("app-password"));
+
+        // 重新打开使用同一个密钥文件
+        let reopened = SecretStore::open(&path).unwrap();
+        assert_eq!(reopened.decrypt(&first).unwrap(), "app-password");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_wrong_key_or_tampered_data_is_rejected() {
+        let path = temp_key_path("tamper");
+        let other_path = temp_key_path("other");
+        let store = SecretStore::open(&path).unwrap();
+        let other = SecretStore::open(&other_path).unwrap();
+
+        let ciphertext = store.encrypt("secret").unwrap();
+        assert!(other.decrypt(&ciphertext).is_err());
+
+        // 修改密文中间的一个字符
+        let index = CIPHERTEXT_PREFIX.len() + 20;
+        let replacement = if &ciphertext[index..=index] == "A" {
+            "B"
+        } else {
+            "A"
+        };
+        let mut tampered = ciphertext.clone();
+        tampered.replace_range(index..=index, replacement);
+        assert!(store.decrypt(&tampered).is_err());
+        assert!(store.decrypt("plain-text").is_err());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+        let _ = fs::remove_dir_all(other_path.parent().unwrap());
+    }
+}
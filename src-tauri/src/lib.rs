@@ -10,8 +10,9 @@ use core::Feature;
 #[cfg(target_os = "windows")]
 use features::pet::PetFeature;
 use features::{
-    achievement::AchievementFeature, pomodoro::PomodoroFeature, settings::SettingsFeature,
-    tag::TagFeature, todo::TodoFeature, window::WindowFeature,
+    achievement::AchievementFeature, backup::BackupFeature, pomodoro::PomodoroFeature,
+    search::SearchFeature, settings::SettingsFeature, tag::TagFeature, todo::TodoFeature,
+    window::WindowFeature,
 };
 use infrastructure::database::{init_db, DatabaseRegistry};
 use std::sync::Arc;
@@ -26,6 +27,8 @@ fn init_features() -> Vec<Arc<dyn Feature>> {
         PomodoroFeature::new(),
         TagFeature::new(),
         AchievementFeature::new(),
+        BackupFeature::new(),
+        SearchFeature::new(),
         Arc::new(WindowFeature::new()),
     ];
 
@@ -62,6 +65,7 @@ pub fn run() {
 
             // 创建 AppState
             let mut state = AppState::new(handle.clone(), db, features.clone());
+            state.set_database_registry(db_registry);
 
             // 注册 WebSocket Handlers（仅桌面平台）
             #[cfg(not(any(target_os = "android", target_os = "ios")))]